@@ -0,0 +1,5 @@
+ALTER TABLE "app_channel"
+DROP COLUMN "min_supported_version_code";
+
+ALTER TABLE "app_manage"
+DROP COLUMN "force_update";
@@ -0,0 +1,5 @@
+ALTER TABLE "app_manage"
+ADD COLUMN "force_update" BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE "app_channel"
+ADD COLUMN "min_supported_version_code" BIGINT;
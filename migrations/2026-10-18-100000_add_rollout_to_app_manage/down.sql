@@ -0,0 +1,3 @@
+ALTER TABLE "app_manage"
+DROP COLUMN "rollout_status",
+DROP COLUMN "rollout_percentage";
@@ -0,0 +1,3 @@
+ALTER TABLE "app_manage"
+ADD COLUMN "rollout_percentage" INTEGER NOT NULL DEFAULT 100,
+ADD COLUMN "rollout_status" VARCHAR NOT NULL DEFAULT 'active';
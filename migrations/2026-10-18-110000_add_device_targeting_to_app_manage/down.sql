@@ -0,0 +1,4 @@
+ALTER TABLE "app_manage"
+DROP COLUMN "targeting_rules",
+DROP COLUMN "native_abis",
+DROP COLUMN "min_sdk_version";
@@ -0,0 +1,4 @@
+ALTER TABLE "app_manage"
+ADD COLUMN "min_sdk_version" INTEGER,
+ADD COLUMN "native_abis" TEXT[] NOT NULL DEFAULT '{}',
+ADD COLUMN "targeting_rules" JSONB;
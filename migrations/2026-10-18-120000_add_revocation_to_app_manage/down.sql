@@ -0,0 +1,5 @@
+ALTER TABLE "app_manage"
+DROP COLUMN "rollback_app_id",
+DROP COLUMN "revoke_time",
+DROP COLUMN "revoke_reason",
+DROP COLUMN "is_revoked";
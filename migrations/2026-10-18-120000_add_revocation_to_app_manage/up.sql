@@ -0,0 +1,5 @@
+ALTER TABLE "app_manage"
+ADD COLUMN "is_revoked" BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN "revoke_reason" VARCHAR,
+ADD COLUMN "revoke_time" TIMESTAMP,
+ADD COLUMN "rollback_app_id" UUID REFERENCES "app_manage"("id");
@@ -0,0 +1,4 @@
+ALTER TABLE "app_manage"
+DROP COLUMN "is_published",
+DROP COLUMN "expire_at",
+DROP COLUMN "publish_at";
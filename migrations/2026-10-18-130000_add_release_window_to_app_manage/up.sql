@@ -0,0 +1,4 @@
+ALTER TABLE "app_manage"
+ADD COLUMN "publish_at" TIMESTAMP,
+ADD COLUMN "expire_at" TIMESTAMP,
+ADD COLUMN "is_published" BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,5 @@
+DROP TABLE "app_signer_pin";
+
+ALTER TABLE "app_manage"
+DROP COLUMN "signature_schemes",
+DROP COLUMN "signer_sha256";
@@ -0,0 +1,16 @@
+ALTER TABLE "app_manage"
+ADD COLUMN "signer_sha256" VARCHAR,
+ADD COLUMN "signature_schemes" TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE TABLE "app_signer_pin"
+(
+    "id"                     UUID      NOT NULL PRIMARY KEY,
+    "package_name"           VARCHAR   NOT NULL,
+    "create_user_id"         UUID      NOT NULL,
+    "signer_sha256"          VARCHAR   NOT NULL,
+    "rotation_signer_sha256" VARCHAR,
+    "create_time"            TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "update_time"            TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_app_signer_pin_users FOREIGN KEY (create_user_id) REFERENCES users (id),
+    CONSTRAINT uq_app_signer_pin_package UNIQUE (create_user_id, package_name)
+);
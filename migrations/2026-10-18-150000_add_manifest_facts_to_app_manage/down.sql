@@ -0,0 +1,6 @@
+ALTER TABLE "app_manage"
+DROP COLUMN "is_test_only",
+DROP COLUMN "is_debuggable",
+DROP COLUMN "features",
+DROP COLUMN "permissions",
+DROP COLUMN "target_sdk_version";
@@ -0,0 +1,6 @@
+ALTER TABLE "app_manage"
+ADD COLUMN "target_sdk_version" INTEGER,
+ADD COLUMN "permissions" TEXT[] NOT NULL DEFAULT '{}',
+ADD COLUMN "features" TEXT[] NOT NULL DEFAULT '{}',
+ADD COLUMN "is_debuggable" BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN "is_test_only" BOOLEAN NOT NULL DEFAULT FALSE;
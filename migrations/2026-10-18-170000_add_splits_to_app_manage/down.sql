@@ -0,0 +1,2 @@
+ALTER TABLE "app_manage"
+DROP COLUMN "splits";
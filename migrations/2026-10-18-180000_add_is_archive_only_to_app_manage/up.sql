@@ -0,0 +1,2 @@
+ALTER TABLE "app_manage"
+ADD COLUMN "is_archive_only" BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+ALTER TABLE "app_manage"
+DROP COLUMN "file_sha256",
+DROP COLUMN "file_md5";
@@ -0,0 +1,3 @@
+ALTER TABLE "app_manage"
+ADD COLUMN "file_sha256" VARCHAR,
+ADD COLUMN "file_md5" VARCHAR;
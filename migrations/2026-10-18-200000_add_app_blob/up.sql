@@ -0,0 +1,11 @@
+CREATE TABLE "app_blob"
+(
+    "id"          UUID      NOT NULL PRIMARY KEY,
+    "kind"        VARCHAR   NOT NULL,
+    "file_name"   VARCHAR   NOT NULL,
+    "file_size"   BIGINT    NOT NULL,
+    "ref_count"   INTEGER   NOT NULL DEFAULT 0,
+    "create_time" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "update_time" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uq_app_blob_kind_file_name UNIQUE (kind, file_name)
+);
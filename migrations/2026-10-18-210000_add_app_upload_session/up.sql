@@ -0,0 +1,17 @@
+CREATE TABLE "app_upload_session"
+(
+    "id"             UUID      NOT NULL PRIMARY KEY,
+    "create_user_id" UUID      NOT NULL,
+    "file_name"      VARCHAR   NOT NULL,
+    "file_size"      BIGINT    NOT NULL,
+    "chunk_size"     BIGINT    NOT NULL,
+    "total_chunks"   INTEGER   NOT NULL,
+    "file_sha256"    VARCHAR,
+    "status"         VARCHAR   NOT NULL DEFAULT 'uploading',
+    "expires_at"     TIMESTAMP NOT NULL,
+    "create_time"    TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "update_time"    TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_app_upload_session_users FOREIGN KEY (create_user_id) REFERENCES users (id)
+);
+
+CREATE INDEX "idx_app_upload_session_expires_at" ON "app_upload_session" ("expires_at");
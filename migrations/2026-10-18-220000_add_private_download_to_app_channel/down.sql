@@ -0,0 +1,4 @@
+ALTER TABLE "app_channel"
+DROP COLUMN "bind_download_device";
+ALTER TABLE "app_channel"
+DROP COLUMN "is_private";
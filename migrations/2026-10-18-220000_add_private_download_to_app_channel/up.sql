@@ -0,0 +1,4 @@
+ALTER TABLE "app_channel"
+ADD COLUMN "is_private" BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE "app_channel"
+ADD COLUMN "bind_download_device" BOOLEAN NOT NULL DEFAULT FALSE;
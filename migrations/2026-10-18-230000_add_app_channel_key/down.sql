@@ -0,0 +1,2 @@
+DROP TABLE "app_request_nonce";
+DROP TABLE "app_channel_key";
@@ -0,0 +1,27 @@
+CREATE TABLE "app_channel_key"
+(
+    "id"             UUID      NOT NULL PRIMARY KEY,
+    "channel_id"     UUID      NOT NULL,
+    "app_key"        VARCHAR   NOT NULL,
+    "app_secret"     VARCHAR   NOT NULL,
+    "create_user_id" UUID      NOT NULL,
+    "create_time"    TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "update_time"    TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "is_delete"      BOOLEAN   NOT NULL DEFAULT FALSE,
+    CONSTRAINT uq_app_channel_key_app_key UNIQUE (app_key),
+    CONSTRAINT fk_app_channel_key_app_channel FOREIGN KEY (channel_id) REFERENCES app_channel (id) ON DELETE CASCADE,
+    CONSTRAINT fk_app_channel_key_users FOREIGN KEY (create_user_id) REFERENCES users (id)
+);
+
+CREATE INDEX "idx_app_channel_key_channel_id" ON "app_channel_key" ("channel_id");
+
+CREATE TABLE "app_request_nonce"
+(
+    "app_key"     VARCHAR   NOT NULL,
+    "nonce"       VARCHAR   NOT NULL,
+    "create_time" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "expires_at"  TIMESTAMP NOT NULL,
+    PRIMARY KEY ("app_key", "nonce")
+);
+
+CREATE INDEX "idx_app_request_nonce_expires_at" ON "app_request_nonce" ("expires_at");
@@ -0,0 +1,19 @@
+CREATE TABLE "app_delta_patch"
+(
+    "id"               UUID      NOT NULL PRIMARY KEY,
+    "app_id"           UUID      NOT NULL,
+    "base_app_id"      UUID      NOT NULL,
+    "status"           VARCHAR   NOT NULL DEFAULT 'pending',
+    "base_file_sha256" VARCHAR,
+    "file_name"        VARCHAR,
+    "patch_sha256"     VARCHAR,
+    "patch_size"       BIGINT,
+    "error_msg"        VARCHAR,
+    "create_time"      TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "update_time"      TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uq_app_delta_patch_app_id_base_app_id UNIQUE (app_id, base_app_id),
+    CONSTRAINT fk_app_delta_patch_app_manage FOREIGN KEY (app_id) REFERENCES app_manage (id) ON DELETE CASCADE,
+    CONSTRAINT fk_app_delta_patch_base_app_manage FOREIGN KEY (base_app_id) REFERENCES app_manage (id) ON DELETE CASCADE
+);
+
+CREATE INDEX "idx_app_delta_patch_file_name" ON "app_delta_patch" ("file_name");
@@ -0,0 +1,2 @@
+ALTER TABLE "app_blob"
+DROP COLUMN "original_name";
@@ -0,0 +1,2 @@
+ALTER TABLE "app_blob"
+ADD COLUMN "original_name" VARCHAR;
@@ -0,0 +1,13 @@
+CREATE TABLE "user_role"
+(
+    "user_id"     UUID      NOT NULL,
+    "role"        VARCHAR   NOT NULL,
+    "create_time" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY ("user_id", "role"),
+    CONSTRAINT fk_user_role_users FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+-- 已有用户此前可以执行所有操作，升级后保留为管理员
+INSERT INTO "user_role" ("user_id", "role")
+SELECT "id", 'admin'
+FROM "users";
@@ -0,0 +1,13 @@
+ALTER TABLE "app_signer_pin"
+DROP CONSTRAINT uq_app_signer_pin_package,
+DROP COLUMN "organization_id",
+ADD CONSTRAINT uq_app_signer_pin_package UNIQUE (create_user_id, package_name);
+
+ALTER TABLE "app_manage"
+DROP COLUMN "organization_id";
+
+ALTER TABLE "app_channel"
+DROP COLUMN "organization_id";
+
+DROP TABLE "organization_member";
+DROP TABLE "organization";
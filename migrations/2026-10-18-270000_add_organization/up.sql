@@ -0,0 +1,81 @@
+CREATE TABLE "organization"
+(
+    "id"             UUID      NOT NULL PRIMARY KEY,
+    "name"           VARCHAR   NOT NULL,
+    "is_personal"    BOOLEAN   NOT NULL DEFAULT FALSE,
+    "create_user_id" UUID      NOT NULL,
+    "create_time"    TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "update_time"    TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "is_delete"      BOOLEAN   NOT NULL DEFAULT FALSE,
+    CONSTRAINT fk_organization_users FOREIGN KEY (create_user_id) REFERENCES users (id)
+);
+
+-- 每个用户只有一个个人组织
+CREATE UNIQUE INDEX "uq_organization_personal" ON "organization" ("create_user_id") WHERE "is_personal";
+
+CREATE TABLE "organization_member"
+(
+    "organization_id" UUID      NOT NULL,
+    "user_id"         UUID      NOT NULL,
+    "member_role"     VARCHAR   NOT NULL,
+    "create_time"     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY ("organization_id", "user_id"),
+    CONSTRAINT fk_organization_member_organization FOREIGN KEY (organization_id) REFERENCES organization (id) ON DELETE CASCADE,
+    CONSTRAINT fk_organization_member_users FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX "idx_organization_member_user_id" ON "organization_member" ("user_id");
+
+-- 已有用户各自创建个人组织，原先按用户隔离的渠道、应用和签名绑定归属到个人组织
+INSERT INTO "organization" ("id", "name", "is_personal", "create_user_id")
+SELECT gen_random_uuid(), "username", TRUE, "id"
+FROM "users";
+
+INSERT INTO "organization_member" ("organization_id", "user_id", "member_role")
+SELECT "id", "create_user_id", 'owner'
+FROM "organization";
+
+ALTER TABLE "app_channel"
+ADD COLUMN "organization_id" UUID;
+
+UPDATE "app_channel"
+SET "organization_id" = "organization"."id"
+FROM "organization"
+WHERE "organization"."create_user_id" = "app_channel"."create_user_id"
+  AND "organization"."is_personal";
+
+ALTER TABLE "app_channel"
+ALTER COLUMN "organization_id" SET NOT NULL,
+ADD CONSTRAINT fk_app_channel_organization FOREIGN KEY (organization_id) REFERENCES organization (id);
+
+CREATE INDEX "idx_app_channel_organization_id" ON "app_channel" ("organization_id");
+
+-- 应用跟随所属渠道归属到同一组织
+ALTER TABLE "app_manage"
+ADD COLUMN "organization_id" UUID;
+
+UPDATE "app_manage"
+SET "organization_id" = "app_channel"."organization_id"
+FROM "app_channel"
+WHERE "app_channel"."id" = "app_manage"."channel_id";
+
+ALTER TABLE "app_manage"
+ALTER COLUMN "organization_id" SET NOT NULL,
+ADD CONSTRAINT fk_app_manage_organization FOREIGN KEY (organization_id) REFERENCES organization (id);
+
+CREATE INDEX "idx_app_manage_organization_id" ON "app_manage" ("organization_id");
+
+ALTER TABLE "app_signer_pin"
+ADD COLUMN "organization_id" UUID;
+
+UPDATE "app_signer_pin"
+SET "organization_id" = "organization"."id"
+FROM "organization"
+WHERE "organization"."create_user_id" = "app_signer_pin"."create_user_id"
+  AND "organization"."is_personal";
+
+ALTER TABLE "app_signer_pin"
+ALTER COLUMN "organization_id" SET NOT NULL,
+DROP CONSTRAINT uq_app_signer_pin_package,
+ADD CONSTRAINT fk_app_signer_pin_organization FOREIGN KEY (organization_id) REFERENCES organization (id),
+ADD CONSTRAINT uq_app_signer_pin_package UNIQUE (organization_id, package_name);
@@ -0,0 +1,21 @@
+CREATE TABLE "api_token"
+(
+    "id"              UUID      NOT NULL PRIMARY KEY,
+    "user_id"         UUID      NOT NULL,
+    "organization_id" UUID      NOT NULL,
+    "token_name"      VARCHAR   NOT NULL,
+    "token_prefix"    VARCHAR   NOT NULL,
+    "token_hash"      VARCHAR   NOT NULL,
+    "scopes"          TEXT[]    NOT NULL DEFAULT '{}',
+    "channel_ids"     UUID[]    NOT NULL DEFAULT '{}',
+    "expires_at"      TIMESTAMP,
+    "last_used_time"  TIMESTAMP,
+    "create_time"     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "update_time"     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "is_delete"       BOOLEAN   NOT NULL DEFAULT FALSE,
+    CONSTRAINT uq_api_token_token_hash UNIQUE (token_hash),
+    CONSTRAINT fk_api_token_users FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    CONSTRAINT fk_api_token_organization FOREIGN KEY (organization_id) REFERENCES organization (id) ON DELETE CASCADE
+);
+
+CREATE INDEX "idx_api_token_user_id" ON "api_token" ("user_id");
@@ -0,0 +1,5 @@
+ALTER TABLE "users"
+ADD COLUMN "access_token" VARCHAR NOT NULL DEFAULT '',
+ADD COLUMN "refresh_token" VARCHAR NOT NULL DEFAULT '';
+
+DROP TABLE "user_session";
@@ -0,0 +1,21 @@
+CREATE TABLE "user_session"
+(
+    "id"                 UUID      NOT NULL PRIMARY KEY,
+    "user_id"            UUID      NOT NULL,
+    "access_token_hash"  VARCHAR   NOT NULL,
+    "refresh_token_hash" VARCHAR   NOT NULL,
+    "user_agent"         VARCHAR,
+    "ip_address"         VARCHAR,
+    "create_time"        TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "last_seen_time"     TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "expires_at"         TIMESTAMP NOT NULL,
+    "is_delete"          BOOLEAN   NOT NULL DEFAULT FALSE,
+    CONSTRAINT fk_user_session_users FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX "idx_user_session_user_id" ON "user_session" ("user_id");
+
+-- 会话表只保存 Token 哈希，原先保存在用户表中的 Token 明文不再使用，升级后需要重新登录
+ALTER TABLE "users"
+DROP COLUMN "access_token",
+DROP COLUMN "refresh_token";
@@ -0,0 +1 @@
+DROP TABLE "user_session_rotated_token";
@@ -0,0 +1,10 @@
+-- 每个会话就是一个刷新Token家族，记录家族中已轮换的刷新Token，用于发现被盗用后重放的旧Token
+CREATE TABLE "user_session_rotated_token"
+(
+    "token_hash"   VARCHAR   NOT NULL PRIMARY KEY,
+    "session_id"   UUID      NOT NULL,
+    "rotated_time" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_user_session_rotated_token_user_session FOREIGN KEY (session_id) REFERENCES user_session (id) ON DELETE CASCADE
+);
+
+CREATE INDEX "idx_user_session_rotated_token_session_id" ON "user_session_rotated_token" ("session_id");
@@ -0,0 +1,51 @@
+-- 生成的渠道包，每条记录是对渠道包文件的一个引用
+CREATE TABLE "app_channel_apk"
+(
+    "id"               UUID      NOT NULL PRIMARY KEY,
+    "channel_id"       UUID      NOT NULL,
+    "master_file_name" VARCHAR   NOT NULL,
+    "file_name"        VARCHAR   NOT NULL,
+    "create_time"      TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "update_time"      TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uq_app_channel_apk_channel_id_master_file_name UNIQUE (channel_id, master_file_name),
+    CONSTRAINT fk_app_channel_apk_app_channel FOREIGN KEY (channel_id) REFERENCES app_channel (id)
+);
+
+CREATE INDEX "idx_app_channel_apk_master_file_name" ON "app_channel_apk" ("master_file_name");
+
+-- 引用数改为在增删引用的事务中维护，按现有版本和补丁重新统计一次
+UPDATE "app_blob" SET "ref_count" = 0;
+
+UPDATE "app_blob"
+SET "ref_count" = refs."count"
+FROM (SELECT "name", COUNT(*) AS "count"
+      FROM (SELECT DISTINCT m."id", split_part(split_part(f."path", 'name=', 2), '&', 1) AS "name"
+            FROM "app_manage" m
+                     CROSS JOIN LATERAL (SELECT m."file_path" AS "path"
+                                         UNION
+                                         SELECT s ->> 'file_path'
+                                         FROM jsonb_array_elements(COALESCE(m."splits", '[]'::jsonb)) s) f
+            WHERE m."is_delete" = FALSE) release_files
+      GROUP BY "name") refs
+WHERE "app_blob"."kind" = 'apk'
+  AND "app_blob"."file_name" = refs."name";
+
+UPDATE "app_blob"
+SET "ref_count" = refs."count"
+FROM (SELECT split_part(split_part("app_icon_path", 'name=', 2), '&', 1) AS "name", COUNT(*) AS "count"
+      FROM "app_manage"
+      WHERE "is_delete" = FALSE
+      GROUP BY 1) refs
+WHERE "app_blob"."kind" = 'icon'
+  AND "app_blob"."file_name" = refs."name";
+
+UPDATE "app_blob"
+SET "ref_count" = refs."count"
+FROM (SELECT p."file_name" AS "name", COUNT(*) AS "count"
+      FROM "app_delta_patch" p
+               INNER JOIN "app_manage" m ON m."id" = p."app_id"
+      WHERE m."is_delete" = FALSE
+        AND p."status" = 'ready'
+      GROUP BY 1) refs
+WHERE "app_blob"."kind" = 'patch'
+  AND "app_blob"."file_name" = refs."name";
@@ -0,0 +1,5 @@
+DROP INDEX "idx_app_delta_patch_next_retry_time";
+
+ALTER TABLE "app_delta_patch"
+    DROP COLUMN "next_retry_time",
+    DROP COLUMN "failure_count";
@@ -0,0 +1,11 @@
+-- 生成失败的补丁按退避时间重试，达到重试上限后不再生成
+ALTER TABLE "app_delta_patch"
+    ADD COLUMN "failure_count"   INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN "next_retry_time" TIMESTAMP;
+
+UPDATE "app_delta_patch"
+SET "failure_count"   = 1,
+    "next_retry_time" = CURRENT_TIMESTAMP
+WHERE "status" = 'failed';
+
+CREATE INDEX "idx_app_delta_patch_next_retry_time" ON "app_delta_patch" ("next_retry_time");
@@ -0,0 +1,3 @@
+ALTER TABLE "app_signer_pin"
+DROP CONSTRAINT uq_app_signer_pin_package,
+ADD CONSTRAINT uq_app_signer_pin_package UNIQUE (organization_id, package_name);
@@ -0,0 +1,9 @@
+-- 签名证书绑定按包名全局唯一，只有持有绑定的组织可以发布该包；多个组织绑定了同一包名时保留最早的绑定
+DELETE FROM "app_signer_pin" AS "pin"
+USING "app_signer_pin" AS "earlier"
+WHERE "earlier"."package_name" = "pin"."package_name"
+  AND ("earlier"."create_time", "earlier"."id") < ("pin"."create_time", "pin"."id");
+
+ALTER TABLE "app_signer_pin"
+DROP CONSTRAINT uq_app_signer_pin_package,
+ADD CONSTRAINT uq_app_signer_pin_package UNIQUE (package_name);
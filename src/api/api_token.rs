@@ -0,0 +1,266 @@
+use crate::api::app_channel::find_organization_channel;
+use crate::middleware::permission_guard::require_login_session;
+use crate::model::api_token::{
+    ApiToken, ApiTokenItem, CreateApiTokenReq, CreateApiTokenResp, DeleteApiTokenReq,
+    DeleteApiTokenResp,
+};
+use crate::model::body::parse_json_body;
+use crate::model::error::{ApiOut, AppError};
+use crate::model::user_role::Permission;
+use crate::schema::*;
+use crate::utils::api_token_utils::{
+    api_token_prefix, generate_api_token, hash_api_token, parse_api_token_scopes,
+};
+use crate::utils::database_utils::{
+    current_organization, current_user, current_user_roles, try_connect_database,
+};
+use crate::utils::operation_log_utils::{
+    OP_CREATE_API_TOKEN, OP_DELETE_API_TOKEN, record_operation,
+};
+use chrono::Local;
+use diesel::prelude::*;
+use salvo::prelude::*;
+use salvo_oapi::endpoint;
+use uuid::Uuid;
+
+#[endpoint(
+    tags("api_token"),
+    summary = "创建API令牌",
+    description = "为当前用户在当前组织创建长期有效的API令牌，供 CI 在请求头 Authorization: Bearer 中使用；令牌权限不能超过用户角色，限定渠道时只能授予 channel:read、app:read 和 app:publish",
+    request_body = CreateApiTokenReq
+)]
+pub async fn create_api_token(depot: &mut Depot, req: &mut Request) -> ApiOut<CreateApiTokenResp> {
+    let create_req = match parse_json_body::<CreateApiTokenReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+    let token_name = create_req.token_name.trim();
+    if token_name.is_empty() {
+        return ApiOut::err(AppError::BadRequest("令牌名称不能为空".to_string()));
+    }
+    let permissions = match parse_api_token_scopes(&create_req.scopes) {
+        Ok(permissions) => permissions,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    // 令牌权限不能超过当前用户的角色
+    let roles = current_user_roles(depot);
+    if let Some(permission) = permissions
+        .iter()
+        .find(|permission| !roles.has_permission(**permission))
+    {
+        return ApiOut::err(AppError::FORBIDDEN(format!(
+            "当前用户没有'{}'权限，不能授予API令牌",
+            permission.as_str()
+        )));
+    }
+
+    // 渠道管理和版本管理不区分渠道，限定渠道的令牌只用于查看和发布
+    if !create_req.channel_ids.is_empty()
+        && let Some(permission) = permissions.iter().find(|permission| {
+            matches!(
+                permission,
+                Permission::ChannelManage | Permission::AppRelease
+            )
+        })
+    {
+        return ApiOut::err(AppError::BadRequest(format!(
+            "限定渠道的API令牌不能授予'{}'权限",
+            permission.as_str()
+        )));
+    }
+
+    let now = Local::now().naive_local();
+    if create_req
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= now)
+    {
+        return ApiOut::err(AppError::BadRequest("过期时间必须晚于当前时间".to_string()));
+    }
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let organization = match current_organization(depot) {
+        Ok(organization) => organization,
+        Err(err) => return ApiOut::err(err),
+    };
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let mut channel_ids = Vec::with_capacity(create_req.channel_ids.len());
+    for channel_id in create_req.channel_ids {
+        if let Err(err) = find_organization_channel(&mut conn, organization.id, channel_id) {
+            return ApiOut::err(err);
+        }
+        if !channel_ids.contains(&channel_id) {
+            channel_ids.push(channel_id);
+        }
+    }
+
+    let token = generate_api_token();
+    let new_token = ApiToken {
+        id: Uuid::new_v4(),
+        user_id: current_user.id,
+        organization_id: organization.id,
+        token_name: token_name.to_string(),
+        token_prefix: api_token_prefix(&token),
+        token_hash: hash_api_token(&token),
+        scopes: permissions
+            .iter()
+            .map(|permission| permission.as_str().to_string())
+            .collect(),
+        channel_ids,
+        expires_at: create_req.expires_at,
+        last_used_time: None,
+        create_time: now,
+        update_time: now,
+        is_delete: false,
+    };
+    if let Err(e) = diesel::insert_into(api_token::table)
+        .values(&new_token)
+        .execute(&mut conn)
+    {
+        return ApiOut::err(AppError::Internal(format!("创建API令牌失败：{}", e)));
+    }
+
+    if let Err(e) = record_operation(
+        &mut conn,
+        current_user.id,
+        &current_user.username,
+        OP_CREATE_API_TOKEN,
+        format!(
+            "在组织'{}'创建API令牌'{}'({})成功，权限：{}",
+            organization.name,
+            new_token.token_name,
+            new_token.token_prefix,
+            new_token.scopes.join(",")
+        ),
+    ) {
+        return ApiOut::err(e);
+    }
+
+    ApiOut::ok(CreateApiTokenResp {
+        token_id: new_token.id,
+        token_name: new_token.token_name,
+        token,
+        organization_id: organization.id,
+        expires_at: new_token.expires_at,
+        create_info: "API令牌创建成功，请妥善保存 token，之后无法再次查看".to_string(),
+    })
+}
+
+#[endpoint(
+    tags("api_token"),
+    summary = "获取我的API令牌列表",
+    description = "获取当前用户未吊销的全部API令牌，不包含令牌明文"
+)]
+pub async fn get_api_token_list(depot: &mut Depot) -> ApiOut<Vec<ApiTokenItem>> {
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    match api_token::table
+        .filter(api_token::user_id.eq(current_user.id))
+        .filter(api_token::is_delete.eq(false))
+        .order(api_token::create_time.desc())
+        .load::<ApiToken>(&mut conn)
+    {
+        Ok(tokens) => ApiOut::ok(
+            tokens
+                .into_iter()
+                .map(|token| ApiTokenItem {
+                    token_id: token.id,
+                    token_name: token.token_name,
+                    token_prefix: token.token_prefix,
+                    organization_id: token.organization_id,
+                    scopes: token.scopes,
+                    channel_ids: token.channel_ids,
+                    expires_at: token.expires_at,
+                    last_used_time: token.last_used_time,
+                    create_time: token.create_time,
+                })
+                .collect(),
+        ),
+        Err(e) => ApiOut::err(AppError::Internal(format!("查询API令牌失败：{}", e))),
+    }
+}
+
+#[endpoint(
+    tags("api_token"),
+    summary = "吊销API令牌",
+    description = "吊销当前用户的API令牌，吊销后立即失效",
+    request_body = DeleteApiTokenReq
+)]
+pub async fn delete_api_token(depot: &mut Depot, req: &mut Request) -> ApiOut<DeleteApiTokenResp> {
+    let delete_req = match parse_json_body::<DeleteApiTokenReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let result = diesel::update(
+        api_token::table
+            .filter(api_token::id.eq(delete_req.token_id))
+            .filter(api_token::user_id.eq(current_user.id))
+            .filter(api_token::is_delete.eq(false)),
+    )
+    .set((
+        api_token::is_delete.eq(true),
+        api_token::update_time.eq(Local::now().naive_local()),
+    ))
+    .get_result::<ApiToken>(&mut conn)
+    .optional();
+
+    match result {
+        Ok(Some(token)) => {
+            if let Err(e) = record_operation(
+                &mut conn,
+                current_user.id,
+                &current_user.username,
+                OP_DELETE_API_TOKEN,
+                format!(
+                    "吊销API令牌'{}'({})成功",
+                    token.token_name, token.token_prefix
+                ),
+            ) {
+                return ApiOut::err(e);
+            }
+
+            ApiOut::ok(DeleteApiTokenResp {
+                token_id: token.id,
+                delete_info: format!("API令牌'{}'已吊销", token.token_name),
+            })
+        }
+        Ok(None) => ApiOut::err(AppError::NotFound(format!(
+            "API令牌Id'{}' 未找到",
+            delete_req.token_id
+        ))),
+        Err(e) => ApiOut::err(AppError::Internal(format!("吊销API令牌失败:{}", e))),
+    }
+}
+
+// API令牌只能由登录用户管理，不能用API令牌创建新令牌
+pub fn api_token_router() -> Router {
+    Router::with_path("api_token")
+        .hoop(require_login_session())
+        .push(Router::with_path("create_api_token").post(create_api_token))
+        .push(Router::with_path("get_api_token_list").post(get_api_token_list))
+        .push(Router::with_path("delete_api_token").post(delete_api_token))
+}
@@ -1,18 +1,31 @@
+use crate::middleware::permission_guard::require_permission;
+use crate::model::api_token::ApiTokenScope;
 use crate::model::app_channel::{
-    AppChannel, CreateAppChannelReq, CreateAppChannelResp, DeleteAppChannelReq,
-    DeleteAppChannelResp, GetAppChannelListReq, GetAppChannelListResp, GetAppChannelListRespItem,
-    SearchAppChannelReq, SearchAppChannelResp, UpdateAppChannelReq, UpdateAppChannelResp,
+    AppChannel, AppChannelKey, AppChannelKeyItem, CreateAppChannelKeyReq, CreateAppChannelKeyResp,
+    CreateAppChannelReq, CreateAppChannelResp, DeleteAppChannelKeyReq, DeleteAppChannelKeyResp,
+    DeleteAppChannelReq, DeleteAppChannelResp, GetAppChannelKeyListReq, GetAppChannelKeyListResp,
+    GetAppChannelListReq, GetAppChannelListResp, GetAppChannelListRespItem, SearchAppChannelReq,
+    SearchAppChannelResp, UpdateAppChannelReq, UpdateAppChannelResp,
 };
 use crate::model::body::parse_json_body;
 use crate::model::error::{ApiOut, AppError};
+use crate::model::user_role::Permission;
 use crate::model::users::User;
 use crate::schema::*;
-use crate::utils::database_utils::connect_database;
+use crate::utils::api_token_utils::api_token_channel_ids;
+use crate::utils::blob_store_utils::remove_channel_apks_by_channel;
+use crate::utils::database_utils::{
+    connect_database, current_api_token_scope, current_organization,
+};
 use crate::utils::operation_log_utils::{
-    OP_CREATE_APP_CHANNEL, OP_DELETE_APP_CHANNEL, record_operation,
+    OP_CREATE_APP_CHANNEL, OP_CREATE_APP_CHANNEL_KEY, OP_DELETE_APP_CHANNEL,
+    OP_DELETE_APP_CHANNEL_KEY, record_operation,
 };
+use crate::utils::request_signature_utils::generate_app_key_pair;
 use chrono::Local;
 use diesel::RunQueryDsl;
+use diesel::dsl::exists;
+use diesel::pg::Pg;
 use diesel::prelude::*;
 use salvo::prelude::*;
 use salvo_oapi::endpoint;
@@ -27,6 +40,15 @@ fn normalize_optional_text(value: &str) -> Option<String> {
     }
 }
 
+fn validate_min_supported_version_code(value: Option<i64>) -> Result<(), AppError> {
+    match value {
+        Some(code) if code < 0 => Err(AppError::BadRequest(
+            "最低支持版本号不能小于0".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
 #[endpoint(tags("app_channel"), summary = "创建渠道", description = "创建渠道",request_body = CreateAppChannelReq)]
 pub async fn create_app_channel(
     depot: &mut Depot,
@@ -45,15 +67,24 @@ pub async fn create_app_channel(
         .expect("未找到用户。")
         .username
         .clone();
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(e) => return ApiOut::err(e),
+    };
 
     if app_channel_create.channel_name.is_empty() {
         return ApiOut::err(AppError::BadRequest("渠道名称不能为空".to_string()));
     }
+    if let Err(e) =
+        validate_min_supported_version_code(app_channel_create.min_supported_version_code)
+    {
+        return ApiOut::err(e);
+    }
 
     //检查渠道是否存在
     let existing_app_channel = app_channel::table
         .filter(app_channel::channel_name.eq(&app_channel_create.channel_name))
-        .filter(app_channel::create_user_id.eq(&current_user_id))
+        .filter(app_channel::organization_id.eq(organization_id))
         .filter(app_channel::is_delete.eq(false))
         .first::<AppChannel>(&mut conn)
         .optional()
@@ -76,6 +107,10 @@ pub async fn create_app_channel(
         create_time: now,
         update_time: now,
         is_delete: false,
+        min_supported_version_code: app_channel_create.min_supported_version_code,
+        is_private: app_channel_create.is_private,
+        bind_download_device: app_channel_create.bind_download_device,
+        organization_id,
     };
 
     //插入数据到数据库
@@ -97,6 +132,9 @@ pub async fn create_app_channel(
             ApiOut::ok(CreateAppChannelResp {
                 channel_name: app_channel_create.channel_name.to_string(),
                 remark: app_channel_create.remark.trim().to_string(),
+                min_supported_version_code: app_channel_create.min_supported_version_code,
+                is_private: app_channel_create.is_private,
+                bind_download_device: app_channel_create.bind_download_device,
                 create_info: format!("渠道'{}'创建成功！", app_channel_create.channel_name),
             })
         }
@@ -117,7 +155,11 @@ pub async fn get_app_channel_list_by_page(
         Err(e) => return ApiOut::err(e),
     };
 
-    let user_id = depot.get::<User>("user").expect("未找到用户。").id;
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(e) => return ApiOut::err(e),
+    };
+    let token_scope = current_api_token_scope(depot);
 
     let mut conn = connect_database(depot);
     //检查分页参数是否合法
@@ -129,10 +171,7 @@ pub async fn get_app_channel_list_by_page(
 
     let channel_name_keyword = get_app_channel_list_req.channel_name.trim();
 
-    let mut total_channel_query = app_channel::table
-        .filter(app_channel::create_user_id.eq(&user_id))
-        .filter(app_channel::is_delete.eq(false))
-        .into_boxed();
+    let mut total_channel_query = organization_channel_query(organization_id, token_scope.as_ref());
 
     if !channel_name_keyword.is_empty() {
         total_channel_query = total_channel_query.filter(
@@ -152,11 +191,9 @@ pub async fn get_app_channel_list_by_page(
             / get_app_channel_list_req.page_size
     };
 
-    //分页查询当前用户下的所有渠道
-    let mut all_app_channel_query = app_channel::table
-        .filter(app_channel::create_user_id.eq(&user_id))
-        .filter(app_channel::is_delete.eq(false))
-        .into_boxed();
+    //分页查询当前组织下的所有渠道
+    let mut all_app_channel_query =
+        organization_channel_query(organization_id, token_scope.as_ref());
 
     if !channel_name_keyword.is_empty() {
         all_app_channel_query = all_app_channel_query.filter(
@@ -169,7 +206,7 @@ pub async fn get_app_channel_list_by_page(
         .limit(get_app_channel_list_req.page_size)
         .offset(get_app_channel_list_req.page_index * get_app_channel_list_req.page_size)
         .load::<AppChannel>(&mut conn)
-        .expect("获取当前用户的渠道数据失败");
+        .expect("获取当前组织的渠道数据失败");
 
     let app_channel_list = merge_channel_list_resp(all_app_channel);
 
@@ -182,6 +219,21 @@ pub async fn get_app_channel_list_by_page(
     ApiOut::ok(resp)
 }
 
+///查询当前组织下未删除的渠道，限定渠道的API令牌只能查到令牌范围内的渠道
+fn organization_channel_query(
+    organization_id: Uuid,
+    token_scope: Option<&ApiTokenScope>,
+) -> app_channel::BoxedQuery<'static, Pg> {
+    let mut query = app_channel::table
+        .filter(app_channel::organization_id.eq(organization_id))
+        .filter(app_channel::is_delete.eq(false))
+        .into_boxed();
+    if let Some(channel_ids) = api_token_channel_ids(token_scope) {
+        query = query.filter(app_channel::id.eq_any(channel_ids.to_vec()));
+    }
+    query
+}
+
 ///合并渠道列表响应
 fn merge_channel_list_resp(all_app_channel: Vec<AppChannel>) -> Vec<GetAppChannelListRespItem> {
     let app_channel_list: Vec<GetAppChannelListRespItem> = all_app_channel
@@ -190,6 +242,9 @@ fn merge_channel_list_resp(all_app_channel: Vec<AppChannel>) -> Vec<GetAppChanne
             channel_id: channel.id,
             channel_name: channel.channel_name.to_string(),
             remark: channel.remark.unwrap_or_default(),
+            min_supported_version_code: channel.min_supported_version_code,
+            is_private: channel.is_private,
+            bind_download_device: channel.bind_download_device,
             create_time: channel.create_time,
             update_time: channel.update_time,
         })
@@ -199,19 +254,21 @@ fn merge_channel_list_resp(all_app_channel: Vec<AppChannel>) -> Vec<GetAppChanne
 
 #[endpoint(
     tags("app_channel"),
-    summary = "获取当前用户下所有渠道列表",
-    description = "获取当前用户下所有渠道列表"
+    summary = "获取当前组织下所有渠道列表",
+    description = "获取当前组织下所有渠道列表"
 )]
 pub async fn get_app_channel_list(depot: &mut Depot) -> ApiOut<GetAppChannelListResp> {
-    let user_id = depot.get::<User>("user").expect("未找到用户。").id;
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(e) => return ApiOut::err(e),
+    };
+    let token_scope = current_api_token_scope(depot);
     let mut conn = connect_database(depot);
-    //查询当前用户下的所有渠道
-    let all_app_channel = app_channel::table
-        .filter(app_channel::create_user_id.eq(&user_id))
-        .filter(app_channel::is_delete.eq(false))
+    //查询当前组织下的所有渠道
+    let all_app_channel = organization_channel_query(organization_id, token_scope.as_ref())
         .order(app_channel::create_time.desc())
         .load::<AppChannel>(&mut conn)
-        .expect("获取当前用户的渠道数据失败");
+        .expect("获取当前组织的渠道数据失败");
 
     let app_channel_list = merge_channel_list_resp(all_app_channel);
     let list_len = app_channel_list.len() as i64;
@@ -234,13 +291,16 @@ pub async fn search_app_channel(
         Ok(v) => v,
         Err(e) => return ApiOut::err(e),
     };
-    let user_id = depot.get::<User>("user").expect("未找到用户。").id;
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let token_scope = current_api_token_scope(depot);
 
     let mut conn = connect_database(depot);
     //根据渠道名称查询渠道
-    let app_channel_list = app_channel::table
-        .filter(app_channel::create_user_id.eq(&user_id))
-        .filter(app_channel::is_delete.eq(false))
+    let app_channel_list = organization_channel_query(organization_id, token_scope.as_ref())
         .filter(
             app_channel::channel_name.like(format!("%{}%", search_app_channel_req.channel_name)),
         )
@@ -270,32 +330,67 @@ pub async fn update_app_channel(
         Err(e) => return ApiOut::err(e),
     };
 
+    if let Err(e) = validate_min_supported_version_code(app_channel_req.min_supported_version_code)
+    {
+        return ApiOut::err(e);
+    }
+    if app_channel_req.clear_min_supported_version_code
+        && app_channel_req.min_supported_version_code.is_some()
+    {
+        return ApiOut::err(AppError::BadRequest(
+            "清除最低支持版本号时不能同时设置最低支持版本号".to_string(),
+        ));
+    }
+    // 未传最低支持版本号时保持不变，明确要求清除时置空
+    let min_supported_version_code = if app_channel_req.clear_min_supported_version_code {
+        Some(None)
+    } else {
+        app_channel_req.min_supported_version_code.map(Some)
+    };
+
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(e) => return ApiOut::err(e),
+    };
+
     let mut conn = connect_database(depot);
 
-    let result = diesel::update(app_channel::table.find(app_channel_req.channel_id))
-        .set((
-            app_channel::channel_name.eq(&app_channel_req.channel_name),
-            app_channel::remark.eq(normalize_optional_text(&app_channel_req.remark)),
-            app_channel::update_time.eq(&Local::now().naive_local()),
-            app_channel::is_delete.eq(false),
-        ))
-        .execute(&mut conn);
+    let result = diesel::update(
+        app_channel::table
+            .find(app_channel_req.channel_id)
+            .filter(app_channel::organization_id.eq(organization_id)),
+    )
+    .set((
+        app_channel::channel_name.eq(&app_channel_req.channel_name),
+        app_channel::remark.eq(normalize_optional_text(&app_channel_req.remark)),
+        min_supported_version_code.map(|value| app_channel::min_supported_version_code.eq(value)),
+        // 未传的私有渠道设置保持不变
+        app_channel_req
+            .is_private
+            .map(|value| app_channel::is_private.eq(value)),
+        app_channel_req
+            .bind_download_device
+            .map(|value| app_channel::bind_download_device.eq(value)),
+        app_channel::update_time.eq(&Local::now().naive_local()),
+        app_channel::is_delete.eq(false),
+    ))
+    .returning(AppChannel::as_returning())
+    .get_result(&mut conn)
+    .optional();
 
     match result {
-        Ok(affected_rows) => {
-            if affected_rows == 0 {
-                ApiOut::err(AppError::NotFound(
-                    format!("渠道Id'{}' 未找到", app_channel_req.channel_id).to_string(),
-                ))
-            } else {
-                ApiOut::ok(UpdateAppChannelResp {
-                    channel_id: app_channel_req.channel_id,
-                    channel_name: app_channel_req.channel_name,
-                    remark: app_channel_req.remark.trim().to_string(),
-                    update_info: "更新渠道信息成功".to_string(),
-                })
-            }
-        }
+        Ok(None) => ApiOut::err(AppError::NotFound(
+            format!("渠道Id'{}' 未找到", app_channel_req.channel_id).to_string(),
+        )),
+        Ok(Some(app_channel)) => ApiOut::ok(UpdateAppChannelResp {
+            channel_id: app_channel.id,
+            channel_name: app_channel.channel_name,
+            remark: app_channel.remark.unwrap_or_default(),
+            min_supported_version_code: app_channel.min_supported_version_code,
+            is_private: app_channel.is_private,
+            bind_download_device: app_channel.bind_download_device,
+            update_info: "更新渠道信息成功".to_string(),
+        }),
         Err(e) => ApiOut::err(AppError::Internal(
             format!("更新渠道信息失败:{}", e).to_string(),
         )),
@@ -312,12 +407,27 @@ pub async fn delete_app_channel(
         Err(e) => return ApiOut::err(e),
     };
 
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(e) => return ApiOut::err(e),
+    };
     let mut conn = connect_database(depot);
     let current_user = depot.get::<User>("user").expect("未找到用户。");
 
-    let result = diesel::update(app_channel::table.find(app_channel_req.channel_id))
+    // 删除渠道时一并删除其渠道包记录并释放引用
+    let result = conn.transaction::<_, AppError, _>(|conn| {
+        let affected_rows = diesel::update(
+            app_channel::table
+                .find(app_channel_req.channel_id)
+                .filter(app_channel::organization_id.eq(organization_id)),
+        )
         .set((app_channel::is_delete.eq(&true),))
-        .execute(&mut conn);
+        .execute(conn)?;
+        if affected_rows > 0 {
+            remove_channel_apks_by_channel(conn, app_channel_req.channel_id)?;
+        }
+        Ok(affected_rows)
+    });
 
     match result {
         Ok(affected_rows) => {
@@ -358,12 +468,31 @@ pub async fn completely_delete_app_channel(
         Err(e) => return ApiOut::err(e),
     };
 
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(e) => return ApiOut::err(e),
+    };
     let mut conn = connect_database(depot);
     let current_user = depot.get::<User>("user").expect("未找到用户。");
 
-    let affected =
-        diesel::delete(app_channel::table.filter(app_channel::id.eq(app_channel_req.channel_id)))
-            .execute(&mut conn);
+    let affected = conn.transaction::<_, AppError, _>(|conn| {
+        let channel_exists = diesel::select(exists(
+            app_channel::table
+                .filter(app_channel::id.eq(app_channel_req.channel_id))
+                .filter(app_channel::organization_id.eq(organization_id)),
+        ))
+        .get_result::<bool>(conn)?;
+        if !channel_exists {
+            return Ok(0);
+        }
+        remove_channel_apks_by_channel(conn, app_channel_req.channel_id)?;
+        Ok(diesel::delete(
+            app_channel::table
+                .filter(app_channel::id.eq(app_channel_req.channel_id))
+                .filter(app_channel::organization_id.eq(organization_id)),
+        )
+        .execute(conn)?)
+    });
 
     match affected {
         Ok(0) => ApiOut::err(AppError::NotFound(
@@ -391,14 +520,325 @@ pub async fn completely_delete_app_channel(
     }
 }
 
+#[endpoint(
+    tags("app_channel"),
+    summary = "创建渠道访问密钥",
+    description = "为渠道创建 app_key/app_secret，渠道存在未吊销的密钥后，检查更新接口必须使用密钥签名访问",
+    request_body = CreateAppChannelKeyReq
+)]
+pub async fn create_app_channel_key(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<CreateAppChannelKeyResp> {
+    let create_key_req = match parse_json_body::<CreateAppChannelKeyReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(e) => return ApiOut::err(e),
+    };
+    let mut conn = connect_database(depot);
+    let current_user = depot.get::<User>("user").expect("未找到用户。").clone();
+
+    let channel =
+        match find_organization_channel(&mut conn, organization_id, create_key_req.channel_id) {
+            Ok(channel) => channel,
+            Err(e) => return ApiOut::err(e),
+        };
+
+    let now = Local::now().naive_local();
+    let (app_key, app_secret) = generate_app_key_pair();
+    let channel_key = AppChannelKey {
+        id: Uuid::new_v4(),
+        channel_id: channel.id,
+        app_key,
+        app_secret,
+        create_user_id: current_user.id,
+        create_time: now,
+        update_time: now,
+        is_delete: false,
+    };
+    if let Err(e) = diesel::insert_into(app_channel_key::table)
+        .values(&channel_key)
+        .execute(&mut conn)
+    {
+        return ApiOut::err(AppError::Internal(format!("创建渠道访问密钥失败：{}", e)));
+    }
+
+    if let Err(e) = record_operation(
+        &mut conn,
+        current_user.id,
+        &current_user.username,
+        OP_CREATE_APP_CHANNEL_KEY,
+        format!(
+            "为渠道'{}'创建访问密钥'{}'成功",
+            channel.channel_name, channel_key.app_key
+        ),
+    ) {
+        return ApiOut::err(e);
+    }
+
+    ApiOut::ok(CreateAppChannelKeyResp {
+        key_id: channel_key.id,
+        channel_id: channel.id,
+        app_key: channel_key.app_key,
+        app_secret: channel_key.app_secret,
+        create_info: "渠道访问密钥创建成功，请妥善保存 app_secret，之后无法再次查看".to_string(),
+    })
+}
+
+#[endpoint(
+    tags("app_channel"),
+    summary = "获取渠道访问密钥列表",
+    description = "获取渠道未吊销的访问密钥，不返回 app_secret",
+    request_body = GetAppChannelKeyListReq
+)]
+pub async fn get_app_channel_key_list(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<GetAppChannelKeyListResp> {
+    let key_list_req = match parse_json_body::<GetAppChannelKeyListReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(e) => return ApiOut::err(e),
+    };
+    let mut conn = connect_database(depot);
+
+    let channel =
+        match find_organization_channel(&mut conn, organization_id, key_list_req.channel_id) {
+            Ok(channel) => channel,
+            Err(e) => return ApiOut::err(e),
+        };
+
+    let channel_keys = match app_channel_key::table
+        .filter(app_channel_key::channel_id.eq(channel.id))
+        .filter(app_channel_key::is_delete.eq(false))
+        .order(app_channel_key::create_time.desc())
+        .load::<AppChannelKey>(&mut conn)
+    {
+        Ok(channel_keys) => channel_keys,
+        Err(e) => {
+            return ApiOut::err(AppError::Internal(format!("查询渠道访问密钥失败:{}", e)));
+        }
+    };
+
+    ApiOut::ok(GetAppChannelKeyListResp {
+        key_list: channel_keys
+            .into_iter()
+            .map(|channel_key| AppChannelKeyItem {
+                key_id: channel_key.id,
+                app_key: channel_key.app_key,
+                create_time: channel_key.create_time,
+            })
+            .collect(),
+    })
+}
+
+#[endpoint(
+    tags("app_channel"),
+    summary = "吊销渠道访问密钥",
+    description = "吊销渠道访问密钥，渠道的密钥全部吊销后检查更新接口恢复匿名访问",
+    request_body = DeleteAppChannelKeyReq
+)]
+pub async fn delete_app_channel_key(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<DeleteAppChannelKeyResp> {
+    let delete_key_req = match parse_json_body::<DeleteAppChannelKeyReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(e) => return ApiOut::err(e),
+    };
+    let mut conn = connect_database(depot);
+    let current_user = depot.get::<User>("user").expect("未找到用户。").clone();
+
+    let organization_channel_ids = app_channel::table
+        .filter(app_channel::organization_id.eq(organization_id))
+        .select(app_channel::id);
+    let result = diesel::update(
+        app_channel_key::table
+            .filter(app_channel_key::id.eq(delete_key_req.key_id))
+            .filter(app_channel_key::channel_id.eq_any(organization_channel_ids))
+            .filter(app_channel_key::is_delete.eq(false)),
+    )
+    .set((
+        app_channel_key::is_delete.eq(true),
+        app_channel_key::update_time.eq(Local::now().naive_local()),
+    ))
+    .get_result::<AppChannelKey>(&mut conn)
+    .optional();
+
+    match result {
+        Ok(Some(channel_key)) => {
+            if let Err(e) = record_operation(
+                &mut conn,
+                current_user.id,
+                &current_user.username,
+                OP_DELETE_APP_CHANNEL_KEY,
+                format!("吊销渠道访问密钥'{}'成功", channel_key.app_key),
+            ) {
+                return ApiOut::err(e);
+            }
+
+            ApiOut::ok(DeleteAppChannelKeyResp {
+                key_id: channel_key.id,
+                delete_info: format!("渠道访问密钥'{}'已吊销", channel_key.app_key),
+            })
+        }
+        Ok(None) => ApiOut::err(AppError::NotFound(format!(
+            "渠道访问密钥Id'{}' 未找到",
+            delete_key_req.key_id
+        ))),
+        Err(e) => ApiOut::err(AppError::Internal(format!("吊销渠道访问密钥失败:{}", e))),
+    }
+}
+
+// 查询当前组织未删除的渠道
+pub fn find_organization_channel(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    channel_id: Uuid,
+) -> Result<AppChannel, AppError> {
+    app_channel::table
+        .filter(app_channel::id.eq(channel_id))
+        .filter(app_channel::organization_id.eq(organization_id))
+        .filter(app_channel::is_delete.eq(false))
+        .first::<AppChannel>(conn)
+        .optional()
+        .map_err(|e| AppError::Internal(format!("查询渠道失败:{}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("渠道Id'{}' 未找到", channel_id)))
+}
+
 pub fn app_channel_router() -> Router {
     Router::with_path("app_channel")
-        .push(Router::with_path("create_app_channel").post(create_app_channel))
-        .push(Router::with_path("get_app_channel_list_by_page").post(get_app_channel_list_by_page))
-        .push(Router::with_path("get_all_app_channel_list").post(get_app_channel_list))
-        .push(Router::with_path("update_app_channel").post(update_app_channel))
-        .push(Router::with_path("delete_app_channel").post(delete_app_channel))
         .push(
-            Router::with_path("completely_delete_app_channel").post(completely_delete_app_channel),
+            Router::new()
+                .hoop(require_permission(Permission::ChannelRead))
+                .push(
+                    Router::with_path("get_app_channel_list_by_page")
+                        .post(get_app_channel_list_by_page),
+                )
+                .push(Router::with_path("get_all_app_channel_list").post(get_app_channel_list)),
         )
+        .push(
+            Router::new()
+                .hoop(require_permission(Permission::ChannelManage))
+                .push(Router::with_path("create_app_channel").post(create_app_channel))
+                .push(Router::with_path("update_app_channel").post(update_app_channel))
+                .push(Router::with_path("delete_app_channel").post(delete_app_channel))
+                .push(
+                    Router::with_path("completely_delete_app_channel")
+                        .post(completely_delete_app_channel),
+                )
+                .push(Router::with_path("create_app_channel_key").post(create_app_channel_key))
+                .push(Router::with_path("get_app_channel_key_list").post(get_app_channel_key_list))
+                .push(Router::with_path("delete_app_channel_key").post(delete_app_channel_key)),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 连接真实数据库验证限定渠道的API令牌只能列出令牌范围内的渠道，未设置 `TEST_DATABASE_URL` 时跳过；
+    // 测试会执行数据库迁移，并在结束时删除创建的用户、组织和渠道
+    #[test]
+    fn channel_limited_token_lists_only_its_channels() {
+        use crate::db::MIGRATIONS;
+        use crate::model::organization::Organization;
+        use diesel_migrations::MigrationHarness;
+
+        let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("未设置 TEST_DATABASE_URL，跳过渠道令牌范围测试");
+            return;
+        };
+        let mut conn = PgConnection::establish(&database_url).unwrap();
+        conn.run_pending_migrations(MIGRATIONS).unwrap();
+
+        let now = Local::now().naive_local();
+        let user_id = Uuid::new_v4();
+        let username = format!("channel-scope-{}", user_id.simple());
+        diesel::insert_into(users::table)
+            .values(&User {
+                id: user_id,
+                username: username.clone(),
+                password: String::new(),
+                full_name: username,
+                create_time: now,
+                update_time: now,
+                is_delete: false,
+            })
+            .execute(&mut conn)
+            .unwrap();
+        let organization_id = Uuid::new_v4();
+        diesel::insert_into(organization::table)
+            .values(&Organization {
+                id: organization_id,
+                name: "渠道令牌范围测试".to_string(),
+                is_personal: false,
+                create_user_id: user_id,
+                create_time: now,
+                update_time: now,
+                is_delete: false,
+            })
+            .execute(&mut conn)
+            .unwrap();
+        let channel = |channel_name: &str| AppChannel {
+            id: Uuid::new_v4(),
+            channel_name: channel_name.to_string(),
+            remark: None,
+            create_user_id: user_id,
+            create_time: now,
+            update_time: now,
+            is_delete: false,
+            min_supported_version_code: None,
+            is_private: false,
+            bind_download_device: false,
+            organization_id,
+        };
+        let channels = vec![channel("channel-a"), channel("channel-b")];
+        let (channel_a, channel_b) = (channels[0].id, channels[1].id);
+        diesel::insert_into(app_channel::table)
+            .values(&channels)
+            .execute(&mut conn)
+            .unwrap();
+
+        let scope = ApiTokenScope {
+            token_id: Uuid::new_v4(),
+            permissions: vec![Permission::ChannelRead],
+            channel_ids: vec![channel_a],
+        };
+        let listed = |scope: Option<&ApiTokenScope>, conn: &mut PgConnection| {
+            organization_channel_query(organization_id, scope)
+                .select(app_channel::id)
+                .order(app_channel::channel_name.asc())
+                .load::<Uuid>(conn)
+                .unwrap()
+        };
+        let limited = listed(Some(&scope), &mut conn);
+        let unlimited = listed(None, &mut conn);
+
+        diesel::delete(app_channel::table.filter(app_channel::organization_id.eq(organization_id)))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::delete(organization::table.find(organization_id))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::delete(users::table.find(user_id))
+            .execute(&mut conn)
+            .unwrap();
+
+        assert_eq!(limited, vec![channel_a]);
+        assert_eq!(unlimited, vec![channel_a, channel_b]);
+    }
 }
@@ -1,161 +1,429 @@
+use crate::api::app_channel::find_organization_channel;
+use crate::middleware::permission_guard::require_permission;
+use crate::model::app_channel::AppChannel;
 use crate::model::app_manage::{
-    AppCheckUpdateReq, AppCheckUpdateResp, AppManage, DeleteAppReq, DeleteAppResp, GetAppInfoReq,
-    GetAppListReq, GetAppListResp, GetAppListRespItem, UploadAppFileCompleteReq,
-    UploadAppFileCompleteResp, UploadAppFileResp,
+    AppCheckUpdateReq, AppCheckUpdateResp, AppDeltaPatch, AppManage, AppReleaseDiffResp,
+    AppSignerPin, ApproveSignerRotationReq, ApproveSignerRotationResp, ChannelApkItem,
+    DeleteAppReq, DeleteAppResp, DeltaPatchItem, DeltaPatchStatus, GenerateChannelApksReq,
+    GenerateChannelApksResp, GetAppInfoReq, GetAppListReq, GetAppListResp, GetAppListRespItem,
+    GetAppReleaseDiffReq, ReleaseStatus, RevokeAppReq, RevokeAppResp, RolloutStatus, SplitApkItem,
+    SplitKind, UpdateAppForceUpdateReq, UpdateAppForceUpdateResp, UpdateAppRolloutReq,
+    UpdateAppRolloutResp, UpdateAppScheduleReq, UpdateAppScheduleResp, UpdateAppTargetingReq,
+    UpdateAppTargetingResp, UpdateType, UploadAppFileCompleteReq, UploadAppFileCompleteResp,
+    UploadAppFileResp,
 };
 use crate::model::body::parse_json_body;
 use crate::model::error::{ApiOut, AppError};
+use crate::model::user_role::Permission;
 use crate::schema::*;
-use crate::utils::apk_utils::extract_apk_metadata;
-use crate::utils::database_utils::{current_user, try_connect_database};
+use crate::store::{FileStore, fetch_to_local, get_file_store, get_nonce_store};
+use crate::utils::aab_utils::{extract_aab_metadata, is_app_bundle_file};
+use crate::utils::api_token_utils::{api_token_channel_ids, ensure_api_token_channel};
+use crate::utils::apk_signing_block_utils::{read_signing_block_file, write_channel_file};
+use crate::utils::apk_utils::{ApkIcon, ApkMetadata, extract_apk_metadata, extract_split_info};
+use crate::utils::blob_store_utils::{
+    BLOB_KIND_APK, BLOB_KIND_ICON, BLOB_KIND_PATCH, StagedFile, StagingDir, StoredBlob,
+    add_blob_references, blob_extension, blob_file_name_from_url, blob_key,
+    find_blob_original_name, lock_channel_apk_master, record_channel_apk, remove_blob_references,
+    remove_channel_apks_by_master, stage_file, store_blob_bytes, store_staged_file,
+};
+use crate::utils::bsdiff_utils::PATCH_FORMAT;
+use crate::utils::database_utils::{
+    current_api_token_scope, current_organization, current_user, try_connect_database,
+};
+use crate::utils::device_targeting_utils::{
+    is_release_available_for_device, validate_targeting_rules,
+};
+use crate::utils::download_signature_utils::{
+    download_url_expires_secs, download_url_secret, sign_download_url, sign_download_url_now,
+};
+use crate::utils::file_digest_utils::compute_file_digest;
+use crate::utils::manifest_diff_utils::{added_dangerous_permissions, diff_releases};
 use crate::utils::operation_log_utils::{
-    OP_DELETE_APP, OP_PUBLISH_APP, OP_UPLOAD_APP_FILE, record_operation,
+    OP_APPROVE_SIGNER_ROTATION, OP_DELETE_APP, OP_GENERATE_CHANNEL_APKS, OP_PUBLISH_APP,
+    OP_REVOKE_APP, OP_ROTATE_APP_SIGNER, OP_SCHEDULE_APP, OP_UPDATE_APP_FORCE_UPDATE,
+    OP_UPDATE_APP_ROLLOUT, OP_UPDATE_APP_TARGETING, OP_UPLOAD_APP_FILE, record_operation,
 };
-use chrono::Local;
+use crate::utils::request_signature_utils::{
+    APP_KEY_HEADER, CHECK_UPDATE_SIGNATURE_PATH, NONCE_HEADER, RequestSignature, SIGNATURE_HEADER,
+    SIGNATURE_TIMESTAMP_TOLERANCE_SECS, TIMESTAMP_HEADER, channels_with_active_keys,
+    find_active_channel_key, is_timestamp_fresh, is_valid_nonce,
+};
+use crate::utils::split_apk_utils::{
+    BASE_SPLIT_NAME, classify_split, extract_bundle_apks, is_bundle_file, select_splits_for_device,
+    split_native_abis, validate_split_set,
+};
+use chrono::{Local, NaiveDateTime};
 use diesel::PgTextExpressionMethods;
 use diesel::RunQueryDsl;
+use diesel::pg::Pg;
 use diesel::prelude::*;
-use salvo::oapi::extract::FormFile;
 use salvo::prelude::*;
+use sha2::{Digest, Sha256};
 use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
 use tracing::info;
 use uuid::Uuid;
 
-const APP_MANAGE_DIR: &str = "app_manage";
-const APK_UPLOAD_DIR: &str = "app_manage/apk";
 const PUBLIC_APP_MANAGE_PREFIX: &str = "/api/public/app_manage";
+const FULL_ROLLOUT_PERCENTAGE: i32 = 100;
+// 上传文件大小上限 1GB
+pub const MAX_UPLOAD_FILE_SIZE: i64 = 1024 * 1024 * 1024;
+// 上传文件支持的表单字段名，拆分 APK 可在同一字段中传入多个文件
+const UPLOAD_FILE_FIELDS: &[&str] = &["file", "upload_file", "app_file"];
 
 #[endpoint(
     tags("app_manage"),
     summary = "上传APP文件",
-    description = "上传APP文件"
+    description = "上传APP文件，拆分 APK 可上传 .apks/.xapk 安装包或在同一字段中传入基础包及其配置包"
 )]
 pub async fn upload_app_file(depot: &mut Depot, req: &mut Request) -> ApiOut<UploadAppFileResp> {
     // 默认安全上限仅 64KB，上传 APK 会在 multipart 解析阶段失败。
     // 上传文件大小上限设置为 1GB
-    req.set_secure_max_size(1024 * 1024 * 1024);
-
-    let file_part = match req.try_file("file").await {
-        Ok(Some(f)) => Some(f),
-        Ok(None) => match req.try_file("upload_file").await {
-            Ok(Some(f)) => Some(f),
-            Ok(None) => match req.try_file("app_file").await {
-                Ok(Some(f)) => Some(f),
-                Ok(None) => None,
-                Err(e) => {
-                    return ApiOut::err(AppError::BadRequest(format!(
-                        "解析 multipart 失败（app_file）: {}。请不要手动设置 Content-Type，并确认使用 form-data file 类型字段",
-                        e
-                    )));
-                }
-            },
+    req.set_secure_max_size(MAX_UPLOAD_FILE_SIZE as usize);
+
+    let mut uploaded_files: Vec<(String, PathBuf)> = Vec::new();
+    for field in UPLOAD_FILE_FIELDS {
+        match req.try_files(field).await {
+            Ok(Some(file_parts)) if !file_parts.is_empty() => {
+                uploaded_files = file_parts
+                    .iter()
+                    .map(|file_part| {
+                        let raw_name = file_part.name().unwrap_or("file.bin");
+                        let filename = Path::new(raw_name)
+                            .file_name()
+                            .and_then(|s| s.to_str())
+                            .filter(|s| !s.is_empty())
+                            .unwrap_or("file.bin");
+                        (filename.to_string(), file_part.path().clone())
+                    })
+                    .collect();
+                break;
+            }
+            Ok(_) => {}
             Err(e) => {
                 return ApiOut::err(AppError::BadRequest(format!(
-                    "解析 multipart 失败（upload_file）: {}。请不要手动设置 Content-Type，并确认使用 form-data file 类型字段",
-                    e
+                    "解析 multipart 失败（{}）: {}。请不要手动设置 Content-Type，并确认使用 form-data file 类型字段",
+                    field, e
                 )));
             }
-        },
-        Err(e) => {
-            return ApiOut::err(AppError::BadRequest(format!(
-                "解析 multipart 失败（file）: {}。请不要手动设置 Content-Type，并确认使用 form-data file 类型字段",
-                e
-            )));
         }
-    };
+    }
 
-    let Some(file_part) = file_part else {
+    if uploaded_files.is_empty() {
         return ApiOut::err(AppError::BadRequest(
             "未找到上传文件字段，请使用 multipart/form-data 并传入 file（兼容 upload_file/app_file）"
                 .to_string(),
         ));
-    };
+    }
+
+    finish_app_file_upload(depot, &uploaded_files).await
+}
 
-    let file = FormFile::new(file_part);
-    let raw_name = file.name().unwrap_or("file.bin");
-    let filename = Path::new(raw_name)
-        .file_name()
-        .and_then(|s| s.to_str())
-        .filter(|s| !s.is_empty())
-        .unwrap_or("file.bin");
-    let timestamp = Local::now().format("%Y%m%d%H%M%S%3f").to_string();
-    let stamped_filename = build_timestamped_filename(filename, &timestamp);
-    let dest = format!("{}/{}", APK_UPLOAD_DIR, stamped_filename);
+// 解析、校验并保存已接收的上传文件，普通上传和分片上传合并后共用
+pub async fn finish_app_file_upload(
+    depot: &mut Depot,
+    uploaded_files: &[(String, PathBuf)],
+) -> ApiOut<UploadAppFileResp> {
+    let store = match get_file_store(depot) {
+        Ok(store) => store,
+        Err(err) => return ApiOut::err(err),
+    };
+    let staging_dir = match StagingDir::new() {
+        Ok(staging_dir) => Arc::new(staging_dir),
+        Err(e) => return ApiOut::err(AppError::Internal(format!("创建暂存目录失败: {}", e))),
+    };
+    // 先在本地完成解析和校验，校验通过后再写入存储
+    let staged_files = match run_blocking({
+        let uploaded_files = uploaded_files.to_vec();
+        let staging_dir = staging_dir.clone();
+        move || stage_uploaded_apk_files(&uploaded_files, &staging_dir)
+    })
+    .await
+    {
+        Ok(staged_files) => staged_files,
+        Err(err) => return ApiOut::err(err),
+    };
+    let apk_files: Vec<(PathBuf, String)> = staged_files
+        .iter()
+        .map(|staged| (staged.path.clone(), staged.file_name.clone()))
+        .collect();
 
-    info!("uploading apk to {}", dest);
+    // 多个 APK 时按拆分 APK 处理，基础包作为发布文件
+    let (base_index, splits) = if apk_files.len() > 1 {
+        match run_blocking(move || resolve_split_set(&apk_files)).await {
+            Ok(split_set) => split_set,
+            Err(err) => return ApiOut::err(err),
+        }
+    } else {
+        (0, Vec::new())
+    };
+    let base_file = &staged_files[base_index];
+    let is_archive_only = is_app_bundle_file(&base_file.file_name);
 
-    if let Err(e) = std::fs::create_dir_all(APK_UPLOAD_DIR) {
-        return ApiOut::err(AppError::Internal(format!("创建上传目录失败: {}", e)));
+    let mut apk_metadata = match extract_upload_metadata(&base_file.path, &base_file.file_name) {
+        Ok(metadata) => metadata,
+        Err(e) => return ApiOut::err(AppError::Unprocessable(format!("APK 解析失败: {}", e))),
+    };
+    if splits.is_empty()
+        && let Some(split_name) = apk_metadata.split_name.as_deref()
+    {
+        return ApiOut::err(AppError::Unprocessable(format!(
+            "上传的 APK 是拆分配置包'{}'，请同时上传基础包及其配置包",
+            split_name
+        )));
     }
 
-    if let Err(e) = std::fs::copy(file.path(), &dest) {
-        ApiOut::err(AppError::Internal(format!("文件上传失败: {}", e)))
-    } else {
-        let apk_metadata = match extract_apk_metadata(
-            Path::new(&dest),
-            &stamped_filename,
-            Path::new(APP_MANAGE_DIR),
-        ) {
-            Ok(metadata) => metadata,
-            Err(e) => {
-                let _ = std::fs::remove_file(&dest);
-                return ApiOut::err(AppError::Unprocessable(format!("APK 解析失败: {}", e)));
-            }
-        };
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let current_user_id = current_user.id;
+    let current_username = current_user.username.clone();
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(err) => return ApiOut::err(err),
+    };
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    // 上传时提前校验签名，避免签名不一致的 APK 写入存储
+    let signer_check = resolve_release_signer_pin_action(
+        &mut conn,
+        organization_id,
+        &apk_metadata,
+        is_archive_only,
+    );
+    if let Err(err) = signer_check {
+        return ApiOut::err(err);
+    }
 
-        let current_user = match current_user(depot) {
-            Ok(user) => user,
-            Err(err) => return ApiOut::err(err),
-        };
-        let current_user_id = current_user.id;
-        let current_username = current_user.username.clone();
-        let mut conn = match try_connect_database(depot) {
-            Ok(conn) => conn,
+    // 写入失败时已写入的文件尚未被引用，由清理任务在保留期后删除
+    let apk_blobs = match store_uploaded_apk_files(&mut conn, store.as_ref(), &staged_files).await {
+        Ok(apk_blobs) => apk_blobs,
+        Err(err) => return ApiOut::err(err),
+    };
+    let icon_blob =
+        match store_app_icon(&mut conn, store.as_ref(), apk_metadata.app_icon.take()).await {
+            Ok(icon_blob) => icon_blob,
             Err(err) => return ApiOut::err(err),
         };
-        if let Err(e) = record_operation(
-            &mut conn,
-            current_user_id,
-            &current_username,
-            OP_UPLOAD_APP_FILE,
-            format!("上传应用文件'{}'成功", apk_metadata.file_name),
-        ) {
-            return ApiOut::err(e);
-        }
-
-        ApiOut::ok(UploadAppFileResp {
-            file_path: to_public_app_manage_file_url("apk", &dest),
-            file_name: apk_metadata.file_name,
-            app_name: apk_metadata.app_name,
-            package_name: apk_metadata.package_name,
-            app_icon_path: apk_metadata
-                .app_icon_path
-                .as_deref()
-                .map(|path| to_public_app_manage_file_url("icon", path)),
-            version_name: apk_metadata.version_name,
-            version_code: apk_metadata.version_code,
-            file_size: apk_metadata.file_size,
-            upload_file_info: "文件上传成功！".to_string(),
-        })
+    let base_blob = &apk_blobs[base_index];
+    let is_deduplicated = apk_blobs.iter().all(|blob| blob.is_deduplicated);
+    let mut upload_detail = if splits.is_empty() {
+        format!("上传应用文件'{}'成功", apk_metadata.file_name)
+    } else {
+        format!(
+            "上传应用文件'{}'成功，拆分 APK 共 {} 个",
+            apk_metadata.file_name,
+            splits.len()
+        )
+    };
+    if is_deduplicated {
+        upload_detail.push_str("，已存在相同内容的文件，未重复保存");
     }
+    if let Err(e) = record_operation(
+        &mut conn,
+        current_user_id,
+        &current_username,
+        OP_UPLOAD_APP_FILE,
+        upload_detail,
+    ) {
+        return ApiOut::err(e);
+    }
+
+    let native_abis = if splits.is_empty() {
+        apk_metadata.native_abis
+    } else {
+        split_native_abis(&apk_metadata.native_abis, &splits)
+    };
+
+    ApiOut::ok(UploadAppFileResp {
+        file_path: to_public_app_manage_file_url("apk", &base_blob.file_name),
+        file_name: apk_metadata.file_name,
+        app_name: apk_metadata.app_name,
+        package_name: apk_metadata.package_name,
+        app_icon_path: icon_blob
+            .as_ref()
+            .map(|blob| to_public_app_manage_file_url("icon", &blob.file_name)),
+        version_name: apk_metadata.version_name,
+        version_code: apk_metadata.version_code,
+        file_size: apk_metadata.file_size,
+        min_sdk_version: apk_metadata.min_sdk_version,
+        native_abis,
+        signer_sha256: apk_metadata.signer_sha256,
+        signature_schemes: apk_metadata.signature_schemes,
+        target_sdk_version: apk_metadata.target_sdk_version,
+        permissions: apk_metadata.permissions,
+        features: apk_metadata.features,
+        is_debuggable: apk_metadata.is_debuggable,
+        is_test_only: apk_metadata.is_test_only,
+        splits,
+        is_archive_only,
+        file_sha256: base_blob.digest.sha256.clone(),
+        file_md5: base_blob.digest.md5.clone(),
+        is_deduplicated,
+        upload_file_info: "文件上传成功！".to_string(),
+    })
 }
 
-//组装带时间格式的APP文件名称
-fn build_timestamped_filename(filename: &str, timestamp: &str) -> String {
-    let path = Path::new(filename);
-    let stem = path
-        .file_stem()
-        .and_then(|value| value.to_str())
-        .filter(|value| !value.trim().is_empty())
-        .unwrap_or("file");
-    let extension = path.extension().and_then(|value| value.to_str());
+// 暂存上传的 APK 文件并计算摘要，.apks/.xapk 安装包解压出其中的拆分 APK
+fn stage_uploaded_apk_files(
+    uploaded_files: &[(String, PathBuf)],
+    staging_dir: &StagingDir,
+) -> Result<Vec<StagedFile>, AppError> {
+    if let [(filename, temp_path)] = uploaded_files
+        && is_bundle_file(filename)
+    {
+        info!("extracting apk bundle {}", filename);
+        let bundle_apks = extract_bundle_apks(temp_path)
+            .map_err(|e| AppError::Unprocessable(format!("安装包解析失败: {}", e)))?;
+        return bundle_apks
+            .into_iter()
+            .map(|(entry_name, bytes)| {
+                let original_name = Path::new(&entry_name)
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or(&entry_name);
+                staging_dir
+                    .stage_bytes(&bytes, original_name, "apk")
+                    .map_err(|e| {
+                        AppError::Internal(format!(
+                            "暂存安装包中的 APK '{}' 失败: {}",
+                            entry_name, e
+                        ))
+                    })
+            })
+            .collect();
+    }
+
+    let mut staged_files: Vec<StagedFile> = Vec::with_capacity(uploaded_files.len());
+    for (filename, temp_path) in uploaded_files {
+        if is_bundle_file(filename) || (uploaded_files.len() > 1 && is_app_bundle_file(filename)) {
+            return Err(AppError::BadRequest(format!(
+                "安装包'{}'需要单独上传，不能与其他文件一起上传",
+                filename
+            )));
+        }
 
-    match extension.filter(|value| !value.trim().is_empty()) {
-        Some(extension) => format!("{stem}_{timestamp}.{extension}"),
-        None => format!("{stem}_{timestamp}"),
+        info!("uploading apk {}", filename);
+        let staged = stage_file(temp_path, filename, &blob_extension(filename, "apk"))
+            .map_err(|e| AppError::Internal(format!("文件上传失败: {}", e)))?;
+        if staged_files
+            .iter()
+            .any(|saved| saved.file_name == staged.file_name)
+        {
+            return Err(AppError::BadRequest(format!("上传文件'{}'重复", filename)));
+        }
+        staged_files.push(staged);
+    }
+
+    Ok(staged_files)
+}
+
+// 按内容摘要将暂存的 APK 登记并写入存储，已存在相同内容的文件时直接复用
+async fn store_uploaded_apk_files(
+    conn: &mut PgConnection,
+    store: &dyn FileStore,
+    staged_files: &[StagedFile],
+) -> Result<Vec<StoredBlob>, AppError> {
+    let mut apk_blobs: Vec<StoredBlob> = Vec::with_capacity(staged_files.len());
+    for staged in staged_files {
+        apk_blobs.push(store_staged_file(conn, store, BLOB_KIND_APK, staged).await?);
+    }
+    Ok(apk_blobs)
+}
+
+// 按内容摘要保存图标，相同图标只保存一份
+async fn store_app_icon(
+    conn: &mut PgConnection,
+    store: &dyn FileStore,
+    app_icon: Option<ApkIcon>,
+) -> Result<Option<StoredBlob>, AppError> {
+    match app_icon {
+        Some(icon) => store_blob_bytes(
+            conn,
+            store,
+            BLOB_KIND_ICON,
+            icon.data,
+            &icon.extension,
+            None,
+        )
+        .await
+        .map(Some),
+        None => Ok(None),
+    }
+}
+
+// 在阻塞线程池中执行计算摘要等耗时的同步操作，避免阻塞异步运行时
+async fn run_blocking<T, F>(f: F) -> Result<T, AppError>
+where
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::Internal(format!("后台任务执行失败: {}", e)))?
+}
+
+// 提取上传文件的元数据，AAB 解析 protobuf 清单，其余按 APK 解析
+fn extract_upload_metadata(path: &Path, file_name: &str) -> anyhow::Result<ApkMetadata> {
+    if is_app_bundle_file(file_name) {
+        extract_aab_metadata(path, file_name)
+    } else {
+        extract_apk_metadata(path, file_name)
     }
 }
 
+// 解析并校验拆分 APK，返回基础包的下标和拆分文件列表（基础包在前）
+fn resolve_split_set(
+    apk_files: &[(PathBuf, String)],
+) -> Result<(usize, Vec<SplitApkItem>), AppError> {
+    let split_infos = apk_files
+        .iter()
+        .map(|(path, file_name)| {
+            extract_split_info(path, file_name).map_err(|e| {
+                AppError::Unprocessable(format!("APK 解析失败（{}）: {}", file_name, e))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    validate_split_set(&split_infos)
+        .map_err(|e| AppError::Unprocessable(format!("拆分 APK 校验失败: {}", e)))?;
+
+    let base_index = split_infos
+        .iter()
+        .position(|info| info.split_name.is_none())
+        .ok_or_else(|| AppError::Unprocessable("拆分 APK 缺少基础包".to_string()))?;
+    let mut splits: Vec<SplitApkItem> = split_infos
+        .into_iter()
+        .zip(apk_files)
+        .map(|(info, (path, _))| {
+            let digest = compute_file_digest(path)
+                .map_err(|e| AppError::Internal(format!("计算文件摘要失败: {}", e)))?;
+            let (kind, qualifier) = classify_split(info.split_name.as_deref());
+            Ok(SplitApkItem {
+                split_name: info
+                    .split_name
+                    .unwrap_or_else(|| BASE_SPLIT_NAME.to_string()),
+                kind,
+                qualifier,
+                file_path: to_public_app_manage_file_url("apk", &info.file_name),
+                file_name: info.file_name,
+                file_size: info.file_size as i64,
+                file_sha256: Some(digest.sha256),
+                file_md5: Some(digest.md5),
+            })
+        })
+        .collect::<Result<_, AppError>>()?;
+    splits.sort_by(|left, right| {
+        (left.kind != SplitKind::Base, &left.split_name)
+            .cmp(&(right.kind != SplitKind::Base, &right.split_name))
+    });
+
+    Ok((base_index, splits))
+}
+
 // 组装公共APP管理文件URL
 fn to_public_app_manage_file_url(kind: &str, path: &str) -> String {
     let normalized = path.replace('\\', "/");
@@ -168,6 +436,198 @@ fn to_public_app_manage_file_url(kind: &str, path: &str) -> String {
     format!("{PUBLIC_APP_MANAGE_PREFIX}/{kind}?name={filename}")
 }
 
+/// 生成渠道包
+#[endpoint(
+    tags("app_manage"),
+    summary = "生成渠道包",
+    description = "将渠道名称写入已上传母包的 APK 签名块（VasDolly/Walle 格式），为每个渠道生成无需重新签名的渠道包",
+    request_body = GenerateChannelApksReq
+)]
+pub async fn generate_channel_apks(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<GenerateChannelApksResp> {
+    let generate_req = match parse_json_body::<GenerateChannelApksReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let master_filename = match resolve_uploaded_apk_path(&generate_req.file_path) {
+        Ok(file_name) => file_name,
+        Err(err) => return ApiOut::err(err),
+    };
+    if is_app_bundle_file(&master_filename) {
+        return ApiOut::err(AppError::BadRequest(
+            "AAB 文件不能生成渠道包，请上传 APK 母包".to_string(),
+        ));
+    }
+
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(err) => return ApiOut::err(err),
+    };
+    // 母包必须是当前组织版本引用的文件，不能读取其他组织上传的 APK
+    if let Err(err) = find_organization_app_by_file(&mut conn, organization_id, &master_filename) {
+        return ApiOut::err(err);
+    }
+
+    let store = match get_file_store(depot) {
+        Ok(store) => store,
+        Err(err) => return ApiOut::err(err),
+    };
+    // 母包下载到暂存目录后按区段复制生成渠道包，不把整个 APK 读入内存
+    let staging_dir = match StagingDir::new() {
+        Ok(dir) => dir,
+        Err(e) => return ApiOut::err(AppError::Internal(format!("创建暂存目录失败: {}", e))),
+    };
+    let master_path =
+        match fetch_uploaded_apk(store.as_ref(), &master_filename, staging_dir.path()).await {
+            Ok(path) => path,
+            Err(err) => return ApiOut::err(err),
+        };
+    let signing_block_path = master_path.clone();
+    match tokio::task::spawn_blocking(move || read_signing_block_file(&signing_block_path)).await {
+        Ok(Ok(Some(_))) => {}
+        Ok(Ok(None)) => {
+            return ApiOut::err(AppError::Unprocessable(
+                "母包未使用 v2 及以上签名，无法写入渠道信息".to_string(),
+            ));
+        }
+        Ok(Err(e)) => {
+            return ApiOut::err(AppError::Unprocessable(format!(
+                "解析母包签名块失败: {}",
+                e
+            )));
+        }
+        Err(e) => {
+            return ApiOut::err(AppError::Internal(format!("解析母包签名块失败: {}", e)));
+        }
+    }
+
+    // 渠道包文件名在母包原始文件名后追加渠道名称
+    let master_original_name =
+        match find_blob_original_name(&mut conn, BLOB_KIND_APK, &master_filename) {
+            Ok(original_name) => original_name,
+            Err(e) => {
+                return ApiOut::err(AppError::Internal(format!("查询母包文件失败:{}", e)));
+            }
+        };
+
+    let mut channel_query = app_channel::table
+        .filter(app_channel::organization_id.eq(organization_id))
+        .filter(app_channel::is_delete.eq(false))
+        .into_boxed();
+    if !generate_req.channel_ids.is_empty() {
+        channel_query = channel_query.filter(app_channel::id.eq_any(&generate_req.channel_ids));
+    }
+    // 限定渠道的API令牌未指定渠道时只生成令牌范围内的渠道包
+    let token_scope = current_api_token_scope(depot);
+    if let Some(scope) = token_scope
+        .as_ref()
+        .filter(|scope| !scope.channel_ids.is_empty())
+    {
+        for channel_id in &generate_req.channel_ids {
+            if let Err(err) = ensure_api_token_channel(Some(scope), *channel_id) {
+                return ApiOut::err(err);
+            }
+        }
+        channel_query = channel_query.filter(app_channel::id.eq_any(scope.channel_ids.clone()));
+    }
+    let channels = match channel_query
+        .order(app_channel::create_time.asc())
+        .load::<AppChannel>(&mut conn)
+    {
+        Ok(channels) => channels,
+        Err(e) => return ApiOut::err(AppError::Internal(format!("查询渠道失败:{}", e))),
+    };
+    if channels.is_empty() {
+        return ApiOut::err(AppError::NotFound("未找到可用的渠道".to_string()));
+    }
+    if let Some(missing_id) = generate_req
+        .channel_ids
+        .iter()
+        .find(|id| !channels.iter().any(|channel| channel.id == **id))
+    {
+        return ApiOut::err(AppError::NotFound(format!("渠道Id'{}' 未找到", missing_id)));
+    }
+
+    let mut channel_apk_list = Vec::with_capacity(channels.len());
+    for channel in &channels {
+        let file_name = build_channel_apk_filename(
+            master_original_name.as_deref().unwrap_or(&master_filename),
+            channel,
+        );
+        let staged = match stamp_channel_apk(
+            &master_path,
+            staging_dir.path(),
+            &channel.channel_name,
+            &file_name,
+        )
+        .await
+        {
+            Ok(staged) => staged,
+            Err(err) => return ApiOut::err(err),
+        };
+        let channel_blob =
+            match store_staged_file(&mut conn, store.as_ref(), BLOB_KIND_APK, &staged).await {
+                Ok(blob) => blob,
+                Err(err) => return ApiOut::err(err),
+            };
+        let _ = std::fs::remove_file(&staged.path);
+        // 渠道包记录和引用数在同一事务中写入，母包版本已被删除时不再记录
+        if let Err(err) = conn.transaction::<_, AppError, _>(|conn| {
+            lock_channel_apk_master(conn, &master_filename)?;
+            find_organization_app_by_file(conn, organization_id, &master_filename)?;
+            record_channel_apk(conn, channel.id, &master_filename, &channel_blob.file_name)
+        }) {
+            return ApiOut::err(err);
+        }
+        info!(
+            "generated channel apk {} for {}",
+            channel_blob.key, channel.channel_name
+        );
+
+        let file_path = to_public_app_manage_file_url("apk", &channel_blob.file_name);
+        channel_apk_list.push(ChannelApkItem {
+            channel_id: channel.id,
+            channel_name: channel.channel_name.clone(),
+            download_url: sign_download_url_now(&file_path),
+            file_path,
+            file_name,
+            file_size: channel_blob.file_size as i64,
+            file_sha256: channel_blob.digest.sha256,
+        });
+    }
+
+    if let Err(e) = record_operation(
+        &mut conn,
+        current_user.id,
+        &current_user.username,
+        OP_GENERATE_CHANNEL_APKS,
+        format!(
+            "为母包'{}'生成渠道包：{}",
+            master_filename,
+            channels
+                .iter()
+                .map(|channel| channel.channel_name.as_str())
+                .collect::<Vec<_>>()
+                .join("、")
+        ),
+    ) {
+        return ApiOut::err(e);
+    }
+
+    ApiOut::ok(GenerateChannelApksResp { channel_apk_list })
+}
+
 /// 发布应用
 #[endpoint(tags("app_manage"), summary = "发布应用", description = "发布应用")]
 pub async fn upload_app_file_complete(
@@ -193,18 +653,32 @@ pub async fn upload_app_file_complete(
         return ApiOut::err(AppError::BadRequest("文件路径不能为空".to_string()));
     }
 
-    let apk_path = match resolve_uploaded_apk_path(&get_upload_app_file_complete_req.file_path) {
-        Ok(path) => path,
+    let apk_filename = match resolve_uploaded_apk_path(&get_upload_app_file_complete_req.file_path)
+    {
+        Ok(file_name) => file_name,
         Err(err) => return ApiOut::err(err),
     };
 
-    let apk_filename = match apk_path.file_name().and_then(|value| value.to_str()) {
-        Some(name) if !name.trim().is_empty() => name.to_string(),
-        _ => return ApiOut::err(AppError::BadRequest("无效的上传文件路径".to_string())),
-    };
+    let is_archive_only = is_app_bundle_file(&apk_filename);
+    if is_archive_only && !get_upload_app_file_complete_req.split_file_paths.is_empty() {
+        return ApiOut::err(AppError::BadRequest("AAB 发布不支持拆分文件".to_string()));
+    }
 
-    let apk_metadata = match extract_apk_metadata(&apk_path, &apk_filename, Path::new(APP_MANAGE_DIR))
+    let store = match get_file_store(depot) {
+        Ok(store) => store,
+        Err(err) => return ApiOut::err(err),
+    };
+    let staging_dir = match StagingDir::new() {
+        Ok(staging_dir) => staging_dir,
+        Err(e) => return ApiOut::err(AppError::Internal(format!("创建暂存目录失败: {}", e))),
+    };
+    let apk_path = match fetch_uploaded_apk(store.as_ref(), &apk_filename, staging_dir.path()).await
     {
+        Ok(path) => path,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let mut apk_metadata = match extract_upload_metadata(&apk_path, &apk_filename) {
         Ok(metadata) => metadata,
         Err(e) => {
             return ApiOut::err(AppError::Unprocessable(format!(
@@ -214,6 +688,83 @@ pub async fn upload_app_file_complete(
         }
     };
 
+    // 拆分 APK 发布时 file_path 为基础包，其余拆分文件随版本一起保存
+    let splits = match collect_release_apk_files(
+        store.as_ref(),
+        staging_dir.path(),
+        &apk_path,
+        &apk_filename,
+        &get_upload_app_file_complete_req.split_file_paths,
+    )
+    .await
+    {
+        Ok(apk_files) if apk_files.len() > 1 => {
+            match run_blocking(move || resolve_split_set(&apk_files)).await {
+                Ok((0, splits)) => splits,
+                Ok(_) => {
+                    return ApiOut::err(AppError::BadRequest(
+                        "拆分 APK 发布时文件路径必须为基础包".to_string(),
+                    ));
+                }
+                Err(err) => return ApiOut::err(err),
+            }
+        }
+        Ok(_) => Vec::new(),
+        Err(err) => return ApiOut::err(err),
+    };
+    if splits.is_empty()
+        && let Some(split_name) = apk_metadata.split_name.as_deref()
+    {
+        return ApiOut::err(AppError::Unprocessable(format!(
+            "APK 是拆分配置包'{}'，请通过 split_file_paths 同时提交基础包及其配置包",
+            split_name
+        )));
+    }
+
+    let Some(version_code) = apk_metadata
+        .version_code
+        .clone()
+        .filter(|value| parse_version_code(value).is_some())
+    else {
+        return ApiOut::err(AppError::Unprocessable(
+            "APK 版本号(versionCode)缺失或不是有效数字".to_string(),
+        ));
+    };
+    let digest_path = apk_path.clone();
+    let file_digest = match run_blocking(move || {
+        compute_file_digest(&digest_path)
+            .map_err(|e| AppError::Internal(format!("计算文件摘要失败: {}", e)))
+    })
+    .await
+    {
+        Ok(digest) => digest,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let rollout_percentage = get_upload_app_file_complete_req
+        .rollout_percentage
+        .unwrap_or(FULL_ROLLOUT_PERCENTAGE);
+    if let Err(e) = validate_rollout_percentage(rollout_percentage) {
+        return ApiOut::err(e);
+    }
+
+    let now = Local::now().naive_local();
+    let publish_at = get_upload_app_file_complete_req.publish_at;
+    let expire_at = get_upload_app_file_complete_req.expire_at;
+    if let Err(e) = validate_release_window(publish_at, expire_at, now) {
+        return ApiOut::err(e);
+    }
+    let is_scheduled =
+        resolve_release_status(publish_at, expire_at, now) == ReleaseStatus::Scheduled;
+
+    let targeting_rules = match get_upload_app_file_complete_req.targeting_rules.as_ref() {
+        Some(rules) => match validate_targeting_rules(rules).and_then(|_| to_json_value(rules)) {
+            Ok(value) => Some(value),
+            Err(e) => return ApiOut::err(e),
+        },
+        None => None,
+    };
+
     let mut conn = match try_connect_database(depot) {
         Ok(conn) => conn,
         Err(err) => return ApiOut::err(err),
@@ -222,10 +773,89 @@ pub async fn upload_app_file_complete(
         Ok(user) => user,
         Err(err) => return ApiOut::err(err),
     };
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(err) => return ApiOut::err(err),
+    };
     let current_user_id = current_user.id;
     let current_username = current_user.username.clone();
 
-    let now = Local::now().naive_local();
+    // 只能发布到当前组织的渠道，版本记录的渠道名称以该渠道为准
+    let channel = match find_organization_channel(
+        &mut conn,
+        organization_id,
+        get_upload_app_file_complete_req.channel_id,
+    ) {
+        Ok(channel) => channel,
+        Err(err) => return ApiOut::err(err),
+    };
+    if let Err(err) = ensure_api_token_channel(
+        current_api_token_scope(depot).as_ref(),
+        get_upload_app_file_complete_req.channel_id,
+    ) {
+        return ApiOut::err(err);
+    }
+
+    let signer_pin_action = match resolve_release_signer_pin_action(
+        &mut conn,
+        organization_id,
+        &apk_metadata,
+        is_archive_only,
+    ) {
+        Ok(action) => action,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    // 相比同渠道上一版本新增危险权限时，需要发布人确认后才能发布
+    let previous_release = match find_previous_release(
+        &mut conn,
+        organization_id,
+        &apk_metadata.package_name,
+        get_upload_app_file_complete_req.channel_id,
+        &version_code,
+    ) {
+        Ok(app) => app,
+        Err(err) => return ApiOut::err(err),
+    };
+    let added_dangerous_permissions = previous_release
+        .as_ref()
+        .map(|app| added_dangerous_permissions(&app.permissions, &apk_metadata.permissions))
+        .unwrap_or_default();
+    if !added_dangerous_permissions.is_empty()
+        && !get_upload_app_file_complete_req.confirm_dangerous_permissions
+    {
+        return ApiOut::err(AppError::Custom {
+            status: StatusCode::CONFLICT,
+            msg: format!(
+                "相比上一版本新增危险权限：{}，请确认后设置 confirm_dangerous_permissions 重新提交",
+                added_dangerous_permissions.join("、")
+            ),
+            err_code: Some("DANGEROUS_PERMISSIONS_UNCONFIRMED".to_string()),
+        });
+    }
+
+    let components = match to_json_value(&apk_metadata.components) {
+        Ok(value) => value,
+        Err(err) => return ApiOut::err(err),
+    };
+    let (native_abis, splits_value) = if splits.is_empty() {
+        (apk_metadata.native_abis.clone(), None)
+    } else {
+        match to_json_value(&splits) {
+            Ok(value) => (
+                split_native_abis(&apk_metadata.native_abis, &splits),
+                Some(value),
+            ),
+            Err(err) => return ApiOut::err(err),
+        }
+    };
+
+    let icon_blob =
+        match store_app_icon(&mut conn, store.as_ref(), apk_metadata.app_icon.take()).await {
+            Ok(icon_blob) => icon_blob,
+            Err(err) => return ApiOut::err(err),
+        };
+
     let server_file_path = to_public_app_manage_file_url("apk", &apk_metadata.file_name);
 
     let new_app = AppManage {
@@ -239,46 +869,125 @@ pub async fn upload_app_file_complete(
         file_path: Some(server_file_path),
         file_name: Some(apk_metadata.file_name.clone()),
         package_name: Some(apk_metadata.package_name.clone()),
-        app_icon_path: apk_metadata
-            .app_icon_path
-            .as_deref()
-            .map(|path| to_public_app_manage_file_url("icon", path)),
+        app_icon_path: icon_blob
+            .as_ref()
+            .map(|blob| to_public_app_manage_file_url("icon", &blob.file_name)),
         version_name: apk_metadata.version_name.clone(),
-        version_code: apk_metadata.version_code.unwrap_or_default(),
+        version_code,
         file_size: apk_metadata.file_size as i64,
-        channel_name: Some(get_upload_app_file_complete_req.channel_name.clone()),
-        channel_id: get_upload_app_file_complete_req.channel_id,
+        channel_name: Some(channel.channel_name.clone()),
+        channel_id: channel.id,
         update_log: Some(get_upload_app_file_complete_req.update_log.clone()),
+        force_update: get_upload_app_file_complete_req.force_update,
+        rollout_percentage,
+        rollout_status: RolloutStatus::Active.as_str().to_string(),
+        min_sdk_version: apk_metadata.min_sdk_version,
+        native_abis,
+        targeting_rules,
+        is_revoked: false,
+        revoke_reason: None,
+        revoke_time: None,
+        rollback_app_id: None,
+        publish_at,
+        expire_at,
+        is_published: !is_scheduled,
+        signer_sha256: apk_metadata.signer_sha256.clone(),
+        signature_schemes: apk_metadata.signature_schemes.clone(),
+        target_sdk_version: apk_metadata.target_sdk_version,
+        permissions: apk_metadata.permissions.clone(),
+        features: apk_metadata.features.clone(),
+        is_debuggable: apk_metadata.is_debuggable,
+        is_test_only: apk_metadata.is_test_only,
+        components: Some(components),
+        splits: splits_value,
+        is_archive_only,
+        file_sha256: Some(file_digest.sha256),
+        file_md5: Some(file_digest.md5),
+        organization_id,
     };
 
-    match diesel::insert_into(app_manage::table)
-        .values(&new_app)
-        .execute(&mut conn)
-    {
+    // 版本记录和文件引用数在同一事务中写入
+    match conn.transaction::<_, AppError, _>(|conn| {
+        apply_signer_pin_action(conn, signer_pin_action, &new_app, now)?;
+        diesel::insert_into(app_manage::table)
+            .values(&new_app)
+            .execute(conn)?;
+        let (apk_names, icon_name) = release_blob_names(
+            new_app.file_path.as_deref(),
+            new_app.app_icon_path.as_deref(),
+            new_app.splits.as_ref(),
+        );
+        add_blob_references(conn, BLOB_KIND_APK, &apk_names)?;
+        add_blob_references(conn, BLOB_KIND_ICON, &Vec::from_iter(icon_name))
+    }) {
         Ok(_) => {
+            if signer_pin_action == SignerPinAction::Rotate
+                && let Err(e) = record_operation(
+                    &mut conn,
+                    current_user_id,
+                    &current_username,
+                    OP_ROTATE_APP_SIGNER,
+                    format!(
+                        "包'{}'签名证书已轮换为：{}",
+                        apk_metadata.package_name,
+                        new_app.signer_sha256.clone().unwrap_or_default()
+                    ),
+                )
+            {
+                return ApiOut::err(e);
+            }
+
+            let version_name = new_app.version_name.clone().unwrap_or_default();
+            // 定时发布的版本在上线时由后台任务记录发布事件
+            let (operation_type, operation_detail, upload_app_complete_info) =
+                match publish_at.filter(|_| is_scheduled) {
+                    Some(publish_at) => (
+                        OP_SCHEDULE_APP,
+                        format!(
+                            "预约发布应用'{}'，版本：{}，发布时间：{}{}",
+                            new_app.app_name,
+                            version_name,
+                            publish_at,
+                            dangerous_permissions_note(&added_dangerous_permissions)
+                        ),
+                        format!(
+                            "应用'{}' (版本：{}) 将于 {} 发布！",
+                            new_app.app_name, version_name, publish_at
+                        ),
+                    ),
+                    None => (
+                        OP_PUBLISH_APP,
+                        format!(
+                            "发布应用'{}'成功，版本：{}{}",
+                            new_app.app_name,
+                            version_name,
+                            dangerous_permissions_note(&added_dangerous_permissions)
+                        ),
+                        format!(
+                            "应用'{}' (版本：{}) 发布成功！",
+                            new_app.app_name, version_name
+                        ),
+                    ),
+                };
             if let Err(e) = record_operation(
                 &mut conn,
                 current_user_id,
                 &current_username,
-                OP_PUBLISH_APP,
-                format!(
-                    "发布应用'{}'成功，版本：{}",
-                    new_app.app_name,
-                    new_app.version_name.clone().unwrap_or_default()
-                ),
+                operation_type,
+                operation_detail,
             ) {
                 return ApiOut::err(e);
             }
 
             ApiOut::ok(UploadAppFileCompleteResp {
-                upload_app_complete_info: format!(
-                    "应用'{}' (版本：{}) 发布成功！",
-                    new_app.app_name,
-                    new_app.version_name.clone().unwrap_or_default()
-                ),
+                upload_app_complete_info,
+                added_dangerous_permissions,
             })
         }
-        Err(e) => ApiOut::err(AppError::Internal(format!("保存应用信息失败：{}", e))),
+        Err(AppError::Internal(e)) => {
+            ApiOut::err(AppError::Internal(format!("保存应用信息失败：{}", e)))
+        }
+        Err(err) => ApiOut::err(err),
     }
 }
 
@@ -296,8 +1005,8 @@ pub async fn get_app_list_by_page(depot: &mut Depot, req: &mut Request) -> ApiOu
         ));
     }
 
-    let user_id = match current_user(depot) {
-        Ok(user) => user.id,
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
         Err(err) => return ApiOut::err(err),
     };
     let mut conn = match try_connect_database(depot) {
@@ -306,9 +1015,13 @@ pub async fn get_app_list_by_page(depot: &mut Depot, req: &mut Request) -> ApiOu
     };
 
     let keyword = get_app_list_req.search_key.trim();
+    let now = Local::now().naive_local();
+    // 限定渠道的API令牌只能查看令牌范围内渠道的版本
+    let token_scope = current_api_token_scope(depot);
+    let token_channel_ids = api_token_channel_ids(token_scope.as_ref());
 
     let mut total_query = app_manage::table
-        .filter(app_manage::create_user_id.eq(&user_id))
+        .filter(app_manage::organization_id.eq(organization_id))
         .filter(app_manage::is_delete.eq(false))
         .into_boxed();
 
@@ -321,6 +1034,12 @@ pub async fn get_app_list_by_page(depot: &mut Depot, req: &mut Request) -> ApiOu
                 .or(app_manage::channel_name.ilike(pattern)),
         );
     }
+    if let Some(release_status) = get_app_list_req.release_status {
+        total_query = filter_by_release_status(total_query, release_status, now);
+    }
+    if let Some(channel_ids) = token_channel_ids {
+        total_query = total_query.filter(app_manage::channel_id.eq_any(channel_ids.to_vec()));
+    }
 
     let total_app_count = match total_query.count().get_result::<i64>(&mut conn) {
         Ok(count) => count,
@@ -334,7 +1053,7 @@ pub async fn get_app_list_by_page(depot: &mut Depot, req: &mut Request) -> ApiOu
     };
 
     let mut data_query = app_manage::table
-        .filter(app_manage::create_user_id.eq(&user_id))
+        .filter(app_manage::organization_id.eq(organization_id))
         .filter(app_manage::is_delete.eq(false))
         .into_boxed();
 
@@ -347,6 +1066,12 @@ pub async fn get_app_list_by_page(depot: &mut Depot, req: &mut Request) -> ApiOu
                 .or(app_manage::channel_name.ilike(pattern)),
         );
     }
+    if let Some(release_status) = get_app_list_req.release_status {
+        data_query = filter_by_release_status(data_query, release_status, now);
+    }
+    if let Some(channel_ids) = token_channel_ids {
+        data_query = data_query.filter(app_manage::channel_id.eq_any(channel_ids.to_vec()));
+    }
 
     let all_app_list = match data_query
         .order(app_manage::create_time.desc())
@@ -358,10 +1083,18 @@ pub async fn get_app_list_by_page(depot: &mut Depot, req: &mut Request) -> ApiOu
         Err(e) => return ApiOut::err(AppError::Internal(format!("获取应用列表失败:{}", e))),
     };
 
+    // 未发布和私有渠道的文件需要签名下载，管理列表统一返回签名地址
     let app_list = all_app_list
         .into_iter()
-        .map(|app| get_app_resp_item(&app))
-        .collect();
+        .map(|app| {
+            let mut item = get_app_resp_item(&app, now);
+            item.app_download_url = sign_download_url_now(&item.app_download_url);
+            for split in &mut item.splits {
+                split.file_path = sign_download_url_now(&split.file_path);
+            }
+            item
+        })
+        .collect();
 
     ApiOut::ok(GetAppListResp {
         app_list,
@@ -394,17 +1127,99 @@ pub async fn delete_app(depot: &mut Depot, req: &mut Request) -> ApiOut<DeleteAp
         Ok(user) => user,
         Err(err) => return ApiOut::err(err),
     };
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(err) => return ApiOut::err(err),
+    };
     let user_id = current_user.id;
     let username = current_user.username.clone();
 
+    // 删除版本和释放其文件引用在同一事务中执行
+    let result = conn.transaction::<_, AppError, _>(|conn| {
+        let deleted = diesel::update(
+            app_manage::table
+                .filter(app_manage::id.eq(delete_app_req.app_id))
+                .filter(app_manage::organization_id.eq(organization_id))
+                .filter(app_manage::is_delete.eq(false)),
+        )
+        .set((
+            app_manage::is_delete.eq(true),
+            app_manage::update_time.eq(Local::now().naive_local()),
+        ))
+        .returning(AppManage::as_returning())
+        .get_result::<AppManage>(conn)
+        .optional()?;
+        let Some(app) = deleted else {
+            return Ok(false);
+        };
+        release_app_blob_references(conn, &app)?;
+        Ok(true)
+    });
+
+    match result {
+        Ok(false) => ApiOut::err(AppError::NotFound(format!(
+            "应用Id'{}' 未找到",
+            delete_app_req.app_id
+        ))),
+        Ok(true) => {
+            if let Err(e) = record_operation(
+                &mut conn,
+                user_id,
+                &username,
+                OP_DELETE_APP,
+                format!("删除应用'{}'成功", delete_app_req.app_name),
+            ) {
+                return ApiOut::err(e);
+            }
+
+            ApiOut::ok(DeleteAppResp {
+                app_id: delete_app_req.app_id,
+                delete_info: format!("应用'{}'删除成功", delete_app_req.app_name),
+            })
+        }
+        Err(AppError::Internal(e)) => {
+            ApiOut::err(AppError::Internal(format!("删除应用失败:{}", e)))
+        }
+        Err(err) => ApiOut::err(err),
+    }
+}
+
+#[endpoint(
+    tags("app_manage"),
+    summary = "设置强制更新",
+    description = "设置应用版本是否强制更新",
+    request_body = UpdateAppForceUpdateReq
+)]
+pub async fn update_app_force_update(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<UpdateAppForceUpdateResp> {
+    let update_req = match parse_json_body::<UpdateAppForceUpdateReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(err) => return ApiOut::err(err),
+    };
+
     let result = diesel::update(
         app_manage::table
-            .filter(app_manage::id.eq(delete_app_req.app_id))
-            .filter(app_manage::create_user_id.eq(user_id))
+            .filter(app_manage::id.eq(update_req.app_id))
+            .filter(app_manage::organization_id.eq(organization_id))
             .filter(app_manage::is_delete.eq(false)),
     )
     .set((
-        app_manage::is_delete.eq(true),
+        app_manage::force_update.eq(update_req.force_update),
         app_manage::update_time.eq(Local::now().naive_local()),
     ))
     .execute(&mut conn);
@@ -412,35 +1227,43 @@ pub async fn delete_app(depot: &mut Depot, req: &mut Request) -> ApiOut<DeleteAp
     match result {
         Ok(0) => ApiOut::err(AppError::NotFound(format!(
             "应用Id'{}' 未找到",
-            delete_app_req.app_id
+            update_req.app_id
         ))),
         Ok(_) => {
             if let Err(e) = record_operation(
                 &mut conn,
-                user_id,
-                &username,
-                OP_DELETE_APP,
-                format!("删除应用'{}'成功", delete_app_req.app_name),
+                current_user.id,
+                &current_user.username,
+                OP_UPDATE_APP_FORCE_UPDATE,
+                format!(
+                    "设置应用'{}'强制更新为：{}",
+                    update_req.app_id, update_req.force_update
+                ),
             ) {
                 return ApiOut::err(e);
             }
 
-            ApiOut::ok(DeleteAppResp {
-                app_id: delete_app_req.app_id,
-                delete_info: format!("应用'{}'删除成功", delete_app_req.app_name),
+            ApiOut::ok(UpdateAppForceUpdateResp {
+                app_id: update_req.app_id,
+                force_update: update_req.force_update,
+                update_info: "设置强制更新成功".to_string(),
             })
         }
-        Err(e) => ApiOut::err(AppError::Internal(format!("删除应用失败:{}", e))),
+        Err(e) => ApiOut::err(AppError::Internal(format!("设置强制更新失败:{}", e))),
     }
 }
 
 #[endpoint(
-    tags("public"),
-    summary = "应用详情",
-    description = "根据应用ID查询应用详情"
+    tags("app_manage"),
+    summary = "调整灰度发布",
+    description = "提高应用版本灰度比例，或暂停、恢复、终止灰度发布",
+    request_body = UpdateAppRolloutReq
 )]
-pub async fn get_app_info(depot: &mut Depot, req: &mut Request) -> ApiOut<GetAppListRespItem> {
-    let get_app_info_req = match parse_json_body::<GetAppInfoReq>(req).await {
+pub async fn update_app_rollout(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<UpdateAppRolloutResp> {
+    let update_req = match parse_json_body::<UpdateAppRolloutReq>(req).await {
         Ok(v) => v,
         Err(e) => return ApiOut::err(e),
     };
@@ -449,121 +1272,1592 @@ pub async fn get_app_info(depot: &mut Depot, req: &mut Request) -> ApiOut<GetApp
         Ok(conn) => conn,
         Err(err) => return ApiOut::err(err),
     };
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(err) => return ApiOut::err(err),
+    };
+
     let app = match app_manage::table
+        .filter(app_manage::id.eq(update_req.app_id))
+        .filter(app_manage::organization_id.eq(organization_id))
         .filter(app_manage::is_delete.eq(false))
-        .filter(app_manage::id.eq(get_app_info_req.app_id))
         .first::<AppManage>(&mut conn)
     {
         Ok(app) => app,
         Err(diesel::result::Error::NotFound) => {
-            return ApiOut::err(AppError::NotFound("应用不存在".to_string()));
+            return ApiOut::err(AppError::NotFound(format!(
+                "应用Id'{}' 未找到",
+                update_req.app_id
+            )));
         }
-        Err(e) => return ApiOut::err(AppError::Internal(format!("获取应用详情失败:{}", e))),
+        Err(e) => return ApiOut::err(AppError::Internal(format!("查询应用失败:{}", e))),
+    };
+
+    let (rollout_percentage, rollout_status) = match resolve_rollout_change(&app, &update_req) {
+        Ok(value) => value,
+        Err(e) => return ApiOut::err(e),
     };
 
-    ApiOut::ok(get_app_resp_item(&app))
+    let result = diesel::update(app_manage::table.filter(app_manage::id.eq(app.id)))
+        .set((
+            app_manage::rollout_percentage.eq(rollout_percentage),
+            app_manage::rollout_status.eq(rollout_status.as_str()),
+            app_manage::update_time.eq(Local::now().naive_local()),
+        ))
+        .execute(&mut conn);
+
+    match result {
+        Ok(_) => {
+            if let Err(e) = record_operation(
+                &mut conn,
+                current_user.id,
+                &current_user.username,
+                OP_UPDATE_APP_ROLLOUT,
+                format!(
+                    "调整应用'{}'(版本号：{})灰度发布：{}% -> {}%，状态：{} -> {}",
+                    app.app_name,
+                    app.version_code,
+                    app.rollout_percentage,
+                    rollout_percentage,
+                    app.rollout_status,
+                    rollout_status.as_str()
+                ),
+            ) {
+                return ApiOut::err(e);
+            }
+
+            ApiOut::ok(UpdateAppRolloutResp {
+                app_id: app.id,
+                rollout_percentage,
+                rollout_status,
+                update_info: "调整灰度发布成功".to_string(),
+            })
+        }
+        Err(e) => ApiOut::err(AppError::Internal(format!("调整灰度发布失败:{}", e))),
+    }
 }
 
 #[endpoint(
-    tags("public"),
-    summary = "检查应用更新",
-    description = "根据包名和渠道查询最新应用版本",
-    request_body = AppCheckUpdateReq
+    tags("app_manage"),
+    summary = "设置设备定向规则",
+    description = "设置应用版本的设备定向规则，只有命中规则的设备才会收到该版本",
+    request_body = UpdateAppTargetingReq
 )]
-pub async fn app_check_update(depot: &mut Depot, req: &mut Request) -> ApiOut<AppCheckUpdateResp> {
-    let app_check_update_req = match parse_json_body::<AppCheckUpdateReq>(req).await {
+pub async fn update_app_targeting(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<UpdateAppTargetingResp> {
+    let update_req = match parse_json_body::<UpdateAppTargetingReq>(req).await {
         Ok(v) => v,
         Err(e) => return ApiOut::err(e),
     };
 
-    if let Err(e) = validate_app_check_update_req(&app_check_update_req) {
-        return ApiOut::err(e);
+    let targeting_rules = match update_req.targeting_rules.as_ref() {
+        Some(rules) => match validate_targeting_rules(rules).and_then(|_| to_json_value(rules)) {
+            Ok(value) => Some(value),
+            Err(e) => return ApiOut::err(e),
+        },
+        None => None,
+    };
+
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let result = diesel::update(
+        app_manage::table
+            .filter(app_manage::id.eq(update_req.app_id))
+            .filter(app_manage::organization_id.eq(organization_id))
+            .filter(app_manage::is_delete.eq(false)),
+    )
+    .set((
+        app_manage::targeting_rules.eq(&targeting_rules),
+        app_manage::update_time.eq(Local::now().naive_local()),
+    ))
+    .execute(&mut conn);
+
+    match result {
+        Ok(0) => ApiOut::err(AppError::NotFound(format!(
+            "应用Id'{}' 未找到",
+            update_req.app_id
+        ))),
+        Ok(_) => {
+            if let Err(e) = record_operation(
+                &mut conn,
+                current_user.id,
+                &current_user.username,
+                OP_UPDATE_APP_TARGETING,
+                format!(
+                    "设置应用'{}'设备定向规则为：{}",
+                    update_req.app_id,
+                    targeting_rules
+                        .as_ref()
+                        .map(|value| value.to_string())
+                        .unwrap_or_else(|| "不限制".to_string())
+                ),
+            ) {
+                return ApiOut::err(e);
+            }
+
+            ApiOut::ok(UpdateAppTargetingResp {
+                app_id: update_req.app_id,
+                targeting_rules: update_req.targeting_rules,
+                update_info: "设置设备定向规则成功".to_string(),
+            })
+        }
+        Err(e) => ApiOut::err(AppError::Internal(format!("设置设备定向规则失败:{}", e))),
+    }
+}
+
+#[endpoint(
+    tags("app_manage"),
+    summary = "撤回应用版本",
+    description = "撤回有问题的应用版本并记录原因，可指定回滚目标版本",
+    request_body = RevokeAppReq
+)]
+pub async fn revoke_app(depot: &mut Depot, req: &mut Request) -> ApiOut<RevokeAppResp> {
+    let revoke_req = match parse_json_body::<RevokeAppReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let revoke_reason = revoke_req.revoke_reason.trim().to_string();
+    if revoke_reason.is_empty() {
+        return ApiOut::err(AppError::BadRequest("撤回原因不能为空".to_string()));
     }
 
     let mut conn = match try_connect_database(depot) {
         Ok(conn) => conn,
         Err(err) => return ApiOut::err(err),
     };
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(err) => return ApiOut::err(err),
+    };
+
     let app = match app_manage::table
+        .filter(app_manage::id.eq(revoke_req.app_id))
+        .filter(app_manage::organization_id.eq(organization_id))
         .filter(app_manage::is_delete.eq(false))
-        .filter(app_manage::package_name.eq(Some(app_check_update_req.package_name.clone())))
-        .filter(app_manage::channel_name.eq(Some(app_check_update_req.channel_name.clone())))
-        .order((
-            app_manage::create_time.desc(),
-            app_manage::update_time.desc(),
-        ))
         .first::<AppManage>(&mut conn)
     {
         Ok(app) => app,
         Err(diesel::result::Error::NotFound) => {
-            return ApiOut::err(AppError::NotFound("未找到匹配的应用版本".to_string()));
+            return ApiOut::err(AppError::NotFound(format!(
+                "应用Id'{}' 未找到",
+                revoke_req.app_id
+            )));
         }
-        Err(e) => return ApiOut::err(AppError::Internal(format!("检查应用更新失败:{}", e))),
+        Err(e) => return ApiOut::err(AppError::Internal(format!("查询应用失败:{}", e))),
     };
 
-    ApiOut::ok(build_app_check_update_resp(&app))
-}
-
-// 校验应用更新请求参数
-fn validate_app_check_update_req(app_check_update_req: &AppCheckUpdateReq) -> Result<(), AppError> {
-    if app_check_update_req.package_name.trim().is_empty() {
-        return Err(AppError::BadRequest("包名不能为空".to_string()));
-    }
-
-    if app_check_update_req.channel_name.trim().is_empty() {
-        return Err(AppError::BadRequest("渠道不能为空".to_string()));
+    let rollback_app = match revoke_req.rollback_app_id {
+        Some(rollback_app_id) => match app_manage::table
+            .filter(app_manage::id.eq(rollback_app_id))
+            .filter(app_manage::organization_id.eq(organization_id))
+            .filter(app_manage::is_delete.eq(false))
+            .first::<AppManage>(&mut conn)
+        {
+            Ok(rollback_app) => Some(rollback_app),
+            Err(diesel::result::Error::NotFound) => {
+                return ApiOut::err(AppError::NotFound(format!(
+                    "回滚目标应用Id'{}' 未找到",
+                    rollback_app_id
+                )));
+            }
+            Err(e) => return ApiOut::err(AppError::Internal(format!("查询回滚目标失败:{}", e))),
+        },
+        None => None,
+    };
+    if let Some(rollback_app) = &rollback_app
+        && let Err(e) = validate_rollback_target(&app, rollback_app)
+    {
+        return ApiOut::err(e);
     }
 
-    Ok(())
-}
+    let result = diesel::update(app_manage::table.filter(app_manage::id.eq(app.id)))
+        .set((
+            app_manage::is_revoked.eq(true),
+            app_manage::revoke_reason.eq(Some(revoke_reason.clone())),
+            app_manage::revoke_time.eq(Some(Local::now().naive_local())),
+            app_manage::rollback_app_id.eq(revoke_req.rollback_app_id),
+            app_manage::update_time.eq(Local::now().naive_local()),
+        ))
+        .execute(&mut conn);
 
-// 构建应用更新响应
-fn build_app_check_update_resp(app: &AppManage) -> AppCheckUpdateResp {
-    AppCheckUpdateResp {
-        app_name: app.app_name.clone(),
-        package_name: app.package_name.clone().unwrap_or_default(),
-        channel_name: app.channel_name.clone().unwrap_or_default(),
-        version_name: app.version_name.clone().unwrap_or_default(),
-        version_code: app.version_code.clone(),
-        app_download_url: app.app_download_url.clone(),
-    }
-}
+    match result {
+        Ok(_) => {
+            let rollback_info = rollback_app
+                .as_ref()
+                .map(|rollback_app| format!("，回滚到版本号：{}", rollback_app.version_code))
+                .unwrap_or_default();
+            if let Err(e) = record_operation(
+                &mut conn,
+                current_user.id,
+                &current_user.username,
+                OP_REVOKE_APP,
+                format!(
+                    "撤回应用'{}'(版本号：{})，原因：{}{}",
+                    app.app_name, app.version_code, revoke_reason, rollback_info
+                ),
+            ) {
+                return ApiOut::err(e);
+            }
 
-// 应用详情响应项
-fn get_app_resp_item(app: &AppManage) -> GetAppListRespItem {
-    GetAppListRespItem {
-        app_id: app.id,
-        app_name: app.app_name.clone(),
-        app_download_url: app.app_download_url.clone(),
-        channel_id: app.channel_id,
-        file_path: app.file_path.clone().unwrap_or_default(),
-        file_name: app.file_name.clone().unwrap_or_default(),
-        package_name: app.package_name.clone().unwrap_or_default(),
-        app_icon_path: app.app_icon_path.clone().unwrap_or_default(),
-        version_name: app.version_name.clone().unwrap_or_default(),
-        version_code: app.version_code.clone(),
-        file_size: app.file_size,
-        channel_name: app.channel_name.clone().unwrap_or_default(),
-        update_log: app.update_log.clone().unwrap_or_default(),
-        create_time: app.create_time,
-        update_time: app.update_time,
+            ApiOut::ok(RevokeAppResp {
+                app_id: app.id,
+                rollback_app_id: revoke_req.rollback_app_id,
+                revoke_info: format!("应用'{}'(版本号：{})已撤回", app.app_name, app.version_code),
+            })
+        }
+        Err(e) => ApiOut::err(AppError::Internal(format!("撤回应用失败:{}", e))),
     }
 }
 
-fn resolve_uploaded_apk_path(file_path: &str) -> Result<PathBuf, AppError> {
-    let Some((path_part, query_part)) = file_path.split_once('?') else {
-        return Err(AppError::BadRequest("文件路径格式无效".to_string()));
+#[endpoint(
+    tags("app_manage"),
+    summary = "设置发布时间窗口",
+    description = "设置应用版本的定时发布时间和过期时间",
+    request_body = UpdateAppScheduleReq
+)]
+pub async fn update_app_schedule(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<UpdateAppScheduleResp> {
+    let update_req = match parse_json_body::<UpdateAppScheduleReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
     };
 
-    if path_part != format!("{PUBLIC_APP_MANAGE_PREFIX}/apk") {
-        return Err(AppError::BadRequest("文件路径不是受信任的上传 APK 地址".to_string()));
+    let now = Local::now().naive_local();
+    if let Err(e) = validate_release_window(update_req.publish_at, update_req.expire_at, now) {
+        return ApiOut::err(e);
     }
+    let release_status = resolve_release_status(update_req.publish_at, update_req.expire_at, now);
 
-    let filename = query_part
-        .split('&')
-        .find_map(|pair| pair.strip_prefix("name="))
-        .filter(|value| !value.trim().is_empty())
-        .ok_or_else(|| AppError::BadRequest("文件路径缺少 name 参数".to_string()))?;
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let app = match app_manage::table
+        .filter(app_manage::id.eq(update_req.app_id))
+        .filter(app_manage::organization_id.eq(organization_id))
+        .filter(app_manage::is_delete.eq(false))
+        .first::<AppManage>(&mut conn)
+    {
+        Ok(app) => app,
+        Err(diesel::result::Error::NotFound) => {
+            return ApiOut::err(AppError::NotFound(format!(
+                "应用Id'{}' 未找到",
+                update_req.app_id
+            )));
+        }
+        Err(e) => return ApiOut::err(AppError::Internal(format!("查询应用失败:{}", e))),
+    };
+    if let Err(err) =
+        ensure_api_token_channel(current_api_token_scope(depot).as_ref(), app.channel_id)
+    {
+        return ApiOut::err(err);
+    }
+
+    // 推迟到未来发布时重新标记为未上线，到期后由后台任务上线并记录发布事件
+    let is_published = app.is_published && release_status != ReleaseStatus::Scheduled;
+    let result = diesel::update(app_manage::table.filter(app_manage::id.eq(app.id)))
+        .set((
+            app_manage::publish_at.eq(update_req.publish_at),
+            app_manage::expire_at.eq(update_req.expire_at),
+            app_manage::is_published.eq(is_published),
+            app_manage::update_time.eq(now),
+        ))
+        .execute(&mut conn);
+
+    match result {
+        Ok(_) => {
+            if let Err(e) = record_operation(
+                &mut conn,
+                current_user.id,
+                &current_user.username,
+                OP_SCHEDULE_APP,
+                format!(
+                    "设置应用'{}'(版本号：{})发布时间：{}，过期时间：{}",
+                    app.app_name,
+                    app.version_code,
+                    update_req
+                        .publish_at
+                        .map(|value| value.to_string())
+                        .unwrap_or_else(|| "立即发布".to_string()),
+                    update_req
+                        .expire_at
+                        .map(|value| value.to_string())
+                        .unwrap_or_else(|| "不过期".to_string())
+                ),
+            ) {
+                return ApiOut::err(e);
+            }
+
+            ApiOut::ok(UpdateAppScheduleResp {
+                app_id: app.id,
+                publish_at: update_req.publish_at,
+                expire_at: update_req.expire_at,
+                release_status,
+                update_info: "设置发布时间窗口成功".to_string(),
+            })
+        }
+        Err(e) => ApiOut::err(AppError::Internal(format!("设置发布时间窗口失败:{}", e))),
+    }
+}
+
+#[endpoint(
+    tags("app_manage"),
+    summary = "批准签名证书轮换",
+    description = "批准包更换签名证书，使用新证书签名的版本发布后替换原有的签名绑定",
+    request_body = ApproveSignerRotationReq
+)]
+pub async fn approve_signer_rotation(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<ApproveSignerRotationResp> {
+    let approve_req = match parse_json_body::<ApproveSignerRotationReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let package_name = approve_req.package_name.trim();
+    if package_name.is_empty() {
+        return ApiOut::err(AppError::BadRequest("包名不能为空".to_string()));
+    }
+    let rotation_signer_sha256 = approve_req.signer_sha256.trim().to_ascii_lowercase();
+    if rotation_signer_sha256.len() != 64
+        || !rotation_signer_sha256
+            .chars()
+            .all(|c| c.is_ascii_hexdigit())
+    {
+        return ApiOut::err(AppError::BadRequest(
+            "签名证书指纹必须是64位十六进制SHA-256值".to_string(),
+        ));
+    }
+
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    // 只能批准当前组织持有的签名绑定，其他组织的绑定按不存在处理
+    let pin = match find_signer_pin(&mut conn, package_name) {
+        Ok(Some(pin)) if pin.organization_id == organization_id => pin,
+        Ok(_) => {
+            return ApiOut::err(AppError::NotFound(format!(
+                "包'{}'尚未绑定签名证书",
+                package_name
+            )));
+        }
+        Err(err) => return ApiOut::err(err),
+    };
+    if pin.signer_sha256 == rotation_signer_sha256 {
+        return ApiOut::err(AppError::BadRequest(
+            "新签名证书与当前绑定的签名证书相同".to_string(),
+        ));
+    }
+
+    let result = diesel::update(app_signer_pin::table.filter(app_signer_pin::id.eq(pin.id)))
+        .set((
+            app_signer_pin::rotation_signer_sha256.eq(Some(&rotation_signer_sha256)),
+            app_signer_pin::update_time.eq(Local::now().naive_local()),
+        ))
+        .execute(&mut conn);
+
+    match result {
+        Ok(_) => {
+            if let Err(e) = record_operation(
+                &mut conn,
+                current_user.id,
+                &current_user.username,
+                OP_APPROVE_SIGNER_ROTATION,
+                format!(
+                    "批准包'{}'签名证书由{}轮换为{}",
+                    package_name, pin.signer_sha256, rotation_signer_sha256
+                ),
+            ) {
+                return ApiOut::err(e);
+            }
+
+            ApiOut::ok(ApproveSignerRotationResp {
+                package_name: package_name.to_string(),
+                signer_sha256: pin.signer_sha256,
+                rotation_signer_sha256,
+                approve_info: "批准签名证书轮换成功".to_string(),
+            })
+        }
+        Err(e) => ApiOut::err(AppError::Internal(format!("批准签名证书轮换失败:{}", e))),
+    }
+}
+
+#[endpoint(
+    tags("app_manage"),
+    summary = "版本差异",
+    description = "比较两个版本解析出的清单信息，包括权限、SDK版本、特性、ABI和四大组件；未指定基准版本时与同渠道上一版本比较",
+    request_body = GetAppReleaseDiffReq
+)]
+pub async fn get_app_release_diff(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<AppReleaseDiffResp> {
+    let diff_req = match parse_json_body::<GetAppReleaseDiffReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    let organization_id = match current_organization(depot) {
+        Ok(organization) => organization.id,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let target = match find_organization_app(&mut conn, organization_id, diff_req.target_app_id) {
+        Ok(app) => app,
+        Err(err) => return ApiOut::err(err),
+    };
+    // 限定渠道的API令牌只能比较令牌范围内渠道的版本
+    let token_scope = current_api_token_scope(depot);
+    if let Err(err) = ensure_api_token_channel(token_scope.as_ref(), target.channel_id) {
+        return ApiOut::err(err);
+    }
+    let base = match diff_req.base_app_id {
+        Some(base_app_id) => match find_organization_app(&mut conn, organization_id, base_app_id) {
+            Ok(app) => app,
+            Err(err) => return ApiOut::err(err),
+        },
+        None => match find_previous_release(
+            &mut conn,
+            organization_id,
+            target.package_name.as_deref().unwrap_or_default(),
+            target.channel_id,
+            &target.version_code,
+        ) {
+            Ok(Some(app)) => app,
+            Ok(None) => {
+                return ApiOut::err(AppError::NotFound(format!(
+                    "应用Id'{}' 没有可对比的上一版本",
+                    target.id
+                )));
+            }
+            Err(err) => return ApiOut::err(err),
+        },
+    };
+
+    if let Err(err) = ensure_api_token_channel(token_scope.as_ref(), base.channel_id) {
+        return ApiOut::err(err);
+    }
+
+    if base.id == target.id {
+        return ApiOut::err(AppError::BadRequest("不能与自身比较".to_string()));
+    }
+    if base.package_name != target.package_name {
+        return ApiOut::err(AppError::BadRequest("只能比较同一包名的版本".to_string()));
+    }
+
+    ApiOut::ok(diff_releases(&base, &target))
+}
+
+#[endpoint(
+    tags("public"),
+    summary = "应用详情",
+    description = "根据应用ID查询应用详情"
+)]
+pub async fn get_app_info(depot: &mut Depot, req: &mut Request) -> ApiOut<GetAppListRespItem> {
+    let get_app_info_req = match parse_json_body::<GetAppInfoReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    let app = match app_manage::table
+        .filter(app_manage::is_delete.eq(false))
+        .filter(app_manage::id.eq(get_app_info_req.app_id))
+        .first::<AppManage>(&mut conn)
+    {
+        Ok(app) => app,
+        Err(diesel::result::Error::NotFound) => {
+            return ApiOut::err(AppError::NotFound("应用不存在".to_string()));
+        }
+        Err(e) => return ApiOut::err(AppError::Internal(format!("获取应用详情失败:{}", e))),
+    };
+
+    // 公开接口不暴露未到发布时间或已过期的版本，私有渠道的版本只通过检查更新下发
+    let now = Local::now().naive_local();
+    if !is_within_release_window(&app, now) {
+        return ApiOut::err(AppError::NotFound("应用不存在".to_string()));
+    }
+    match app_channel::table
+        .filter(app_channel::id.eq(app.channel_id))
+        .select(app_channel::is_private)
+        .first::<bool>(&mut conn)
+        .optional()
+    {
+        Ok(Some(true)) => return ApiOut::err(AppError::NotFound("应用不存在".to_string())),
+        Ok(_) => {}
+        Err(e) => return ApiOut::err(AppError::Internal(format!("获取应用详情失败:{}", e))),
+    }
+
+    ApiOut::ok(get_app_resp_item(&app, now))
+}
+
+#[endpoint(
+    tags("public"),
+    summary = "检查应用更新",
+    description = "根据包名和渠道查询最新应用版本",
+    request_body = AppCheckUpdateReq
+)]
+pub async fn app_check_update(depot: &mut Depot, req: &mut Request) -> ApiOut<AppCheckUpdateResp> {
+    let app_check_update_req = match parse_json_body::<AppCheckUpdateReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    if let Err(e) = validate_app_check_update_req(&app_check_update_req) {
+        return ApiOut::err(e);
+    }
+
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    let client_channel_id = match authenticate_check_update_client(
+        depot,
+        req,
+        &mut conn,
+        &app_check_update_req.channel_name,
+    )
+    .await
+    {
+        Ok(channel_id) => channel_id,
+        Err(err) => return ApiOut::err(err),
+    };
+    // 包名已绑定签名证书时只下发持有绑定的组织发布的版本
+    let pin_organization_id = match find_signer_pin(&mut conn, &app_check_update_req.package_name) {
+        Ok(pin) => pin.map(|pin| pin.organization_id),
+        Err(err) => return ApiOut::err(err),
+    };
+    // 渠道名称只在组织内唯一，签名请求只查询密钥所属渠道的版本
+    let mut apps_query = app_manage::table
+        .filter(app_manage::is_delete.eq(false))
+        .filter(app_manage::is_archive_only.eq(false))
+        .filter(app_manage::package_name.eq(Some(app_check_update_req.package_name.clone())))
+        .filter(app_manage::channel_name.eq(Some(app_check_update_req.channel_name.clone())))
+        .into_boxed();
+    if let Some(organization_id) = pin_organization_id {
+        apps_query = apps_query.filter(app_manage::organization_id.eq(organization_id));
+    }
+    if let Some(channel_id) = client_channel_id {
+        apps_query = apps_query.filter(app_manage::channel_id.eq(channel_id));
+    }
+    let apps = match apps_query.load::<AppManage>(&mut conn) {
+        Ok(apps) => apps,
+        Err(e) => return ApiOut::err(AppError::Internal(format!("检查应用更新失败:{}", e))),
+    };
+    // 匿名请求看不到配置了访问密钥的渠道，结果与渠道不存在时一致
+    let apps = match client_channel_id {
+        Some(_) => apps,
+        None => {
+            let channel_ids: Vec<Uuid> = apps.iter().map(|app| app.channel_id).collect();
+            let keyed_channel_ids = match channels_with_active_keys(&mut conn, &channel_ids) {
+                Ok(keyed_channel_ids) => keyed_channel_ids,
+                Err(e) => {
+                    return ApiOut::err(AppError::Internal(format!("查询渠道访问密钥失败:{}", e)));
+                }
+            };
+            let apps: Vec<AppManage> = apps
+                .into_iter()
+                .filter(|app| !keyed_channel_ids.contains(&app.channel_id))
+                .collect();
+            // 尚未绑定签名证书的包在多个组织存在同名渠道时无法确定发布方，拒绝匿名检查，避免下发其他组织上传的版本
+            if spans_multiple_organizations(&apps) {
+                return ApiOut::err(AppError::Custom {
+                    status: StatusCode::CONFLICT,
+                    msg: "该包名和渠道存在多个发布方，请使用 app_key 签名访问".to_string(),
+                    err_code: Some("APP_CHANNEL_AMBIGUOUS".to_string()),
+                });
+            }
+            apps
+        }
+    };
+
+    let now = Local::now().naive_local();
+    let revoked_release = find_revoked_release(&apps, app_check_update_req.version_code).cloned();
+
+    // 只保留当前设备可见且可安装的版本，未命中灰度或定向规则的设备回落到更早的版本
+    let device_bucket = app_check_update_req
+        .device_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|device_id| !device_id.is_empty())
+        .map(|device_id| rollout_bucket(&app_check_update_req.package_name, device_id));
+    let apps: Vec<AppManage> = apps
+        .into_iter()
+        .filter(|app| !app.is_revoked)
+        .filter(|app| is_within_release_window(app, now))
+        .filter(|app| is_visible_in_rollout(app, device_bucket))
+        .filter(|app| is_release_available_for_device(app, &app_check_update_req))
+        .collect();
+
+    // 客户端当前版本已被撤回且没有更新的可用版本时，下发指定的回滚版本
+    let rollback_app = revoked_release
+        .as_ref()
+        .and_then(|revoked_release| {
+            select_rollback_target(&apps, revoked_release, app_check_update_req.version_code)
+        })
+        .cloned();
+
+    // 渠道不存在、没有已发布版本或版本均未命中灰度和定向规则时，按无可用更新正常返回
+    let Some(app) = rollback_app.as_ref().or_else(|| select_latest_app(&apps)) else {
+        let mut resp = build_no_update_resp(&app_check_update_req);
+        if let Some(revoked_release) = &revoked_release {
+            mark_current_version_revoked(&mut resp, revoked_release);
+        }
+        return ApiOut::ok(resp);
+    };
+
+    let (min_supported_version_code, is_private, bind_download_device) = match app_channel::table
+        .filter(app_channel::id.eq(app.channel_id))
+        .select((
+            app_channel::min_supported_version_code,
+            app_channel::is_private,
+            app_channel::bind_download_device,
+        ))
+        .first::<(Option<i64>, bool, bool)>(&mut conn)
+        .optional()
+    {
+        Ok(value) => value.unwrap_or_default(),
+        Err(e) => return ApiOut::err(AppError::Internal(format!("查询渠道更新策略失败:{}", e))),
+    };
+    // 绑定设备的私有渠道需要设备ID签发下载地址
+    let download_device_id = if is_private && bind_download_device {
+        match app_check_update_req
+            .device_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|device_id| !device_id.is_empty())
+        {
+            Some(device_id) => Some(device_id.to_string()),
+            None => {
+                return ApiOut::err(AppError::BadRequest(
+                    "该渠道的下载地址绑定设备，请上报设备ID".to_string(),
+                ));
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut resp = match &rollback_app {
+        Some(rollback_app) => build_rollback_resp(rollback_app, min_supported_version_code),
+        None => build_app_check_update_resp(
+            app,
+            &apps,
+            app_check_update_req.version_code,
+            min_supported_version_code,
+        ),
+    };
+    if let Some(revoked_release) = &revoked_release {
+        mark_current_version_revoked(&mut resp, revoked_release);
+    }
+    if resp.app_download_url.is_some() {
+        resp.splits = select_splits_for_device(&parse_splits(app), &app_check_update_req);
+    }
+    if resp.app_download_url.is_some() && resp.splits.is_empty() {
+        resp.delta_patch = match find_delta_patch(&mut conn, app, &app_check_update_req) {
+            Ok(delta_patch) => delta_patch,
+            Err(err) => return ApiOut::err(err),
+        };
+    }
+    if is_private && resp.app_download_url.is_some() {
+        sign_check_update_urls(&mut resp, download_device_id.as_deref());
+    }
+
+    ApiOut::ok(resp)
+}
+
+// 版本是否来自多个组织
+fn spans_multiple_organizations(apps: &[AppManage]) -> bool {
+    apps.iter()
+        .any(|app| app.organization_id != apps[0].organization_id)
+}
+
+// 客户端携带 app_key 时校验请求签名，返回密钥所属渠道；未携带时为匿名访问
+async fn authenticate_check_update_client(
+    depot: &mut Depot,
+    req: &mut Request,
+    conn: &mut PgConnection,
+    channel_name: &str,
+) -> Result<Option<Uuid>, AppError> {
+    let Some(app_key) = req.header::<String>(APP_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let invalid = |msg: &str| AppError::unauthorized_with_code(msg, "APP_SIGNATURE_INVALID");
+    let (Some(timestamp), Some(nonce), Some(signature)) = (
+        req.header::<String>(TIMESTAMP_HEADER)
+            .and_then(|value| value.trim().parse::<i64>().ok()),
+        req.header::<String>(NONCE_HEADER),
+        req.header::<String>(SIGNATURE_HEADER),
+    ) else {
+        return Err(invalid(
+            "缺少签名参数，需要 X-Timestamp、X-Nonce 和 X-Signature 请求头",
+        ));
+    };
+    if !is_timestamp_fresh(timestamp, Local::now().timestamp()) {
+        return Err(AppError::unauthorized_with_code(
+            "请求时间戳已过期，请校准设备时间",
+            "APP_SIGNATURE_EXPIRED",
+        ));
+    }
+    if !is_valid_nonce(&nonce) {
+        return Err(invalid(
+            "X-Nonce 只能包含字母、数字、'-' 和 '_'，且不超过 64 个字符",
+        ));
+    }
+
+    let channel_key = find_active_channel_key(conn, &app_key)
+        .map_err(|e| AppError::Internal(format!("查询渠道访问密钥失败:{}", e)))?
+        .ok_or_else(|| invalid("app_key 无效或已吊销"))?;
+    let key_channel_name = app_channel::table
+        .filter(app_channel::id.eq(channel_key.channel_id))
+        .filter(app_channel::is_delete.eq(false))
+        .select(app_channel::channel_name)
+        .first::<String>(conn)
+        .optional()
+        .map_err(|e| AppError::Internal(format!("查询渠道失败:{}", e)))?;
+    if key_channel_name.as_deref() != Some(channel_name) {
+        return Err(invalid("app_key 不属于该渠道"));
+    }
+
+    let method = req.method().as_str().to_string();
+    let body = req
+        .payload()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("读取请求体失败: {}", e)))?
+        .clone();
+    let request_signature = RequestSignature {
+        method: &method,
+        path: CHECK_UPDATE_SIGNATURE_PATH,
+        timestamp,
+        nonce: &nonce,
+        body: &body,
+    };
+    if !request_signature.verify(&channel_key.app_secret, &signature) {
+        return Err(invalid("请求签名校验失败"));
+    }
+
+    // 随机数保留整个时间戳偏差窗口，窗口内重放的请求会被拒绝
+    let nonce_store = get_nonce_store(depot)?;
+    let nonce_ttl = chrono::Duration::seconds(2 * SIGNATURE_TIMESTAMP_TOLERANCE_SECS);
+    if !nonce_store.try_use(&app_key, &nonce, nonce_ttl).await? {
+        return Err(AppError::unauthorized_with_code(
+            "请求随机数已使用，请勿重放请求",
+            "APP_NONCE_REUSED",
+        ));
+    }
+
+    Ok(Some(channel_key.channel_id))
+}
+
+// 私有渠道下发带过期时间的签名下载地址，拆分 APK 的每个文件单独签名
+fn sign_check_update_urls(resp: &mut AppCheckUpdateResp, device_id: Option<&str>) {
+    let secret = download_url_secret();
+    let expires_at = Local::now().timestamp() + download_url_expires_secs();
+    resp.app_download_url = resp
+        .app_download_url
+        .as_deref()
+        .map(|url| sign_download_url(&secret, url, device_id, expires_at));
+    for split in &mut resp.splits {
+        split.file_path = sign_download_url(&secret, &split.file_path, device_id, expires_at);
+    }
+    if let Some(delta_patch) = &mut resp.delta_patch {
+        delta_patch.patch_url =
+            sign_download_url(&secret, &delta_patch.patch_url, device_id, expires_at);
+    }
+    resp.download_url_expires_at = Some(expires_at);
+}
+
+// 查询基于客户端当前安装包的差分补丁，按安装包 SHA-256 匹配基础版本，多个补丁时取最小的；
+// 未上报 SHA-256 时不下发补丁，版本号相同的安装包内容可能不同
+fn find_delta_patch(
+    conn: &mut PgConnection,
+    app: &AppManage,
+    app_check_update_req: &AppCheckUpdateReq,
+) -> Result<Option<DeltaPatchItem>, AppError> {
+    let Some(current_file_sha256) = app_check_update_req
+        .current_file_sha256
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_ascii_lowercase)
+    else {
+        return Ok(None);
+    };
+    let patches = app_delta_patch::table
+        .filter(app_delta_patch::app_id.eq(app.id))
+        .filter(app_delta_patch::status.eq(DeltaPatchStatus::Ready.as_str()))
+        .filter(app_delta_patch::base_file_sha256.eq(&current_file_sha256))
+        .load::<AppDeltaPatch>(conn)
+        .map_err(|e| AppError::Internal(format!("查询差分补丁失败:{}", e)))?;
+    if patches.is_empty() {
+        return Ok(None);
+    }
+    let base_app_ids: Vec<Uuid> = patches.iter().map(|patch| patch.base_app_id).collect();
+    let base_version_codes = app_manage::table
+        .filter(app_manage::id.eq_any(&base_app_ids))
+        .select((app_manage::id, app_manage::version_code))
+        .load::<(Uuid, String)>(conn)
+        .map_err(|e| AppError::Internal(format!("查询差分补丁失败:{}", e)))?;
+
+    let delta_patch = patches
+        .into_iter()
+        .filter_map(|patch| {
+            let (_, base_version_code) = base_version_codes
+                .iter()
+                .find(|(id, _)| *id == patch.base_app_id)?;
+            Some(DeltaPatchItem {
+                patch_format: PATCH_FORMAT.to_string(),
+                patch_url: to_public_app_manage_file_url("patch", &patch.file_name?),
+                patch_size: patch.patch_size?,
+                patch_sha256: patch.patch_sha256?,
+                base_version_code: base_version_code.clone(),
+                base_file_sha256: patch.base_file_sha256?,
+            })
+        })
+        .min_by_key(|delta_patch| delta_patch.patch_size);
+
+    Ok(delta_patch)
+}
+
+// 校验应用更新请求参数
+fn validate_app_check_update_req(app_check_update_req: &AppCheckUpdateReq) -> Result<(), AppError> {
+    if app_check_update_req.package_name.trim().is_empty() {
+        return Err(AppError::BadRequest("包名不能为空".to_string()));
+    }
+
+    if app_check_update_req.channel_name.trim().is_empty() {
+        return Err(AppError::BadRequest("渠道不能为空".to_string()));
+    }
+
+    Ok(())
+}
+
+// 解析数据库中以字符串保存的版本号
+fn parse_version_code(version_code: &str) -> Option<i64> {
+    version_code.trim().parse::<i64>().ok()
+}
+
+// 按数值版本号选出最新版本，版本号相同时取最新上传的记录
+fn select_latest_app(apps: &[AppManage]) -> Option<&AppManage> {
+    apps.iter()
+        .filter_map(|app| parse_version_code(&app.version_code).map(|code| (code, app)))
+        .max_by_key(|(code, app)| (*code, app.create_time, app.update_time))
+        .map(|(_, app)| app)
+}
+
+// 查询当前组织未删除的应用
+fn find_organization_app(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    app_id: Uuid,
+) -> Result<AppManage, AppError> {
+    app_manage::table
+        .filter(app_manage::id.eq(app_id))
+        .filter(app_manage::organization_id.eq(organization_id))
+        .filter(app_manage::is_delete.eq(false))
+        .first::<AppManage>(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => {
+                AppError::NotFound(format!("应用Id'{}' 未找到", app_id))
+            }
+            e => AppError::Internal(format!("查询应用失败:{}", e)),
+        })
+}
+
+// 查询当前组织中以该 APK 为安装包的未删除版本
+fn find_organization_app_by_file(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    file_name: &str,
+) -> Result<AppManage, AppError> {
+    app_manage::table
+        .filter(app_manage::organization_id.eq(organization_id))
+        .filter(app_manage::is_delete.eq(false))
+        .filter(app_manage::app_download_url.eq(to_public_app_manage_file_url("apk", file_name)))
+        .order(app_manage::create_time.desc())
+        .first::<AppManage>(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => {
+                AppError::NotFound("当前组织未发布该母包，请先发布母包".to_string())
+            }
+            e => AppError::Internal(format!("查询母包版本失败:{}", e)),
+        })
+}
+
+// 查找同包名同渠道下版本号低于指定版本的最新版本，只考虑已发布、未撤回且可下载的版本
+fn find_previous_release(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    package_name: &str,
+    channel_id: Uuid,
+    version_code: &str,
+) -> Result<Option<AppManage>, AppError> {
+    let Some(version_code) = parse_version_code(version_code) else {
+        return Ok(None);
+    };
+    let apps = app_manage::table
+        .filter(app_manage::organization_id.eq(organization_id))
+        .filter(app_manage::package_name.eq(package_name))
+        .filter(app_manage::channel_id.eq(channel_id))
+        .filter(app_manage::is_delete.eq(false))
+        .filter(app_manage::is_published.eq(true))
+        .filter(app_manage::is_revoked.eq(false))
+        .filter(app_manage::is_archive_only.eq(false))
+        .load::<AppManage>(conn)
+        .map_err(|e| AppError::Internal(format!("查询上一版本失败:{}", e)))?;
+
+    let earlier_apps: Vec<AppManage> = apps
+        .into_iter()
+        .filter(|app| parse_version_code(&app.version_code).is_some_and(|code| code < version_code))
+        .collect();
+    Ok(select_latest_app(&earlier_apps).cloned())
+}
+
+// 操作日志中追加新增危险权限说明
+fn dangerous_permissions_note(permissions: &[String]) -> String {
+    if permissions.is_empty() {
+        String::new()
+    } else {
+        format!("，新增危险权限：{}", permissions.join("、"))
+    }
+}
+
+/// 发布版本时对包签名绑定执行的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SignerPinAction {
+    ///首次发布，绑定当前签名证书
+    Pin,
+    ///签名证书与绑定一致
+    Keep,
+    ///使用已批准的新签名证书，完成轮换
+    Rotate,
+}
+
+// 查询包名绑定的签名证书，同一包名只有一个绑定，归属于首次发布该包的组织
+fn find_signer_pin(
+    conn: &mut PgConnection,
+    package_name: &str,
+) -> Result<Option<AppSignerPin>, AppError> {
+    app_signer_pin::table
+        .filter(app_signer_pin::package_name.eq(package_name))
+        .first::<AppSignerPin>(conn)
+        .optional()
+        .map_err(|e| AppError::Internal(format!("查询签名绑定失败:{}", e)))
+}
+
+// 校验发布组织持有包的签名绑定，且 APK 签名证书与绑定的签名证书一致
+fn resolve_signer_pin_action(
+    pin: Option<&AppSignerPin>,
+    organization_id: Uuid,
+    package_name: &str,
+    signer_sha256: Option<&str>,
+) -> Result<SignerPinAction, AppError> {
+    let Some(signer_sha256) = signer_sha256 else {
+        return Err(AppError::Unprocessable("APK 未签名，无法发布".to_string()));
+    };
+    let Some(pin) = pin else {
+        return Ok(SignerPinAction::Pin);
+    };
+    if pin.organization_id != organization_id {
+        return Err(AppError::FORBIDDEN(format!(
+            "包'{}'已由其他组织发布并绑定签名证书，不能发布",
+            package_name
+        )));
+    }
+
+    if pin.signer_sha256 == signer_sha256 {
+        Ok(SignerPinAction::Keep)
+    } else if pin.rotation_signer_sha256.as_deref() == Some(signer_sha256) {
+        Ok(SignerPinAction::Rotate)
+    } else {
+        Err(AppError::Unprocessable(format!(
+            "APK 签名证书({})与包'{}'已绑定的签名证书({})不一致，如需更换签名请先批准签名证书轮换",
+            signer_sha256, package_name, pin.signer_sha256
+        )))
+    }
+}
+
+// 校验发布文件的签名绑定，AAB 仅存档且签名通常为上传密钥，不参与签名绑定
+fn resolve_release_signer_pin_action(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    apk_metadata: &ApkMetadata,
+    is_archive_only: bool,
+) -> Result<SignerPinAction, AppError> {
+    if is_archive_only {
+        return Ok(SignerPinAction::Keep);
+    }
+
+    let signer_pin = find_signer_pin(conn, &apk_metadata.package_name)?;
+    resolve_signer_pin_action(
+        signer_pin.as_ref(),
+        organization_id,
+        &apk_metadata.package_name,
+        apk_metadata.signer_sha256.as_deref(),
+    )
+}
+
+// 按发布版本更新包签名绑定
+fn apply_signer_pin_action(
+    conn: &mut PgConnection,
+    action: SignerPinAction,
+    app: &AppManage,
+    now: NaiveDateTime,
+) -> Result<(), diesel::result::Error> {
+    let (Some(package_name), Some(signer_sha256)) = (&app.package_name, &app.signer_sha256) else {
+        return Ok(());
+    };
+
+    match action {
+        SignerPinAction::Keep => Ok(()),
+        SignerPinAction::Pin => diesel::insert_into(app_signer_pin::table)
+            .values(&AppSignerPin {
+                id: Uuid::new_v4(),
+                package_name: package_name.clone(),
+                create_user_id: app.create_user_id,
+                signer_sha256: signer_sha256.clone(),
+                rotation_signer_sha256: None,
+                create_time: now,
+                update_time: now,
+                organization_id: app.organization_id,
+            })
+            .execute(conn)
+            .map(|_| ()),
+        SignerPinAction::Rotate => diesel::update(
+            app_signer_pin::table
+                .filter(app_signer_pin::organization_id.eq(app.organization_id))
+                .filter(app_signer_pin::package_name.eq(package_name))
+                .filter(app_signer_pin::rotation_signer_sha256.eq(signer_sha256)),
+        )
+        .set((
+            app_signer_pin::signer_sha256.eq(signer_sha256),
+            app_signer_pin::rotation_signer_sha256.eq(None::<String>),
+            app_signer_pin::update_time.eq(now),
+        ))
+        .execute(conn)
+        .map(|_| ()),
+    }
+}
+
+// 校验发布时间窗口：过期时间必须晚于发布时间和当前时间
+fn validate_release_window(
+    publish_at: Option<NaiveDateTime>,
+    expire_at: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+) -> Result<(), AppError> {
+    if let Some(expire_at) = expire_at
+        && expire_at <= publish_at.unwrap_or(now).max(now)
+    {
+        return Err(AppError::BadRequest(
+            "过期时间必须晚于发布时间和当前时间".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+// 根据发布时间窗口计算发布状态
+fn resolve_release_status(
+    publish_at: Option<NaiveDateTime>,
+    expire_at: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+) -> ReleaseStatus {
+    if publish_at.is_some_and(|publish_at| publish_at > now) {
+        ReleaseStatus::Scheduled
+    } else if expire_at.is_some_and(|expire_at| expire_at <= now) {
+        ReleaseStatus::Expired
+    } else {
+        ReleaseStatus::Live
+    }
+}
+
+// 判断版本当前是否在发布时间窗口内
+fn is_within_release_window(app: &AppManage, now: NaiveDateTime) -> bool {
+    resolve_release_status(app.publish_at, app.expire_at, now) == ReleaseStatus::Live
+}
+
+// 按发布状态筛选应用列表，与 resolve_release_status 的判断保持一致
+fn filter_by_release_status<'a>(
+    query: app_manage::BoxedQuery<'a, Pg>,
+    release_status: ReleaseStatus,
+    now: NaiveDateTime,
+) -> app_manage::BoxedQuery<'a, Pg> {
+    let published = app_manage::publish_at
+        .is_null()
+        .or(app_manage::publish_at.le(now));
+    match release_status {
+        ReleaseStatus::Scheduled => query.filter(app_manage::publish_at.gt(now)),
+        ReleaseStatus::Expired => query
+            .filter(published)
+            .filter(app_manage::expire_at.le(now)),
+        ReleaseStatus::Live => query.filter(published).filter(
+            app_manage::expire_at
+                .is_null()
+                .or(app_manage::expire_at.gt(now)),
+        ),
+    }
+}
+
+// 设备定向规则、清单组件等转换为数据库JSON
+fn to_json_value<T: serde::Serialize>(value: &T) -> Result<serde_json::Value, AppError> {
+    serde_json::to_value(value).map_err(|e| AppError::Internal(format!("序列化JSON失败:{}", e)))
+}
+
+// 校验灰度发布比例
+fn validate_rollout_percentage(rollout_percentage: i32) -> Result<(), AppError> {
+    if !(0..=FULL_ROLLOUT_PERCENTAGE).contains(&rollout_percentage) {
+        return Err(AppError::BadRequest("灰度发布比例必须在0到100之间".to_string()));
+    }
+
+    Ok(())
+}
+
+// 计算调整后的灰度比例和状态：比例只能提高，终止后不可再调整
+fn resolve_rollout_change(
+    app: &AppManage,
+    update_req: &UpdateAppRolloutReq,
+) -> Result<(i32, RolloutStatus), AppError> {
+    let current_status =
+        RolloutStatus::from_db(&app.rollout_status).unwrap_or(RolloutStatus::Active);
+    if current_status == RolloutStatus::Halted {
+        return Err(AppError::BadRequest("灰度发布已终止，不能再调整".to_string()));
+    }
+
+    let rollout_percentage = update_req
+        .rollout_percentage
+        .unwrap_or(app.rollout_percentage);
+    validate_rollout_percentage(rollout_percentage)?;
+    if rollout_percentage < app.rollout_percentage {
+        return Err(AppError::BadRequest(
+            "灰度发布比例只能提高，如需停止请暂停或终止发布".to_string(),
+        ));
+    }
+
+    Ok((
+        rollout_percentage,
+        update_req.rollout_status.unwrap_or(current_status),
+    ))
+}
+
+// 根据包名和设备ID计算稳定的灰度分桶（0-99），同一设备每次请求结果一致
+fn rollout_bucket(package_name: &str, device_id: &str) -> i32 {
+    let digest = Sha256::digest(format!("{package_name}:{device_id}").as_bytes());
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(prefix) % FULL_ROLLOUT_PERCENTAGE as u64) as i32
+}
+
+// 判断版本对当前设备是否可见：暂停或终止的版本不下发，未全量的版本只下发给命中分桶的设备
+fn is_visible_in_rollout(app: &AppManage, device_bucket: Option<i32>) -> bool {
+    if RolloutStatus::from_db(&app.rollout_status) != Some(RolloutStatus::Active) {
+        return false;
+    }
+
+    app.rollout_percentage >= FULL_ROLLOUT_PERCENTAGE
+        || device_bucket.is_some_and(|bucket| bucket < app.rollout_percentage)
+}
+
+// 判断更新类型：低于渠道最低支持版本，或跳过的版本中存在强制更新时必须更新
+fn resolve_update_type(
+    apps: &[AppManage],
+    latest_version_code: i64,
+    client_version_code: i64,
+    min_supported_version_code: Option<i64>,
+) -> UpdateType {
+    if latest_version_code <= client_version_code {
+        return UpdateType::None;
+    }
+
+    let below_min_supported =
+        min_supported_version_code.is_some_and(|min_code| client_version_code < min_code);
+    let skips_forced_release = apps.iter().any(|app| {
+        app.force_update
+            && parse_version_code(&app.version_code).is_some_and(|code| {
+                code > client_version_code && code <= latest_version_code
+            })
+    });
+
+    if below_min_supported || skips_forced_release {
+        UpdateType::Mandatory
+    } else {
+        UpdateType::Optional
+    }
+}
+
+// 查找客户端当前版本对应的已撤回记录，同一版本多次撤回时取最近一次
+fn find_revoked_release(apps: &[AppManage], client_version_code: i64) -> Option<&AppManage> {
+    apps.iter()
+        .filter(|app| {
+            app.is_revoked && parse_version_code(&app.version_code) == Some(client_version_code)
+        })
+        .max_by_key(|app| app.revoke_time)
+}
+
+// 查找撤回记录指定的回滚目标，目标已撤回时不再回滚
+fn find_rollback_target<'a>(
+    apps: &'a [AppManage],
+    revoked_release: &AppManage,
+) -> Option<&'a AppManage> {
+    let rollback_app_id = revoked_release.rollback_app_id?;
+    apps.iter()
+        .find(|app| app.id == rollback_app_id && !app.is_revoked)
+}
+
+// 撤回版本的客户端需要回滚时返回回滚目标：apps 为已按设备可用性过滤的版本，存在比客户端当前版本更新的版本时直接升级，不再回滚
+fn select_rollback_target<'a>(
+    apps: &'a [AppManage],
+    revoked_release: &AppManage,
+    client_version_code: i64,
+) -> Option<&'a AppManage> {
+    let has_newer_release = select_latest_app(apps)
+        .and_then(|app| parse_version_code(&app.version_code))
+        .is_some_and(|code| code > client_version_code);
+    if has_newer_release {
+        return None;
+    }
+    find_rollback_target(apps, revoked_release)
+}
+
+// 校验回滚目标：必须是同一包名和渠道下未撤回的其他版本
+fn validate_rollback_target(app: &AppManage, rollback_app: &AppManage) -> Result<(), AppError> {
+    if rollback_app.package_name != app.package_name || rollback_app.channel_id != app.channel_id {
+        return Err(AppError::BadRequest(
+            "回滚目标必须是同一包名和渠道下的版本".to_string(),
+        ));
+    }
+
+    if rollback_app.is_revoked {
+        return Err(AppError::BadRequest("回滚目标版本已被撤回".to_string()));
+    }
+
+    if rollback_app.is_archive_only {
+        return Err(AppError::BadRequest(
+            "回滚目标版本仅存档，不能下发给客户端".to_string(),
+        ));
+    }
+
+    if parse_version_code(&rollback_app.version_code) == parse_version_code(&app.version_code) {
+        return Err(AppError::BadRequest(
+            "回滚目标不能与被撤回的版本号相同".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+// 构建回滚响应，回滚目标的版本号可能低于客户端当前版本
+fn build_rollback_resp(
+    rollback_app: &AppManage,
+    min_supported_version_code: Option<i64>,
+) -> AppCheckUpdateResp {
+    AppCheckUpdateResp {
+        has_update: true,
+        update_type: UpdateType::Mandatory,
+        min_supported_version_code,
+        current_version_revoked: false,
+        revoke_reason: None,
+        is_rollback: true,
+        app_name: rollback_app.app_name.clone(),
+        package_name: rollback_app.package_name.clone().unwrap_or_default(),
+        channel_name: rollback_app.channel_name.clone().unwrap_or_default(),
+        version_name: rollback_app.version_name.clone().unwrap_or_default(),
+        version_code: rollback_app.version_code.clone(),
+        app_download_url: Some(rollback_app.app_download_url.clone()),
+        file_sha256: rollback_app.file_sha256.clone(),
+        file_md5: rollback_app.file_md5.clone(),
+        splits: Vec::new(),
+        download_url_expires_at: None,
+        delta_patch: None,
+    }
+}
+
+// 标记客户端当前版本已撤回，有可用版本时必须更新
+fn mark_current_version_revoked(resp: &mut AppCheckUpdateResp, revoked_release: &AppManage) {
+    resp.current_version_revoked = true;
+    resp.revoke_reason = revoked_release.revoke_reason.clone();
+    if resp.has_update {
+        resp.update_type = UpdateType::Mandatory;
+    }
+}
+
+// 没有可见版本时的响应，版本号为客户端当前版本号，不暴露渠道的更新策略
+fn build_no_update_resp(app_check_update_req: &AppCheckUpdateReq) -> AppCheckUpdateResp {
+    AppCheckUpdateResp {
+        has_update: false,
+        update_type: UpdateType::None,
+        min_supported_version_code: None,
+        current_version_revoked: false,
+        revoke_reason: None,
+        is_rollback: false,
+        app_name: String::new(),
+        package_name: app_check_update_req.package_name.clone(),
+        channel_name: app_check_update_req.channel_name.clone(),
+        version_name: String::new(),
+        version_code: app_check_update_req.version_code.to_string(),
+        app_download_url: None,
+        file_sha256: None,
+        file_md5: None,
+        splits: Vec::new(),
+        download_url_expires_at: None,
+        delta_patch: None,
+    }
+}
+
+// 构建应用更新响应
+fn build_app_check_update_resp(
+    app: &AppManage,
+    apps: &[AppManage],
+    client_version_code: i64,
+    min_supported_version_code: Option<i64>,
+) -> AppCheckUpdateResp {
+    let latest_version_code = parse_version_code(&app.version_code).unwrap_or_default();
+    let update_type = resolve_update_type(
+        apps,
+        latest_version_code,
+        client_version_code,
+        min_supported_version_code,
+    );
+    let has_update = update_type != UpdateType::None;
+
+    AppCheckUpdateResp {
+        has_update,
+        update_type,
+        min_supported_version_code,
+        current_version_revoked: false,
+        revoke_reason: None,
+        is_rollback: false,
+        app_name: app.app_name.clone(),
+        package_name: app.package_name.clone().unwrap_or_default(),
+        channel_name: app.channel_name.clone().unwrap_or_default(),
+        version_name: app.version_name.clone().unwrap_or_default(),
+        version_code: app.version_code.clone(),
+        app_download_url: has_update.then(|| app.app_download_url.clone()),
+        file_sha256: app.file_sha256.clone().filter(|_| has_update),
+        file_md5: app.file_md5.clone().filter(|_| has_update),
+        splits: Vec::new(),
+        download_url_expires_at: None,
+        delta_patch: None,
+    }
+}
+
+// 解析版本保存的拆分 APK 文件列表，单个 APK 发布时为空
+fn parse_splits(app: &AppManage) -> Vec<SplitApkItem> {
+    app.splits
+        .clone()
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+// 应用详情响应项
+fn get_app_resp_item(app: &AppManage, now: NaiveDateTime) -> GetAppListRespItem {
+    GetAppListRespItem {
+        app_id: app.id,
+        app_name: app.app_name.clone(),
+        app_download_url: app.app_download_url.clone(),
+        channel_id: app.channel_id,
+        file_path: app.file_path.clone().unwrap_or_default(),
+        file_name: app.file_name.clone().unwrap_or_default(),
+        package_name: app.package_name.clone().unwrap_or_default(),
+        app_icon_path: app.app_icon_path.clone().unwrap_or_default(),
+        version_name: app.version_name.clone().unwrap_or_default(),
+        version_code: app.version_code.clone(),
+        file_size: app.file_size,
+        channel_name: app.channel_name.clone().unwrap_or_default(),
+        update_log: app.update_log.clone().unwrap_or_default(),
+        force_update: app.force_update,
+        rollout_percentage: app.rollout_percentage,
+        rollout_status: app.rollout_status.clone(),
+        min_sdk_version: app.min_sdk_version,
+        native_abis: app.native_abis.clone(),
+        targeting_rules: app
+            .targeting_rules
+            .clone()
+            .and_then(|value| serde_json::from_value(value).ok()),
+        is_revoked: app.is_revoked,
+        revoke_reason: app.revoke_reason.clone(),
+        revoke_time: app.revoke_time,
+        rollback_app_id: app.rollback_app_id,
+        publish_at: app.publish_at,
+        expire_at: app.expire_at,
+        release_status: resolve_release_status(app.publish_at, app.expire_at, now),
+        signer_sha256: app.signer_sha256.clone(),
+        signature_schemes: app.signature_schemes.clone(),
+        target_sdk_version: app.target_sdk_version,
+        permissions: app.permissions.clone(),
+        features: app.features.clone(),
+        is_debuggable: app.is_debuggable,
+        is_test_only: app.is_test_only,
+        splits: parse_splits(app),
+        is_archive_only: app.is_archive_only,
+        file_sha256: app.file_sha256.clone(),
+        file_md5: app.file_md5.clone(),
+        create_time: app.create_time,
+        update_time: app.update_time,
+    }
+}
+
+// 渠道包文件名：`{母包文件名}_{渠道名称}.apk`，渠道名称中的特殊字符替换为下划线，下载时作为保存的文件名
+fn build_channel_apk_filename(master_filename: &str, channel: &AppChannel) -> String {
+    let stem = Path::new(master_filename)
+        .file_stem()
+        .and_then(|value| value.to_str())
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or("app");
+    let channel_part: String = channel
+        .channel_name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let channel_part = if channel_part.trim_matches('_').is_empty() {
+        channel.id.simple().to_string()[..8].to_string()
+    } else {
+        channel_part
+    };
+
+    format!("{stem}_{channel_part}.apk")
+}
+
+// 版本引用的存储文件：APK（拆分 APK 为基础包和全部配置包，同一文件只计一次）和图标
+fn release_blob_names(
+    file_path: Option<&str>,
+    app_icon_path: Option<&str>,
+    splits: Option<&serde_json::Value>,
+) -> (Vec<String>, Option<String>) {
+    let split_paths: Vec<String> = splits
+        .and_then(|value| serde_json::from_value::<Vec<SplitApkItem>>(value.clone()).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|split| split.file_path)
+        .collect();
+    let mut apk_names: Vec<String> = Vec::new();
+    for path in file_path
+        .into_iter()
+        .chain(split_paths.iter().map(String::as_str))
+    {
+        if let Some(name) = blob_file_name_from_url(path)
+            && !apk_names.iter().any(|existing| existing == name)
+        {
+            apk_names.push(name.to_string());
+        }
+    }
+    let icon_name = app_icon_path
+        .and_then(blob_file_name_from_url)
+        .map(str::to_string);
+    (apk_names, icon_name)
+}
+
+// 释放已删除版本的文件引用：版本的 APK 和图标、以其为目标版本的差分补丁；
+// 母包不再被组织内其他版本引用时一并删除为其生成的渠道包记录
+fn release_app_blob_references(conn: &mut PgConnection, app: &AppManage) -> Result<(), AppError> {
+    let (apk_names, icon_name) = release_blob_names(
+        app.file_path.as_deref(),
+        app.app_icon_path.as_deref(),
+        app.splits.as_ref(),
+    );
+    remove_blob_references(conn, BLOB_KIND_APK, &apk_names)?;
+    remove_blob_references(conn, BLOB_KIND_ICON, &Vec::from_iter(icon_name))?;
+
+    let patch_names = app_delta_patch::table
+        .filter(app_delta_patch::app_id.eq(app.id))
+        .filter(app_delta_patch::status.eq(DeltaPatchStatus::Ready.as_str()))
+        .select(app_delta_patch::file_name)
+        .load::<Option<String>>(conn)?;
+    remove_blob_references(
+        conn,
+        BLOB_KIND_PATCH,
+        &patch_names.into_iter().flatten().collect::<Vec<_>>(),
+    )?;
+
+    let Some(master_file_name) = blob_file_name_from_url(&app.app_download_url) else {
+        return Ok(());
+    };
+    lock_channel_apk_master(conn, master_file_name)?;
+    let is_still_referenced = diesel::select(diesel::dsl::exists(
+        app_manage::table
+            .filter(app_manage::organization_id.eq(app.organization_id))
+            .filter(app_manage::is_delete.eq(false))
+            .filter(app_manage::app_download_url.eq(&app.app_download_url)),
+    ))
+    .get_result::<bool>(conn)?;
+    if !is_still_referenced {
+        remove_channel_apks_by_master(conn, app.organization_id, master_file_name)?;
+    }
+    Ok(())
+}
+
+// 解析受信任的上传 APK 地址，返回存储中的文件名
+fn resolve_uploaded_apk_path(file_path: &str) -> Result<String, AppError> {
+    let Some((path_part, query_part)) = file_path.split_once('?') else {
+        return Err(AppError::BadRequest("文件路径格式无效".to_string()));
+    };
+
+    if path_part != format!("{PUBLIC_APP_MANAGE_PREFIX}/apk") {
+        return Err(AppError::BadRequest("文件路径不是受信任的上传 APK 地址".to_string()));
+    }
+
+    let filename = query_part
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("name="))
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| AppError::BadRequest("文件路径缺少 name 参数".to_string()))?;
 
     let relative_path = Path::new(filename);
     if relative_path.components().any(|component| {
@@ -574,13 +2868,91 @@ fn resolve_uploaded_apk_path(file_path: &str) -> Result<PathBuf, AppError> {
     }) {
         return Err(AppError::FORBIDDEN("非法文件路径".to_string()));
     }
+    if relative_path.components().count() != 1 {
+        return Err(AppError::BadRequest("无效的上传文件路径".to_string()));
+    }
+
+    Ok(filename.to_string())
+}
+
+// 取得已上传 APK 的本地文件，非本地存储时下载到暂存目录
+async fn fetch_uploaded_apk(
+    store: &dyn FileStore,
+    file_name: &str,
+    staging_dir: &Path,
+) -> Result<PathBuf, AppError> {
+    fetch_to_local(store, &blob_key(BLOB_KIND_APK, file_name), staging_dir)
+        .await?
+        .ok_or_else(|| AppError::NotFound("上传文件不存在".to_string()))
+}
+
+// 在暂存目录中生成写入渠道信息的渠道包并计算摘要
+async fn stamp_channel_apk(
+    master_path: &Path,
+    staging_dir: &Path,
+    channel_name: &str,
+    file_name: &str,
+) -> Result<StagedFile, AppError> {
+    let master_path = master_path.to_path_buf();
+    let target = staging_dir.join(format!("{}.apk", Uuid::new_v4().simple()));
+    let channel_name = channel_name.to_string();
+    let file_name = file_name.to_string();
+    tokio::task::spawn_blocking(move || {
+        write_channel_file(&master_path, &target, &channel_name)
+            .map_err(|e| AppError::Unprocessable(format!("写入渠道信息失败: {}", e)))?;
+        stage_file(&target, &file_name, "apk")
+            .map_err(|e| AppError::Internal(format!("计算文件摘要失败: {}", e)))
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("写入渠道信息失败: {}", e)))?
+}
 
-    let full_path = PathBuf::from(APK_UPLOAD_DIR).join(relative_path);
-    if !full_path.is_file() {
-        return Err(AppError::NotFound("上传文件不存在".to_string()));
+// 汇总发布的 APK 文件，基础包在前，重复的文件只保留一个
+async fn collect_release_apk_files(
+    store: &dyn FileStore,
+    staging_dir: &Path,
+    apk_path: &Path,
+    apk_filename: &str,
+    split_file_paths: &[String],
+) -> Result<Vec<(PathBuf, String)>, AppError> {
+    let mut apk_files = vec![(apk_path.to_path_buf(), apk_filename.to_string())];
+    for split_file_path in split_file_paths {
+        let split_filename = resolve_uploaded_apk_path(split_file_path)?;
+        if apk_files.iter().any(|(_, name)| name == &split_filename) {
+            continue;
+        }
+        let split_path = fetch_uploaded_apk(store, &split_filename, staging_dir).await?;
+        apk_files.push((split_path, split_filename));
     }
+    Ok(apk_files)
+}
 
-    Ok(full_path)
+pub fn app_manage_router() -> Router {
+    Router::with_path("app_manage")
+        .push(
+            Router::new()
+                .hoop(require_permission(Permission::AppRead))
+                .push(Router::with_path("get_app_list_by_page").post(get_app_list_by_page))
+                .push(Router::with_path("get_app_release_diff").post(get_app_release_diff)),
+        )
+        .push(
+            Router::new()
+                .hoop(require_permission(Permission::AppPublish))
+                .push(Router::with_path("upload_app_file").post(upload_app_file))
+                .push(Router::with_path("generate_channel_apks").post(generate_channel_apks))
+                .push(Router::with_path("upload_app_file_complete").post(upload_app_file_complete))
+                .push(Router::with_path("update_app_schedule").post(update_app_schedule)),
+        )
+        .push(
+            Router::new()
+                .hoop(require_permission(Permission::AppRelease))
+                .push(Router::with_path("delete_app").post(delete_app))
+                .push(Router::with_path("update_app_force_update").post(update_app_force_update))
+                .push(Router::with_path("update_app_rollout").post(update_app_rollout))
+                .push(Router::with_path("update_app_targeting").post(update_app_targeting))
+                .push(Router::with_path("revoke_app").post(revoke_app))
+                .push(Router::with_path("approve_signer_rotation").post(approve_signer_rotation)),
+        )
 }
 
 #[cfg(test)]
@@ -593,17 +2965,460 @@ mod tests {
         assert!(matches!(result, Err(AppError::BadRequest(_))));
     }
 
+    #[test]
+    fn channel_apk_filename_uses_master_name_and_channel() {
+        let now = Local::now().naive_local();
+        let mut channel = AppChannel {
+            id: Uuid::new_v4(),
+            channel_name: "华为 store".to_string(),
+            remark: None,
+            create_user_id: Uuid::new_v4(),
+            create_time: now,
+            update_time: now,
+            is_delete: false,
+            min_supported_version_code: None,
+            is_private: false,
+            bind_download_device: false,
+            organization_id: Uuid::new_v4(),
+        };
+        assert_eq!(
+            build_channel_apk_filename("demo-1.0.apk", &channel),
+            "demo-1.0____store.apk"
+        );
+        channel.channel_name = "小米".to_string();
+        assert_eq!(
+            build_channel_apk_filename("demo.apk", &channel),
+            format!("demo_{}.apk", &channel.id.simple().to_string()[..8])
+        );
+    }
+
+    #[test]
+    fn release_blob_names_deduplicates_base_in_splits() {
+        let splits = serde_json::json!([
+            {
+                "split_name": "base",
+                "kind": "base",
+                "qualifier": null,
+                "file_name": "a.apk",
+                "file_path": "/api/public/app_manage/apk?name=a.apk",
+                "file_size": 1
+            },
+            {
+                "split_name": "config.arm64_v8a",
+                "kind": "abi",
+                "qualifier": "arm64-v8a",
+                "file_name": "b.apk",
+                "file_path": "/api/public/app_manage/apk?name=b.apk",
+                "file_size": 1
+            }
+        ]);
+        let (apk_names, icon_name) = release_blob_names(
+            Some("/api/public/app_manage/apk?name=a.apk"),
+            Some("/api/public/app_manage/icon?name=c.png"),
+            Some(&splits),
+        );
+        assert_eq!(apk_names, vec!["a.apk".to_string(), "b.apk".to_string()]);
+        assert_eq!(icon_name.as_deref(), Some("c.png"));
+    }
+
     #[test]
     fn resolve_uploaded_apk_path_rejects_traversal() {
         let result = resolve_uploaded_apk_path("/api/public/app_manage/apk?name=../test.apk");
         assert!(matches!(result, Err(AppError::FORBIDDEN(_))));
     }
-}
 
-pub fn app_manage_router() -> Router {
-    Router::with_path("app_manage")
-        .push(Router::with_path("upload_app_file").post(upload_app_file))
-        .push(Router::with_path("upload_app_file_complete").post(upload_app_file_complete))
-        .push(Router::with_path("get_app_list_by_page").post(get_app_list_by_page))
-        .push(Router::with_path("delete_app").post(delete_app))
+    fn test_app(version_code: &str, create_time: &str) -> AppManage {
+        let create_time =
+            chrono::NaiveDateTime::parse_from_str(create_time, "%Y-%m-%d %H:%M:%S").unwrap();
+        AppManage {
+            id: Uuid::new_v4(),
+            app_name: "demo".to_string(),
+            app_download_url: format!("/api/public/app_manage/apk?name={version_code}.apk"),
+            create_user_id: Uuid::new_v4(),
+            channel_id: Uuid::nil(),
+            create_time,
+            update_time: create_time,
+            is_delete: false,
+            file_path: None,
+            file_name: None,
+            package_name: Some("com.example.demo".to_string()),
+            app_icon_path: None,
+            version_name: Some(format!("v{version_code}")),
+            version_code: version_code.to_string(),
+            file_size: 0,
+            channel_name: Some("official".to_string()),
+            update_log: None,
+            force_update: false,
+            rollout_percentage: 100,
+            rollout_status: "active".to_string(),
+            min_sdk_version: None,
+            native_abis: Vec::new(),
+            targeting_rules: None,
+            is_revoked: false,
+            revoke_reason: None,
+            revoke_time: None,
+            rollback_app_id: None,
+            publish_at: None,
+            expire_at: None,
+            is_published: true,
+            signer_sha256: Some("a".repeat(64)),
+            signature_schemes: vec!["v2".to_string()],
+            target_sdk_version: None,
+            permissions: Vec::new(),
+            features: Vec::new(),
+            is_debuggable: false,
+            is_test_only: false,
+            components: None,
+            splits: None,
+            is_archive_only: false,
+            file_sha256: None,
+            file_md5: None,
+            organization_id: Uuid::new_v4(),
+        }
+    }
+
+    #[test]
+    fn select_latest_app_compares_version_code_numerically() {
+        let apps = vec![
+            test_app("9", "2026-01-01 00:00:00"),
+            test_app("10", "2026-01-02 00:00:00"),
+            // 重新上传的旧版本不应成为最新版本
+            test_app("8", "2026-01-03 00:00:00"),
+            test_app("not-a-number", "2026-01-04 00:00:00"),
+        ];
+
+        let latest = select_latest_app(&apps).unwrap();
+        assert_eq!(latest.version_code, "10");
+    }
+
+    #[test]
+    fn build_app_check_update_resp_only_returns_url_when_newer() {
+        let app = test_app("10", "2026-01-01 00:00:00");
+
+        let apps = vec![app];
+        let resp = build_app_check_update_resp(&apps[0], &apps, 9, None);
+        assert!(resp.has_update);
+        assert!(resp.app_download_url.is_some());
+
+        let resp = build_app_check_update_resp(&apps[0], &apps, 10, None);
+        assert!(!resp.has_update);
+        assert!(resp.app_download_url.is_none());
+    }
+
+    #[test]
+    fn releases_from_several_organizations_are_ambiguous() {
+        let first = test_app("10", "2026-01-01 00:00:00");
+        let mut same_organization = test_app("11", "2026-01-02 00:00:00");
+        same_organization.organization_id = first.organization_id;
+        assert!(!spans_multiple_organizations(&[]));
+        assert!(!spans_multiple_organizations(&[
+            first.clone(),
+            same_organization
+        ]));
+        assert!(spans_multiple_organizations(&[
+            first,
+            test_app("12", "2026-01-03 00:00:00")
+        ]));
+    }
+
+    #[test]
+    fn no_visible_release_returns_no_update() {
+        let req = AppCheckUpdateReq {
+            package_name: "com.example.demo".to_string(),
+            channel_name: "official".to_string(),
+            version_code: 12,
+            device_id: None,
+            sdk_int: None,
+            supported_abis: Vec::new(),
+            manufacturer: None,
+            model: None,
+            locale: None,
+            screen_density: None,
+            current_file_sha256: None,
+        };
+
+        let resp = build_no_update_resp(&req);
+        assert!(!resp.has_update);
+        assert_eq!(resp.update_type, UpdateType::None);
+        assert_eq!(resp.version_code, "12");
+        assert_eq!(resp.channel_name, "official");
+        assert!(resp.app_download_url.is_none());
+
+        let mut revoked = test_app("12", "2026-01-01 00:00:00");
+        revoked.is_revoked = true;
+        revoked.revoke_reason = Some("崩溃".to_string());
+        let mut resp = build_no_update_resp(&req);
+        mark_current_version_revoked(&mut resp, &revoked);
+        assert!(resp.current_version_revoked);
+        assert!(!resp.has_update);
+        assert_eq!(resp.update_type, UpdateType::None);
+    }
+
+    #[test]
+    fn resolve_update_type_honors_forced_releases_and_min_supported() {
+        let mut forced = test_app("11", "2026-01-01 00:00:00");
+        forced.force_update = true;
+        let apps = vec![forced, test_app("12", "2026-01-02 00:00:00")];
+
+        assert_eq!(resolve_update_type(&apps, 12, 12, Some(20)), UpdateType::None);
+        assert_eq!(resolve_update_type(&apps, 12, 11, None), UpdateType::Optional);
+        // 跳过了强制更新的 11 版本
+        assert_eq!(resolve_update_type(&apps, 12, 10, None), UpdateType::Mandatory);
+        assert_eq!(resolve_update_type(&apps, 12, 11, Some(12)), UpdateType::Mandatory);
+    }
+
+    #[test]
+    fn rollout_bucket_is_stable_and_spread() {
+        let bucket = rollout_bucket("com.example.demo", "device-1");
+        assert_eq!(bucket, rollout_bucket("com.example.demo", "device-1"));
+        assert!((0..100).contains(&bucket));
+
+        let in_ten_percent = (0..1000)
+            .filter(|index| rollout_bucket("com.example.demo", &format!("device-{index}")) < 10)
+            .count();
+        assert!((50..150).contains(&in_ten_percent));
+    }
+
+    #[test]
+    fn staged_release_falls_back_to_full_rollout_outside_bucket() {
+        let latest_visible = |device_bucket: Option<i32>| {
+            let mut staged = test_app("11", "2026-01-02 00:00:00");
+            staged.rollout_percentage = 10;
+            let mut paused = test_app("12", "2026-01-03 00:00:00");
+            paused.rollout_status = "paused".to_string();
+            let apps: Vec<AppManage> = vec![test_app("10", "2026-01-01 00:00:00"), staged, paused]
+                .into_iter()
+                .filter(|app| is_visible_in_rollout(app, device_bucket))
+                .collect();
+            select_latest_app(&apps).map(|app| app.version_code.clone())
+        };
+
+        assert_eq!(latest_visible(Some(3)), Some("11".to_string()));
+        assert_eq!(latest_visible(Some(10)), Some("10".to_string()));
+        assert_eq!(latest_visible(None), Some("10".to_string()));
+    }
+
+    #[test]
+    fn resolve_rollout_change_only_raises_and_halt_is_final() {
+        let mut app = test_app("11", "2026-01-01 00:00:00");
+        app.rollout_percentage = 20;
+        let req = |rollout_percentage, rollout_status| UpdateAppRolloutReq {
+            app_id: app.id,
+            rollout_percentage,
+            rollout_status,
+        };
+
+        assert_eq!(
+            resolve_rollout_change(&app, &req(Some(50), None)).unwrap(),
+            (50, RolloutStatus::Active)
+        );
+        assert!(resolve_rollout_change(&app, &req(Some(10), None)).is_err());
+        assert!(resolve_rollout_change(&app, &req(Some(101), None)).is_err());
+        assert_eq!(
+            resolve_rollout_change(&app, &req(None, Some(RolloutStatus::Paused))).unwrap(),
+            (20, RolloutStatus::Paused)
+        );
+
+        app.rollout_status = "halted".to_string();
+        assert!(resolve_rollout_change(&app, &req(None, Some(RolloutStatus::Active))).is_err());
+    }
+
+    #[test]
+    fn release_availability_respects_min_sdk_abis_and_rules() {
+        let device = AppCheckUpdateReq {
+            package_name: "com.example.demo".to_string(),
+            channel_name: "official".to_string(),
+            version_code: 1,
+            device_id: None,
+            sdk_int: Some(23),
+            supported_abis: vec!["armeabi-v7a".to_string()],
+            manufacturer: Some("Google".to_string()),
+            model: None,
+            locale: None,
+            screen_density: None,
+            current_file_sha256: None,
+        };
+
+        let mut app = test_app("11", "2026-01-01 00:00:00");
+        app.min_sdk_version = Some(24);
+        assert!(!is_release_available_for_device(&app, &device));
+
+        app.min_sdk_version = Some(21);
+        app.native_abis = vec!["arm64-v8a".to_string()];
+        assert!(!is_release_available_for_device(&app, &device));
+
+        app.native_abis = vec!["arm64-v8a".to_string(), "armeabi-v7a".to_string()];
+        app.targeting_rules = Some(serde_json::json!({ "manufacturers": ["google"] }));
+        assert!(is_release_available_for_device(&app, &device));
+
+        app.targeting_rules = Some(serde_json::json!({ "exclude_manufacturers": ["Google"] }));
+        assert!(!is_release_available_for_device(&app, &device));
+    }
+
+    #[test]
+    fn revoked_client_version_rolls_back_to_designated_lower_version() {
+        let stable = test_app("10", "2026-01-01 00:00:00");
+        let mut broken = test_app("11", "2026-01-02 00:00:00");
+        broken.is_revoked = true;
+        broken.revoke_reason = Some("启动崩溃".to_string());
+        broken.rollback_app_id = Some(stable.id);
+        let apps = vec![stable, broken];
+
+        let revoked_release = find_revoked_release(&apps, 11).unwrap();
+        let rollback_app = find_rollback_target(&apps, revoked_release).unwrap();
+        assert_eq!(rollback_app.version_code, "10");
+        assert!(find_revoked_release(&apps, 10).is_none());
+
+        let mut resp = build_rollback_resp(rollback_app, None);
+        mark_current_version_revoked(&mut resp, revoked_release);
+        assert!(resp.has_update && resp.is_rollback && resp.current_version_revoked);
+        assert_eq!(resp.update_type, UpdateType::Mandatory);
+        assert_eq!(resp.version_code, "10");
+        assert_eq!(resp.revoke_reason.as_deref(), Some("启动崩溃"));
+
+        assert!(validate_rollback_target(&apps[1], &apps[0]).is_ok());
+        assert!(validate_rollback_target(&apps[0], &apps[1]).is_err());
+    }
+
+    #[test]
+    fn rollback_only_applies_without_newer_eligible_release() {
+        let stable = test_app("10", "2026-01-01 00:00:00");
+        let mut broken = test_app("11", "2026-01-02 00:00:00");
+        broken.is_revoked = true;
+        broken.rollback_app_id = Some(stable.id);
+        let mut fixed = test_app("12", "2026-01-03 00:00:00");
+        fixed.rollout_status = "paused".to_string();
+        let apps = vec![stable, broken.clone(), fixed];
+
+        // 与检查更新相同，回滚目标只在过滤后的可用版本中查找
+        let eligible = |apps: &[AppManage]| -> Vec<AppManage> {
+            apps.iter()
+                .filter(|app| !app.is_revoked && is_visible_in_rollout(app, None))
+                .cloned()
+                .collect()
+        };
+
+        // 修复版本暂停发布时回滚到指定版本
+        let visible = eligible(&apps);
+        let rollback_app = select_rollback_target(&visible, &broken, 11).unwrap();
+        assert_eq!(rollback_app.version_code, "10");
+
+        // 修复版本可用时直接升级，不再回滚
+        let mut apps = apps;
+        apps[2].rollout_status = "active".to_string();
+        let visible = eligible(&apps);
+        assert!(select_rollback_target(&visible, &broken, 11).is_none());
+        assert_eq!(select_latest_app(&visible).unwrap().version_code, "12");
+
+        // 回滚目标对当前设备不可用时不回滚
+        apps[0].rollout_status = "halted".to_string();
+        apps[2].rollout_status = "paused".to_string();
+        assert!(select_rollback_target(&eligible(&apps), &broken, 11).is_none());
+    }
+
+    #[test]
+    fn release_window_controls_visibility() {
+        let now = chrono::NaiveDateTime::parse_from_str("2026-01-10 12:00:00", "%Y-%m-%d %H:%M:%S")
+            .unwrap();
+        let mut app = test_app("11", "2026-01-01 00:00:00");
+        assert!(is_within_release_window(&app, now));
+
+        app.publish_at = Some(now + chrono::Duration::hours(14));
+        assert!(!is_within_release_window(&app, now));
+        assert_eq!(
+            resolve_release_status(app.publish_at, app.expire_at, now),
+            ReleaseStatus::Scheduled
+        );
+        assert!(is_within_release_window(
+            &app,
+            now + chrono::Duration::hours(14)
+        ));
+
+        app.publish_at = None;
+        app.expire_at = Some(now);
+        assert_eq!(
+            resolve_release_status(app.publish_at, app.expire_at, now),
+            ReleaseStatus::Expired
+        );
+
+        assert!(validate_release_window(None, Some(now), now).is_err());
+        assert!(
+            validate_release_window(
+                Some(now + chrono::Duration::hours(2)),
+                Some(now + chrono::Duration::hours(1)),
+                now
+            )
+            .is_err()
+        );
+        assert!(validate_release_window(None, Some(now + chrono::Duration::hours(1)), now).is_ok());
+    }
+
+    #[test]
+    fn resolve_signer_pin_action_rejects_foreign_signer() {
+        let now = Local::now().naive_local();
+        let mut pin = AppSignerPin {
+            id: Uuid::new_v4(),
+            package_name: "com.example.demo".to_string(),
+            create_user_id: Uuid::new_v4(),
+            signer_sha256: "a".repeat(64),
+            rotation_signer_sha256: None,
+            create_time: now,
+            update_time: now,
+            organization_id: Uuid::new_v4(),
+        };
+        let organization_id = pin.organization_id;
+        let old_signer = "a".repeat(64);
+        let new_signer = "b".repeat(64);
+
+        assert_eq!(
+            resolve_signer_pin_action(None, organization_id, "com.example.demo", Some(&new_signer))
+                .unwrap(),
+            SignerPinAction::Pin
+        );
+        assert_eq!(
+            resolve_signer_pin_action(
+                Some(&pin),
+                organization_id,
+                "com.example.demo",
+                Some(&old_signer)
+            )
+            .unwrap(),
+            SignerPinAction::Keep
+        );
+        assert!(matches!(
+            resolve_signer_pin_action(
+                Some(&pin),
+                organization_id,
+                "com.example.demo",
+                Some(&new_signer)
+            ),
+            Err(AppError::Unprocessable(_))
+        ));
+        assert!(matches!(
+            resolve_signer_pin_action(None, organization_id, "com.example.demo", None),
+            Err(AppError::Unprocessable(_))
+        ));
+
+        assert!(matches!(
+            resolve_signer_pin_action(
+                Some(&pin),
+                Uuid::new_v4(),
+                "com.example.demo",
+                Some(&old_signer)
+            ),
+            Err(AppError::FORBIDDEN(_))
+        ));
+
+        pin.rotation_signer_sha256 = Some(new_signer.clone());
+        assert_eq!(
+            resolve_signer_pin_action(
+                Some(&pin),
+                organization_id,
+                "com.example.demo",
+                Some(&new_signer)
+            )
+            .unwrap(),
+            SignerPinAction::Rotate
+        );
+    }
 }
@@ -0,0 +1,555 @@
+use crate::api::app_manage::{MAX_UPLOAD_FILE_SIZE, finish_app_file_upload};
+use crate::middleware::permission_guard::require_permission;
+use crate::model::app_manage::UploadAppFileResp;
+use crate::model::app_upload_session::{
+    AppUploadSession, CreateUploadSessionReq, UploadSessionReq, UploadSessionResp,
+    UploadSessionStatus,
+};
+use crate::model::body::parse_json_body;
+use crate::model::error::{ApiOut, AppError};
+use crate::model::user_role::Permission;
+use crate::schema::*;
+use crate::store::{FileStore, get_file_store};
+use crate::utils::blob_store_utils::StagingDir;
+use crate::utils::database_utils::{current_user, try_connect_database};
+use crate::utils::file_digest_utils::compute_file_digest;
+use crate::utils::upload_session_utils::{
+    assemble_chunks, chunk_count, expected_chunk_size, received_chunks, remove_session_chunks,
+    write_chunk,
+};
+use bytes::Bytes;
+use chrono::{Duration, Local};
+use diesel::RunQueryDsl;
+use diesel::connection::{AnsiTransactionManager, TransactionManager};
+use diesel::prelude::*;
+use salvo::prelude::*;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+// 默认分片大小 8MB
+const DEFAULT_CHUNK_SIZE: i64 = 8 * 1024 * 1024;
+const MIN_CHUNK_SIZE: i64 = 256 * 1024;
+const MAX_CHUNK_SIZE: i64 = 64 * 1024 * 1024;
+// 会话有效期，每次上传分片后顺延
+const UPLOAD_SESSION_EXPIRE_HOURS: i64 = 24;
+// 分片上传支持的文件类型
+const UPLOAD_FILE_EXTENSIONS: &[&str] = &["apk", "aab", "apks", "xapk"];
+
+#[endpoint(
+    tags("app_manage"),
+    summary = "创建分片上传会话",
+    description = "创建分片上传会话，大文件按分片上传后再合并，上传中断后可从缺失的分片继续",
+    request_body = CreateUploadSessionReq
+)]
+pub async fn create_upload_session(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<UploadSessionResp> {
+    let create_req = match parse_json_body::<CreateUploadSessionReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let file_name = match normalize_upload_file_name(&create_req.file_name) {
+        Ok(file_name) => file_name,
+        Err(err) => return ApiOut::err(err),
+    };
+    if create_req.file_size <= 0 {
+        return ApiOut::err(AppError::BadRequest("文件大小必须大于0".to_string()));
+    }
+    if create_req.file_size > MAX_UPLOAD_FILE_SIZE {
+        return ApiOut::err(AppError::BadRequest(format!(
+            "文件大小不能超过 {} 字节",
+            MAX_UPLOAD_FILE_SIZE
+        )));
+    }
+    let chunk_size = create_req.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+    if !(MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&chunk_size) {
+        return ApiOut::err(AppError::BadRequest(format!(
+            "分片大小需在 {} 到 {} 字节之间",
+            MIN_CHUNK_SIZE, MAX_CHUNK_SIZE
+        )));
+    }
+    let file_sha256 = match create_req.file_sha256.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(value) if value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Some(value.to_ascii_lowercase())
+        }
+        Some(_) => {
+            return ApiOut::err(AppError::BadRequest(
+                "file_sha256 需为 64 位十六进制字符串".to_string(),
+            ));
+        }
+    };
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let now = Local::now().naive_local();
+    let session = AppUploadSession {
+        id: Uuid::new_v4(),
+        create_user_id: current_user.id,
+        file_name,
+        file_size: create_req.file_size,
+        chunk_size,
+        total_chunks: chunk_count(create_req.file_size, chunk_size),
+        file_sha256,
+        status: UploadSessionStatus::Uploading.as_str().to_string(),
+        expires_at: now + Duration::hours(UPLOAD_SESSION_EXPIRE_HOURS),
+        create_time: now,
+        update_time: now,
+    };
+    let session = match diesel::insert_into(app_upload_session::table)
+        .values(&session)
+        .get_result::<AppUploadSession>(&mut conn)
+    {
+        Ok(session) => session,
+        Err(e) => return ApiOut::err(AppError::Internal(format!("创建上传会话失败: {}", e))),
+    };
+    info!(session_id = %session.id, file_name = %session.file_name, "创建分片上传会话");
+
+    ApiOut::ok(to_upload_session_resp(&session, Vec::new()))
+}
+
+#[endpoint(
+    tags("app_manage"),
+    summary = "上传分片",
+    description = "上传分片，请求体为分片原始字节，通过 session_id 与 offset 查询参数指定会话和分片偏移，offset 需为分片大小的整数倍，重复上传同一分片会覆盖"
+)]
+pub async fn upload_session_chunk(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<UploadSessionResp> {
+    let Some(session_id) = req
+        .query::<String>("session_id")
+        .and_then(|value| Uuid::parse_str(value.trim()).ok())
+    else {
+        return ApiOut::err(AppError::BadRequest(
+            "缺少或无效的 session_id 参数".to_string(),
+        ));
+    };
+    let Some(offset) = req.query::<i64>("offset") else {
+        return ApiOut::err(AppError::BadRequest("缺少或无效的 offset 参数".to_string()));
+    };
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let store = match get_file_store(depot) {
+        Ok(store) => store,
+        Err(err) => return ApiOut::err(err),
+    };
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    let session = match find_active_session(&mut conn, session_id, current_user.id) {
+        Ok(session) => session,
+        Err(err) => return ApiOut::err(err),
+    };
+    if session.status != UploadSessionStatus::Uploading.as_str() {
+        return ApiOut::err(session_completing_error());
+    }
+    if offset < 0 || offset >= session.file_size || offset % session.chunk_size != 0 {
+        return ApiOut::err(AppError::BadRequest(format!(
+            "offset 需为分片大小 {} 的整数倍且小于文件大小",
+            session.chunk_size
+        )));
+    }
+    let index = (offset / session.chunk_size) as i32;
+    let expected_size = expected_chunk_size(session.file_size, session.chunk_size, index);
+
+    let data = match req.payload_with_max_size(session.chunk_size as usize).await {
+        Ok(data) => data.clone(),
+        Err(e) => {
+            return ApiOut::err(AppError::BadRequest(format!("读取分片数据失败: {}", e)));
+        }
+    };
+    if data.len() as i64 != expected_size {
+        return ApiOut::err(AppError::BadRequest(format!(
+            "分片 {} 大小应为 {} 字节，实际为 {} 字节",
+            index,
+            expected_size,
+            data.len()
+        )));
+    }
+
+    if let Err(err) = write_chunk_locked(&mut conn, store.as_ref(), session.id, index, data).await {
+        return ApiOut::err(err);
+    }
+
+    let now = Local::now().naive_local();
+    let session = match diesel::update(
+        app_upload_session::table
+            .filter(app_upload_session::id.eq(session.id))
+            .filter(app_upload_session::status.eq(UploadSessionStatus::Uploading.as_str())),
+    )
+    .set((
+        app_upload_session::expires_at.eq(now + Duration::hours(UPLOAD_SESSION_EXPIRE_HOURS)),
+        app_upload_session::update_time.eq(now),
+    ))
+    .get_result::<AppUploadSession>(&mut conn)
+    .optional()
+    {
+        Ok(Some(session)) => session,
+        // 分片写入完成后会话已被认领合并，分片已包含在合并结果中或会在合并失败后保留
+        Ok(None) => match find_active_session(&mut conn, session_id, current_user.id) {
+            Ok(session) => session,
+            Err(err) => return ApiOut::err(err),
+        },
+        Err(e) => return ApiOut::err(AppError::Internal(format!("更新上传会话失败: {}", e))),
+    };
+
+    let received = match received_chunks(
+        store.as_ref(),
+        session.id,
+        session.file_size,
+        session.chunk_size,
+    )
+    .await
+    {
+        Ok(received) => received,
+        Err(err) => return ApiOut::err(err),
+    };
+    ApiOut::ok(to_upload_session_resp(&session, received))
+}
+
+#[endpoint(
+    tags("app_manage"),
+    summary = "查询分片上传会话",
+    description = "查询分片上传会话，返回已接收和缺失的分片，用于断点续传",
+    request_body = UploadSessionReq
+)]
+pub async fn get_upload_session(depot: &mut Depot, req: &mut Request) -> ApiOut<UploadSessionResp> {
+    let session_req = match parse_json_body::<UploadSessionReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let store = match get_file_store(depot) {
+        Ok(store) => store,
+        Err(err) => return ApiOut::err(err),
+    };
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    let session = match find_active_session(&mut conn, session_req.session_id, current_user.id) {
+        Ok(session) => session,
+        Err(err) => return ApiOut::err(err),
+    };
+    drop(conn);
+
+    let received = match received_chunks(
+        store.as_ref(),
+        session.id,
+        session.file_size,
+        session.chunk_size,
+    )
+    .await
+    {
+        Ok(received) => received,
+        Err(err) => return ApiOut::err(err),
+    };
+    ApiOut::ok(to_upload_session_resp(&session, received))
+}
+
+#[endpoint(
+    tags("app_manage"),
+    summary = "完成分片上传",
+    description = "合并全部分片并按上传APP文件的流程解析和保存，返回结果与上传APP文件相同",
+    request_body = UploadSessionReq
+)]
+pub async fn complete_upload_session(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<UploadAppFileResp> {
+    let session_req = match parse_json_body::<UploadSessionReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    let session = match find_active_session(&mut conn, session_req.session_id, current_user.id) {
+        Ok(session) => session,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    // 只有成功将状态改为合并中的请求继续处理，避免重复提交同时合并
+    match claim_session(&mut conn, session.id) {
+        Ok(true) => {}
+        Ok(false) => return ApiOut::err(session_completing_error()),
+        Err(err) => return ApiOut::err(err),
+    }
+    let store = match get_file_store(depot) {
+        Ok(store) => store,
+        Err(err) => {
+            release_session(&mut conn, session.id);
+            return ApiOut::err(err);
+        }
+    };
+
+    let received = match received_chunks(
+        store.as_ref(),
+        session.id,
+        session.file_size,
+        session.chunk_size,
+    )
+    .await
+    {
+        Ok(received) => received,
+        Err(err) => {
+            release_session(&mut conn, session.id);
+            return ApiOut::err(err);
+        }
+    };
+    if received.len() as i32 != session.total_chunks {
+        release_session(&mut conn, session.id);
+        return ApiOut::err(AppError::BadRequest(format!(
+            "分片未全部上传，已接收 {}/{} 个，请先上传缺失的分片",
+            received.len(),
+            session.total_chunks
+        )));
+    }
+
+    let staging_dir = match StagingDir::new() {
+        Ok(staging_dir) => staging_dir,
+        Err(e) => {
+            release_session(&mut conn, session.id);
+            return ApiOut::err(AppError::Internal(format!("创建暂存目录失败: {}", e)));
+        }
+    };
+    let assembled_path = staging_dir.path().join(&session.file_name);
+    if let Err(err) = assemble_session_file(store.as_ref(), &session, &assembled_path).await {
+        // 摘要不一致时无法确定是哪个分片损坏，删除会话，由客户端重新上传
+        if matches!(err, AppError::Unprocessable(_)) {
+            remove_session(&mut conn, store.as_ref(), session.id).await;
+        } else {
+            release_session(&mut conn, session.id);
+        }
+        return ApiOut::err(err);
+    }
+    drop(conn);
+
+    let uploaded_files = [(session.file_name.clone(), assembled_path)];
+    let result = finish_app_file_upload(depot, &uploaded_files).await;
+
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    match &result {
+        ApiOut::Ok(_) => remove_session(&mut conn, store.as_ref(), session.id).await,
+        // 解析或保存失败时保留分片，会话恢复为上传中，可重试或等待过期清理
+        ApiOut::Err(_) => release_session(&mut conn, session.id),
+    }
+
+    result
+}
+
+// 查询当前用户未过期的上传会话
+fn find_active_session(
+    conn: &mut PgConnection,
+    session_id: Uuid,
+    user_id: Uuid,
+) -> Result<AppUploadSession, AppError> {
+    app_upload_session::table
+        .filter(app_upload_session::id.eq(session_id))
+        .filter(app_upload_session::create_user_id.eq(user_id))
+        .filter(app_upload_session::expires_at.gt(Local::now().naive_local()))
+        .first::<AppUploadSession>(conn)
+        .optional()
+        .map_err(|e| AppError::Internal(format!("查询上传会话失败: {}", e)))?
+        .ok_or_else(|| AppError::NotFound("上传会话不存在或已过期".to_string()))
+}
+
+// 在持有会话行锁（FOR KEY SHARE）的事务中写入分片。合并认领和过期清理以 FOR UPDATE 锁定会话，
+// 会等待进行中的分片写入完成；分片写入之间互不阻塞。请求中断时连接带着未结束的事务被连接池丢弃，事务随之回滚
+async fn write_chunk_locked(
+    conn: &mut PgConnection,
+    store: &dyn FileStore,
+    session_id: Uuid,
+    index: i32,
+    data: Bytes,
+) -> Result<(), AppError> {
+    AnsiTransactionManager::begin_transaction(conn)
+        .map_err(|e| AppError::Internal(format!("开启事务失败: {}", e)))?;
+    let result = async {
+        let status = app_upload_session::table
+            .find(session_id)
+            .filter(app_upload_session::expires_at.gt(Local::now().naive_local()))
+            .select(app_upload_session::status)
+            .for_key_share()
+            .first::<String>(conn)
+            .optional()
+            .map_err(|e| AppError::Internal(format!("查询上传会话失败: {}", e)))?;
+        match status {
+            None => return Err(AppError::NotFound("上传会话不存在或已过期".to_string())),
+            Some(status) if status != UploadSessionStatus::Uploading.as_str() => {
+                return Err(session_completing_error());
+            }
+            Some(_) => {}
+        }
+        write_chunk(store, session_id, index, data)
+            .await
+            .map_err(|e| AppError::Internal(format!("保存分片失败: {}", e)))
+    }
+    .await;
+    match result {
+        Ok(()) => AnsiTransactionManager::commit_transaction(conn)
+            .map_err(|e| AppError::Internal(format!("提交事务失败: {}", e))),
+        Err(err) => {
+            let _ = AnsiTransactionManager::rollback_transaction(conn);
+            Err(err)
+        }
+    }
+}
+
+// 锁定会话并将状态从上传中改为合并中，等待进行中的分片写入完成；认领时顺延有效期，避免合并期间被清理
+fn claim_session(conn: &mut PgConnection, session_id: Uuid) -> Result<bool, AppError> {
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        let uploading = app_upload_session::table
+            .find(session_id)
+            .filter(app_upload_session::status.eq(UploadSessionStatus::Uploading.as_str()))
+            .select(app_upload_session::id)
+            .for_update()
+            .first::<Uuid>(conn)
+            .optional()?;
+        if uploading.is_none() {
+            return Ok(false);
+        }
+        let now = Local::now().naive_local();
+        diesel::update(app_upload_session::table.find(session_id))
+            .set((
+                app_upload_session::status.eq(UploadSessionStatus::Completing.as_str()),
+                app_upload_session::expires_at
+                    .eq(now + Duration::hours(UPLOAD_SESSION_EXPIRE_HOURS)),
+                app_upload_session::update_time.eq(now),
+            ))
+            .execute(conn)?;
+        Ok(true)
+    })
+    .map_err(|e| AppError::Internal(format!("更新上传会话失败: {}", e)))
+}
+
+// 合并分片，会话提供了文件摘要时校验合并结果
+async fn assemble_session_file(
+    store: &dyn FileStore,
+    session: &AppUploadSession,
+    assembled_path: &Path,
+) -> Result<(), AppError> {
+    assemble_chunks(store, session.id, session.total_chunks, assembled_path).await?;
+    let Some(expected_sha256) = session.file_sha256.clone() else {
+        return Ok(());
+    };
+    let target: PathBuf = assembled_path.to_path_buf();
+    let digest = tokio::task::spawn_blocking(move || compute_file_digest(&target))
+        .await
+        .map_err(|e| AppError::Internal(format!("计算文件摘要失败: {}", e)))?
+        .map_err(|e| AppError::Internal(format!("计算文件摘要失败: {}", e)))?;
+    if digest.sha256 != expected_sha256 {
+        return Err(AppError::Unprocessable(format!(
+            "合并后的文件 SHA-256 为 {}，与创建会话时提供的 {} 不一致，请重新创建会话上传",
+            digest.sha256, expected_sha256
+        )));
+    }
+    Ok(())
+}
+
+// 将会话恢复为上传中
+fn release_session(conn: &mut PgConnection, session_id: Uuid) {
+    if let Err(e) = diesel::update(app_upload_session::table.find(session_id))
+        .set(app_upload_session::status.eq(UploadSessionStatus::Uploading.as_str()))
+        .execute(conn)
+    {
+        warn!(session_id = %session_id, error = %e, "恢复上传会话状态失败");
+    }
+}
+
+// 删除会话及其分片，删除失败的分片由清理任务处理
+async fn remove_session(conn: &mut PgConnection, store: &dyn FileStore, session_id: Uuid) {
+    if let Err(e) = diesel::delete(app_upload_session::table.find(session_id)).execute(conn) {
+        warn!(session_id = %session_id, error = %e, "删除上传会话失败");
+    }
+    if let Err(e) = remove_session_chunks(store, session_id).await {
+        warn!(session_id = %session_id, error = %e, "删除上传会话分片失败");
+    }
+}
+
+fn session_completing_error() -> AppError {
+    AppError::Custom {
+        status: StatusCode::CONFLICT,
+        msg: "上传会话正在合并，请稍后查询会话状态".to_string(),
+        err_code: None,
+    }
+}
+
+fn normalize_upload_file_name(raw_name: &str) -> Result<String, AppError> {
+    let file_name = Path::new(raw_name.trim())
+        .file_name()
+        .and_then(|s| s.to_str())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AppError::BadRequest("文件名称不能为空".to_string()))?;
+    let extension = Path::new(file_name)
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_ascii_lowercase())
+        .unwrap_or_default();
+    if !UPLOAD_FILE_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(AppError::BadRequest(format!(
+            "不支持的文件类型'{}'，仅支持 .apk/.aab/.apks/.xapk",
+            file_name
+        )));
+    }
+    Ok(file_name.to_string())
+}
+
+fn to_upload_session_resp(session: &AppUploadSession, received: Vec<i32>) -> UploadSessionResp {
+    let missing_chunks = (0..session.total_chunks)
+        .filter(|index| !received.contains(index))
+        .collect();
+    let received_size = received
+        .iter()
+        .map(|index| expected_chunk_size(session.file_size, session.chunk_size, *index))
+        .sum();
+    UploadSessionResp {
+        session_id: session.id,
+        file_name: session.file_name.clone(),
+        file_size: session.file_size,
+        chunk_size: session.chunk_size,
+        total_chunks: session.total_chunks,
+        received_chunks: received,
+        missing_chunks,
+        received_size,
+        status: session.status.clone(),
+        expires_at: session.expires_at,
+    }
+}
+
+pub fn app_upload_session_router() -> Router {
+    Router::with_path("app_manage/upload_session")
+        .hoop(require_permission(Permission::AppPublish))
+        .push(Router::with_path("create").post(create_upload_session))
+        .push(Router::with_path("chunk").put(upload_session_chunk))
+        .push(Router::with_path("get").post(get_upload_session))
+        .push(Router::with_path("complete").post(complete_upload_session))
+}
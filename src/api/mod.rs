@@ -1,5 +1,8 @@
+pub mod api_token;
 pub mod app_channel;
 pub mod app_manage;
+pub mod app_upload_session;
 pub mod operation_log;
+pub mod organization;
 pub mod ping;
 pub mod users;
@@ -0,0 +1,396 @@
+use crate::middleware::permission_guard::require_login_session;
+use crate::model::body::parse_json_body;
+use crate::model::error::{ApiOut, AppError};
+use crate::model::organization::{
+    AddOrganizationMemberReq, CreateOrganizationReq, GetOrganizationMemberListReq, MemberRole,
+    OrganizationItem, OrganizationMember, OrganizationMemberItem, RemoveOrganizationMemberReq,
+    RemoveOrganizationMemberResp,
+};
+use crate::model::user_role::{CurrentRoles, Permission};
+use crate::model::users::User;
+use crate::schema::*;
+use crate::utils::database_utils::{current_user, current_user_roles, try_connect_database};
+use crate::utils::operation_log_utils::{
+    OP_CREATE_ORGANIZATION, OP_REMOVE_ORGANIZATION_MEMBER, OP_UPDATE_ORGANIZATION_MEMBER,
+    record_operation,
+};
+use crate::utils::organization_utils::{
+    create_organization_with_owner, find_member_role, find_organization, has_other_owner,
+    load_user_organizations,
+};
+use chrono::{Local, NaiveDateTime};
+use diesel::prelude::*;
+use salvo::prelude::*;
+use salvo_oapi::endpoint;
+use uuid::Uuid;
+
+#[endpoint(
+    tags("organization"),
+    summary = "创建组织",
+    description = "创建组织，创建者成为组织所有者；之后在请求头 X-Organization-Id 中指定组织即可管理组织内的渠道和应用",
+    request_body = CreateOrganizationReq
+)]
+pub async fn create_organization(depot: &mut Depot, req: &mut Request) -> ApiOut<OrganizationItem> {
+    let create_req = match parse_json_body::<CreateOrganizationReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+    let name = create_req.name.trim();
+    if name.is_empty() {
+        return ApiOut::err(AppError::BadRequest("组织名称不能为空".to_string()));
+    }
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let organization = match create_organization_with_owner(
+        &mut conn,
+        name,
+        false,
+        current_user.id,
+        Local::now().naive_local(),
+    ) {
+        Ok(organization) => organization,
+        Err(e) => return ApiOut::err(AppError::Internal(format!("创建组织失败: {}", e))),
+    };
+
+    if let Err(e) = record_operation(
+        &mut conn,
+        current_user.id,
+        &current_user.username,
+        OP_CREATE_ORGANIZATION,
+        format!("创建组织'{}'成功", organization.name),
+    ) {
+        return ApiOut::err(e);
+    }
+
+    ApiOut::ok(OrganizationItem {
+        organization_id: organization.id,
+        name: organization.name,
+        is_personal: organization.is_personal,
+        member_role: MemberRole::Owner,
+        create_time: organization.create_time,
+    })
+}
+
+#[endpoint(
+    tags("organization"),
+    summary = "获取我的组织列表",
+    description = "获取当前用户加入的全部组织，个人组织在前"
+)]
+pub async fn get_my_organization_list(depot: &mut Depot) -> ApiOut<Vec<OrganizationItem>> {
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    match load_user_organizations(&mut conn, current_user.id) {
+        Ok(organizations) => ApiOut::ok(organizations),
+        Err(e) => ApiOut::err(AppError::Internal(format!("查询组织失败: {}", e))),
+    }
+}
+
+#[endpoint(
+    tags("organization"),
+    summary = "获取组织成员列表",
+    description = "获取组织的全部成员，需要是组织成员或拥有用户管理权限",
+    request_body = GetOrganizationMemberListReq
+)]
+pub async fn get_organization_member_list(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<Vec<OrganizationMemberItem>> {
+    let member_list_req = match parse_json_body::<GetOrganizationMemberListReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let roles = current_user_roles(depot);
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    if let Err(err) = find_organization(&mut conn, member_list_req.organization_id) {
+        return ApiOut::err(err);
+    }
+    match find_member_role(&mut conn, member_list_req.organization_id, current_user.id) {
+        Ok(Some(_)) => {}
+        Ok(None) if roles.has_permission(Permission::UserManage) => {}
+        Ok(None) => {
+            return ApiOut::err(AppError::FORBIDDEN(format!(
+                "当前用户不是组织'{}'的成员",
+                member_list_req.organization_id
+            )));
+        }
+        Err(e) => return ApiOut::err(AppError::Internal(format!("查询组织成员失败: {}", e))),
+    }
+
+    let members = match organization_member::table
+        .inner_join(users::table)
+        .filter(organization_member::organization_id.eq(member_list_req.organization_id))
+        .order(organization_member::create_time.asc())
+        .select((OrganizationMember::as_select(), User::as_select()))
+        .load::<(OrganizationMember, User)>(&mut conn)
+    {
+        Ok(members) => members,
+        Err(e) => return ApiOut::err(AppError::Internal(format!("查询组织成员失败: {}", e))),
+    };
+
+    ApiOut::ok(
+        members
+            .into_iter()
+            .map(|(member, user)| {
+                organization_member_item(
+                    user,
+                    MemberRole::from_db(&member.member_role).unwrap_or(MemberRole::Member),
+                    member.create_time,
+                )
+            })
+            .collect(),
+    )
+}
+
+#[endpoint(
+    tags("organization"),
+    summary = "添加组织成员",
+    description = "按用户名添加组织成员或修改成员角色，需要是组织所有者或拥有用户管理权限，组织中至少保留一个所有者",
+    request_body = AddOrganizationMemberReq
+)]
+pub async fn add_organization_member(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<OrganizationMemberItem> {
+    let add_req = match parse_json_body::<AddOrganizationMemberReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let roles = current_user_roles(depot);
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let organization = match find_organization(&mut conn, add_req.organization_id) {
+        Ok(organization) => organization,
+        Err(err) => return ApiOut::err(err),
+    };
+    if let Err(err) = ensure_organization_manager(&mut conn, &roles, organization.id, &current_user)
+    {
+        return ApiOut::err(err);
+    }
+
+    let target_user = match users::table
+        .filter(users::username.eq(add_req.username.trim()))
+        .filter(users::is_delete.eq(false))
+        .first::<User>(&mut conn)
+        .optional()
+    {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return ApiOut::err(AppError::NotFound(format!(
+                "用户'{}' 未找到",
+                add_req.username.trim()
+            )));
+        }
+        Err(e) => return ApiOut::err(AppError::Internal(format!("查询用户失败: {}", e))),
+    };
+
+    if add_req.member_role != MemberRole::Owner {
+        match has_other_owner(&mut conn, organization.id, target_user.id) {
+            Ok(true) => {}
+            Ok(false) => {
+                return ApiOut::err(AppError::BadRequest(
+                    "组织中至少需要保留一个所有者".to_string(),
+                ));
+            }
+            Err(e) => {
+                return ApiOut::err(AppError::Internal(format!("查询组织成员失败: {}", e)));
+            }
+        }
+    }
+
+    let member = match diesel::insert_into(organization_member::table)
+        .values(&OrganizationMember {
+            organization_id: organization.id,
+            user_id: target_user.id,
+            member_role: add_req.member_role.as_str().to_string(),
+            create_time: Local::now().naive_local(),
+        })
+        .on_conflict((
+            organization_member::organization_id,
+            organization_member::user_id,
+        ))
+        .do_update()
+        .set(organization_member::member_role.eq(add_req.member_role.as_str()))
+        .get_result::<OrganizationMember>(&mut conn)
+    {
+        Ok(member) => member,
+        Err(e) => return ApiOut::err(AppError::Internal(format!("添加组织成员失败: {}", e))),
+    };
+
+    if let Err(e) = record_operation(
+        &mut conn,
+        current_user.id,
+        &current_user.username,
+        OP_UPDATE_ORGANIZATION_MEMBER,
+        format!(
+            "设置用户'{}'为组织'{}'的{}",
+            target_user.username,
+            organization.name,
+            add_req.member_role.as_str()
+        ),
+    ) {
+        return ApiOut::err(e);
+    }
+
+    ApiOut::ok(organization_member_item(
+        target_user,
+        add_req.member_role,
+        member.create_time,
+    ))
+}
+
+#[endpoint(
+    tags("organization"),
+    summary = "移除组织成员",
+    description = "移除组织成员，成员可以自行退出，移除其他成员需要是组织所有者或拥有用户管理权限，组织中至少保留一个所有者",
+    request_body = RemoveOrganizationMemberReq
+)]
+pub async fn remove_organization_member(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<RemoveOrganizationMemberResp> {
+    let remove_req = match parse_json_body::<RemoveOrganizationMemberReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let roles = current_user_roles(depot);
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let organization = match find_organization(&mut conn, remove_req.organization_id) {
+        Ok(organization) => organization,
+        Err(err) => return ApiOut::err(err),
+    };
+    if remove_req.user_id != current_user.id
+        && let Err(err) =
+            ensure_organization_manager(&mut conn, &roles, organization.id, &current_user)
+    {
+        return ApiOut::err(err);
+    }
+
+    match has_other_owner(&mut conn, organization.id, remove_req.user_id) {
+        Ok(true) => {}
+        Ok(false) => {
+            return ApiOut::err(AppError::BadRequest(
+                "组织中至少需要保留一个所有者".to_string(),
+            ));
+        }
+        Err(e) => return ApiOut::err(AppError::Internal(format!("查询组织成员失败: {}", e))),
+    }
+
+    let result = diesel::delete(
+        organization_member::table
+            .filter(organization_member::organization_id.eq(organization.id))
+            .filter(organization_member::user_id.eq(remove_req.user_id)),
+    )
+    .execute(&mut conn);
+
+    match result {
+        Ok(0) => ApiOut::err(AppError::NotFound(format!(
+            "用户Id'{}' 不是组织'{}'的成员",
+            remove_req.user_id, organization.name
+        ))),
+        Ok(_) => {
+            if let Err(e) = record_operation(
+                &mut conn,
+                current_user.id,
+                &current_user.username,
+                OP_REMOVE_ORGANIZATION_MEMBER,
+                format!(
+                    "将用户Id'{}'移出组织'{}'",
+                    remove_req.user_id, organization.name
+                ),
+            ) {
+                return ApiOut::err(e);
+            }
+
+            ApiOut::ok(RemoveOrganizationMemberResp {
+                organization_id: organization.id,
+                user_id: remove_req.user_id,
+                remove_info: "移除组织成员成功".to_string(),
+            })
+        }
+        Err(e) => ApiOut::err(AppError::Internal(format!("移除组织成员失败: {}", e))),
+    }
+}
+
+// 校验当前用户可以管理组织成员：组织所有者或拥有用户管理权限
+fn ensure_organization_manager(
+    conn: &mut PgConnection,
+    roles: &CurrentRoles,
+    organization_id: Uuid,
+    user: &User,
+) -> Result<(), AppError> {
+    if roles.has_permission(Permission::UserManage) {
+        return Ok(());
+    }
+    match find_member_role(conn, organization_id, user.id) {
+        Ok(Some(MemberRole::Owner)) => Ok(()),
+        Ok(_) => Err(AppError::FORBIDDEN(
+            "只有组织所有者可以管理组织成员".to_string(),
+        )),
+        Err(e) => Err(AppError::Internal(format!("查询组织成员失败: {}", e))),
+    }
+}
+
+fn organization_member_item(
+    user: User,
+    member_role: MemberRole,
+    create_time: NaiveDateTime,
+) -> OrganizationMemberItem {
+    OrganizationMemberItem {
+        user_id: user.id,
+        username: user.username,
+        full_name: user.full_name,
+        member_role,
+        create_time,
+    }
+}
+
+pub fn organization_router() -> Router {
+    Router::with_path("organization")
+        .hoop(require_login_session())
+        .push(Router::with_path("create_organization").post(create_organization))
+        .push(Router::with_path("get_my_organization_list").post(get_my_organization_list))
+        .push(Router::with_path("get_organization_member_list").post(get_organization_member_list))
+        .push(Router::with_path("add_organization_member").post(add_organization_member))
+        .push(Router::with_path("remove_organization_member").post(remove_organization_member))
+}
@@ -4,6 +4,7 @@ use crate::model::users::{GetUserReq, GetUserResp};
 use salvo::prelude::*;
 
 #[endpoint(tags("ping"), summary = "ping测试", description = "ping测试")]
+#[allow(clippy::useless_conversion)]
 pub async fn ping() -> ApiOut<String> {
     ApiOut::ok("ping success! 可以ping通！".to_string()).into()
 }
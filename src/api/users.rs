@@ -1,26 +1,51 @@
+use crate::middleware::permission_guard::{require_login_session, require_permission};
+use crate::model::api_token::ApiTokenScope;
 use crate::model::body::parse_json_body;
 use crate::model::error::{ApiOut, AppError, NoData};
 use crate::model::jwt::{AccessTokenClaims, RefreshTokenReq, TokenResp};
+use crate::model::organization::CurrentOrganization;
 use crate::model::response::ApiResponse;
+use crate::model::user_role::{CurrentRoles, Permission, Role, UpdateUserRolesReq, UserRoleItem};
+use crate::model::user_session::{
+    CurrentSession, RefreshTokenStatus, RevokeAllUserSessionsReq, RevokeAllUserSessionsResp,
+    RevokeUserSessionReq, RevokeUserSessionResp, SessionClient, UserSessionItem,
+};
 use crate::model::users::{
     CaptchaResp, LoginReq, LoginResp, RegisterReq, RegisterResp, User, UserInfoResp,
 };
 use crate::schema::*;
-use crate::store::{get_captcha_store, get_token_store};
+use crate::store::{TokenStore, get_captcha_store, get_token_store};
+use crate::utils::api_token_utils::{api_token_scope, authenticate_api_token, bearer_api_token};
 use crate::utils::auth_captcha_utils;
-use crate::utils::database_utils::{current_user, try_connect_database};
+use crate::utils::database_utils::{
+    current_session_id, current_user, current_user_roles, try_connect_database,
+};
 use crate::utils::jwt_service::{
-    generate_access_token, generate_refresh_token, refresh_access_token,
+    generate_access_token, generate_refresh_token, refresh_access_token, verify_refresh_token,
+};
+use crate::utils::operation_log_utils::{
+    record_operation, OP_LOGIN, OP_REFRESH_TOKEN_REUSED, OP_REVOKE_ALL_SESSIONS, OP_REVOKE_SESSION,
+    OP_UPDATE_USER_ROLES,
+};
+use crate::utils::organization_utils::{
+    ORGANIZATION_ID_HEADER, create_personal_organization, parse_organization_id,
+    resolve_current_organization,
 };
-use crate::utils::operation_log_utils::{record_operation, OP_LOGIN};
 use crate::utils::password_utils::{hash_password, verify_password_result};
+use crate::utils::user_role_utils::{
+    has_other_admin, load_user_roles, lock_admin_roles, registration_role, replace_user_roles,
+};
 use chrono::Local;
 use diesel::prelude::*;
 use salvo::http::StatusCode;
+use salvo::http::header::AUTHORIZATION;
 use salvo::prelude::*;
 use salvo_oapi::endpoint;
 use uuid::Uuid;
 
+/// 已轮换的刷新Token被再次使用时返回的错误码，客户端收到后应清除本地Token并重新登录
+pub const REFRESH_TOKEN_REUSED: &str = "REFRESH_TOKEN_REUSED";
+
 #[endpoint(
     tags("Users"),
     summary = "获取登录注册验证码",
@@ -114,16 +139,19 @@ pub async fn register(depot: &mut Depot, req: &mut Request) -> ApiOut<RegisterRe
         full_name: register_req.username.clone(),
         create_time: now,
         update_time: now,
-        access_token: "".to_string(),
-        refresh_token: "".to_string(),
         is_delete: false,
     };
 
-    //插入数据到数据库
-    if let Err(e) = diesel::insert_into(users::table)
-        .values(&new_user)
-        .execute(&mut conn)
-    {
+    //插入数据到数据库，同时创建用户的个人组织；第一个注册的用户为管理员，其余用户使用默认角色
+    if let Err(e) = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        let role = registration_role(conn)?;
+        diesel::insert_into(users::table)
+            .values(&new_user)
+            .execute(conn)?;
+        replace_user_roles(conn, new_user.id, &[role])?;
+        create_personal_organization(conn, &new_user, now)?;
+        Ok(())
+    }) {
         return ApiOut::err(AppError::Internal(format!("插入新用户失败: {}", e)));
     }
 
@@ -134,6 +162,7 @@ pub async fn register(depot: &mut Depot, req: &mut Request) -> ApiOut<RegisterRe
 }
 
 #[endpoint(tags("Users"), summary = "登录", description = "登录",request_body = LoginReq)]
+#[allow(clippy::bool_comparison)]
 pub async fn login(depot: &mut Depot, req: &mut Request) -> ApiOut<LoginResp> {
     let login_req = match parse_json_body::<LoginReq>(req).await {
         Ok(v) => v,
@@ -198,11 +227,22 @@ pub async fn login(depot: &mut Depot, req: &mut Request) -> ApiOut<LoginResp> {
         Err(err) => return ApiOut::err(err),
     };
 
-    match generate_access_token(&user_id, &username) {
-        Ok(access_token_str) => match generate_refresh_token(&user_id, &username) {
+    //每次登录创建新会话，不影响其他设备已登录的会话
+    let session_id = Uuid::new_v4();
+    let session = session_id.to_string();
+    match generate_access_token(&user_id, &username, &session) {
+        Ok(access_token_str) => match generate_refresh_token(&user_id, &username, &session) {
             Ok(refresh_token_str) => {
                 match token_store
-                    .save_tokens(user_uuid, access_token_str, refresh_token_str)
+                    .create_session(
+                        session_id,
+                        user_uuid,
+                        TokenResp {
+                            access_token: access_token_str,
+                            refresh_token: refresh_token_str,
+                        },
+                        session_client(req),
+                    )
                     .await
                 {
                     Ok(token_resp) => {
@@ -252,11 +292,281 @@ pub async fn get_users_info(depot: &mut Depot) -> ApiOut<UserInfoResp> {
         username: current_user.username.clone(),
         create_time: current_user.create_time,
         is_delete: current_user.is_delete,
+        roles: current_user_roles(depot).0,
     };
 
     ApiOut::ok(user_response_model)
 }
 
+#[endpoint(
+    tags("Users"),
+    summary = "获取用户列表",
+    security(("Authorization" = [])),
+    description = "获取所有用户及其角色，需要管理员角色"
+)]
+pub async fn get_user_list(depot: &mut Depot) -> ApiOut<Vec<UserRoleItem>> {
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let users = match users::table
+        .filter(users::is_delete.eq(false))
+        .order(users::create_time.asc())
+        .load::<User>(&mut conn)
+    {
+        Ok(users) => users,
+        Err(e) => return ApiOut::err(AppError::Internal(format!("查询用户失败: {}", e))),
+    };
+
+    let mut user_list = Vec::with_capacity(users.len());
+    for user in users {
+        let roles = match load_user_roles(&mut conn, user.id) {
+            Ok(roles) => roles,
+            Err(e) => return ApiOut::err(AppError::Internal(format!("查询用户角色失败: {}", e))),
+        };
+        user_list.push(UserRoleItem {
+            id: user.id,
+            username: user.username,
+            full_name: user.full_name,
+            roles,
+            create_time: user.create_time,
+        });
+    }
+
+    ApiOut::ok(user_list)
+}
+
+#[endpoint(
+    tags("Users"),
+    summary = "修改用户角色",
+    security(("Authorization" = [])),
+    description = "覆盖用户的全部角色，需要管理员角色，系统中至少保留一个管理员",
+    request_body = UpdateUserRolesReq
+)]
+pub async fn update_user_roles(depot: &mut Depot, req: &mut Request) -> ApiOut<UserRoleItem> {
+    let update_req = match parse_json_body::<UpdateUserRolesReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+    let mut roles: Vec<Role> = Vec::with_capacity(update_req.roles.len());
+    for role in update_req.roles {
+        if !roles.contains(&role) {
+            roles.push(role);
+        }
+    }
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let target_user = match users::table
+        .filter(users::id.eq(update_req.user_id))
+        .filter(users::is_delete.eq(false))
+        .first::<User>(&mut conn)
+        .optional()
+    {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return ApiOut::err(AppError::NotFound(format!(
+                "用户Id'{}' 未找到",
+                update_req.user_id
+            )));
+        }
+        Err(e) => return ApiOut::err(AppError::Internal(format!("查询用户失败: {}", e))),
+    };
+
+    // 加锁后再检查剩余管理员，两个管理员同时互相取消管理员角色时只有一方成功
+    let role_names: Vec<&str> = roles.iter().map(Role::as_str).collect();
+    if let Err(e) = conn.transaction::<_, AppError, _>(|conn| {
+        lock_admin_roles(conn)
+            .map_err(|e| AppError::Internal(format!("修改用户角色失败: {}", e)))?;
+        if !roles.contains(&Role::Admin)
+            && !has_other_admin(conn, target_user.id)
+                .map_err(|e| AppError::Internal(format!("查询用户角色失败: {}", e)))?
+        {
+            return Err(AppError::BadRequest(
+                "系统中至少需要保留一个管理员".to_string(),
+            ));
+        }
+        replace_user_roles(conn, target_user.id, &roles)
+            .map_err(|e| AppError::Internal(format!("修改用户角色失败: {}", e)))?;
+        record_operation(
+            conn,
+            current_user.id,
+            &current_user.username,
+            OP_UPDATE_USER_ROLES,
+            format!(
+                "修改用户'{}'的角色为[{}]",
+                target_user.username,
+                role_names.join(", ")
+            ),
+        )
+    }) {
+        return ApiOut::err(e);
+    }
+
+    ApiOut::ok(UserRoleItem {
+        id: target_user.id,
+        username: target_user.username,
+        full_name: target_user.full_name,
+        roles,
+        create_time: target_user.create_time,
+    })
+}
+
+#[endpoint(
+    tags("Users"),
+    summary = "获取我的登录会话",
+    security(("Authorization" = [])),
+    description = "获取当前用户未吊销且未过期的全部登录会话，最近活跃的在前"
+)]
+pub async fn get_session_list(depot: &mut Depot) -> ApiOut<Vec<UserSessionItem>> {
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let current_session_id = current_session_id(depot);
+    let token_store = match get_token_store(depot) {
+        Ok(store) => store,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    match token_store.list_sessions(current_user.id).await {
+        Ok(sessions) => ApiOut::ok(
+            sessions
+                .into_iter()
+                .map(|session| UserSessionItem {
+                    session_id: session.id,
+                    user_agent: session.user_agent,
+                    ip_address: session.ip_address,
+                    create_time: session.create_time,
+                    last_seen_time: session.last_seen_time,
+                    is_current: current_session_id == Some(session.id),
+                })
+                .collect(),
+        ),
+        Err(err) => ApiOut::err(err),
+    }
+}
+
+#[endpoint(
+    tags("Users"),
+    summary = "吊销登录会话",
+    security(("Authorization" = [])),
+    description = "吊销当前用户的指定登录会话，该会话的Token立即失效",
+    request_body = RevokeUserSessionReq
+)]
+pub async fn revoke_session(depot: &mut Depot, req: &mut Request) -> ApiOut<RevokeUserSessionResp> {
+    let revoke_req = match parse_json_body::<RevokeUserSessionReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let token_store = match get_token_store(depot) {
+        Ok(store) => store,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    match token_store
+        .revoke_session(current_user.id, revoke_req.session_id)
+        .await
+    {
+        Ok(true) => {
+            let mut conn = match try_connect_database(depot) {
+                Ok(conn) => conn,
+                Err(err) => return ApiOut::err(err),
+            };
+            if let Err(e) = record_operation(
+                &mut conn,
+                current_user.id,
+                &current_user.username,
+                OP_REVOKE_SESSION,
+                format!("吊销登录会话'{}'成功", revoke_req.session_id),
+            ) {
+                return ApiOut::err(e);
+            }
+
+            ApiOut::ok(RevokeUserSessionResp {
+                session_id: revoke_req.session_id,
+                revoke_info: "登录会话已吊销".to_string(),
+            })
+        }
+        Ok(false) => ApiOut::err(AppError::NotFound(format!(
+            "登录会话Id'{}' 未找到",
+            revoke_req.session_id
+        ))),
+        Err(err) => ApiOut::err(err),
+    }
+}
+
+#[endpoint(
+    tags("Users"),
+    summary = "吊销全部登录会话",
+    security(("Authorization" = [])),
+    description = "吊销当前用户的全部登录会话，keep_current 为 true 时保留当前会话",
+    request_body = RevokeAllUserSessionsReq
+)]
+pub async fn revoke_all_sessions(
+    depot: &mut Depot,
+    req: &mut Request,
+) -> ApiOut<RevokeAllUserSessionsResp> {
+    let revoke_req = match parse_json_body::<RevokeAllUserSessionsReq>(req).await {
+        Ok(v) => v,
+        Err(e) => return ApiOut::err(e),
+    };
+
+    let current_user = match current_user(depot) {
+        Ok(user) => user,
+        Err(err) => return ApiOut::err(err),
+    };
+    let keep_session_id = if revoke_req.keep_current {
+        current_session_id(depot)
+    } else {
+        None
+    };
+    let token_store = match get_token_store(depot) {
+        Ok(store) => store,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let revoke_count = match token_store
+        .revoke_all_sessions(current_user.id, keep_session_id)
+        .await
+    {
+        Ok(count) => count,
+        Err(err) => return ApiOut::err(err),
+    };
+
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return ApiOut::err(err),
+    };
+    if let Err(e) = record_operation(
+        &mut conn,
+        current_user.id,
+        &current_user.username,
+        OP_REVOKE_ALL_SESSIONS,
+        format!("吊销全部登录会话成功，共{}个", revoke_count),
+    ) {
+        return ApiOut::err(e);
+    }
+
+    ApiOut::ok(RevokeAllUserSessionsResp {
+        revoke_count,
+        revoke_info: format!("已吊销{}个登录会话", revoke_count),
+    })
+}
+
 #[endpoint(tags("Users"),  summary = "刷新Token", description = "刷新Token",request_body = RefreshTokenReq
 )]
 pub async fn refresh_token(req: &mut Request, depot: &mut Depot) -> ApiOut<TokenResp> {
@@ -277,24 +587,46 @@ pub async fn refresh_token(req: &mut Request, depot: &mut Depot) -> ApiOut<Token
         Err(err) => return ApiOut::err(err),
     };
 
+    //从刷新Token中取出所属会话，会话即刷新Token家族
+    let (session_id, username) = match verify_refresh_token(&refresh_req.refresh_token)
+        .ok()
+        .and_then(|claims| {
+            Uuid::parse_str(&claims.session_id)
+                .ok()
+                .map(|session_id| (session_id, claims.user_name))
+        }) {
+        Some(session) => session,
+        None => {
+            return ApiOut::err(AppError::unauthorized_with_code(
+                "刷新Token无效,请重新登录!",
+                "REFRESH_TOKEN_INVALID",
+            ));
+        }
+    };
+
     match token_store
-        .refresh_token_matches(user_uuid, &refresh_req.refresh_token)
+        .refresh_token_status(session_id, user_uuid, &refresh_req.refresh_token)
         .await
     {
-        Ok(true) => match refresh_access_token(&refresh_req.refresh_token) {
+        Ok(RefreshTokenStatus::Current) => match refresh_access_token(&refresh_req.refresh_token) {
             Ok(refresh_token_resp) => {
                 match token_store
-                    .save_tokens(
-                        user_uuid,
-                        refresh_token_resp.access_token,
-                        refresh_token_resp.refresh_token,
+                    .rotate_session_tokens(
+                        session_id,
+                        &refresh_req.refresh_token,
+                        TokenResp {
+                            access_token: refresh_token_resp.access_token,
+                            refresh_token: refresh_token_resp.refresh_token,
+                        },
                     )
                     .await
                 {
-                    Ok(token_resp) => ApiOut::ok(TokenResp {
+                    Ok(Some(token_resp)) => ApiOut::ok(TokenResp {
                         access_token: token_resp.access_token,
                         refresh_token: token_resp.refresh_token,
                     }),
+                    //同一刷新Token被并发请求抢先轮换，属于重复提交，不吊销会话
+                    Ok(None) => ApiOut::err(recently_rotated_refresh_token_error()),
                     Err(app_error) => ApiOut::Err(app_error),
                 }
             }
@@ -303,7 +635,13 @@ pub async fn refresh_token(req: &mut Request, depot: &mut Depot) -> ApiOut<Token
                 "REFRESH_TOKEN_INVALID",
             )),
         },
-        Ok(false) => ApiOut::err(AppError::unauthorized_with_code(
+        Ok(RefreshTokenStatus::Rotated) => ApiOut::err(
+            revoke_reused_refresh_token_family(depot, user_uuid, &username, session_id).await,
+        ),
+        Ok(RefreshTokenStatus::RecentlyRotated) => {
+            ApiOut::err(recently_rotated_refresh_token_error())
+        }
+        Ok(RefreshTokenStatus::Unknown) => ApiOut::err(AppError::unauthorized_with_code(
             "未查询到刷新Token或刷新Token不一致,请重新登录!",
             "REFRESH_TOKEN_INVALID",
         )),
@@ -311,9 +649,77 @@ pub async fn refresh_token(req: &mut Request, depot: &mut Depot) -> ApiOut<Token
     }
 }
 
+//刷新Token刚被轮换（多标签页同时刷新或超时重试），客户端应改用最新的刷新Token
+fn recently_rotated_refresh_token_error() -> AppError {
+    AppError::unauthorized_with_code(
+        "刷新Token已被轮换,请使用最新的刷新Token!",
+        "REFRESH_TOKEN_INVALID",
+    )
+}
+
+//已轮换的刷新Token再次出现，说明Token可能被盗用：吊销整个会话并记录安全事件
+async fn revoke_reused_refresh_token_family(
+    depot: &mut Depot,
+    user_id: Uuid,
+    username: &str,
+    session_id: Uuid,
+) -> AppError {
+    let token_store = match get_token_store(depot) {
+        Ok(store) => store,
+        Err(err) => return err,
+    };
+    let mut conn = match try_connect_database(depot) {
+        Ok(conn) => conn,
+        Err(err) => return err,
+    };
+    revoke_refresh_token_family(
+        token_store.as_ref(),
+        &mut conn,
+        user_id,
+        username,
+        session_id,
+    )
+    .await
+}
+
+async fn revoke_refresh_token_family(
+    token_store: &dyn TokenStore,
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    username: &str,
+    session_id: Uuid,
+) -> AppError {
+    if let Err(err) = token_store.revoke_session(user_id, session_id).await {
+        return err;
+    }
+
+    if let Err(err) = record_operation(
+        conn,
+        user_id,
+        username,
+        OP_REFRESH_TOKEN_REUSED,
+        format!(
+            "检测到会话'{}'已轮换的刷新Token被再次使用，已吊销该会话",
+            session_id
+        ),
+    ) {
+        return err;
+    }
+
+    AppError::unauthorized_with_code(
+        "刷新Token已被使用过，当前会话已吊销，请重新登录!",
+        REFRESH_TOKEN_REUSED,
+    )
+}
+
 //验证Token
 #[handler]
-pub async fn auth_token(depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCtrl) {
+pub async fn auth_token(
+    req: &mut Request,
+    depot: &mut Depot,
+    res: &mut Response,
+    ctrl: &mut FlowCtrl,
+) {
     let auth_state = depot.jwt_auth_state();
     let token_data = depot.jwt_auth_data::<AccessTokenClaims>().cloned();
     let auth_error = depot.jwt_auth_error();
@@ -330,6 +736,34 @@ pub async fn auth_token(depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCt
         }));
     };
 
+    //API令牌不是JWT，按令牌哈希单独校验
+    let api_token = bearer_api_token(
+        req.headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok()),
+    )
+    .map(str::to_string);
+    if let Some(api_token) = api_token {
+        match load_api_token_context(depot, req, &api_token) {
+            Ok((user, roles, organization, scope)) => {
+                depot.inject(CurrentRoles(roles));
+                depot.inject(organization);
+                depot.inject(scope);
+                depot.insert("user", user);
+            }
+            Err(err) => {
+                ctrl.skip_rest();
+                render_error(
+                    res,
+                    err.http_status(),
+                    err.to_string(),
+                    err.err_code().as_deref(),
+                );
+            }
+        }
+        return;
+    }
+
     match auth_state {
         JwtAuthState::Authorized => {
             match token_data {
@@ -369,6 +803,19 @@ pub async fn auth_token(depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCt
                             return;
                         }
                     };
+                    let session_id = match Uuid::parse_str(&token_data.claims.session_id) {
+                        Ok(uuid) => uuid,
+                        Err(e) => {
+                            ctrl.skip_rest();
+                            render_error(
+                                res,
+                                StatusCode::UNAUTHORIZED,
+                                format!("无效的会话ID格式: {}", e),
+                                Some("ACCESS_TOKEN_INVALID"),
+                            );
+                            return;
+                        }
+                    };
 
                     let token_store = match get_token_store(depot) {
                         Ok(store) => store,
@@ -403,8 +850,29 @@ pub async fn auth_token(depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCt
 
                     if let Some(user) = existing_user {
                         if let Some(ref token) = auth_token_owned {
-                            match token_store.access_token_matches(user_id_uuid, token).await {
+                            match token_store
+                                .access_token_matches(session_id, user_id_uuid, token)
+                                .await
+                            {
                                 Ok(true) => {
+                                    //加载用户角色和当前组织，供接口权限校验和数据隔离使用
+                                    let (roles, organization) =
+                                        match load_current_user_context(depot, req, user.id) {
+                                            Ok(context) => context,
+                                            Err(err) => {
+                                                ctrl.skip_rest();
+                                                render_error(
+                                                    res,
+                                                    err.http_status(),
+                                                    err.to_string(),
+                                                    err.err_code().as_deref(),
+                                                );
+                                                return;
+                                            }
+                                        };
+                                    depot.inject(CurrentRoles(roles));
+                                    depot.inject(organization);
+                                    depot.inject(CurrentSession(session_id));
                                     //验证通过则插入用户信息
                                     depot.insert("user", user);
                                     //验证通过，继续执行后续handler，不返回任何内容
@@ -414,7 +882,7 @@ pub async fn auth_token(depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCt
                                     render_error(
                                         res,
                                         StatusCode::UNAUTHORIZED,
-                                        "数据库Token不一致！".to_string(),
+                                        "会话已吊销或Token已刷新，请重新登录！".to_string(),
                                         Some("ACCESS_TOKEN_REVOKED"),
                                     );
                                 }
@@ -474,6 +942,72 @@ pub async fn auth_token(depot: &mut Depot, res: &mut Response, ctrl: &mut FlowCt
     }
 }
 
+//查询当前登录用户的角色和请求头指定的组织
+fn load_current_user_context(
+    depot: &mut Depot,
+    req: &Request,
+    user_id: Uuid,
+) -> Result<(Vec<Role>, CurrentOrganization), AppError> {
+    let organization_id = parse_organization_id(
+        req.headers()
+            .get(ORGANIZATION_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    )?;
+    let mut conn = try_connect_database(depot)?;
+    let roles = load_user_roles(&mut conn, user_id)
+        .map_err(|e| AppError::Internal(format!("查询用户角色失败: {}", e)))?;
+    let organization = resolve_current_organization(&mut conn, user_id, organization_id)?;
+    Ok((roles, organization))
+}
+
+//校验API令牌，查询令牌所属用户、角色和令牌绑定的组织
+fn load_api_token_context(
+    depot: &mut Depot,
+    req: &Request,
+    token: &str,
+) -> Result<(User, Vec<Role>, CurrentOrganization, ApiTokenScope), AppError> {
+    let organization_id = parse_organization_id(
+        req.headers()
+            .get(ORGANIZATION_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    )?;
+    let mut conn = try_connect_database(depot)?;
+    let api_token = authenticate_api_token(&mut conn, token, Local::now().naive_local())?;
+    if organization_id.is_some_and(|organization_id| organization_id != api_token.organization_id) {
+        return Err(AppError::FORBIDDEN(format!(
+            "API令牌'{}'只能访问创建时所在的组织",
+            api_token.token_name
+        )));
+    }
+
+    let user = users::table
+        .filter(users::id.eq(api_token.user_id))
+        .filter(users::is_delete.eq(false))
+        .first::<User>(&mut conn)
+        .optional()
+        .map_err(|e| AppError::Internal(format!("查询用户失败: {}", e)))?
+        .ok_or_else(|| {
+            AppError::unauthorized_with_code("API令牌所属用户不存在", "API_TOKEN_INVALID")
+        })?;
+    let roles = load_user_roles(&mut conn, user.id)
+        .map_err(|e| AppError::Internal(format!("查询用户角色失败: {}", e)))?;
+    let organization =
+        resolve_current_organization(&mut conn, user.id, Some(api_token.organization_id))?;
+    Ok((user, roles, organization, api_token_scope(&api_token)))
+}
+
+//登录客户端的 User-Agent 和 IP，记录到会话中
+fn session_client(req: &Request) -> SessionClient {
+    SessionClient {
+        user_agent: req
+            .headers()
+            .get("user-agent")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+        ip_address: req.remote_addr().ip().map(|ip| ip.to_string()),
+    }
+}
+
 //验证验证码
 async fn validate_captcha(
     depot: &mut Depot,
@@ -514,5 +1048,190 @@ pub fn user_router_not_auth() -> Router {
 
 //需要token的路由
 pub fn users_router() -> Router {
-    Router::with_path("users").push(Router::with_path("get_users_info").post(get_users_info))
+    Router::with_path("users")
+        .push(Router::with_path("get_users_info").post(get_users_info))
+        .push(
+            Router::new()
+                .hoop(require_login_session())
+                .push(Router::with_path("get_session_list").post(get_session_list))
+                .push(Router::with_path("revoke_session").post(revoke_session))
+                .push(Router::with_path("revoke_all_sessions").post(revoke_all_sessions)),
+        )
+        .push(
+            Router::with_path("get_user_list")
+                .hoop(require_permission(Permission::UserManage))
+                .post(get_user_list),
+        )
+        .push(
+            Router::with_path("update_user_roles")
+                .hoop(require_permission(Permission::UserManage))
+                .post(update_user_roles),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use salvo::conn::SocketAddr;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn session_client_reads_user_agent_and_remote_ip() {
+        let mut req = Request::new();
+        req.headers_mut()
+            .insert("user-agent", "Mozilla/5.0 (Macintosh)".parse().unwrap());
+        *req.remote_addr_mut() = SocketAddr::from(std::net::SocketAddr::new(
+            IpAddr::V4(Ipv4Addr::new(10, 0, 0, 8)),
+            51234,
+        ));
+
+        let client = session_client(&req);
+        assert_eq!(
+            client.user_agent.as_deref(),
+            Some("Mozilla/5.0 (Macintosh)")
+        );
+        assert_eq!(client.ip_address.as_deref(), Some("10.0.0.8"));
+
+        let client = session_client(&Request::new());
+        assert_eq!(client.user_agent, None);
+    }
+
+    // 连接真实数据库验证刷新Token的轮换、宽限期和重放处理，未设置 `TEST_DATABASE_URL` 时跳过；
+    // 测试会执行数据库迁移，并在结束时删除创建的用户、会话和操作日志
+    #[tokio::test]
+    async fn reused_refresh_token_revokes_session_and_records_operation() {
+        use crate::db::MIGRATIONS;
+        use crate::model::jwt::TokenResp;
+        use crate::store::{PostgresTokenStore, REFRESH_TOKEN_REUSE_GRACE_SECS};
+        use diesel::r2d2::{ConnectionManager, Pool};
+        use diesel_migrations::MigrationHarness;
+        use std::sync::Arc;
+
+        let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("未设置 TEST_DATABASE_URL，跳过刷新Token重放测试");
+            return;
+        };
+        // Token 有效期等配置与服务启动时一样从环境变量或 .env 读取
+        dotenvy::dotenv().ok();
+        let pool = Pool::builder()
+            .max_size(2)
+            .build(ConnectionManager::<PgConnection>::new(database_url))
+            .unwrap();
+        let mut conn = pool.get().unwrap();
+        conn.run_pending_migrations(MIGRATIONS).unwrap();
+
+        let now = Local::now().naive_local();
+        let user_id = Uuid::new_v4();
+        let username = format!("refresh-{}", user_id.simple());
+        diesel::insert_into(users::table)
+            .values(&User {
+                id: user_id,
+                username: username.clone(),
+                password: String::new(),
+                full_name: username.clone(),
+                create_time: now,
+                update_time: now,
+                is_delete: false,
+            })
+            .execute(&mut conn)
+            .unwrap();
+
+        let store = PostgresTokenStore::new(Arc::new(pool.clone()));
+        let session_id = Uuid::new_v4();
+        let tokens = |suffix: &str| TokenResp {
+            access_token: format!("access-{suffix}-{session_id}"),
+            refresh_token: format!("refresh-{suffix}-{session_id}"),
+        };
+        let first = tokens("1");
+        let second = tokens("2");
+        store
+            .create_session(session_id, user_id, tokens("1"), SessionClient::default())
+            .await
+            .unwrap();
+        let status = |token: String| {
+            let store = &store;
+            async move {
+                store
+                    .refresh_token_status(session_id, user_id, &token)
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(
+            status(first.refresh_token.clone()).await,
+            RefreshTokenStatus::Current
+        );
+        assert_eq!(
+            status("unknown".to_string()).await,
+            RefreshTokenStatus::Unknown
+        );
+
+        // 轮换后旧Token在宽限期内按重复提交处理，并发的第二次轮换落空
+        assert!(
+            store
+                .rotate_session_tokens(session_id, &first.refresh_token, tokens("2"))
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            store
+                .rotate_session_tokens(session_id, &first.refresh_token, tokens("3"))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            status(second.refresh_token.clone()).await,
+            RefreshTokenStatus::Current
+        );
+        assert_eq!(
+            status(first.refresh_token.clone()).await,
+            RefreshTokenStatus::RecentlyRotated
+        );
+
+        // 超过宽限期后再次出现旧Token，吊销整个会话并记录安全事件
+        diesel::update(
+            user_session_rotated_token::table
+                .filter(user_session_rotated_token::session_id.eq(session_id)),
+        )
+        .set(
+            user_session_rotated_token::rotated_time
+                .eq(now - chrono::Duration::seconds(REFRESH_TOKEN_REUSE_GRACE_SECS + 1)),
+        )
+        .execute(&mut conn)
+        .unwrap();
+        assert_eq!(
+            status(first.refresh_token.clone()).await,
+            RefreshTokenStatus::Rotated
+        );
+
+        let err =
+            revoke_refresh_token_family(&store, &mut conn, user_id, &username, session_id).await;
+        assert_eq!(err.err_code().as_deref(), Some(REFRESH_TOKEN_REUSED));
+        assert!(
+            !store
+                .access_token_matches(session_id, user_id, &second.access_token)
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            status(second.refresh_token.clone()).await,
+            RefreshTokenStatus::Unknown
+        );
+        let logged = operation_log::table
+            .filter(operation_log::user_id.eq(user_id))
+            .filter(operation_log::operation_type.eq(OP_REFRESH_TOKEN_REUSED))
+            .count()
+            .get_result::<i64>(&mut conn)
+            .unwrap();
+        assert_eq!(logged, 1);
+
+        diesel::delete(operation_log::table.filter(operation_log::user_id.eq(user_id)))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::delete(users::table.filter(users::id.eq(user_id)))
+            .execute(&mut conn)
+            .unwrap();
+    }
 }
@@ -1 +1,2 @@
 pub mod access_log;
+pub mod permission_guard;
@@ -0,0 +1,181 @@
+use crate::model::api_token::ApiTokenScope;
+use crate::model::error::AppError;
+use crate::model::user_role::{CurrentRoles, Permission};
+use salvo::{Depot, FlowCtrl, Handler, Request, Response, Writer};
+
+// 接口权限校验，需在鉴权中间件写入当前用户角色之后执行
+pub struct PermissionGuard {
+    permission: Permission,
+}
+
+pub fn require_permission(permission: Permission) -> PermissionGuard {
+    PermissionGuard { permission }
+}
+
+#[salvo::async_trait]
+impl Handler for PermissionGuard {
+    async fn handle(
+        &self,
+        req: &mut Request,
+        depot: &mut Depot,
+        res: &mut Response,
+        ctrl: &mut FlowCtrl,
+    ) {
+        let allowed = depot
+            .obtain::<CurrentRoles>()
+            .is_ok_and(|roles| roles.has_permission(self.permission));
+        if !allowed {
+            ctrl.skip_rest();
+            AppError::FORBIDDEN(format!(
+                "当前用户没有'{}'权限，请联系管理员分配角色",
+                self.permission.as_str()
+            ))
+            .write(req, depot, res)
+            .await;
+            return;
+        }
+
+        // 使用API令牌时还需令牌的权限范围包含该权限
+        let token_denied = depot
+            .obtain::<ApiTokenScope>()
+            .is_ok_and(|scope| !scope.allows_permission(self.permission));
+        if token_denied {
+            ctrl.skip_rest();
+            AppError::FORBIDDEN(format!("API令牌没有'{}'权限", self.permission.as_str()))
+                .write(req, depot, res)
+                .await;
+        }
+    }
+}
+
+// 仅允许登录Token访问，拒绝API令牌，用于组织成员和API令牌管理等接口
+pub struct LoginSessionGuard;
+
+pub fn require_login_session() -> LoginSessionGuard {
+    LoginSessionGuard
+}
+
+#[salvo::async_trait]
+impl Handler for LoginSessionGuard {
+    async fn handle(
+        &self,
+        req: &mut Request,
+        depot: &mut Depot,
+        res: &mut Response,
+        ctrl: &mut FlowCtrl,
+    ) {
+        if depot.obtain::<ApiTokenScope>().is_err() {
+            return;
+        }
+
+        ctrl.skip_rest();
+        AppError::FORBIDDEN("该接口不支持API令牌访问，请使用登录Token".to_string())
+            .write(req, depot, res)
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::user_role::Role;
+    use salvo::prelude::*;
+    use salvo::test::{ResponseExt, TestClient};
+
+    #[handler]
+    async fn ok_handler() -> &'static str {
+        "ok"
+    }
+
+    fn service(roles: Vec<Role>, permission: Permission) -> Service {
+        let router = Router::new()
+            .hoop(affix_state::inject(CurrentRoles(roles)))
+            .hoop(require_permission(permission))
+            .get(ok_handler);
+        Service::new(router)
+    }
+
+    #[tokio::test]
+    async fn permission_guard_rejects_missing_permission() {
+        let allowed = TestClient::get("http://127.0.0.1/")
+            .send(&service(vec![Role::Publisher], Permission::AppPublish))
+            .await;
+        assert_eq!(allowed.status_code, Some(StatusCode::OK));
+
+        let mut denied = TestClient::get("http://127.0.0.1/")
+            .send(&service(vec![Role::Viewer], Permission::AppPublish))
+            .await;
+        assert_eq!(denied.status_code, Some(StatusCode::FORBIDDEN));
+        assert!(denied.take_string().await.unwrap().contains("FORBIDDEN"));
+
+        let no_roles = TestClient::get("http://127.0.0.1/")
+            .send(&service(Vec::new(), Permission::ChannelRead))
+            .await;
+        assert_eq!(no_roles.status_code, Some(StatusCode::FORBIDDEN));
+
+        assert!(
+            Role::ReleaseManager
+                .permissions()
+                .contains(&Permission::AppRelease)
+        );
+        assert!(
+            !Role::ReleaseManager
+                .permissions()
+                .contains(&Permission::UserManage)
+        );
+        assert!(
+            CurrentRoles(vec![Role::Viewer, Role::Admin]).has_permission(Permission::UserManage)
+        );
+        assert_eq!(Role::from_db("release_manager"), Some(Role::ReleaseManager));
+        assert_eq!(Role::from_db("owner"), None);
+    }
+
+    #[tokio::test]
+    async fn api_token_scope_limits_permissions_and_login_only_routes() {
+        let scope = ApiTokenScope {
+            token_id: uuid::Uuid::new_v4(),
+            permissions: vec![Permission::AppRead, Permission::AppPublish],
+            channel_ids: Vec::new(),
+        };
+        let token_service = |permission: Permission| {
+            Service::new(
+                Router::new()
+                    .hoop(
+                        affix_state::inject(CurrentRoles(vec![Role::Admin])).inject(scope.clone()),
+                    )
+                    .hoop(require_permission(permission))
+                    .get(ok_handler),
+            )
+        };
+
+        let allowed = TestClient::get("http://127.0.0.1/")
+            .send(&token_service(Permission::AppPublish))
+            .await;
+        assert_eq!(allowed.status_code, Some(StatusCode::OK));
+        let denied = TestClient::get("http://127.0.0.1/")
+            .send(&token_service(Permission::AppRelease))
+            .await;
+        assert_eq!(denied.status_code, Some(StatusCode::FORBIDDEN));
+
+        let login_only = Service::new(
+            Router::new()
+                .hoop(affix_state::inject(scope.clone()))
+                .hoop(require_login_session())
+                .get(ok_handler),
+        );
+        let rejected = TestClient::get("http://127.0.0.1/").send(&login_only).await;
+        assert_eq!(rejected.status_code, Some(StatusCode::FORBIDDEN));
+        let session = TestClient::get("http://127.0.0.1/")
+            .send(&Service::new(
+                Router::new().hoop(require_login_session()).get(ok_handler),
+            ))
+            .await;
+        assert_eq!(session.status_code, Some(StatusCode::OK));
+
+        assert_eq!(
+            Permission::from_db("app:publish"),
+            Some(Permission::AppPublish)
+        );
+        assert_eq!(Permission::from_db("app:delete"), None);
+    }
+}
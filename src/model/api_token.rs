@@ -0,0 +1,132 @@
+use crate::model::user_role::Permission;
+use crate::schema::api_token;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use salvo_oapi::ToSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+///数据库API令牌表结构字段，供 CI 等无法登录的场景长期调用接口，只保存令牌的哈希
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
+#[diesel(table_name = api_token)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ApiToken {
+    ///令牌ID
+    pub id: Uuid,
+    ///令牌所属用户ID，令牌的权限不超过该用户的角色
+    pub user_id: Uuid,
+    ///令牌所属组织ID，令牌只能访问该组织的渠道和应用
+    pub organization_id: Uuid,
+    ///令牌名称
+    pub token_name: String,
+    ///令牌前缀，用于在列表中辨认令牌
+    pub token_prefix: String,
+    ///令牌 SHA-256 哈希
+    pub token_hash: String,
+    ///令牌权限范围，如 app:publish
+    pub scopes: Vec<String>,
+    ///限定可发布的渠道ID，为空时不限渠道
+    pub channel_ids: Vec<Uuid>,
+    ///过期时间，为空时永不过期
+    pub expires_at: Option<NaiveDateTime>,
+    ///最后使用时间
+    pub last_used_time: Option<NaiveDateTime>,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+    ///更新时间
+    pub update_time: NaiveDateTime,
+    ///是否吊销
+    pub is_delete: bool,
+}
+
+///当前请求使用的API令牌权限范围，由鉴权中间件写入 Depot，使用登录Token时不存在
+#[derive(Debug, Clone)]
+pub struct ApiTokenScope {
+    ///令牌ID
+    pub token_id: Uuid,
+    ///令牌权限范围
+    pub permissions: Vec<Permission>,
+    ///限定可发布的渠道ID，为空时不限渠道
+    pub channel_ids: Vec<Uuid>,
+}
+
+impl ApiTokenScope {
+    pub fn allows_permission(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    pub fn allows_channel(&self, channel_id: Uuid) -> bool {
+        self.channel_ids.is_empty() || self.channel_ids.contains(&channel_id)
+    }
+}
+
+///创建API令牌请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateApiTokenReq {
+    ///令牌名称
+    pub token_name: String,
+    ///令牌权限范围：channel:read/channel:manage/app:read/app:publish/app:release
+    pub scopes: Vec<String>,
+    ///限定可发布的渠道ID，未传时不限渠道
+    #[serde(default)]
+    pub channel_ids: Vec<Uuid>,
+    ///过期时间，未传时永不过期
+    #[serde(default)]
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+///创建API令牌返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateApiTokenResp {
+    ///令牌ID
+    pub token_id: Uuid,
+    ///令牌名称
+    pub token_name: String,
+    ///令牌明文，仅在创建时返回一次，请求时放在 Authorization: Bearer 请求头中
+    pub token: String,
+    ///令牌所属组织ID
+    pub organization_id: Uuid,
+    ///过期时间
+    pub expires_at: Option<NaiveDateTime>,
+    ///创建信息
+    pub create_info: String,
+}
+
+///API令牌信息，不包含令牌明文
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiTokenItem {
+    ///令牌ID
+    pub token_id: Uuid,
+    ///令牌名称
+    pub token_name: String,
+    ///令牌前缀
+    pub token_prefix: String,
+    ///令牌所属组织ID
+    pub organization_id: Uuid,
+    ///令牌权限范围
+    pub scopes: Vec<String>,
+    ///限定可发布的渠道ID
+    pub channel_ids: Vec<Uuid>,
+    ///过期时间
+    pub expires_at: Option<NaiveDateTime>,
+    ///最后使用时间
+    pub last_used_time: Option<NaiveDateTime>,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+}
+
+///吊销API令牌请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeleteApiTokenReq {
+    ///令牌ID
+    pub token_id: Uuid,
+}
+
+///吊销API令牌返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeleteApiTokenResp {
+    ///令牌ID
+    pub token_id: Uuid,
+    ///吊销信息
+    pub delete_info: String,
+}
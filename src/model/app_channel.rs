@@ -25,7 +25,38 @@ pub struct AppChannel {
     ///渠道更新时间
     pub update_time: NaiveDateTime,
     ///是否删除
-    pub is_delete: bool
+    pub is_delete: bool,
+    ///最低支持版本号，低于该版本的客户端必须更新
+    pub min_supported_version_code: Option<i64>,
+    ///是否为私有渠道，私有渠道的 APK 只能通过检查更新下发的签名地址下载
+    pub is_private: bool,
+    ///私有渠道的下载地址是否绑定检查更新时上报的设备ID
+    pub bind_download_device: bool,
+    ///所属组织ID
+    pub organization_id: Uuid,
+}
+
+///数据库渠道访问密钥表结构字段
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
+#[diesel(table_name = app_channel_key)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AppChannelKey {
+    ///密钥ID
+    pub id: Uuid,
+    ///渠道ID
+    pub channel_id: Uuid,
+    ///客户端访问标识
+    pub app_key: String,
+    ///客户端签名密钥
+    pub app_secret: String,
+    ///创建者id
+    pub create_user_id: Uuid,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+    ///更新时间
+    pub update_time: NaiveDateTime,
+    ///是否删除（吊销）
+    pub is_delete: bool,
 }
 
 ///创建应用渠道请求参数
@@ -37,6 +68,15 @@ pub struct CreateAppChannelReq {
     ///渠道备注
     #[serde(default)]
     pub remark: String,
+    ///最低支持版本号，低于该版本的客户端必须更新
+    #[serde(default)]
+    pub min_supported_version_code: Option<i64>,
+    ///是否为私有渠道
+    #[serde(default)]
+    pub is_private: bool,
+    ///私有渠道的下载地址是否绑定设备ID
+    #[serde(default)]
+    pub bind_download_device: bool,
 }
 
 ///创建应用渠道返回参数
@@ -46,6 +86,12 @@ pub struct CreateAppChannelResp {
     pub channel_name: String,
     ///渠道备注
     pub remark: String,
+    ///最低支持版本号
+    pub min_supported_version_code: Option<i64>,
+    ///是否为私有渠道
+    pub is_private: bool,
+    ///私有渠道的下载地址是否绑定设备ID
+    pub bind_download_device: bool,
     ///创建渠道信息
     pub create_info: String,
 }
@@ -81,6 +127,12 @@ pub struct GetAppChannelListRespItem {
     pub channel_name: String,
     ///渠道备注
     pub remark: String,
+    ///最低支持版本号
+    pub min_supported_version_code: Option<i64>,
+    ///是否为私有渠道
+    pub is_private: bool,
+    ///私有渠道的下载地址是否绑定设备ID
+    pub bind_download_device: bool,
     ///创建渠道时间
     pub create_time: NaiveDateTime,
     ///更新渠道时间
@@ -95,6 +147,7 @@ pub struct SearchAppChannelReq {
     ///渠道名称
     pub channel_name: String,
 }
+///搜索渠道信息返回参数
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SearchAppChannelResp {
     pub channel_list: Vec<GetAppChannelListRespItem>,
@@ -103,7 +156,6 @@ pub struct SearchAppChannelResp {
     ///总共页数
     pub total_page_count: i64,
 }
-///搜索渠道信息返回参数
 
 ///更新渠道信息请求参数
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -115,6 +167,18 @@ pub struct UpdateAppChannelReq {
     ///渠道备注
     #[serde(default)]
     pub remark: String,
+    ///最低支持版本号，未传时保持不变
+    #[serde(default)]
+    pub min_supported_version_code: Option<i64>,
+    ///是否清除最低支持版本号，为 true 时不能同时传入 min_supported_version_code
+    #[serde(default)]
+    pub clear_min_supported_version_code: bool,
+    ///是否为私有渠道，未传时保持不变
+    #[serde(default)]
+    pub is_private: Option<bool>,
+    ///私有渠道的下载地址是否绑定设备ID，未传时保持不变
+    #[serde(default)]
+    pub bind_download_device: Option<bool>,
 }
 
 ///更新渠道信息返回参数
@@ -126,6 +190,12 @@ pub struct UpdateAppChannelResp {
     pub channel_name: String,
     ///渠道备注
     pub remark: String,
+    ///最低支持版本号
+    pub min_supported_version_code: Option<i64>,
+    ///是否为私有渠道
+    pub is_private: bool,
+    ///私有渠道的下载地址是否绑定设备ID
+    pub bind_download_device: bool,
     ///更新信息
     pub update_info: String,
 }
@@ -146,3 +216,65 @@ pub struct DeleteAppChannelResp {
     ///删除信息
     pub delete_info: String,
 }
+
+///创建渠道访问密钥请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateAppChannelKeyReq {
+    ///渠道Id
+    pub channel_id: Uuid,
+}
+
+///创建渠道访问密钥返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateAppChannelKeyResp {
+    ///密钥Id
+    pub key_id: Uuid,
+    ///渠道Id
+    pub channel_id: Uuid,
+    ///客户端访问标识
+    pub app_key: String,
+    ///客户端签名密钥，仅在创建时返回一次
+    pub app_secret: String,
+    ///创建信息
+    pub create_info: String,
+}
+
+///查询渠道访问密钥请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GetAppChannelKeyListReq {
+    ///渠道Id
+    pub channel_id: Uuid,
+}
+
+///查询渠道访问密钥返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GetAppChannelKeyListResp {
+    pub key_list: Vec<AppChannelKeyItem>,
+}
+
+///渠道访问密钥信息，不包含签名密钥
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AppChannelKeyItem {
+    ///密钥Id
+    pub key_id: Uuid,
+    ///客户端访问标识
+    pub app_key: String,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+}
+
+///吊销渠道访问密钥请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeleteAppChannelKeyReq {
+    ///密钥Id
+    pub key_id: Uuid,
+}
+
+///吊销渠道访问密钥返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeleteAppChannelKeyResp {
+    ///密钥Id
+    pub key_id: Uuid,
+    ///吊销信息
+    pub delete_info: String,
+}
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 ///数据库应用AppManage表结构字段
-#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Selectable)]
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
 #[diesel(table_name = app_manage)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 #[diesel(sql_type=Timestamp)]
@@ -45,6 +45,298 @@ pub struct AppManage {
     pub channel_name: Option<String>,
     ///更新日志
     pub update_log: Option<String>,
+    ///是否强制更新
+    pub force_update: bool,
+    ///灰度发布比例（0-100）
+    pub rollout_percentage: i32,
+    ///灰度发布状态：active/paused/halted
+    pub rollout_status: String,
+    ///最低支持的系统SDK版本（minSdkVersion）
+    pub min_sdk_version: Option<i32>,
+    ///APK包含的原生库ABI列表
+    pub native_abis: Vec<String>,
+    ///设备定向规则
+    pub targeting_rules: Option<serde_json::Value>,
+    ///是否已撤回
+    pub is_revoked: bool,
+    ///撤回原因
+    pub revoke_reason: Option<String>,
+    ///撤回时间
+    pub revoke_time: Option<NaiveDateTime>,
+    ///撤回后回滚的目标应用ID
+    pub rollback_app_id: Option<Uuid>,
+    ///定时发布时间，为空时立即发布
+    pub publish_at: Option<NaiveDateTime>,
+    ///过期时间，到期后不再下发
+    pub expire_at: Option<NaiveDateTime>,
+    ///是否已上线（定时发布到期后由后台任务更新）
+    pub is_published: bool,
+    ///签名证书SHA-256指纹
+    pub signer_sha256: Option<String>,
+    ///APK使用的签名方案（v1/v2/v3/v3.1）
+    pub signature_schemes: Vec<String>,
+    ///目标SDK版本（targetSdkVersion）
+    pub target_sdk_version: Option<i32>,
+    ///声明的权限列表（uses-permission）
+    pub permissions: Vec<String>,
+    ///声明的软硬件特性列表（uses-feature）
+    pub features: Vec<String>,
+    ///是否可调试（android:debuggable）
+    pub is_debuggable: bool,
+    ///是否仅用于测试（android:testOnly）
+    pub is_test_only: bool,
+    ///清单中声明的四大组件，早期版本未解析时为空
+    pub components: Option<serde_json::Value>,
+    ///拆分 APK 文件列表（含基础包），单个 APK 发布时为空
+    pub splits: Option<serde_json::Value>,
+    ///是否为仅存档的版本（AAB），不会下发给客户端
+    pub is_archive_only: bool,
+    ///安装包文件 SHA-256（小写十六进制），早期版本未计算时为空
+    pub file_sha256: Option<String>,
+    ///安装包文件 MD5（小写十六进制），早期版本未计算时为空
+    pub file_md5: Option<String>,
+    ///所属组织ID，与所属渠道的组织一致
+    pub organization_id: Uuid,
+}
+
+///数据库包签名绑定表结构字段，每个包首次发布时绑定签名证书
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
+#[diesel(table_name = app_signer_pin)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AppSignerPin {
+    ///绑定ID
+    pub id: Uuid,
+    ///包名
+    pub package_name: String,
+    ///创建用户ID
+    pub create_user_id: Uuid,
+    ///已绑定的签名证书SHA-256指纹
+    pub signer_sha256: String,
+    ///已批准轮换的新签名证书SHA-256指纹，使用该签名的版本发布后完成轮换
+    pub rotation_signer_sha256: Option<String>,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+    ///更新时间
+    pub update_time: NaiveDateTime,
+    ///所属组织ID，同一组织内包名唯一
+    pub organization_id: Uuid,
+}
+
+///数据库文件存储表结构字段，APK、图标和差分补丁按内容摘要命名，相同内容只保存一份
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
+#[diesel(table_name = app_blob)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AppBlob {
+    ///文件ID
+    pub id: Uuid,
+    ///文件类型：apk/icon/patch
+    pub kind: String,
+    ///文件名称：`{SHA-256}.{扩展名}`
+    pub file_name: String,
+    ///文件大小（字节）
+    pub file_size: i64,
+    ///引用该文件的未删除版本、差分补丁和渠道包记录数，在增删引用的事务中维护
+    pub ref_count: i32,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+    ///更新时间，重复上传时刷新，清理任务据此保留尚未发布的文件
+    pub update_time: NaiveDateTime,
+    ///上传时的原始文件名，下载时作为默认保存的文件名
+    pub original_name: Option<String>,
+}
+
+///数据库渠道包表结构字段，记录为母包生成的渠道包，每条记录引用一个渠道包文件
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
+#[diesel(table_name = app_channel_apk)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AppChannelApk {
+    ///记录ID
+    pub id: Uuid,
+    ///渠道ID
+    pub channel_id: Uuid,
+    ///母包文件名称
+    pub master_file_name: String,
+    ///渠道包文件名称：`{SHA-256}.apk`
+    pub file_name: String,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+    ///更新时间，重新生成时刷新
+    pub update_time: NaiveDateTime,
+}
+
+///数据库版本差分补丁表结构字段，记录从旧版本 APK 到新版本 APK 的二进制补丁
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
+#[diesel(table_name = app_delta_patch)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AppDeltaPatch {
+    ///补丁ID
+    pub id: Uuid,
+    ///目标版本ID
+    pub app_id: Uuid,
+    ///基础版本ID
+    pub base_app_id: Uuid,
+    ///生成状态：pending/ready/skipped/failed
+    pub status: String,
+    ///基础版本 APK 文件 SHA-256
+    pub base_file_sha256: Option<String>,
+    ///补丁文件名称：`{SHA-256}.patch`
+    pub file_name: Option<String>,
+    ///补丁文件 SHA-256
+    pub patch_sha256: Option<String>,
+    ///补丁文件大小（字节）
+    pub patch_size: Option<i64>,
+    ///跳过或失败原因
+    pub error_msg: Option<String>,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+    ///更新时间
+    pub update_time: NaiveDateTime,
+    ///连续生成失败次数
+    pub failure_count: i32,
+    ///失败后下次重试时间，达到重试上限时为空
+    pub next_retry_time: Option<NaiveDateTime>,
+}
+
+///差分补丁生成状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaPatchStatus {
+    ///生成中
+    Pending,
+    ///已生成
+    Ready,
+    ///无需生成（文件过大或补丁不小于完整安装包）
+    Skipped,
+    ///生成失败，按退避时间重试
+    Failed,
+}
+
+impl DeltaPatchStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeltaPatchStatus::Pending => "pending",
+            DeltaPatchStatus::Ready => "ready",
+            DeltaPatchStatus::Skipped => "skipped",
+            DeltaPatchStatus::Failed => "failed",
+        }
+    }
+}
+
+///清单中声明的四大组件，组件名称为完整类名
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct ManifestComponents {
+    ///Activity 及 activity-alias
+    pub activities: Vec<String>,
+    ///Service
+    pub services: Vec<String>,
+    ///BroadcastReceiver
+    pub receivers: Vec<String>,
+    ///ContentProvider
+    pub providers: Vec<String>,
+}
+
+///拆分 APK 类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitKind {
+    ///基础包
+    Base,
+    ///ABI 配置包，如 config.arm64_v8a
+    Abi,
+    ///屏幕密度配置包，如 config.xxhdpi
+    Density,
+    ///语言配置包，如 config.zh
+    Language,
+    ///功能模块包，始终随基础包下发
+    Feature,
+}
+
+///拆分 APK 文件信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SplitApkItem {
+    ///拆分名称（清单 split 属性），基础包为 base
+    pub split_name: String,
+    ///拆分类型：base/abi/density/language/feature
+    pub kind: SplitKind,
+    ///配置限定值，如 arm64-v8a、xxhdpi、zh，基础包和功能模块包为空
+    pub qualifier: Option<String>,
+    ///文件名称
+    pub file_name: String,
+    ///文件路径（下载地址）
+    pub file_path: String,
+    ///文件大小（字节）
+    pub file_size: i64,
+    ///文件 SHA-256（小写十六进制）
+    #[serde(default)]
+    pub file_sha256: Option<String>,
+    ///文件 MD5（小写十六进制）
+    #[serde(default)]
+    pub file_md5: Option<String>,
+}
+
+///设备定向规则，所有已设置的条件都满足时才向设备下发该版本
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct TargetingRules {
+    ///设备最低系统SDK版本
+    pub min_sdk_int: Option<i32>,
+    ///设备最高系统SDK版本
+    pub max_sdk_int: Option<i32>,
+    ///设备支持的ABI中至少包含其中一个
+    pub abis: Vec<String>,
+    ///仅下发给这些厂商，为空时不限制
+    pub manufacturers: Vec<String>,
+    ///不下发给这些厂商
+    pub exclude_manufacturers: Vec<String>,
+    ///仅下发给这些机型，为空时不限制
+    pub models: Vec<String>,
+    ///不下发给这些机型
+    pub exclude_models: Vec<String>,
+    ///仅下发给这些语言区域，按前缀匹配（如 zh 匹配 zh-CN），为空时不限制
+    pub locales: Vec<String>,
+}
+
+///发布状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseStatus {
+    ///等待定时发布
+    Scheduled,
+    ///已上线
+    Live,
+    ///已过期
+    Expired,
+}
+
+///灰度发布状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RolloutStatus {
+    ///发布中
+    Active,
+    ///已暂停，可恢复
+    Paused,
+    ///已终止，不可恢复
+    Halted,
+}
+
+impl RolloutStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RolloutStatus::Active => "active",
+            RolloutStatus::Paused => "paused",
+            RolloutStatus::Halted => "halted",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "active" => Some(RolloutStatus::Active),
+            "paused" => Some(RolloutStatus::Paused),
+            "halted" => Some(RolloutStatus::Halted),
+            _ => None,
+        }
+    }
 }
 
 ///上传文件返回参数
@@ -66,10 +358,74 @@ pub struct UploadAppFileResp {
     pub version_code: Option<String>,
     ///文件大小（字节）
     pub file_size: u64,
+    ///最低支持的系统SDK版本（minSdkVersion）
+    pub min_sdk_version: Option<i32>,
+    ///APK包含的原生库ABI列表
+    pub native_abis: Vec<String>,
+    ///签名证书SHA-256指纹
+    pub signer_sha256: Option<String>,
+    ///APK使用的签名方案（v1/v2/v3/v3.1）
+    pub signature_schemes: Vec<String>,
+    ///目标SDK版本（targetSdkVersion）
+    pub target_sdk_version: Option<i32>,
+    ///声明的权限列表（uses-permission）
+    pub permissions: Vec<String>,
+    ///声明的软硬件特性列表（uses-feature）
+    pub features: Vec<String>,
+    ///是否可调试（android:debuggable）
+    pub is_debuggable: bool,
+    ///是否仅用于测试（android:testOnly）
+    pub is_test_only: bool,
+    ///拆分 APK 文件列表（含基础包），上传单个 APK 时为空
+    pub splits: Vec<SplitApkItem>,
+    ///是否为 AAB 文件，AAB 发布后仅存档，不会下发给客户端
+    pub is_archive_only: bool,
+    ///文件 SHA-256（小写十六进制），拆分 APK 时为基础包的摘要
+    pub file_sha256: String,
+    ///文件 MD5（小写十六进制），拆分 APK 时为基础包的摘要
+    pub file_md5: String,
+    ///服务器上已存在内容相同的文件，本次上传复用已有文件
+    pub is_deduplicated: bool,
     ///上传文件信息
     pub upload_file_info: String,
 }
 
+///生成渠道包请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GenerateChannelApksReq {
+    ///母包文件路径，必须是当前组织已发布版本的安装包地址
+    pub file_path: String,
+    ///需要生成渠道包的渠道ID，未传时为当前用户的全部渠道
+    #[serde(default)]
+    pub channel_ids: Vec<Uuid>,
+}
+
+///生成渠道包返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GenerateChannelApksResp {
+    ///渠道包列表
+    pub channel_apk_list: Vec<ChannelApkItem>,
+}
+
+///渠道包信息
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ChannelApkItem {
+    ///渠道ID
+    pub channel_id: Uuid,
+    ///渠道名称，即写入渠道包的渠道标识
+    pub channel_name: String,
+    ///渠道包文件路径，可直接用于发布应用
+    pub file_path: String,
+    ///渠道包签名下载地址，有效期内可直接下载
+    pub download_url: String,
+    ///渠道包文件名称
+    pub file_name: String,
+    ///文件大小（字节）
+    pub file_size: i64,
+    ///渠道包文件 SHA-256（小写十六进制）
+    pub file_sha256: String,
+}
+
 ///完成应用发布请求参数
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UploadAppFileCompleteReq {
@@ -91,10 +447,32 @@ pub struct UploadAppFileCompleteReq {
     pub file_size: i64,
     ///渠道ID
     pub channel_id: Uuid,
-    ///渠道名称
+    ///渠道名称，仅为兼容保留，保存的渠道名称以 channel_id 对应的渠道为准
+    #[serde(default)]
     pub channel_name: String,
     ///更新日志
     pub update_log: String,
+    ///是否强制更新，未传时为否
+    #[serde(default)]
+    pub force_update: bool,
+    ///灰度发布比例（0-100），未传时为100即全量发布
+    #[serde(default)]
+    pub rollout_percentage: Option<i32>,
+    ///设备定向规则，未传时不限制设备
+    #[serde(default)]
+    pub targeting_rules: Option<TargetingRules>,
+    ///定时发布时间，未传时立即发布
+    #[serde(default)]
+    pub publish_at: Option<NaiveDateTime>,
+    ///过期时间，未传时不过期
+    #[serde(default)]
+    pub expire_at: Option<NaiveDateTime>,
+    ///确认发布新增危险权限的版本，未确认时新增危险权限会被拒绝
+    #[serde(default)]
+    pub confirm_dangerous_permissions: bool,
+    ///拆分 APK 的配置包及功能模块包文件路径（上传接口返回的 splits 中的 file_path），单个 APK 发布时不传
+    #[serde(default)]
+    pub split_file_paths: Vec<String>,
 }
 
 ///完成应用发布返回参数
@@ -102,6 +480,8 @@ pub struct UploadAppFileCompleteReq {
 pub struct UploadAppFileCompleteResp {
     ///发布应用信息
     pub upload_app_complete_info: String,
+    ///相比同渠道上一版本新增的危险权限
+    pub added_dangerous_permissions: Vec<String>,
 }
 
 ///分页查询应用列表请求参数
@@ -114,6 +494,9 @@ pub struct GetAppListReq {
     ///搜索关键词，同时匹配应用名称、包名、渠道名称
     #[serde(default)]
     pub search_key: String,
+    ///按发布状态筛选，未传时返回全部
+    #[serde(default)]
+    pub release_status: Option<ReleaseStatus>,
 }
 
 ///分页查询应用列表返回参数
@@ -156,6 +539,54 @@ pub struct GetAppListRespItem {
     pub channel_name: String,
     ///更新日志
     pub update_log: String,
+    ///是否强制更新
+    pub force_update: bool,
+    ///灰度发布比例（0-100）
+    pub rollout_percentage: i32,
+    ///灰度发布状态：active/paused/halted
+    pub rollout_status: String,
+    ///最低支持的系统SDK版本（minSdkVersion）
+    pub min_sdk_version: Option<i32>,
+    ///APK包含的原生库ABI列表
+    pub native_abis: Vec<String>,
+    ///设备定向规则
+    pub targeting_rules: Option<TargetingRules>,
+    ///是否已撤回
+    pub is_revoked: bool,
+    ///撤回原因
+    pub revoke_reason: Option<String>,
+    ///撤回时间
+    pub revoke_time: Option<NaiveDateTime>,
+    ///撤回后回滚的目标应用ID
+    pub rollback_app_id: Option<Uuid>,
+    ///定时发布时间
+    pub publish_at: Option<NaiveDateTime>,
+    ///过期时间
+    pub expire_at: Option<NaiveDateTime>,
+    ///发布状态：scheduled/live/expired
+    pub release_status: ReleaseStatus,
+    ///签名证书SHA-256指纹
+    pub signer_sha256: Option<String>,
+    ///APK使用的签名方案（v1/v2/v3/v3.1）
+    pub signature_schemes: Vec<String>,
+    ///目标SDK版本（targetSdkVersion）
+    pub target_sdk_version: Option<i32>,
+    ///声明的权限列表（uses-permission）
+    pub permissions: Vec<String>,
+    ///声明的软硬件特性列表（uses-feature）
+    pub features: Vec<String>,
+    ///是否可调试（android:debuggable）
+    pub is_debuggable: bool,
+    ///是否仅用于测试（android:testOnly）
+    pub is_test_only: bool,
+    ///拆分 APK 文件列表（含基础包），单个 APK 发布时为空
+    pub splits: Vec<SplitApkItem>,
+    ///是否为仅存档的版本（AAB），不会下发给客户端
+    pub is_archive_only: bool,
+    ///安装包文件 SHA-256（小写十六进制）
+    pub file_sha256: Option<String>,
+    ///安装包文件 MD5（小写十六进制）
+    pub file_md5: Option<String>,
     ///创建时间
     pub create_time: NaiveDateTime,
     ///更新时间
@@ -187,6 +618,243 @@ pub struct DeleteAppResp {
     pub delete_info: String,
 }
 
+///设置应用强制更新请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateAppForceUpdateReq {
+    ///应用ID
+    pub app_id: Uuid,
+    ///是否强制更新
+    pub force_update: bool,
+}
+
+///设置应用强制更新返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateAppForceUpdateResp {
+    ///应用ID
+    pub app_id: Uuid,
+    ///是否强制更新
+    pub force_update: bool,
+    ///更新结果信息
+    pub update_info: String,
+}
+
+///调整灰度发布请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateAppRolloutReq {
+    ///应用ID
+    pub app_id: Uuid,
+    ///新的灰度发布比例（0-100），只能提高，未传时保持不变
+    #[serde(default)]
+    pub rollout_percentage: Option<i32>,
+    ///新的灰度发布状态，未传时保持不变
+    #[serde(default)]
+    pub rollout_status: Option<RolloutStatus>,
+}
+
+///调整灰度发布返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateAppRolloutResp {
+    ///应用ID
+    pub app_id: Uuid,
+    ///灰度发布比例
+    pub rollout_percentage: i32,
+    ///灰度发布状态
+    pub rollout_status: RolloutStatus,
+    ///更新结果信息
+    pub update_info: String,
+}
+
+///设置设备定向规则请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateAppTargetingReq {
+    ///应用ID
+    pub app_id: Uuid,
+    ///设备定向规则，传空时清除规则
+    #[serde(default)]
+    pub targeting_rules: Option<TargetingRules>,
+}
+
+///设置设备定向规则返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateAppTargetingResp {
+    ///应用ID
+    pub app_id: Uuid,
+    ///设备定向规则
+    pub targeting_rules: Option<TargetingRules>,
+    ///更新结果信息
+    pub update_info: String,
+}
+
+///设置发布时间窗口请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateAppScheduleReq {
+    ///应用ID
+    pub app_id: Uuid,
+    ///定时发布时间，传空时立即发布
+    #[serde(default)]
+    pub publish_at: Option<NaiveDateTime>,
+    ///过期时间，传空时不过期
+    #[serde(default)]
+    pub expire_at: Option<NaiveDateTime>,
+}
+
+///设置发布时间窗口返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateAppScheduleResp {
+    ///应用ID
+    pub app_id: Uuid,
+    ///定时发布时间
+    pub publish_at: Option<NaiveDateTime>,
+    ///过期时间
+    pub expire_at: Option<NaiveDateTime>,
+    ///发布状态
+    pub release_status: ReleaseStatus,
+    ///更新结果信息
+    pub update_info: String,
+}
+
+///撤回应用版本请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RevokeAppReq {
+    ///应用ID
+    pub app_id: Uuid,
+    ///撤回原因
+    pub revoke_reason: String,
+    ///回滚目标应用ID，可以是更低的版本；未传时按正常规则选择其他版本
+    #[serde(default)]
+    pub rollback_app_id: Option<Uuid>,
+}
+
+///撤回应用版本返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RevokeAppResp {
+    ///应用ID
+    pub app_id: Uuid,
+    ///回滚目标应用ID
+    pub rollback_app_id: Option<Uuid>,
+    ///撤回结果信息
+    pub revoke_info: String,
+}
+
+///批准签名证书轮换请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApproveSignerRotationReq {
+    ///包名
+    pub package_name: String,
+    ///新签名证书SHA-256指纹
+    pub signer_sha256: String,
+}
+
+///批准签名证书轮换返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApproveSignerRotationResp {
+    ///包名
+    pub package_name: String,
+    ///当前绑定的签名证书SHA-256指纹
+    pub signer_sha256: String,
+    ///已批准轮换的新签名证书SHA-256指纹
+    pub rotation_signer_sha256: String,
+    ///批准结果信息
+    pub approve_info: String,
+}
+
+///版本差异请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GetAppReleaseDiffReq {
+    ///目标应用ID
+    pub target_app_id: Uuid,
+    ///对比的基准应用ID，未传时取同包名同渠道下的上一版本
+    #[serde(default)]
+    pub base_app_id: Option<Uuid>,
+}
+
+///SDK版本变化
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SdkVersionChange {
+    ///基准版本的值
+    pub from: Option<i32>,
+    ///目标版本的值
+    pub to: Option<i32>,
+}
+
+///布尔标记变化
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FlagChange {
+    ///基准版本的值
+    pub from: bool,
+    ///目标版本的值
+    pub to: bool,
+}
+
+///列表差异
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ListDiff {
+    ///新增项
+    pub added: Vec<String>,
+    ///移除项
+    pub removed: Vec<String>,
+}
+
+///四大组件差异
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ComponentsDiff {
+    ///Activity 差异
+    pub activities: ListDiff,
+    ///Service 差异
+    pub services: ListDiff,
+    ///BroadcastReceiver 差异
+    pub receivers: ListDiff,
+    ///ContentProvider 差异
+    pub providers: ListDiff,
+}
+
+///版本差异返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AppReleaseDiffResp {
+    ///基准应用ID
+    pub base_app_id: Uuid,
+    ///基准版本号
+    pub base_version_code: String,
+    ///目标应用ID
+    pub target_app_id: Uuid,
+    ///目标版本号
+    pub target_version_code: String,
+    ///最低SDK版本变化，未变化时为空
+    pub min_sdk_version: Option<SdkVersionChange>,
+    ///是否提高了最低SDK版本
+    pub min_sdk_raised: bool,
+    ///目标SDK版本变化，未变化时为空
+    pub target_sdk_version: Option<SdkVersionChange>,
+    ///权限差异
+    pub permissions: ListDiff,
+    ///新增的危险权限
+    pub added_dangerous_permissions: Vec<String>,
+    ///软硬件特性差异
+    pub features: ListDiff,
+    ///原生库ABI差异
+    pub native_abis: ListDiff,
+    ///四大组件差异，任一版本缺少组件信息时为空
+    pub components: Option<ComponentsDiff>,
+    ///可调试标记变化，未变化时为空
+    pub is_debuggable: Option<FlagChange>,
+    ///仅测试标记变化，未变化时为空
+    pub is_test_only: Option<FlagChange>,
+    ///签名证书是否变化
+    pub signer_changed: bool,
+}
+
+///更新类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateType {
+    ///无需更新
+    None,
+    ///可选更新
+    Optional,
+    ///强制更新
+    Mandatory,
+}
+
 ///检查应用更新请求参数
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AppCheckUpdateReq {
@@ -194,11 +862,50 @@ pub struct AppCheckUpdateReq {
     pub package_name: String,
     ///渠道名称
     pub channel_name: String,
+    ///客户端当前版本号，未传时视为0
+    #[serde(default)]
+    pub version_code: i64,
+    ///设备唯一标识，用于灰度发布分桶；未传时只返回全量发布的版本
+    #[serde(default)]
+    pub device_id: Option<String>,
+    ///设备系统SDK版本（Build.VERSION.SDK_INT）
+    #[serde(default)]
+    pub sdk_int: Option<i32>,
+    ///设备支持的ABI列表（Build.SUPPORTED_ABIS）
+    #[serde(default)]
+    pub supported_abis: Vec<String>,
+    ///设备厂商（Build.MANUFACTURER）
+    #[serde(default)]
+    pub manufacturer: Option<String>,
+    ///设备机型（Build.MODEL）
+    #[serde(default)]
+    pub model: Option<String>,
+    ///设备语言区域，如 zh-CN
+    #[serde(default)]
+    pub locale: Option<String>,
+    ///设备屏幕密度（DisplayMetrics.densityDpi），用于选择拆分 APK 的密度配置包
+    #[serde(default)]
+    pub screen_density: Option<i32>,
+    ///客户端当前安装包文件 SHA-256；传入时只下发基于该文件生成的差分补丁
+    #[serde(default)]
+    pub current_file_sha256: Option<String>,
 }
 
 ///检查应用更新返回参数
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AppCheckUpdateResp {
+    ///是否有可用更新
+    pub has_update: bool,
+    ///更新类型：none/optional/mandatory
+    pub update_type: UpdateType,
+    ///渠道最低支持版本号，低于该版本的客户端必须更新
+    pub min_supported_version_code: Option<i64>,
+    ///客户端当前版本是否已被撤回
+    pub current_version_revoked: bool,
+    ///客户端当前版本的撤回原因
+    pub revoke_reason: Option<String>,
+    ///是否为回滚到指定版本，回滚目标的版本号可能低于客户端当前版本
+    pub is_rollback: bool,
     ///应用名称
     pub app_name: String,
     ///包名
@@ -207,8 +914,35 @@ pub struct AppCheckUpdateResp {
     pub channel_name: String,
     ///版本名称
     pub version_name: String,
-    ///版本号
+    ///最新版本号，没有可见版本时为客户端当前版本号
     pub version_code: String,
-    ///应用下载地址
-    pub app_download_url: String,
+    ///应用下载地址，无可用更新时为空；拆分 APK 发布时为基础包地址
+    pub app_download_url: Option<String>,
+    ///安装包文件 SHA-256，客户端下载后校验文件完整性，无可用更新时为空
+    pub file_sha256: Option<String>,
+    ///安装包文件 MD5，无可用更新时为空
+    pub file_md5: Option<String>,
+    ///适配当前设备的拆分 APK 文件列表（含基础包），需一并安装；单个 APK 发布或无可用更新时为空
+    pub splits: Vec<SplitApkItem>,
+    ///私有渠道签名下载地址的过期时间（Unix 时间戳，秒），公开渠道为空
+    pub download_url_expires_at: Option<i64>,
+    ///从客户端当前版本到最新版本的差分补丁，没有可用补丁时为空，客户端可回退下载完整安装包
+    pub delta_patch: Option<DeltaPatchItem>,
+}
+
+///差分补丁信息
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeltaPatchItem {
+    ///补丁格式：bsdiff40（标准 BSDIFF40，可用 bspatch 还原）
+    pub patch_format: String,
+    ///补丁下载地址
+    pub patch_url: String,
+    ///补丁文件大小（字节）
+    pub patch_size: i64,
+    ///补丁文件 SHA-256
+    pub patch_sha256: String,
+    ///基础版本号，即补丁适用的客户端版本
+    pub base_version_code: String,
+    ///基础版本 APK 文件 SHA-256，客户端应用补丁前需校验本地安装包
+    pub base_file_sha256: String,
 }
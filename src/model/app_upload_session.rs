@@ -0,0 +1,100 @@
+use crate::schema::*;
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable, Selectable};
+use salvo::prelude::ToSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+///数据库分片上传会话表结构字段
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
+#[diesel(table_name = app_upload_session)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AppUploadSession {
+    ///会话ID
+    pub id: Uuid,
+    ///创建人ID
+    pub create_user_id: Uuid,
+    ///上传文件名称
+    pub file_name: String,
+    ///文件大小（字节）
+    pub file_size: i64,
+    ///分片大小（字节），最后一个分片可以更小
+    pub chunk_size: i64,
+    ///分片总数
+    pub total_chunks: i32,
+    ///客户端提供的文件 SHA-256，合并后校验
+    pub file_sha256: Option<String>,
+    ///会话状态：uploading/completing
+    pub status: String,
+    ///过期时间，每次上传分片时顺延，过期后由清理任务删除
+    pub expires_at: NaiveDateTime,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+    ///更新时间
+    pub update_time: NaiveDateTime,
+}
+
+///分片上传会话状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadSessionStatus {
+    ///上传中
+    Uploading,
+    ///正在合并分片并解析
+    Completing,
+}
+
+impl UploadSessionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UploadSessionStatus::Uploading => "uploading",
+            UploadSessionStatus::Completing => "completing",
+        }
+    }
+}
+
+///创建分片上传会话请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateUploadSessionReq {
+    ///上传文件名称，支持 .apk/.aab/.apks/.xapk
+    pub file_name: String,
+    ///文件大小（字节）
+    pub file_size: i64,
+    ///分片大小（字节），默认 8MB
+    #[serde(default)]
+    pub chunk_size: Option<i64>,
+    ///文件 SHA-256（小写十六进制），传入时合并后校验
+    #[serde(default)]
+    pub file_sha256: Option<String>,
+}
+
+///分片上传会话请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UploadSessionReq {
+    ///会话ID
+    pub session_id: Uuid,
+}
+
+///分片上传会话返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UploadSessionResp {
+    ///会话ID
+    pub session_id: Uuid,
+    ///上传文件名称
+    pub file_name: String,
+    ///文件大小（字节）
+    pub file_size: i64,
+    ///分片大小（字节）
+    pub chunk_size: i64,
+    ///分片总数
+    pub total_chunks: i32,
+    ///已接收的分片序号（从 0 开始）
+    pub received_chunks: Vec<i32>,
+    ///尚未接收的分片序号
+    pub missing_chunks: Vec<i32>,
+    ///已接收的字节数
+    pub received_size: i64,
+    ///会话状态：uploading/completing
+    pub status: String,
+    ///过期时间
+    pub expires_at: NaiveDateTime,
+}
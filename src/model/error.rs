@@ -82,6 +82,13 @@ impl AppError {
     }
 }
 
+// 事务中同时返回数据库错误和业务错误时使用，数据库错误按服务器内部错误处理
+impl From<diesel::result::Error> for AppError {
+    fn from(e: diesel::result::Error) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
+
 #[async_trait]
 impl Writer for AppError {
     async fn write(self, req: &mut Request, depot: &mut Depot, res: &mut Response) {
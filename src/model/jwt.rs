@@ -3,11 +3,13 @@ use salvo::prelude::ToSchema;
 use serde::{Deserialize, Serialize};
 use std::env;
 
+#[allow(clippy::let_and_return)]
 pub fn get_jwt_secret_key() -> String {
     let jwt_secret_key = env::var("JWT_SECRET_KEY").expect("要在env中设置JWT_SECRET_KEY！");
     jwt_secret_key
 }
 
+#[allow(clippy::let_and_return)]
 pub fn get_jwt_refresh_secret_key() -> String {
     let jwt_refresh_secret_key =
         env::var("JWT_REFRESH_SECRET_KEY").expect("要在env中设置JWT_REFRESH_SECRET_KEY！");
@@ -51,6 +53,8 @@ pub struct AccessTokenClaims {
     pub user_name: String,
     /// 用户ID
     pub user_id: String,
+    /// 会话ID
+    pub session_id: String,
     /// 过期时间
     pub exp: i64,
     /// 签发时间
@@ -66,10 +70,14 @@ pub struct RefreshTokenClaims {
     pub user_name: String,
     /// 用户ID
     pub user_id: String,
+    /// 会话ID
+    pub session_id: String,
     /// 过期时间
     pub exp: i64,
     /// 签发时间
     pub iat: i64,
+    /// Token唯一ID，保证同一秒内轮换的刷新Token也不相同
+    pub jti: String,
     /// token类型
     pub token_type: TokenType,
 }
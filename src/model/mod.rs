@@ -1,9 +1,14 @@
+pub mod api_token;
 pub mod app_channel;
 pub mod app_manage;
+pub mod app_upload_session;
 pub mod body;
 pub mod captcha;
 pub mod error;
 pub mod jwt;
 pub mod operation_log;
+pub mod organization;
 pub mod response;
+pub mod user_role;
+pub mod user_session;
 pub mod users;
@@ -0,0 +1,162 @@
+use crate::schema::{organization, organization_member};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use salvo::macros::Extractible;
+use salvo_oapi::ToSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+///数据库组织表结构字段，渠道和应用归属于组织，组织成员共同管理
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
+#[diesel(table_name = organization)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Organization {
+    ///组织ID
+    pub id: Uuid,
+    ///组织名称
+    pub name: String,
+    ///是否为个人组织，每个用户注册时自动创建
+    pub is_personal: bool,
+    ///创建者id
+    pub create_user_id: Uuid,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+    ///更新时间
+    pub update_time: NaiveDateTime,
+    ///是否删除
+    pub is_delete: bool,
+}
+
+///数据库组织成员表结构字段
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
+#[diesel(table_name = organization_member)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OrganizationMember {
+    ///组织ID
+    pub organization_id: Uuid,
+    ///用户ID
+    pub user_id: Uuid,
+    ///成员角色：owner/member
+    pub member_role: String,
+    ///加入时间
+    pub create_time: NaiveDateTime,
+}
+
+///组织成员角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberRole {
+    ///所有者：可管理组织成员
+    Owner,
+    ///成员：可按系统角色访问组织内的渠道和应用
+    Member,
+}
+
+impl MemberRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemberRole::Owner => "owner",
+            MemberRole::Member => "member",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "owner" => Some(MemberRole::Owner),
+            "member" => Some(MemberRole::Member),
+            _ => None,
+        }
+    }
+}
+
+///当前请求所在的组织，由鉴权中间件根据 X-Organization-Id 请求头写入 Depot
+#[derive(Debug, Clone)]
+pub struct CurrentOrganization {
+    ///组织ID
+    pub id: Uuid,
+    ///组织名称
+    pub name: String,
+    ///当前用户在组织中的角色
+    pub member_role: MemberRole,
+}
+
+///创建组织请求参数
+#[derive(Serialize, Deserialize, Extractible, Debug, ToSchema)]
+#[salvo(extract(default_source(from = "body")))]
+pub struct CreateOrganizationReq {
+    ///组织名称
+    pub name: String,
+}
+
+///组织信息
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationItem {
+    ///组织ID
+    pub organization_id: Uuid,
+    ///组织名称
+    pub name: String,
+    ///是否为个人组织
+    pub is_personal: bool,
+    ///当前用户在组织中的角色
+    pub member_role: MemberRole,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+}
+
+///查询组织成员请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GetOrganizationMemberListReq {
+    ///组织ID
+    pub organization_id: Uuid,
+}
+
+///组织成员信息
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationMemberItem {
+    ///用户ID
+    pub user_id: Uuid,
+    ///用户名
+    pub username: String,
+    ///用户全称
+    pub full_name: String,
+    ///成员角色
+    pub member_role: MemberRole,
+    ///加入时间
+    pub create_time: NaiveDateTime,
+}
+
+///添加或修改组织成员请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AddOrganizationMemberReq {
+    ///组织ID
+    pub organization_id: Uuid,
+    ///用户名
+    pub username: String,
+    ///成员角色，未传时为普通成员
+    #[serde(default = "default_member_role")]
+    pub member_role: MemberRole,
+}
+
+fn default_member_role() -> MemberRole {
+    MemberRole::Member
+}
+
+///移除组织成员请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RemoveOrganizationMemberReq {
+    ///组织ID
+    pub organization_id: Uuid,
+    ///用户ID
+    pub user_id: Uuid,
+}
+
+///移除组织成员返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RemoveOrganizationMemberResp {
+    ///组织ID
+    pub organization_id: Uuid,
+    ///用户ID
+    pub user_id: Uuid,
+    ///移除信息
+    pub remove_info: String,
+}
@@ -0,0 +1,161 @@
+use crate::schema::user_role;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use salvo::macros::Extractible;
+use salvo_oapi::ToSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+///数据库用户角色表结构字段，一个用户可以拥有多个角色
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
+#[diesel(table_name = user_role)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UserRole {
+    ///用户ID
+    pub user_id: Uuid,
+    ///角色：admin/release_manager/publisher/viewer
+    pub role: String,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+}
+
+///用户角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    ///管理员：拥有全部权限，可分配用户角色
+    Admin,
+    ///发布经理：可管理渠道，调整已发布版本的灰度、强制更新和定向规则，撤回和删除版本
+    ReleaseManager,
+    ///发布者：可上传文件、发布新版本和生成渠道包
+    Publisher,
+    ///只读：可查看渠道和版本
+    Viewer,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::ReleaseManager => "release_manager",
+            Role::Publisher => "publisher",
+            Role::Viewer => "viewer",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "admin" => Some(Role::Admin),
+            "release_manager" => Some(Role::ReleaseManager),
+            "publisher" => Some(Role::Publisher),
+            "viewer" => Some(Role::Viewer),
+            _ => None,
+        }
+    }
+
+    // 角色拥有的权限，高级角色包含低级角色的全部权限
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            Role::Admin => &[
+                Permission::ChannelRead,
+                Permission::ChannelManage,
+                Permission::AppRead,
+                Permission::AppPublish,
+                Permission::AppRelease,
+                Permission::UserManage,
+            ],
+            Role::ReleaseManager => &[
+                Permission::ChannelRead,
+                Permission::ChannelManage,
+                Permission::AppRead,
+                Permission::AppPublish,
+                Permission::AppRelease,
+            ],
+            Role::Publisher => &[
+                Permission::ChannelRead,
+                Permission::AppRead,
+                Permission::AppPublish,
+            ],
+            Role::Viewer => &[Permission::ChannelRead, Permission::AppRead],
+        }
+    }
+}
+
+///接口权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    ///查看渠道
+    ChannelRead,
+    ///创建、修改和删除渠道及其访问密钥
+    ChannelManage,
+    ///查看版本和版本差异
+    AppRead,
+    ///上传文件、发布新版本、设置定时发布和生成渠道包
+    AppPublish,
+    ///调整已发布版本的灰度、强制更新和定向规则，撤回、删除版本和审批签名轮换
+    AppRelease,
+    ///查看用户和分配角色
+    UserManage,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ChannelRead => "channel:read",
+            Permission::ChannelManage => "channel:manage",
+            Permission::AppRead => "app:read",
+            Permission::AppPublish => "app:publish",
+            Permission::AppRelease => "app:release",
+            Permission::UserManage => "user:manage",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "channel:read" => Some(Permission::ChannelRead),
+            "channel:manage" => Some(Permission::ChannelManage),
+            "app:read" => Some(Permission::AppRead),
+            "app:publish" => Some(Permission::AppPublish),
+            "app:release" => Some(Permission::AppRelease),
+            "user:manage" => Some(Permission::UserManage),
+            _ => None,
+        }
+    }
+}
+
+///当前登录用户的角色，由鉴权中间件写入 Depot
+#[derive(Debug, Clone, Default)]
+pub struct CurrentRoles(pub Vec<Role>);
+
+impl CurrentRoles {
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.0
+            .iter()
+            .any(|role| role.permissions().contains(&permission))
+    }
+}
+
+///修改用户角色请求参数
+#[derive(Serialize, Deserialize, Extractible, Debug, ToSchema)]
+#[salvo(extract(default_source(from = "body")))]
+pub struct UpdateUserRolesReq {
+    ///用户ID
+    pub user_id: Uuid,
+    ///用户的全部角色，覆盖原有角色
+    pub roles: Vec<Role>,
+}
+
+///用户及其角色
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserRoleItem {
+    ///用户ID
+    pub id: Uuid,
+    ///用户名
+    pub username: String,
+    ///用户全称
+    pub full_name: String,
+    ///用户角色
+    pub roles: Vec<Role>,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+}
@@ -0,0 +1,122 @@
+use crate::schema::{user_session, user_session_rotated_token};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use salvo_oapi::ToSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+///数据库用户会话表结构字段，每次登录创建一个会话，只保存 Token 的哈希
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
+#[diesel(table_name = user_session)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UserSession {
+    ///会话ID，写入 Token 的 session_id 声明
+    pub id: Uuid,
+    ///用户ID
+    pub user_id: Uuid,
+    ///访问Token SHA-256 哈希
+    pub access_token_hash: String,
+    ///刷新Token SHA-256 哈希
+    pub refresh_token_hash: String,
+    ///登录时的 User-Agent
+    pub user_agent: Option<String>,
+    ///登录时的客户端IP
+    pub ip_address: Option<String>,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+    ///最后活跃时间
+    pub last_seen_time: NaiveDateTime,
+    ///过期时间，与刷新Token一致
+    pub expires_at: NaiveDateTime,
+    ///是否吊销
+    pub is_delete: bool,
+}
+
+///数据库会话已轮换刷新Token表结构字段，会话即刷新Token家族
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone, Selectable)]
+#[diesel(table_name = user_session_rotated_token)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UserSessionRotatedToken {
+    ///已轮换的刷新Token SHA-256 哈希
+    pub token_hash: String,
+    ///所属会话ID
+    pub session_id: Uuid,
+    ///轮换时间
+    pub rotated_time: NaiveDateTime,
+}
+
+///刷新Token在所属会话中的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshTokenStatus {
+    ///会话当前的刷新Token
+    Current,
+    ///已被轮换的旧刷新Token，超过宽限期后再次出现说明可能被盗用
+    Rotated,
+    ///宽限期内刚被轮换的旧刷新Token，视为多标签页或超时重试的重复提交
+    RecentlyRotated,
+    ///不属于该会话，或会话已过期
+    Unknown,
+}
+
+///登录客户端信息
+#[derive(Debug, Clone, Default)]
+pub struct SessionClient {
+    ///User-Agent
+    pub user_agent: Option<String>,
+    ///客户端IP
+    pub ip_address: Option<String>,
+}
+
+///当前请求所在的会话，由鉴权中间件写入 Depot，使用API令牌时不存在
+#[derive(Debug, Clone, Copy)]
+pub struct CurrentSession(pub Uuid);
+
+///会话信息，不包含 Token
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserSessionItem {
+    ///会话ID
+    pub session_id: Uuid,
+    ///登录时的 User-Agent
+    pub user_agent: Option<String>,
+    ///登录时的客户端IP
+    pub ip_address: Option<String>,
+    ///创建时间
+    pub create_time: NaiveDateTime,
+    ///最后活跃时间
+    pub last_seen_time: NaiveDateTime,
+    ///是否为当前请求所在的会话
+    pub is_current: bool,
+}
+
+///吊销会话请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RevokeUserSessionReq {
+    ///会话ID
+    pub session_id: Uuid,
+}
+
+///吊销会话返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RevokeUserSessionResp {
+    ///会话ID
+    pub session_id: Uuid,
+    ///吊销信息
+    pub revoke_info: String,
+}
+
+///吊销全部会话请求参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct RevokeAllUserSessionsReq {
+    ///是否保留当前会话，未传时当前会话也一并吊销
+    #[serde(default)]
+    pub keep_current: bool,
+}
+
+///吊销全部会话返回参数
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RevokeAllUserSessionsResp {
+    ///吊销的会话数量
+    pub revoke_count: usize,
+    ///吊销信息
+    pub revoke_info: String,
+}
@@ -1,3 +1,4 @@
+use crate::model::user_role::Role;
 use crate::schema::*;
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
@@ -20,10 +21,6 @@ pub struct User {
     pub password: String,
     ///用户全称
     pub full_name: String,
-    ///用户访问Token
-    pub access_token: String,
-    ///用户刷新Token
-    pub refresh_token: String,
     ///用户创建时间
     pub create_time: NaiveDateTime,
     ///用户更新时间
@@ -133,6 +130,8 @@ pub struct UserInfoResp {
     pub create_time: NaiveDateTime,
     ///是否被删除
     pub is_delete: bool,
+    ///用户角色
+    pub roles: Vec<Role>,
 }
 
 ///测试获取用户请求参数
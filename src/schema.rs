@@ -1,5 +1,36 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    api_token (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        organization_id -> Uuid,
+        token_name -> Varchar,
+        token_prefix -> Varchar,
+        token_hash -> Varchar,
+        scopes -> Array<Text>,
+        channel_ids -> Array<Uuid>,
+        expires_at -> Nullable<Timestamp>,
+        last_used_time -> Nullable<Timestamp>,
+        create_time -> Timestamp,
+        update_time -> Timestamp,
+        is_delete -> Bool,
+    }
+}
+
+diesel::table! {
+    app_blob (id) {
+        id -> Uuid,
+        kind -> Varchar,
+        file_name -> Varchar,
+        file_size -> Int8,
+        ref_count -> Int4,
+        create_time -> Timestamp,
+        update_time -> Timestamp,
+        original_name -> Nullable<Varchar>,
+    }
+}
+
 diesel::table! {
     app_channel (id) {
         id -> Uuid,
@@ -9,6 +40,52 @@ diesel::table! {
         create_time -> Timestamp,
         update_time -> Timestamp,
         is_delete -> Bool,
+        min_supported_version_code -> Nullable<Int8>,
+        is_private -> Bool,
+        bind_download_device -> Bool,
+        organization_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    app_channel_apk (id) {
+        id -> Uuid,
+        channel_id -> Uuid,
+        master_file_name -> Varchar,
+        file_name -> Varchar,
+        create_time -> Timestamp,
+        update_time -> Timestamp,
+    }
+}
+
+diesel::table! {
+    app_channel_key (id) {
+        id -> Uuid,
+        channel_id -> Uuid,
+        app_key -> Varchar,
+        app_secret -> Varchar,
+        create_user_id -> Uuid,
+        create_time -> Timestamp,
+        update_time -> Timestamp,
+        is_delete -> Bool,
+    }
+}
+
+diesel::table! {
+    app_delta_patch (id) {
+        id -> Uuid,
+        app_id -> Uuid,
+        base_app_id -> Uuid,
+        status -> Varchar,
+        base_file_sha256 -> Nullable<Varchar>,
+        file_name -> Nullable<Varchar>,
+        patch_sha256 -> Nullable<Varchar>,
+        patch_size -> Nullable<Int8>,
+        error_msg -> Nullable<Varchar>,
+        create_time -> Timestamp,
+        update_time -> Timestamp,
+        failure_count -> Int4,
+        next_retry_time -> Nullable<Timestamp>,
     }
 }
 
@@ -31,6 +108,70 @@ diesel::table! {
         file_size -> Int8,
         channel_name -> Nullable<Varchar>,
         update_log -> Nullable<Varchar>,
+        force_update -> Bool,
+        rollout_percentage -> Int4,
+        rollout_status -> Varchar,
+        min_sdk_version -> Nullable<Int4>,
+        native_abis -> Array<Text>,
+        targeting_rules -> Nullable<Jsonb>,
+        is_revoked -> Bool,
+        revoke_reason -> Nullable<Varchar>,
+        revoke_time -> Nullable<Timestamp>,
+        rollback_app_id -> Nullable<Uuid>,
+        publish_at -> Nullable<Timestamp>,
+        expire_at -> Nullable<Timestamp>,
+        is_published -> Bool,
+        signer_sha256 -> Nullable<Varchar>,
+        signature_schemes -> Array<Text>,
+        target_sdk_version -> Nullable<Int4>,
+        permissions -> Array<Text>,
+        features -> Array<Text>,
+        is_debuggable -> Bool,
+        is_test_only -> Bool,
+        components -> Nullable<Jsonb>,
+        splits -> Nullable<Jsonb>,
+        is_archive_only -> Bool,
+        file_sha256 -> Nullable<Varchar>,
+        file_md5 -> Nullable<Varchar>,
+        organization_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    app_request_nonce (app_key, nonce) {
+        app_key -> Varchar,
+        nonce -> Varchar,
+        create_time -> Timestamp,
+        expires_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    app_signer_pin (id) {
+        id -> Uuid,
+        package_name -> Varchar,
+        create_user_id -> Uuid,
+        signer_sha256 -> Varchar,
+        rotation_signer_sha256 -> Nullable<Varchar>,
+        create_time -> Timestamp,
+        update_time -> Timestamp,
+        organization_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    app_upload_session (id) {
+        id -> Uuid,
+        create_user_id -> Uuid,
+        file_name -> Varchar,
+        file_size -> Int8,
+        chunk_size -> Int8,
+        total_chunks -> Int4,
+        file_sha256 -> Nullable<Varchar>,
+        status -> Varchar,
+        expires_at -> Timestamp,
+        create_time -> Timestamp,
+        update_time -> Timestamp,
     }
 }
 
@@ -54,29 +195,109 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    organization (id) {
+        id -> Uuid,
+        name -> Varchar,
+        is_personal -> Bool,
+        create_user_id -> Uuid,
+        create_time -> Timestamp,
+        update_time -> Timestamp,
+        is_delete -> Bool,
+    }
+}
+
+diesel::table! {
+    organization_member (organization_id, user_id) {
+        organization_id -> Uuid,
+        user_id -> Uuid,
+        member_role -> Varchar,
+        create_time -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_role (user_id, role) {
+        user_id -> Uuid,
+        role -> Varchar,
+        create_time -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_session (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        access_token_hash -> Varchar,
+        refresh_token_hash -> Varchar,
+        user_agent -> Nullable<Varchar>,
+        ip_address -> Nullable<Varchar>,
+        create_time -> Timestamp,
+        last_seen_time -> Timestamp,
+        expires_at -> Timestamp,
+        is_delete -> Bool,
+    }
+}
+
+diesel::table! {
+    user_session_rotated_token (token_hash) {
+        token_hash -> Varchar,
+        session_id -> Uuid,
+        rotated_time -> Timestamp,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Uuid,
         username -> Varchar,
         password -> Varchar,
         full_name -> Varchar,
-        access_token -> Varchar,
-        refresh_token -> Varchar,
         create_time -> Timestamp,
         update_time -> Timestamp,
         is_delete -> Bool,
     }
 }
 
+diesel::joinable!(api_token -> organization (organization_id));
+diesel::joinable!(api_token -> users (user_id));
+diesel::joinable!(app_channel -> organization (organization_id));
 diesel::joinable!(app_channel -> users (create_user_id));
+diesel::joinable!(app_channel_apk -> app_channel (channel_id));
+diesel::joinable!(app_channel_key -> app_channel (channel_id));
+diesel::joinable!(app_channel_key -> users (create_user_id));
+diesel::joinable!(app_delta_patch -> app_manage (app_id));
 diesel::joinable!(app_manage -> app_channel (channel_id));
+diesel::joinable!(app_manage -> organization (organization_id));
 diesel::joinable!(app_manage -> users (create_user_id));
+diesel::joinable!(app_signer_pin -> organization (organization_id));
+diesel::joinable!(app_signer_pin -> users (create_user_id));
+diesel::joinable!(app_upload_session -> users (create_user_id));
 diesel::joinable!(operation_log -> users (user_id));
+diesel::joinable!(organization -> users (create_user_id));
+diesel::joinable!(organization_member -> organization (organization_id));
+diesel::joinable!(organization_member -> users (user_id));
+diesel::joinable!(user_role -> users (user_id));
+diesel::joinable!(user_session -> users (user_id));
+diesel::joinable!(user_session_rotated_token -> user_session (session_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_token,
+    app_blob,
     app_channel,
+    app_channel_apk,
+    app_channel_key,
+    app_delta_patch,
     app_manage,
+    app_request_nonce,
+    app_signer_pin,
+    app_upload_session,
     auth_captcha,
     operation_log,
+    organization,
+    organization_member,
+    user_role,
+    user_session,
+    user_session_rotated_token,
     users,
 );
@@ -1,26 +1,58 @@
+use crate::api::api_token::api_token_router;
 use crate::api::app_channel::app_channel_router;
 use crate::api::app_manage::{app_check_update, app_manage_router, get_app_info};
+use crate::api::app_upload_session::app_upload_session_router;
 use crate::api::operation_log::operation_log_router;
+use crate::api::organization::organization_router;
 use crate::api::ping::ping_router;
 use crate::api::users::{auth_token, user_router_not_auth, users_router};
 use crate::db::establish_connection_pool;
 use crate::middleware::access_log::AccessLog;
 use crate::model::jwt::{AccessTokenClaims, get_jwt_secret_key};
-use crate::store::{CaptchaStore, PostgresCaptchaStore, PostgresTokenStore, TokenStore};
+use crate::store::{
+    CaptchaStore, FileStore, NonceStore, PostgresCaptchaStore, PostgresNonceStore,
+    PostgresTokenStore, TokenStore, create_file_store, get_file_store,
+};
 use crate::utils::app_manage_cleanup_task::start_app_manage_cleanup_task;
+use crate::utils::app_manage_publish_task::start_app_manage_publish_task;
+use crate::utils::blob_store_utils::{
+    APK_STORE_PREFIX, BLOB_KIND_APK, ICON_STORE_PREFIX, PATCH_STORE_PREFIX,
+    find_blob_original_name, is_blob_file_name,
+};
+use crate::utils::database_utils::try_connect_database;
+use crate::utils::delta_patch_task::start_delta_patch_task;
+use crate::utils::download_signature_utils::{
+    DEVICE_ID_HEADER, DownloadKind, download_url_secret, requires_signed_download,
+    requires_signed_patch_download, verify_download_signature,
+};
+use crate::utils::file_digest_utils::{cached_file_digest, sha256_digest_header};
+use crate::utils::http_download_utils::{
+    DownloadPlan, DownloadValidators, attachment_disposition, format_http_date, plan_download,
+};
 use crate::utils::json_error_catcher::json_error_catcher;
+use crate::utils::request_nonce_cleanup_task::start_request_nonce_cleanup_task;
+use crate::utils::upload_session_cleanup_task::start_upload_session_cleanup_task;
+use chrono::{DateTime, Local, TimeZone, Utc};
 use salvo::catcher::Catcher;
-use salvo::fs::NamedFile;
+use salvo::http::header::{
+    ACCEPT_RANGES, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG,
+    LAST_MODIFIED,
+};
 use salvo::jwt_auth::{ConstDecoder, HeaderFinder};
 use salvo::prelude::*;
 use salvo_oapi::SecurityScheme;
 use salvo_oapi::security::{Http, HttpAuthScheme};
 use std::future;
+use std::io::SeekFrom;
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
-use tracing::info;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+use tracing::{error, info, warn};
 
-const PUBLIC_APP_MANAGE_DIR: &str = "app_manage";
+// 对象存储预签名下载地址的有效期
+const PRESIGNED_URL_EXPIRES: Duration = Duration::from_secs(10 * 60);
 
 fn build_public_router() -> Router {
     Router::with_path("api").push(
@@ -29,6 +61,7 @@ fn build_public_router() -> Router {
                 Router::with_path("app_manage")
                     .push(Router::with_path("icon").get(public_app_manage_icon_file))
                     .push(Router::with_path("apk").get(public_app_manage_apk_file))
+                    .push(Router::with_path("patch").get(public_app_manage_patch_file))
                     .push(Router::with_path("app_check_update").post(app_check_update))
                     .push(Router::with_path("get_app_info").post(get_app_info)),
             )
@@ -43,28 +76,224 @@ fn build_protected_router(auth_handler: JwtAuth<AccessTokenClaims, ConstDecoder>
         .hoop(auth_token)
         .push(users_router())
         .push(operation_log_router())
+        .push(organization_router())
+        .push(api_token_router())
         .push(app_channel_router())
         .push(app_manage_router())
+        .push(app_upload_session_router())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::error::AppError;
+    use crate::store::{LocalFileStore, StoredObject, StoredObjectInfo};
+    use bytes::Bytes;
+    use salvo::http::header::{ETAG, IF_RANGE, RANGE};
+    use salvo::prelude::async_trait;
+    use salvo::test::{ResponseExt, TestClient};
+    use std::sync::Mutex;
+
+    const ICON_DATA: &[u8] = b"0123456789";
+
+    // 不提供本地路径的存储，下载走对象存储的转发分支，并记录读取的范围
+    struct ProxyFileStore {
+        inner: LocalFileStore,
+        ranges: Mutex<Vec<(u64, u64)>>,
+    }
+
+    #[async_trait]
+    impl FileStore for ProxyFileStore {
+        async fn put(&self, key: &str, data: Bytes) -> Result<(), AppError> {
+            self.inner.put(key, data).await
+        }
+        async fn put_file(&self, key: &str, source: &Path) -> Result<(), AppError> {
+            self.inner.put_file(key, source).await
+        }
+        async fn get(&self, key: &str) -> Result<Option<StoredObject>, AppError> {
+            self.inner.get(key).await
+        }
+        async fn get_range(
+            &self,
+            key: &str,
+            start: u64,
+            len: u64,
+        ) -> Result<Option<StoredObject>, AppError> {
+            self.ranges.lock().unwrap().push((start, len));
+            self.inner.get_range(key, start, len).await
+        }
+        async fn head(&self, key: &str) -> Result<Option<StoredObjectInfo>, AppError> {
+            self.inner.head(key).await
+        }
+        async fn delete(&self, key: &str) -> Result<(), AppError> {
+            self.inner.delete(key).await
+        }
+        async fn list(&self, prefix: &str) -> Result<Vec<StoredObjectInfo>, AppError> {
+            self.inner.list(prefix).await
+        }
+        async fn presign(
+            &self,
+            _key: &str,
+            _expires_in: Duration,
+            _content_disposition: Option<&str>,
+        ) -> Result<Option<String>, AppError> {
+            Ok(None)
+        }
+    }
+
+    fn temp_store_root() -> PathBuf {
+        std::env::temp_dir().join(format!("server_download_{}", uuid::Uuid::new_v4()))
+    }
+
+    fn download_service(file_store: Arc<dyn FileStore>) -> Service {
+        Service::new(
+            Router::new()
+                .hoop(affix_state::inject(file_store))
+                .push(build_public_router()),
+        )
+    }
+
+    fn icon_url() -> &'static str {
+        "http://127.0.0.1:5800/api/public/app_manage/icon?name=demo.png"
+    }
+
+    fn header(res: &Response, name: salvo::http::header::HeaderName) -> Option<String> {
+        res.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    }
 
     #[test]
     fn public_router_should_expose_get_app_info() {
         let debug = format!("{:?}", build_public_router());
         assert!(debug.contains("get_app_info"));
     }
+
+    #[tokio::test]
+    async fn local_download_serves_ranges_and_if_range() {
+        let root = temp_store_root();
+        let store = Arc::new(LocalFileStore::new(&root));
+        store
+            .put(
+                &format!("{ICON_STORE_PREFIX}/demo.png"),
+                Bytes::from_static(ICON_DATA),
+            )
+            .await
+            .unwrap();
+        let service = download_service(store);
+
+        let mut full = TestClient::get(icon_url()).send(&service).await;
+        assert_eq!(full.status_code, Some(StatusCode::OK));
+        assert_eq!(header(&full, ACCEPT_RANGES).as_deref(), Some("bytes"));
+        let etag = header(&full, ETAG).unwrap();
+        assert_eq!(full.take_string().await.unwrap(), "0123456789");
+
+        let mut partial = TestClient::get(icon_url())
+            .add_header(RANGE, "bytes=2-5", true)
+            .send(&service)
+            .await;
+        assert_eq!(partial.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        assert_eq!(
+            header(&partial, CONTENT_RANGE).as_deref(),
+            Some("bytes 2-5/10")
+        );
+        assert_eq!(partial.take_string().await.unwrap(), "2345");
+
+        // 续传：If-Range 与当前 ETag 一致时返回剩余部分
+        let mut resumed = TestClient::get(icon_url())
+            .add_header(RANGE, "bytes=7-", true)
+            .add_header(IF_RANGE, etag.as_str(), true)
+            .send(&service)
+            .await;
+        assert_eq!(resumed.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        assert_eq!(
+            header(&resumed, CONTENT_RANGE).as_deref(),
+            Some("bytes 7-9/10")
+        );
+        assert_eq!(resumed.take_string().await.unwrap(), "789");
+
+        // 文件已变化时 If-Range 不匹配，返回完整文件
+        let mut stale = TestClient::get(icon_url())
+            .add_header(RANGE, "bytes=7-", true)
+            .add_header(IF_RANGE, "\"stale\"", true)
+            .send(&service)
+            .await;
+        assert_eq!(stale.status_code, Some(StatusCode::OK));
+        assert!(header(&stale, CONTENT_RANGE).is_none());
+        assert_eq!(stale.take_string().await.unwrap(), "0123456789");
+
+        let unsatisfiable = TestClient::get(icon_url())
+            .add_header(RANGE, "bytes=10-", true)
+            .send(&service)
+            .await;
+        assert_eq!(
+            unsatisfiable.status_code,
+            Some(StatusCode::RANGE_NOT_SATISFIABLE)
+        );
+        assert_eq!(
+            header(&unsatisfiable, CONTENT_RANGE).as_deref(),
+            Some("bytes */10")
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn proxied_download_reads_only_requested_range() {
+        let root = temp_store_root();
+        let store = Arc::new(ProxyFileStore {
+            inner: LocalFileStore::new(&root),
+            ranges: Mutex::new(Vec::new()),
+        });
+        store
+            .put(
+                &format!("{ICON_STORE_PREFIX}/demo.png"),
+                Bytes::from_static(ICON_DATA),
+            )
+            .await
+            .unwrap();
+        let service = download_service(store.clone());
+
+        let mut suffix = TestClient::get(icon_url())
+            .add_header(RANGE, "bytes=-3", true)
+            .send(&service)
+            .await;
+        assert_eq!(suffix.status_code, Some(StatusCode::PARTIAL_CONTENT));
+        assert_eq!(
+            header(&suffix, CONTENT_RANGE).as_deref(),
+            Some("bytes 7-9/10")
+        );
+        assert_eq!(suffix.take_string().await.unwrap(), "789");
+
+        let unsatisfiable = TestClient::get(icon_url())
+            .add_header(RANGE, "bytes=20-30", true)
+            .send(&service)
+            .await;
+        assert_eq!(
+            unsatisfiable.status_code,
+            Some(StatusCode::RANGE_NOT_SATISFIABLE)
+        );
+        assert_eq!(store.ranges.lock().unwrap().as_slice(), &[(7, 3)]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 }
 
 pub async fn run() {
     //数据库
     let pool = Arc::new(establish_connection_pool());
-    start_app_manage_cleanup_task(pool.clone());
+    //文件存储
+    let file_store: Arc<dyn FileStore> = create_file_store();
+    start_app_manage_cleanup_task(pool.clone(), file_store.clone());
+    start_app_manage_publish_task(pool.clone());
+    start_upload_session_cleanup_task(pool.clone(), file_store.clone());
+    start_delta_patch_task(pool.clone(), file_store.clone());
 
     let captcha_store: Arc<dyn CaptchaStore> = Arc::new(PostgresCaptchaStore::new(pool.clone()));
     let token_store: Arc<dyn TokenStore> = Arc::new(PostgresTokenStore::new(pool.clone()));
+    let nonce_store: Arc<dyn NonceStore> = Arc::new(PostgresNonceStore::new(pool.clone()));
+    start_request_nonce_cleanup_task(nonce_store.clone());
 
     let auth_handler: JwtAuth<AccessTokenClaims, _> =
         JwtAuth::new(ConstDecoder::from_secret(get_jwt_secret_key().as_bytes()))
@@ -79,7 +308,9 @@ pub async fn run() {
         .hoop(
             affix_state::inject(pool)
                 .inject(captcha_store)
-                .inject(token_store),
+                .inject(token_store)
+                .inject(nonce_store)
+                .inject(file_store),
         );
 
     // 添加公开接口：不需要 Token，可直接访问
@@ -119,18 +350,24 @@ pub async fn run() {
 // 公开应用图标文件
 #[handler]
 async fn public_app_manage_icon_file(req: &mut Request, depot: &mut Depot, res: &mut Response) {
-    serve_public_app_manage_file("icons", req, depot, res).await;
+    serve_public_app_manage_file(ICON_STORE_PREFIX, req, depot, res).await;
 }
 
 // 公开应用APK文件
 #[handler]
 async fn public_app_manage_apk_file(req: &mut Request, depot: &mut Depot, res: &mut Response) {
-    serve_public_app_manage_file("apk", req, depot, res).await;
+    serve_public_app_manage_file(APK_STORE_PREFIX, req, depot, res).await;
+}
+
+// 公开应用差分补丁文件
+#[handler]
+async fn public_app_manage_patch_file(req: &mut Request, depot: &mut Depot, res: &mut Response) {
+    serve_public_app_manage_file(PATCH_STORE_PREFIX, req, depot, res).await;
 }
 
-// 公开应用管理文件
+// 公开应用管理文件：本地存储直接返回文件，对象存储重定向到预签名地址或由服务转发
 pub async fn serve_public_app_manage_file(
-    sub_dir: &str,
+    store_prefix: &str,
     req: &mut Request,
     depot: &mut Depot,
     res: &mut Response,
@@ -156,15 +393,292 @@ pub async fn serve_public_app_manage_file(
         return;
     }
 
-    let full_path = PathBuf::from(PUBLIC_APP_MANAGE_DIR)
-        .join(sub_dir)
-        .join(relative_path);
-    match NamedFile::builder(full_path).build().await {
-        Ok(file) => {
-            file.write(req, depot, res).await;
+    if (store_prefix == APK_STORE_PREFIX || store_prefix == PATCH_STORE_PREFIX)
+        && let Err(status) = check_download_signature(store_prefix, &filename, req, depot)
+    {
+        res.status_code(status);
+        return;
+    }
+
+    let file_store = match get_file_store(depot) {
+        Ok(file_store) => file_store,
+        Err(_) => {
+            res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
+            return;
+        }
+    };
+    // APK 以附件形式下载，保存为上传时的原始文件名
+    let content_disposition = (store_prefix == APK_STORE_PREFIX)
+        .then(|| attachment_disposition(&apk_download_name(&filename, depot)));
+    let key = format!("{store_prefix}/{filename}");
+    match file_store.local_path(&key) {
+        Some(full_path) => {
+            serve_local_file(full_path, &key, content_disposition.as_deref(), req, res).await
+        }
+        None => {
+            serve_stored_object(
+                file_store.as_ref(),
+                &key,
+                content_disposition.as_deref(),
+                req,
+                res,
+            )
+            .await
         }
+    }
+}
+
+// APK 下载时保存的文件名：上传时的原始文件名，未记录时使用存储文件名
+fn apk_download_name(filename: &str, depot: &mut Depot) -> String {
+    let original_name = try_connect_database(depot).ok().and_then(|mut conn| {
+        find_blob_original_name(&mut conn, BLOB_KIND_APK, filename)
+            .inspect_err(|e| warn!(file_name = %filename, error = %e, "查询原始文件名失败"))
+            .ok()
+            .flatten()
+    });
+    original_name.unwrap_or_else(|| {
+        Path::new(filename)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(filename)
+            .to_string()
+    })
+}
+
+// 返回本地存储的文件，支持断点续传和条件请求
+async fn serve_local_file(
+    full_path: PathBuf,
+    key: &str,
+    content_disposition: Option<&str>,
+    req: &Request,
+    res: &mut Response,
+) {
+    let mut file = match tokio::fs::File::open(&full_path).await {
+        Ok(file) => file,
         Err(_) => {
             res.status_code(StatusCode::NOT_FOUND);
+            return;
         }
+    };
+    let metadata = match file.metadata().await {
+        Ok(metadata) if metadata.is_file() => metadata,
+        _ => {
+            res.status_code(StatusCode::NOT_FOUND);
+            return;
+        }
+    };
+
+    // 摘要计算需要读取整个文件，放到阻塞线程中执行，结果按文件缓存
+    let digest_path = full_path.clone();
+    let file_digest = tokio::task::spawn_blocking(move || cached_file_digest(&digest_path))
+        .await
+        .ok()
+        .and_then(|digest| digest.ok());
+    if let Some(digest) = file_digest.as_ref() {
+        let _ = res.add_header("Digest", digest.digest_header(), true);
+    }
+    let etag = file_digest.as_ref().map(|digest| digest.etag());
+    let validators = DownloadValidators {
+        etag: etag.as_deref(),
+        last_modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+        size: metadata.len(),
+    };
+    let Some((start, len)) =
+        prepare_download_response(key, &validators, content_disposition, req, res)
+    else {
+        return;
+    };
+
+    if start > 0
+        && let Err(e) = file.seek(SeekFrom::Start(start)).await
+    {
+        error!(key = %key, error = %e, "读取存储文件失败");
+        res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
+        return;
+    }
+    res.stream(ReaderStream::new(file.take(len)));
+}
+
+// 按条件请求和范围请求设置响应状态和响应头，需要返回文件内容时返回内容的起始位置和长度
+fn prepare_download_response(
+    key: &str,
+    validators: &DownloadValidators,
+    content_disposition: Option<&str>,
+    req: &Request,
+    res: &mut Response,
+) -> Option<(u64, u64)> {
+    let _ = res.add_header(ACCEPT_RANGES, "bytes", true);
+    if let Some(etag) = validators.etag {
+        let _ = res.add_header(ETAG, etag, true);
+    }
+    if let Some(last_modified) = validators.last_modified {
+        let _ = res.add_header(LAST_MODIFIED, format_http_date(last_modified), true);
+    }
+
+    let (start, len) = match plan_download(req.headers(), validators) {
+        DownloadPlan::NotModified => {
+            res.status_code(StatusCode::NOT_MODIFIED);
+            return None;
+        }
+        DownloadPlan::PreconditionFailed => {
+            res.status_code(StatusCode::PRECONDITION_FAILED);
+            return None;
+        }
+        DownloadPlan::RangeNotSatisfiable => {
+            res.status_code(StatusCode::RANGE_NOT_SATISFIABLE);
+            let _ = res.add_header(CONTENT_RANGE, format!("bytes */{}", validators.size), true);
+            return None;
+        }
+        DownloadPlan::Full => (0, validators.size),
+        DownloadPlan::Partial { start, end } => {
+            res.status_code(StatusCode::PARTIAL_CONTENT);
+            let _ = res.add_header(
+                CONTENT_RANGE,
+                format!("bytes {start}-{end}/{}", validators.size),
+                true,
+            );
+            (start, end - start + 1)
+        }
+    };
+    let _ = res.add_header(CONTENT_TYPE, content_type_of(key), true);
+    if let Some(content_disposition) = content_disposition {
+        let _ = res.add_header(CONTENT_DISPOSITION, content_disposition, true);
+    }
+    let _ = res.add_header(CONTENT_LENGTH, len, true);
+    Some((start, len))
+}
+
+// 校验 APK 和差分补丁下载签名：携带签名时必须有效，未携带签名时只能下载非私有渠道的文件
+fn check_download_signature(
+    store_prefix: &str,
+    filename: &str,
+    req: &Request,
+    depot: &mut Depot,
+) -> Result<(), StatusCode> {
+    let kind = if store_prefix == PATCH_STORE_PREFIX {
+        DownloadKind::Patch
+    } else {
+        DownloadKind::Apk
+    };
+    let signature = req.query::<String>("signature");
+    if signature.is_some() {
+        let device_id = req.header::<String>(DEVICE_ID_HEADER);
+        return verify_download_signature(
+            &download_url_secret(),
+            kind,
+            filename,
+            req.query::<i64>("expires"),
+            signature.as_deref(),
+            device_id.as_deref().map(str::trim),
+            chrono::Local::now().timestamp(),
+        )
+        .map_err(|e| {
+            warn!(file_name = %filename, reason = ?e, "签名下载地址校验失败");
+            StatusCode::FORBIDDEN
+        });
+    }
+
+    let mut conn = try_connect_database(depot).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let requires_signature = match kind {
+        DownloadKind::Patch => requires_signed_patch_download(&mut conn, filename),
+        DownloadKind::Apk => requires_signed_download(&mut conn, filename),
+    };
+    match requires_signature {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(StatusCode::FORBIDDEN),
+        Err(e) => {
+            error!(file_name = %filename, error = %e, "查询文件所属渠道失败");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// 返回对象存储中的文件，支持预签名时重定向，由存储直接提供下载
+async fn serve_stored_object(
+    file_store: &dyn FileStore,
+    key: &str,
+    content_disposition: Option<&str>,
+    req: &Request,
+    res: &mut Response,
+) {
+    match file_store
+        .presign(key, PRESIGNED_URL_EXPIRES, content_disposition)
+        .await
+    {
+        Ok(Some(url)) => {
+            res.render(Redirect::temporary(url));
+            return;
+        }
+        Ok(None) => {}
+        Err(e) => {
+            error!(key = %key, error = %e, "生成文件下载地址失败");
+            res.status_code(StatusCode::BAD_GATEWAY);
+            return;
+        }
+    }
+
+    let object_info = match file_store.head(key).await {
+        Ok(Some(object_info)) => object_info,
+        Ok(None) => {
+            res.status_code(StatusCode::NOT_FOUND);
+            return;
+        }
+        Err(e) => {
+            error!(key = %key, error = %e, "读取存储文件失败");
+            res.status_code(StatusCode::BAD_GATEWAY);
+            return;
+        }
+    };
+    // 存储文件按内容摘要命名，文件名中的 SHA-256 即为 ETag 和 Digest
+    let blob_sha256 = Path::new(key)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| is_blob_file_name(name))
+        .and_then(|name| name.split_once('.'))
+        .map(|(sha256, _)| sha256);
+    if let Some(sha256) = blob_sha256 {
+        let _ = res.add_header("Digest", sha256_digest_header(sha256), true);
+    }
+    let etag = blob_sha256.map(|sha256| format!("\"{sha256}\""));
+    let validators = DownloadValidators {
+        etag: etag.as_deref(),
+        last_modified: Local
+            .from_local_datetime(&object_info.last_modified)
+            .earliest()
+            .map(|time| time.with_timezone(&Utc)),
+        size: object_info.size,
+    };
+    let Some((start, len)) =
+        prepare_download_response(key, &validators, content_disposition, req, res)
+    else {
+        return;
+    };
+
+    // 只向存储请求需要返回的范围，不读取整个对象
+    match file_store.get_range(key, start, len).await {
+        Ok(Some(object)) => {
+            res.stream(object.stream);
+        }
+        Ok(None) => {
+            res.status_code(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!(key = %key, error = %e, "读取存储文件失败");
+            res.status_code(StatusCode::BAD_GATEWAY);
+        }
+    }
+}
+
+fn content_type_of(key: &str) -> &'static str {
+    let extension = Path::new(key)
+        .extension()
+        .and_then(|value| value.to_str())
+        .map(|value| value.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("apk") => "application/vnd.android.package-archive",
+        Some("png") => "image/png",
+        Some("webp") => "image/webp",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        _ => "application/octet-stream",
     }
 }
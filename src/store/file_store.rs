@@ -0,0 +1,351 @@
+use crate::model::error::AppError;
+use bytes::Bytes;
+use chrono::{DateTime, Local, NaiveDateTime};
+use futures_util::StreamExt;
+use futures_util::stream::BoxStream;
+use salvo::prelude::async_trait;
+use std::io::{ErrorKind, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+// 写入中的临时文件前缀，写完后重命名为正式文件名，避免读到不完整的文件
+pub const LOCAL_TEMP_PREFIX: &str = ".tmp_";
+
+/// 存储对象信息
+#[derive(Debug, Clone)]
+pub struct StoredObjectInfo {
+    /// 对象键，如 `apk/{文件名}`
+    pub key: String,
+    /// 对象大小（字节）
+    pub size: u64,
+    /// 最后修改时间（本地时间）
+    pub last_modified: NaiveDateTime,
+}
+
+/// 读取的存储对象
+pub struct StoredObject {
+    /// 对象大小（字节）
+    pub size: u64,
+    /// 对象内容
+    pub stream: BoxStream<'static, std::io::Result<Bytes>>,
+}
+
+#[async_trait]
+pub trait FileStore: Send + Sync {
+    async fn put(&self, key: &str, data: Bytes) -> Result<(), AppError>;
+    async fn put_file(&self, key: &str, source: &Path) -> Result<(), AppError>;
+    async fn get(&self, key: &str) -> Result<Option<StoredObject>, AppError>;
+    // 读取对象中从 start 开始、长度为 len 的部分，用于范围请求
+    async fn get_range(
+        &self,
+        key: &str,
+        start: u64,
+        len: u64,
+    ) -> Result<Option<StoredObject>, AppError>;
+    async fn head(&self, key: &str) -> Result<Option<StoredObjectInfo>, AppError>;
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObjectInfo>, AppError>;
+    // 生成限时访问地址，可指定下载时的 Content-Disposition，不支持预签名的存储返回 None，由服务直接读取文件
+    async fn presign(
+        &self,
+        key: &str,
+        expires_in: Duration,
+        content_disposition: Option<&str>,
+    ) -> Result<Option<String>, AppError>;
+
+    // 对象在本地文件系统中的路径，非本地存储返回 None
+    fn local_path(&self, _key: &str) -> Option<PathBuf> {
+        None
+    }
+}
+
+// 取得对象的本地文件：本地存储直接返回文件路径，其他存储下载到临时目录；对象不存在时返回 None
+pub async fn fetch_to_local(
+    store: &dyn FileStore,
+    key: &str,
+    temp_dir: &Path,
+) -> Result<Option<PathBuf>, AppError> {
+    if let Some(path) = store.local_path(key) {
+        return Ok(fs::metadata(&path)
+            .await
+            .is_ok_and(|metadata| metadata.is_file())
+            .then_some(path));
+    }
+
+    let Some(mut object) = store.get(key).await? else {
+        return Ok(None);
+    };
+    let file_name = key.rsplit('/').next().unwrap_or(key);
+    let path = temp_dir.join(format!("{}_{}", Uuid::new_v4().simple(), file_name));
+    let mut file = fs::File::create(&path)
+        .await
+        .map_err(|e| AppError::Internal(format!("创建临时文件失败: {}", e)))?;
+    while let Some(chunk) = object.stream.next().await {
+        let chunk = chunk.map_err(|e| AppError::Internal(format!("读取存储文件失败: {}", e)))?;
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| AppError::Internal(format!("写入临时文件失败: {}", e)))?;
+    }
+    file.flush()
+        .await
+        .map_err(|e| AppError::Internal(format!("写入临时文件失败: {}", e)))?;
+
+    Ok(Some(path))
+}
+
+// 读取对象的全部内容，对象不存在时返回 None
+pub async fn read_all(store: &dyn FileStore, key: &str) -> Result<Option<Vec<u8>>, AppError> {
+    let Some(mut object) = store.get(key).await? else {
+        return Ok(None);
+    };
+    let mut data = Vec::with_capacity(object.size as usize);
+    while let Some(chunk) = object.stream.next().await {
+        let chunk = chunk.map_err(|e| AppError::Internal(format!("读取存储文件失败: {}", e)))?;
+        data.extend_from_slice(&chunk);
+    }
+    Ok(Some(data))
+}
+
+/// 本地文件系统存储，对象键对应根目录下的相对路径
+pub struct LocalFileStore {
+    root: PathBuf,
+}
+
+impl LocalFileStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn resolve(&self, key: &str) -> Result<PathBuf, AppError> {
+        let relative_path = Path::new(key);
+        if key.trim().is_empty()
+            || relative_path
+                .components()
+                .any(|component| !matches!(component, Component::Normal(_)))
+        {
+            return Err(AppError::FORBIDDEN("非法文件路径".to_string()));
+        }
+        Ok(self.root.join(relative_path))
+    }
+
+    // 先写入同目录的临时文件再重命名，保证读到的文件完整
+    async fn write_atomically(
+        &self,
+        key: &str,
+        write_temp: impl AsyncFnOnce(&Path) -> std::io::Result<()>,
+    ) -> Result<(), AppError> {
+        let path = self.resolve(key)?;
+        let parent = path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(parent)
+            .await
+            .map_err(|e| AppError::Internal(format!("创建存储目录失败: {}", e)))?;
+
+        let file_name = path
+            .file_name()
+            .and_then(|value| value.to_str())
+            .unwrap_or("file");
+        let temp_path = parent.join(format!(
+            "{LOCAL_TEMP_PREFIX}{}_{}",
+            Uuid::new_v4().simple(),
+            file_name
+        ));
+        let written = match write_temp(&temp_path).await {
+            Ok(()) => fs::rename(&temp_path, &path).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temp_path).await;
+            return Err(AppError::Internal(format!(
+                "保存文件失败 {}: {}",
+                path.display(),
+                e
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FileStore for LocalFileStore {
+    async fn put(&self, key: &str, data: Bytes) -> Result<(), AppError> {
+        self.write_atomically(key, async |temp_path| fs::write(temp_path, &data).await)
+            .await
+    }
+
+    async fn put_file(&self, key: &str, source: &Path) -> Result<(), AppError> {
+        self.write_atomically(key, async |temp_path| {
+            fs::copy(source, temp_path).await.map(|_| ())
+        })
+        .await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<StoredObject>, AppError> {
+        let path = self.resolve(key)?;
+        let file = match fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(AppError::Internal(format!("读取文件失败: {}", e))),
+        };
+        let size = file
+            .metadata()
+            .await
+            .map_err(|e| AppError::Internal(format!("读取文件信息失败: {}", e)))?
+            .len();
+
+        Ok(Some(StoredObject {
+            size,
+            stream: ReaderStream::new(file).boxed(),
+        }))
+    }
+
+    async fn get_range(
+        &self,
+        key: &str,
+        start: u64,
+        len: u64,
+    ) -> Result<Option<StoredObject>, AppError> {
+        let path = self.resolve(key)?;
+        let mut file = match fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(AppError::Internal(format!("读取文件失败: {}", e))),
+        };
+        file.seek(SeekFrom::Start(start))
+            .await
+            .map_err(|e| AppError::Internal(format!("读取文件失败: {}", e)))?;
+
+        Ok(Some(StoredObject {
+            size: len,
+            stream: ReaderStream::new(file.take(len)).boxed(),
+        }))
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<StoredObjectInfo>, AppError> {
+        let path = self.resolve(key)?;
+        match fs::metadata(&path).await {
+            Ok(metadata) if metadata.is_file() => Ok(Some(StoredObjectInfo {
+                key: key.to_string(),
+                size: metadata.len(),
+                last_modified: modified_time(&metadata),
+            })),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(AppError::Internal(format!("读取文件信息失败: {}", e))),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        let path = self.resolve(key)?;
+        match fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::Internal(format!("删除文件失败: {}", e))),
+        }
+    }
+
+    // 列出前缀（目录）下的文件，包括子目录中的文件，与对象存储按前缀列出的结果一致
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObjectInfo>, AppError> {
+        let prefix = prefix.trim_end_matches('/');
+        let mut objects = Vec::new();
+        let mut pending_dirs = vec![prefix.to_string()];
+        while let Some(dir_key) = pending_dirs.pop() {
+            let dir = self.resolve(&dir_key)?;
+            let mut entries = match fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(AppError::Internal(format!("读取存储目录失败: {}", e))),
+            };
+
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|e| AppError::Internal(format!("读取存储目录失败: {}", e)))?
+            {
+                let Ok(metadata) = entry.metadata().await else {
+                    continue;
+                };
+                let Some(file_name) = entry.file_name().to_str().map(ToOwned::to_owned) else {
+                    continue;
+                };
+                let key = format!("{dir_key}/{file_name}");
+                if metadata.is_dir() {
+                    pending_dirs.push(key);
+                } else if metadata.is_file() {
+                    objects.push(StoredObjectInfo {
+                        key,
+                        size: metadata.len(),
+                        last_modified: modified_time(&metadata),
+                    });
+                }
+            }
+        }
+        Ok(objects)
+    }
+
+    async fn presign(
+        &self,
+        _key: &str,
+        _expires_in: Duration,
+        _content_disposition: Option<&str>,
+    ) -> Result<Option<String>, AppError> {
+        Ok(None)
+    }
+
+    fn local_path(&self, key: &str) -> Option<PathBuf> {
+        self.resolve(key).ok()
+    }
+}
+
+fn modified_time(metadata: &std::fs::Metadata) -> NaiveDateTime {
+    metadata
+        .modified()
+        .map(|time| DateTime::<Local>::from(time).naive_local())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn local_file_store_round_trips_objects() {
+        let root = std::env::temp_dir().join(format!("file_store_{}", Uuid::new_v4()));
+        let store = LocalFileStore::new(&root);
+
+        store
+            .put("apk/demo.apk", Bytes::from_static(b"demo"))
+            .await
+            .unwrap();
+        let head = store.head("apk/demo.apk").await.unwrap().unwrap();
+        let data = read_all(&store, "apk/demo.apk").await.unwrap();
+        let mut range = store
+            .get_range("apk/demo.apk", 1, 2)
+            .await
+            .unwrap()
+            .unwrap();
+        let range_data = range.stream.next().await.unwrap().unwrap();
+        let listed = store.list("apk/").await.unwrap();
+        store.delete("apk/demo.apk").await.unwrap();
+        let deleted = store.head("apk/demo.apk").await.unwrap();
+        let traversal = store.get("apk/../../etc/passwd").await;
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(head.size, 4);
+        assert_eq!(data.as_deref(), Some(b"demo".as_slice()));
+        assert_eq!(range.size, 2);
+        assert_eq!(range_data.as_ref(), b"em");
+        assert_eq!(
+            listed
+                .iter()
+                .map(|object| object.key.as_str())
+                .collect::<Vec<_>>(),
+            vec!["apk/demo.apk"]
+        );
+        assert!(deleted.is_none());
+        assert!(matches!(traversal, Err(AppError::FORBIDDEN(_))));
+    }
+}
@@ -1,13 +1,71 @@
 mod captcha_store;
+mod file_store;
+mod nonce_store;
+mod s3_file_store;
 mod token_store;
 
 pub use captcha_store::{CaptchaStore, PostgresCaptchaStore};
-pub use token_store::{PostgresTokenStore, TokenStore};
+pub use file_store::{
+    FileStore, LOCAL_TEMP_PREFIX, LocalFileStore, StoredObject, StoredObjectInfo, fetch_to_local,
+    read_all,
+};
+pub use nonce_store::{NonceStore, PostgresNonceStore};
+pub use s3_file_store::S3FileStore;
+pub use token_store::{PostgresTokenStore, REFRESH_TOKEN_REUSE_GRACE_SECS, TokenStore};
 
 use crate::model::error::AppError;
+use dotenvy::dotenv;
 use salvo::Depot;
+use std::env;
 use std::sync::Arc;
 
+// 本地存储默认根目录
+const DEFAULT_LOCAL_STORAGE_DIR: &str = "app_manage";
+const DEFAULT_S3_REGION: &str = "us-east-1";
+
+// 按环境变量创建文件存储
+// - `STORAGE_BACKEND`：`local`（默认）或 `s3`
+// - `STORAGE_LOCAL_DIR`：本地存储根目录，默认 `app_manage`
+// - `S3_ENDPOINT`、`S3_BUCKET`、`S3_ACCESS_KEY_ID`、`S3_SECRET_ACCESS_KEY`：S3 兼容存储配置
+// - `S3_REGION`：默认 `us-east-1`
+pub fn create_file_store() -> Arc<dyn FileStore> {
+    dotenv().ok();
+
+    let backend = env::var("STORAGE_BACKEND")
+        .map(|value| value.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    match backend.as_str() {
+        "" | "local" => {
+            let root = env::var("STORAGE_LOCAL_DIR")
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_LOCAL_STORAGE_DIR.to_string());
+            Arc::new(LocalFileStore::new(root))
+        }
+        "s3" => {
+            let endpoint = env::var("S3_ENDPOINT").expect("S3_ENDPOINT must be set");
+            let bucket = env::var("S3_BUCKET").expect("S3_BUCKET must be set");
+            let region = env::var("S3_REGION")
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_S3_REGION.to_string());
+            let access_key_id = env::var("S3_ACCESS_KEY_ID").expect("S3_ACCESS_KEY_ID must be set");
+            let secret_access_key =
+                env::var("S3_SECRET_ACCESS_KEY").expect("S3_SECRET_ACCESS_KEY must be set");
+            let store = S3FileStore::new(
+                &endpoint,
+                &bucket,
+                &region,
+                &access_key_id,
+                &secret_access_key,
+            )
+            .expect("Failed to create S3 file store");
+            Arc::new(store)
+        }
+        other => panic!("unsupported STORAGE_BACKEND: {other}"),
+    }
+}
+
 pub fn get_captcha_store(depot: &mut Depot) -> Result<Arc<dyn CaptchaStore>, AppError> {
     depot
         .obtain::<Arc<dyn CaptchaStore>>()
@@ -21,3 +79,17 @@ pub fn get_token_store(depot: &mut Depot) -> Result<Arc<dyn TokenStore>, AppErro
         .cloned()
         .map_err(|_| AppError::Internal("Token存储未初始化".to_string()))
 }
+
+pub fn get_nonce_store(depot: &mut Depot) -> Result<Arc<dyn NonceStore>, AppError> {
+    depot
+        .obtain::<Arc<dyn NonceStore>>()
+        .cloned()
+        .map_err(|_| AppError::Internal("请求随机数存储未初始化".to_string()))
+}
+
+pub fn get_file_store(depot: &mut Depot) -> Result<Arc<dyn FileStore>, AppError> {
+    depot
+        .obtain::<Arc<dyn FileStore>>()
+        .cloned()
+        .map_err(|_| AppError::Internal("文件存储未初始化".to_string()))
+}
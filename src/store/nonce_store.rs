@@ -0,0 +1,71 @@
+use crate::db::DbPool;
+use crate::model::error::AppError;
+use crate::schema::app_request_nonce;
+use chrono::{Duration, Local};
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, PooledConnection};
+use salvo::prelude::async_trait;
+use std::sync::Arc;
+
+#[async_trait]
+pub trait NonceStore: Send + Sync {
+    // 记录请求随机数，同一 app_key 下随机数在有效期内已使用过时返回 false
+    async fn try_use(&self, app_key: &str, nonce: &str, ttl: Duration) -> Result<bool, AppError>;
+
+    // 删除已过期的随机数，由后台任务定期调用，返回删除的数量
+    async fn purge_expired(&self) -> Result<usize, AppError>;
+}
+
+pub struct PostgresNonceStore {
+    pool: Arc<DbPool>,
+}
+
+impl PostgresNonceStore {
+    pub fn new(pool: Arc<DbPool>) -> Self {
+        Self { pool }
+    }
+
+    fn get_connection(
+        &self,
+    ) -> Result<PooledConnection<ConnectionManager<PgConnection>>, AppError> {
+        self.pool
+            .get()
+            .map_err(|e| AppError::Internal(format!("数据库连接失败: {}", e)))
+    }
+}
+
+#[async_trait]
+impl NonceStore for PostgresNonceStore {
+    async fn try_use(&self, app_key: &str, nonce: &str, ttl: Duration) -> Result<bool, AppError> {
+        let mut conn = self.get_connection()?;
+        let now = Local::now().naive_local();
+        // 过期记录由后台任务定期删除，删除前同一随机数已过期时视为未使用
+        let upsert = diesel::insert_into(app_request_nonce::table)
+            .values((
+                app_request_nonce::app_key.eq(app_key),
+                app_request_nonce::nonce.eq(nonce),
+                app_request_nonce::create_time.eq(now),
+                app_request_nonce::expires_at.eq(now + ttl),
+            ))
+            .on_conflict((app_request_nonce::app_key, app_request_nonce::nonce))
+            .do_update()
+            .set((
+                app_request_nonce::create_time.eq(now),
+                app_request_nonce::expires_at.eq(now + ttl),
+            ));
+        diesel::query_dsl::methods::FilterDsl::filter(upsert, app_request_nonce::expires_at.le(now))
+            .execute(&mut conn)
+            .map(|inserted| inserted > 0)
+            .map_err(|e| AppError::Internal(format!("保存请求随机数失败: {}", e)))
+    }
+
+    async fn purge_expired(&self) -> Result<usize, AppError> {
+        let mut conn = self.get_connection()?;
+        diesel::delete(
+            app_request_nonce::table
+                .filter(app_request_nonce::expires_at.le(Local::now().naive_local())),
+        )
+        .execute(&mut conn)
+        .map_err(|e| AppError::Internal(format!("清理过期请求随机数失败: {}", e)))
+    }
+}
@@ -1,14 +1,24 @@
 use crate::db::DbPool;
 use crate::model::error::AppError;
-use crate::model::jwt::TokenResp;
+use crate::model::jwt::{JWT_CONFIG, TokenResp};
+use crate::model::user_session::{
+    RefreshTokenStatus, SessionClient, UserSession, UserSessionRotatedToken,
+};
 use crate::model::users::User;
-use crate::schema::users;
+use crate::schema::{user_session, user_session_rotated_token, users};
+use crate::utils::file_digest_utils::sha256_hex;
+use chrono::{Duration, Local, NaiveDateTime};
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, PooledConnection};
 use salvo::prelude::async_trait;
 use std::sync::Arc;
 use uuid::Uuid;
 
+// 会话最后活跃时间的刷新间隔，避免每次请求都写数据库
+const LAST_SEEN_INTERVAL_SECS: i64 = 60;
+// 刷新Token轮换后的宽限期，期间再次提交旧Token按重复提交处理，不吊销会话
+pub const REFRESH_TOKEN_REUSE_GRACE_SECS: i64 = 10;
+
 enum TokenField {
     Access,
     Refresh,
@@ -16,30 +26,58 @@ enum TokenField {
 
 #[async_trait]
 pub trait TokenStore: Send + Sync {
-    async fn save_tokens(
+    // 登录时创建新会话，同一用户可以同时保持多个会话
+    async fn create_session(
         &self,
+        session_id: Uuid,
         user_id: Uuid,
-        access_token: String,
-        refresh_token: String,
+        tokens: TokenResp,
+        client: SessionClient,
     ) -> Result<TokenResp, AppError>;
 
+    // 用当前刷新Token换取新Token，旧刷新Token记入会话的已轮换记录；
+    // 当前刷新Token已被并发请求轮换时返回 None
+    async fn rotate_session_tokens(
+        &self,
+        session_id: Uuid,
+        refresh_token: &str,
+        tokens: TokenResp,
+    ) -> Result<Option<TokenResp>, AppError>;
+
     async fn find_user_by_id_and_username(
         &self,
         user_id: Uuid,
         username: &str,
     ) -> Result<Option<User>, AppError>;
 
+    // 访问Token属于用户未吊销的会话时返回 true，并刷新会话的最后活跃时间
     async fn access_token_matches(
         &self,
+        session_id: Uuid,
         user_id: Uuid,
         access_token: &str,
     ) -> Result<bool, AppError>;
 
-    async fn refresh_token_matches(
+    // 判断刷新Token是会话当前的Token、已轮换的旧Token还是无效Token
+    async fn refresh_token_status(
         &self,
+        session_id: Uuid,
         user_id: Uuid,
         refresh_token: &str,
-    ) -> Result<bool, AppError>;
+    ) -> Result<RefreshTokenStatus, AppError>;
+
+    // 查询用户未吊销且未过期的会话，最近活跃的在前
+    async fn list_sessions(&self, user_id: Uuid) -> Result<Vec<UserSession>, AppError>;
+
+    // 吊销用户的指定会话，会话不存在时返回 false
+    async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<bool, AppError>;
+
+    // 吊销用户的全部会话，可保留指定会话，返回吊销数量
+    async fn revoke_all_sessions(
+        &self,
+        user_id: Uuid,
+        keep_session_id: Option<Uuid>,
+    ) -> Result<usize, AppError>;
 }
 
 pub struct PostgresTokenStore {
@@ -58,55 +96,120 @@ impl PostgresTokenStore {
             .get()
             .map_err(|e| AppError::Internal(format!("数据库连接失败: {}", e)))
     }
+}
 
-    fn token_matches(&self, user_id: Uuid, token: &str, field: TokenField) -> Result<bool, AppError> {
-        let mut conn = self.get_connection()?;
+// 使用调用方的连接校验，后续查询或更新复用同一连接
+fn token_matches(
+    conn: &mut PgConnection,
+    session_id: Uuid,
+    user_id: Uuid,
+    token: &str,
+    field: TokenField,
+) -> Result<bool, AppError> {
+    let token_hash = hash_token(token);
 
-        let query = match field {
-            TokenField::Access => users::table
-                .filter(users::id.eq(user_id))
-                .filter(users::access_token.eq(token))
-                .into_boxed(),
-            TokenField::Refresh => users::table
-                .filter(users::id.eq(user_id))
-                .filter(users::refresh_token.eq(token))
-                .into_boxed(),
-        };
+    let query = user_session::table
+        .filter(user_session::id.eq(session_id))
+        .filter(user_session::user_id.eq(user_id))
+        .filter(user_session::is_delete.eq(false))
+        .filter(user_session::expires_at.gt(Local::now().naive_local()))
+        .into_boxed();
+    let query = match field {
+        TokenField::Access => query.filter(user_session::access_token_hash.eq(token_hash)),
+        TokenField::Refresh => query.filter(user_session::refresh_token_hash.eq(token_hash)),
+    };
 
-        query
-            .first::<User>(&mut conn)
-            .optional()
-            .map(|user| user.is_some())
-            .map_err(|e| AppError::Internal(format!("数据库查询错误: {}", e)))
-    }
+    query
+        .first::<UserSession>(conn)
+        .optional()
+        .map(|session| session.is_some())
+        .map_err(|e| AppError::Internal(format!("数据库查询错误: {}", e)))
 }
 
 #[async_trait]
 impl TokenStore for PostgresTokenStore {
-    async fn save_tokens(
+    async fn create_session(
         &self,
+        session_id: Uuid,
         user_id: Uuid,
-        access_token: String,
-        refresh_token: String,
+        tokens: TokenResp,
+        client: SessionClient,
     ) -> Result<TokenResp, AppError> {
         let mut conn = self.get_connection()?;
+        let now = Local::now().naive_local();
 
-        let result = diesel::update(users::table.find(user_id))
+        // 顺带清理该用户已过期的会话
+        diesel::delete(
+            user_session::table
+                .filter(user_session::user_id.eq(user_id))
+                .filter(user_session::expires_at.le(now)),
+        )
+        .execute(&mut conn)
+        .map_err(|e| AppError::Internal(format!("清理过期会话失败: {}", e)))?;
+
+        let session = UserSession {
+            id: session_id,
+            user_id,
+            access_token_hash: hash_token(&tokens.access_token),
+            refresh_token_hash: hash_token(&tokens.refresh_token),
+            user_agent: client.user_agent,
+            ip_address: client.ip_address,
+            create_time: now,
+            last_seen_time: now,
+            expires_at: now + Duration::seconds(JWT_CONFIG.refresh_expires_in),
+            is_delete: false,
+        };
+        diesel::insert_into(user_session::table)
+            .values(&session)
+            .execute(&mut conn)
+            .map_err(|e| AppError::Internal(format!("保存会话失败,请重试！'{}'", e)))?;
+
+        Ok(tokens)
+    }
+
+    async fn rotate_session_tokens(
+        &self,
+        session_id: Uuid,
+        refresh_token: &str,
+        tokens: TokenResp,
+    ) -> Result<Option<TokenResp>, AppError> {
+        let mut conn = self.get_connection()?;
+        let now = Local::now().naive_local();
+        let old_token_hash = hash_token(refresh_token);
+
+        let rotated = conn.transaction::<bool, diesel::result::Error, _>(|conn| {
+            // 只有刷新Token仍是当前Token时才替换，并发重放的请求会落空
+            let affected_rows = diesel::update(
+                user_session::table
+                    .filter(user_session::id.eq(session_id))
+                    .filter(user_session::refresh_token_hash.eq(&old_token_hash))
+                    .filter(user_session::is_delete.eq(false)),
+            )
             .set((
-                users::access_token.eq(&access_token),
-                users::refresh_token.eq(&refresh_token),
+                user_session::access_token_hash.eq(hash_token(&tokens.access_token)),
+                user_session::refresh_token_hash.eq(hash_token(&tokens.refresh_token)),
+                user_session::last_seen_time.eq(now),
+                user_session::expires_at.eq(now + Duration::seconds(JWT_CONFIG.refresh_expires_in)),
             ))
-            .execute(&mut conn);
-
-        match result {
-            Ok(affected_rows) if affected_rows > 0 => Ok(TokenResp {
-                access_token,
-                refresh_token,
-            }),
-            Ok(_) => Err(AppError::Internal(format!(
-                "未找到对应的user_id{}",
-                user_id
-            ))),
+            .execute(conn)?;
+            if affected_rows == 0 {
+                return Ok(false);
+            }
+
+            diesel::insert_into(user_session_rotated_token::table)
+                .values(&UserSessionRotatedToken {
+                    token_hash: old_token_hash.clone(),
+                    session_id,
+                    rotated_time: now,
+                })
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+            Ok(true)
+        });
+
+        match rotated {
+            Ok(true) => Ok(Some(tokens)),
+            Ok(false) => Ok(None),
             Err(e) => Err(AppError::Internal(format!(
                 "更新保存Token失败,请重试！'{}'",
                 e
@@ -131,17 +234,166 @@ impl TokenStore for PostgresTokenStore {
 
     async fn access_token_matches(
         &self,
+        session_id: Uuid,
         user_id: Uuid,
         access_token: &str,
     ) -> Result<bool, AppError> {
-        self.token_matches(user_id, access_token, TokenField::Access)
+        let mut conn = self.get_connection()?;
+        if !token_matches(
+            &mut conn,
+            session_id,
+            user_id,
+            access_token,
+            TokenField::Access,
+        )? {
+            return Ok(false);
+        }
+
+        let now = Local::now().naive_local();
+        diesel::update(
+            user_session::table
+                .filter(user_session::id.eq(session_id))
+                .filter(
+                    user_session::last_seen_time
+                        .lt(now - Duration::seconds(LAST_SEEN_INTERVAL_SECS)),
+                ),
+        )
+        .set(user_session::last_seen_time.eq(now))
+        .execute(&mut conn)
+        .map_err(|e| AppError::Internal(format!("更新会话活跃时间失败: {}", e)))?;
+
+        Ok(true)
     }
 
-    async fn refresh_token_matches(
+    async fn refresh_token_status(
         &self,
+        session_id: Uuid,
         user_id: Uuid,
         refresh_token: &str,
-    ) -> Result<bool, AppError> {
-        self.token_matches(user_id, refresh_token, TokenField::Refresh)
+    ) -> Result<RefreshTokenStatus, AppError> {
+        let mut conn = self.get_connection()?;
+        if token_matches(
+            &mut conn,
+            session_id,
+            user_id,
+            refresh_token,
+            TokenField::Refresh,
+        )? {
+            return Ok(RefreshTokenStatus::Current);
+        }
+
+        // 已吊销的会话同样检查，便于持续记录被盗Token的重放
+        let rotated_time = user_session_rotated_token::table
+            .inner_join(user_session::table)
+            .filter(user_session_rotated_token::token_hash.eq(hash_token(refresh_token)))
+            .filter(user_session_rotated_token::session_id.eq(session_id))
+            .filter(user_session::user_id.eq(user_id))
+            .select(user_session_rotated_token::rotated_time)
+            .first::<NaiveDateTime>(&mut conn)
+            .optional()
+            .map_err(|e| AppError::Internal(format!("数据库查询错误: {}", e)))?;
+
+        Ok(rotated_token_status(
+            rotated_time,
+            Local::now().naive_local(),
+        ))
+    }
+
+    async fn list_sessions(&self, user_id: Uuid) -> Result<Vec<UserSession>, AppError> {
+        let mut conn = self.get_connection()?;
+
+        user_session::table
+            .filter(user_session::user_id.eq(user_id))
+            .filter(user_session::is_delete.eq(false))
+            .filter(user_session::expires_at.gt(Local::now().naive_local()))
+            .order(user_session::last_seen_time.desc())
+            .load::<UserSession>(&mut conn)
+            .map_err(|e| AppError::Internal(format!("查询会话失败: {}", e)))
+    }
+
+    async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<bool, AppError> {
+        let mut conn = self.get_connection()?;
+
+        diesel::update(
+            user_session::table
+                .filter(user_session::id.eq(session_id))
+                .filter(user_session::user_id.eq(user_id))
+                .filter(user_session::is_delete.eq(false)),
+        )
+        .set(user_session::is_delete.eq(true))
+        .execute(&mut conn)
+        .map(|affected_rows| affected_rows > 0)
+        .map_err(|e| AppError::Internal(format!("吊销会话失败: {}", e)))
+    }
+
+    async fn revoke_all_sessions(
+        &self,
+        user_id: Uuid,
+        keep_session_id: Option<Uuid>,
+    ) -> Result<usize, AppError> {
+        let mut conn = self.get_connection()?;
+
+        // 不保留会话时使用空ID，不会匹配任何会话
+        let keep_session_id = keep_session_id.unwrap_or_else(Uuid::nil);
+        diesel::update(
+            user_session::table
+                .filter(user_session::user_id.eq(user_id))
+                .filter(user_session::id.ne(keep_session_id))
+                .filter(user_session::is_delete.eq(false)),
+        )
+        .set(user_session::is_delete.eq(true))
+        .execute(&mut conn)
+        .map_err(|e| AppError::Internal(format!("吊销会话失败: {}", e)))
+    }
+}
+
+// 不是当前Token时，按轮换时间区分宽限期内的重复提交和超过宽限期的重放
+fn rotated_token_status(
+    rotated_time: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+) -> RefreshTokenStatus {
+    match rotated_time {
+        Some(rotated_time)
+            if now - rotated_time <= Duration::seconds(REFRESH_TOKEN_REUSE_GRACE_SECS) =>
+        {
+            RefreshTokenStatus::RecentlyRotated
+        }
+        Some(_) => RefreshTokenStatus::Rotated,
+        None => RefreshTokenStatus::Unknown,
+    }
+}
+
+// 会话只保存 Token 的 SHA-256 哈希
+fn hash_token(token: &str) -> String {
+    sha256_hex(token.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotated_token_within_grace_window_is_not_treated_as_reuse() {
+        let now = Local::now().naive_local();
+
+        assert_eq!(
+            rotated_token_status(Some(now - Duration::seconds(1)), now),
+            RefreshTokenStatus::RecentlyRotated
+        );
+        assert_eq!(
+            rotated_token_status(
+                Some(now - Duration::seconds(REFRESH_TOKEN_REUSE_GRACE_SECS)),
+                now
+            ),
+            RefreshTokenStatus::RecentlyRotated
+        );
+        assert_eq!(
+            rotated_token_status(
+                Some(now - Duration::seconds(REFRESH_TOKEN_REUSE_GRACE_SECS + 1)),
+                now
+            ),
+            RefreshTokenStatus::Rotated
+        );
+        assert_eq!(rotated_token_status(None, now), RefreshTokenStatus::Unknown);
     }
 }
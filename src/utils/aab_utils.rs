@@ -0,0 +1,765 @@
+use crate::model::app_manage::ManifestComponents;
+use crate::utils::apk_signature_utils::{extract_signer_info, is_v1_signature_file};
+use crate::utils::apk_utils::{
+    ApkMetadata, component_names, fallback_app_name, fallback_image_entry, is_image_entry,
+    is_true_attribute, sorted_unique, target_sdk_version, to_apk_icon,
+};
+use anyhow::{Context, Result, anyhow, bail};
+use apk_info::ZipEntry;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const AAB_EXTENSION: &str = "aab";
+// AAB 中基础模块的文件路径，清单和资源表均为 aapt2 的 protobuf 格式
+const BASE_MODULE_DIR: &str = "base/";
+const BASE_MANIFEST_ENTRY: &str = "base/manifest/AndroidManifest.xml";
+const BASE_RESOURCES_ENTRY: &str = "base/resources.pb";
+// 资源引用最多解析的层数，避免循环引用
+const MAX_REFERENCE_DEPTH: usize = 8;
+// anydpi / nodpi 的 density 取值
+const DENSITY_ANY: u32 = 0xfffe;
+const DENSITY_NONE: u32 = 0xffff;
+const DENSITY_DEFAULT: u32 = 160;
+
+const WIRE_TYPE_VARINT: u64 = 0;
+const WIRE_TYPE_FIXED64: u64 = 1;
+const WIRE_TYPE_LENGTH_DELIMITED: u64 = 2;
+const WIRE_TYPE_FIXED32: u64 = 5;
+
+// protobuf 字段值，只区分解析清单和资源表所需的类型
+enum ProtoValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+// 清单 XML 元素（aapt2 Resources.proto 中的 XmlElement）
+#[derive(Debug, Default)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<XmlAttribute>,
+    children: Vec<XmlElement>,
+}
+
+#[derive(Debug, Default)]
+struct XmlAttribute {
+    name: String,
+    value: String,
+    resource_id: Option<u32>,
+}
+
+// 资源表中某个配置下的资源值
+#[derive(Debug)]
+struct ResourceValue {
+    locale: String,
+    density: u32,
+    item: ResourceItem,
+}
+
+#[derive(Debug)]
+enum ResourceItem {
+    Reference(u32),
+    String(String),
+    File(String),
+    Other,
+}
+
+impl XmlElement {
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    fn attribute(&self, name: &str) -> Option<&XmlAttribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+    }
+
+    fn attribute_value(&self, name: &str) -> Option<&str> {
+        self.attribute(name)
+            .map(|attribute| attribute.value.trim())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+// 判断上传文件是否为 Android App Bundle
+pub fn is_app_bundle_file(file_name: &str) -> bool {
+    Path::new(file_name)
+        .extension()
+        .and_then(|value| value.to_str())
+        .is_some_and(|value| value.eq_ignore_ascii_case(AAB_EXTENSION))
+}
+
+// 提取 AAB 元数据，解析基础模块的 protobuf 清单和资源表
+pub fn extract_aab_metadata(aab_path: &Path, file_name: &str) -> Result<ApkMetadata> {
+    // 文件内容直接交给 ZIP 解析，签名块另外从文件读取，内存中只保留一份 AAB
+    let aab_data =
+        fs::read(aab_path).with_context(|| format!("读取 AAB 文件失败: {}", aab_path.display()))?;
+    let file_size = aab_data.len() as u64;
+    let bundle = ZipEntry::new(aab_data).map_err(|e| anyhow!("解析 AAB 文件失败: {:?}", e))?;
+
+    let (manifest_data, _) = bundle
+        .read(BASE_MANIFEST_ENTRY)
+        .map_err(|e| anyhow!("AAB 缺少基础模块清单 {BASE_MANIFEST_ENTRY}: {:?}", e))?;
+    let manifest = parse_xml_node(&manifest_data)
+        .context("解析 AAB 清单失败")?
+        .filter(|element| element.name == "manifest")
+        .ok_or_else(|| anyhow!("AAB 清单缺少 manifest 元素"))?;
+    let resources = match bundle.read(BASE_RESOURCES_ENTRY) {
+        Ok((data, _)) => parse_resource_table(&data).context("解析 AAB 资源表失败")?,
+        Err(_) => HashMap::new(),
+    };
+
+    let package_name = manifest
+        .attribute_value("package")
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| anyhow!("AAB 缺少有效的包名"))?;
+    let (min_sdk_version, target_sdk_version) = sdk_versions(&manifest);
+    let permissions = declared_names(&manifest, &["uses-permission", "uses-permission-sdk-23"]);
+    let features = declared_names(&manifest, &["uses-feature"]);
+
+    let application = manifest.children("application").next();
+    let app_name = application
+        .and_then(|element| element.attribute("label"))
+        .and_then(|attribute| resolve_string_attribute(attribute, &resources))
+        .unwrap_or_else(|| fallback_app_name(aab_path, file_name));
+    let icon_entry = application
+        .and_then(|element| {
+            element
+                .attribute("icon")
+                .or_else(|| element.attribute("roundIcon"))
+        })
+        .and_then(|attribute| attribute.resource_id)
+        .and_then(|resource_id| resolve_icon_entry(&bundle, &resources, resource_id));
+    let app_icon = match icon_entry {
+        Some(icon_entry) => {
+            let (bytes, _) = bundle
+                .read(&icon_entry)
+                .map_err(|e| anyhow!("读取 AAB 图标资源失败 {icon_entry}: {:?}", e))?;
+            to_apk_icon(bytes, &icon_entry)
+        }
+        None => None,
+    };
+
+    let application_attribute = |name: &str| {
+        application
+            .and_then(|element| element.attribute_value(name))
+            .map(ToOwned::to_owned)
+    };
+    let is_debuggable = is_true_attribute(application_attribute("debuggable"));
+    let is_test_only = is_true_attribute(application_attribute("testOnly"));
+    let component_names_of = |tags: &[&str]| {
+        let names = tags
+            .iter()
+            .flat_map(|tag| {
+                application
+                    .into_iter()
+                    .flat_map(move |element| element.children(tag))
+            })
+            .map(|element| element.attribute_value("name"));
+        component_names(&package_name, names)
+    };
+    let components = ManifestComponents {
+        activities: component_names_of(&["activity", "activity-alias"]),
+        services: component_names_of(&["service"]),
+        receivers: component_names_of(&["receiver"]),
+        providers: component_names_of(&["provider"]),
+    };
+
+    // 各模块的原生库位于 `{module}/lib/{abi}/`
+    let native_abis = sorted_unique(bundle.namelist().filter_map(|entry| {
+        let mut parts = entry.split('/');
+        let (_, lib, abi) = (parts.next()?, parts.next()?, parts.next()?);
+        (lib == "lib" && parts.next().is_some()).then_some(abi)
+    }));
+
+    // AAB 只支持 JAR 签名，签名证书通常为上传密钥
+    let mut v1_signature_entries: Vec<&str> = bundle
+        .namelist()
+        .filter(|entry| is_v1_signature_file(entry))
+        .collect();
+    v1_signature_entries.sort();
+    let v1_signature_files = v1_signature_entries
+        .into_iter()
+        .map(|entry| {
+            bundle
+                .read(entry)
+                .map(|(bytes, _)| bytes)
+                .map_err(|e| anyhow!("读取 v1 签名文件失败 {entry}: {:?}", e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let signer_info = extract_signer_info(aab_path, &v1_signature_files)?;
+
+    Ok(ApkMetadata {
+        file_name: file_name.to_string(),
+        app_name,
+        package_name: package_name.clone(),
+        app_icon,
+        version_name: manifest
+            .attribute_value("versionName")
+            .map(ToOwned::to_owned),
+        version_code: manifest
+            .attribute_value("versionCode")
+            .map(ToOwned::to_owned),
+        file_size,
+        min_sdk_version,
+        native_abis,
+        signer_sha256: signer_info.signer_sha256,
+        signature_schemes: signer_info.signature_schemes,
+        target_sdk_version,
+        permissions,
+        features,
+        is_debuggable,
+        is_test_only,
+        components,
+        split_name: None,
+    })
+}
+
+// 读取 uses-sdk 中的 minSdkVersion 和 targetSdkVersion
+fn sdk_versions(manifest: &XmlElement) -> (Option<i32>, Option<i32>) {
+    let uses_sdk = manifest.children("uses-sdk").next();
+    let min_sdk_version = uses_sdk
+        .and_then(|element| element.attribute_value("minSdkVersion"))
+        .and_then(|value| value.parse::<i32>().ok());
+    let target_sdk_version = target_sdk_version(
+        uses_sdk.and_then(|element| element.attribute_value("targetSdkVersion")),
+        min_sdk_version,
+    );
+    (min_sdk_version, target_sdk_version)
+}
+
+// 清单中指定元素声明的名称（权限、特性等），去重排序
+fn declared_names(manifest: &XmlElement, tags: &[&str]) -> Vec<String> {
+    sorted_unique(
+        tags.iter()
+            .flat_map(|tag| manifest.children(tag))
+            .filter_map(|element| element.attribute_value("name")),
+    )
+}
+
+// 解析字符串属性，引用资源时取默认语言下的值
+fn resolve_string_attribute(
+    attribute: &XmlAttribute,
+    resources: &HashMap<u32, Vec<ResourceValue>>,
+) -> Option<String> {
+    let value = match attribute.resource_id {
+        Some(resource_id) => resolve_string(resources, resource_id, 0)?,
+        None => attribute.value.clone(),
+    };
+    Some(value.trim().to_string()).filter(|value| !value.is_empty() && !value.starts_with('@'))
+}
+
+fn resolve_string(
+    resources: &HashMap<u32, Vec<ResourceValue>>,
+    resource_id: u32,
+    depth: usize,
+) -> Option<String> {
+    let values = resources.get(&resource_id)?;
+    let value = values
+        .iter()
+        .find(|value| value.locale.is_empty())
+        .or_else(|| values.first())?;
+    match &value.item {
+        ResourceItem::String(value) => Some(value.clone()),
+        ResourceItem::Reference(resource_id) if depth < MAX_REFERENCE_DEPTH => {
+            resolve_string(resources, *resource_id, depth + 1)
+        }
+        _ => None,
+    }
+}
+
+// 解析图标资源对应的文件，优先选择密度最高的图片；只有自适应图标 XML 时按名称查找同名图片
+fn resolve_icon_entry(
+    bundle: &ZipEntry,
+    resources: &HashMap<u32, Vec<ResourceValue>>,
+    resource_id: u32,
+) -> Option<String> {
+    let mut files = Vec::new();
+    collect_resource_files(resources, resource_id, 0, &mut files);
+
+    let best_image = files
+        .iter()
+        .filter(|(path, _)| is_image_entry(path))
+        .max_by_key(|(_, density)| match *density {
+            DENSITY_ANY | DENSITY_NONE => 0,
+            0 => DENSITY_DEFAULT,
+            density => density,
+        })
+        .map(|(path, _)| path.clone());
+    let entry = best_image.or_else(|| {
+        let (path, _) = files.first()?;
+        let module_entries = bundle
+            .namelist()
+            .filter_map(|entry| entry.strip_prefix(BASE_MODULE_DIR));
+        fallback_image_entry(module_entries, path)
+    })?;
+
+    Some(format!(
+        "{BASE_MODULE_DIR}{}",
+        entry.trim_start_matches('/')
+    ))
+}
+
+fn collect_resource_files(
+    resources: &HashMap<u32, Vec<ResourceValue>>,
+    resource_id: u32,
+    depth: usize,
+    files: &mut Vec<(String, u32)>,
+) {
+    let Some(values) = resources.get(&resource_id) else {
+        return;
+    };
+    for value in values {
+        match &value.item {
+            ResourceItem::File(path) => files.push((path.clone(), value.density)),
+            ResourceItem::Reference(resource_id) if depth < MAX_REFERENCE_DEPTH => {
+                collect_resource_files(resources, *resource_id, depth + 1, files)
+            }
+            _ => {}
+        }
+    }
+}
+
+// 解析 XmlNode { element = 1, text = 2 }，文本节点返回 None
+fn parse_xml_node(data: &[u8]) -> Result<Option<XmlElement>> {
+    for (field, value) in read_proto_fields(data)? {
+        if let (1, ProtoValue::Bytes(element)) = (field, value) {
+            return parse_xml_element(element).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+// 解析 XmlElement { name = 3, attribute = 4, child = 5 }
+fn parse_xml_element(data: &[u8]) -> Result<XmlElement> {
+    let mut element = XmlElement::default();
+    for (field, value) in read_proto_fields(data)? {
+        match (field, value) {
+            (3, ProtoValue::Bytes(name)) => element.name = proto_string(name)?,
+            (4, ProtoValue::Bytes(attribute)) => {
+                element.attributes.push(parse_xml_attribute(attribute)?)
+            }
+            (5, ProtoValue::Bytes(child)) => {
+                if let Some(child) = parse_xml_node(child)? {
+                    element.children.push(child);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(element)
+}
+
+// 解析 XmlAttribute { name = 2, value = 3, compiled_item = 6 }
+// value 为空时取编译后的基本类型值，如 versionCode 的整数和 debuggable 的布尔值
+fn parse_xml_attribute(data: &[u8]) -> Result<XmlAttribute> {
+    let mut attribute = XmlAttribute::default();
+    let mut compiled_value = None;
+    for (field, value) in read_proto_fields(data)? {
+        match (field, value) {
+            (2, ProtoValue::Bytes(name)) => attribute.name = proto_string(name)?,
+            (3, ProtoValue::Bytes(value)) => attribute.value = proto_string(value)?,
+            (6, ProtoValue::Bytes(item)) => match parse_item(item)? {
+                ResourceItem::Reference(resource_id) => attribute.resource_id = Some(resource_id),
+                ResourceItem::String(value) => compiled_value = Some(value),
+                _ => compiled_value = parse_primitive_item(item)?,
+            },
+            _ => {}
+        }
+    }
+    if attribute.value.trim().is_empty()
+        && let Some(value) = compiled_value
+    {
+        attribute.value = value;
+    }
+    Ok(attribute)
+}
+
+// 解析 Item { ref = 1, str = 2, raw_str = 3, file = 5 }
+fn parse_item(data: &[u8]) -> Result<ResourceItem> {
+    for (field, value) in read_proto_fields(data)? {
+        let ProtoValue::Bytes(message) = value else {
+            continue;
+        };
+        let item = match field {
+            1 => ResourceItem::Reference(
+                proto_varint_field(message, 2)?.ok_or_else(|| anyhow!("资源引用缺少 ID"))? as u32,
+            ),
+            2 | 3 => ResourceItem::String(proto_string_field(message, 1)?.unwrap_or_default()),
+            5 => ResourceItem::File(proto_string_field(message, 1)?.unwrap_or_default()),
+            _ => continue,
+        };
+        return Ok(item);
+    }
+    Ok(ResourceItem::Other)
+}
+
+// 解析 Item.prim（字段 7）中的 int_decimal = 6、int_hexadecimal = 7、boolean = 8
+fn parse_primitive_item(data: &[u8]) -> Result<Option<String>> {
+    for (field, value) in read_proto_fields(data)? {
+        let (7, ProtoValue::Bytes(primitive)) = (field, value) else {
+            continue;
+        };
+        for (field, value) in read_proto_fields(primitive)? {
+            match (field, value) {
+                (6, ProtoValue::Varint(value)) => return Ok(Some((value as i32).to_string())),
+                (7, ProtoValue::Varint(value)) => return Ok(Some(value.to_string())),
+                (8, ProtoValue::Varint(value)) => return Ok(Some((value != 0).to_string())),
+                _ => {}
+            }
+        }
+        // proto3 省略默认值，int 为 0 或 boolean 为 false 时没有字段
+        return Ok(None);
+    }
+    Ok(None)
+}
+
+// 解析资源表，返回资源 ID 到各配置下资源值的映射
+// 结构：ResourceTable { package = 2 } -> Package { package_id = 1, type = 3 }
+//      -> Type { type_id = 1, entry = 3 } -> Entry { entry_id = 1, config_value = 6 }
+//      -> ConfigValue { config = 1 { locale = 3, density = 18 }, value = 2 { item = 4 } }
+fn parse_resource_table(data: &[u8]) -> Result<HashMap<u32, Vec<ResourceValue>>> {
+    let mut resources = HashMap::new();
+    for package in proto_messages(data, 2)? {
+        let package_id = proto_id_field(package)?;
+        for resource_type in proto_messages(package, 3)? {
+            let type_id = proto_id_field(resource_type)?;
+            for entry in proto_messages(resource_type, 3)? {
+                let entry_id = proto_id_field(entry)?;
+                let resource_id = (package_id << 24) | (type_id << 16) | entry_id;
+                let mut values = Vec::new();
+                for config_value in proto_messages(entry, 6)? {
+                    let config = proto_messages(config_value, 1)?.into_iter().next();
+                    let item = proto_messages(config_value, 2)?
+                        .into_iter()
+                        .next()
+                        .map(|value| proto_messages(value, 4))
+                        .transpose()?
+                        .and_then(|items| items.into_iter().next());
+                    let Some(item) = item else {
+                        continue;
+                    };
+                    values.push(ResourceValue {
+                        locale: config
+                            .map(|config| proto_string_field(config, 3))
+                            .transpose()?
+                            .flatten()
+                            .unwrap_or_default(),
+                        density: config
+                            .map(|config| proto_varint_field(config, 18))
+                            .transpose()?
+                            .flatten()
+                            .unwrap_or_default() as u32,
+                        item: parse_item(item)?,
+                    });
+                }
+                resources.insert(resource_id, values);
+            }
+        }
+    }
+    Ok(resources)
+}
+
+// 读取 PackageId / TypeId / EntryId 消息（字段 1）中的 id（字段 1），proto3 省略时为 0
+fn proto_id_field(data: &[u8]) -> Result<u32> {
+    let id = match proto_messages(data, 1)?.into_iter().next() {
+        Some(id_message) => proto_varint_field(id_message, 1)?.unwrap_or_default(),
+        None => 0,
+    };
+    Ok(id as u32)
+}
+
+fn proto_messages(data: &[u8], field_number: u64) -> Result<Vec<&[u8]>> {
+    Ok(read_proto_fields(data)?
+        .into_iter()
+        .filter_map(|(field, value)| match value {
+            ProtoValue::Bytes(bytes) if field == field_number => Some(bytes),
+            _ => None,
+        })
+        .collect())
+}
+
+fn proto_varint_field(data: &[u8], field_number: u64) -> Result<Option<u64>> {
+    Ok(read_proto_fields(data)?
+        .into_iter()
+        .find_map(|(field, value)| match value {
+            ProtoValue::Varint(value) if field == field_number => Some(value),
+            _ => None,
+        }))
+}
+
+fn proto_string_field(data: &[u8], field_number: u64) -> Result<Option<String>> {
+    proto_messages(data, field_number)?
+        .into_iter()
+        .next()
+        .map(proto_string)
+        .transpose()
+}
+
+fn proto_string(data: &[u8]) -> Result<String> {
+    String::from_utf8(data.to_vec()).context("字符串不是有效的 UTF-8")
+}
+
+// 按 protobuf 线格式读取消息的全部字段
+fn read_proto_fields(mut data: &[u8]) -> Result<Vec<(u64, ProtoValue<'_>)>> {
+    let mut fields = Vec::new();
+    while !data.is_empty() {
+        let key = read_varint(&mut data)?;
+        let value = match key & 0x07 {
+            WIRE_TYPE_VARINT => ProtoValue::Varint(read_varint(&mut data)?),
+            WIRE_TYPE_FIXED64 => {
+                take_bytes(&mut data, 8)?;
+                ProtoValue::Fixed
+            }
+            WIRE_TYPE_LENGTH_DELIMITED => {
+                let len = read_varint(&mut data)? as usize;
+                ProtoValue::Bytes(take_bytes(&mut data, len)?)
+            }
+            WIRE_TYPE_FIXED32 => {
+                take_bytes(&mut data, 4)?;
+                ProtoValue::Fixed
+            }
+            wire_type => bail!("不支持的 protobuf 字段类型 {}", wire_type),
+        };
+        fields.push((key >> 3, value));
+    }
+    Ok(fields)
+}
+
+fn read_varint(data: &mut &[u8]) -> Result<u64> {
+    let mut value = 0u64;
+    for (index, byte) in data.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * index);
+        if byte & 0x80 == 0 {
+            *data = &data[index + 1..];
+            return Ok(value);
+        }
+    }
+    bail!("protobuf 数据被截断")
+}
+
+fn take_bytes<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    let bytes = data
+        .get(..len)
+        .ok_or_else(|| anyhow!("protobuf 数据被截断"))?;
+    *data = &data[len..];
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varint(mut value: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                out.push(byte);
+                return out;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    fn bytes_field(field: u64, data: &[u8]) -> Vec<u8> {
+        [
+            varint(field << 3 | 2),
+            varint(data.len() as u64),
+            data.to_vec(),
+        ]
+        .concat()
+    }
+
+    fn varint_field(field: u64, value: u64) -> Vec<u8> {
+        [varint(field << 3), varint(value)].concat()
+    }
+
+    fn attribute(name: &str, value: &str, compiled_item: &[u8]) -> Vec<u8> {
+        bytes_field(
+            4,
+            &[
+                bytes_field(2, name.as_bytes()),
+                bytes_field(3, value.as_bytes()),
+                bytes_field(6, compiled_item),
+            ]
+            .concat(),
+        )
+    }
+
+    #[test]
+    fn parse_xml_node_reads_attributes_and_children() {
+        let label_ref = bytes_field(1, &varint_field(2, 0x7f01_0000));
+        let version_code = bytes_field(7, &varint_field(6, 42));
+        let application = bytes_field(
+            1,
+            &[
+                bytes_field(3, b"application"),
+                attribute("label", "@string/app_name", &label_ref),
+            ]
+            .concat(),
+        );
+        let manifest = bytes_field(
+            1,
+            &[
+                bytes_field(3, b"manifest"),
+                attribute("package", "com.example.demo", &[]),
+                attribute("versionCode", "", &version_code),
+                bytes_field(5, &application),
+                bytes_field(5, &bytes_field(2, b"text")),
+            ]
+            .concat(),
+        );
+
+        let manifest = parse_xml_node(&manifest).unwrap().unwrap();
+        assert_eq!(
+            manifest.attribute_value("package"),
+            Some("com.example.demo")
+        );
+        assert_eq!(manifest.attribute_value("versionCode"), Some("42"));
+        let application = manifest.children("application").next().unwrap();
+        assert_eq!(
+            application.attribute("label").unwrap().resource_id,
+            Some(0x7f01_0000)
+        );
+
+        let mut truncated = bytes_field(1, &bytes_field(3, b"manifest"));
+        truncated.pop();
+        assert!(parse_xml_node(&truncated).is_err());
+    }
+
+    fn element(name: &str, attributes: &[(&str, &str)], children: &[Vec<u8>]) -> Vec<u8> {
+        let mut element = bytes_field(3, name.as_bytes());
+        for (name, value) in attributes {
+            element.extend(attribute(name, value, &[]));
+        }
+        for child in children {
+            element.extend(bytes_field(5, child));
+        }
+        bytes_field(1, &element)
+    }
+
+    #[test]
+    fn manifest_declarations_are_sorted_and_sdk_falls_back() {
+        let manifest = element(
+            "manifest",
+            &[("package", "com.example.demo")],
+            &[
+                element("uses-sdk", &[("minSdkVersion", "21")], &[]),
+                element(
+                    "uses-permission",
+                    &[("name", "android.permission.INTERNET")],
+                    &[],
+                ),
+                element(
+                    "uses-permission",
+                    &[("name", "android.permission.CAMERA")],
+                    &[],
+                ),
+                element(
+                    "uses-permission-sdk-23",
+                    &[("name", " android.permission.INTERNET ")],
+                    &[],
+                ),
+                element("uses-feature", &[("name", "android.hardware.camera")], &[]),
+                element("uses-feature", &[("name", "")], &[]),
+            ],
+        );
+        let manifest = parse_xml_node(&manifest).unwrap().unwrap();
+
+        assert_eq!(sdk_versions(&manifest), (Some(21), Some(21)));
+        assert_eq!(
+            declared_names(&manifest, &["uses-permission", "uses-permission-sdk-23"]),
+            vec![
+                "android.permission.CAMERA".to_string(),
+                "android.permission.INTERNET".to_string(),
+            ]
+        );
+        assert_eq!(
+            declared_names(&manifest, &["uses-feature"]),
+            vec!["android.hardware.camera".to_string()]
+        );
+
+        let manifest = element(
+            "manifest",
+            &[],
+            &[element(
+                "uses-sdk",
+                &[("minSdkVersion", "24"), ("targetSdkVersion", "34")],
+                &[],
+            )],
+        );
+        let manifest = parse_xml_node(&manifest).unwrap().unwrap();
+        assert_eq!(sdk_versions(&manifest), (Some(24), Some(34)));
+
+        let manifest = parse_xml_node(&element("manifest", &[], &[]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(sdk_versions(&manifest), (None, None));
+    }
+
+    #[test]
+    fn parse_resource_table_resolves_ids_and_configs() {
+        let config_value = |locale: &str, density: u64, item: Vec<u8>| {
+            let mut config = bytes_field(3, locale.as_bytes());
+            if density > 0 {
+                config.extend(varint_field(18, density));
+            }
+            bytes_field(
+                6,
+                &[
+                    bytes_field(1, &config),
+                    bytes_field(2, &bytes_field(4, &item)),
+                ]
+                .concat(),
+            )
+        };
+        let string_entry = bytes_field(
+            3,
+            &[
+                config_value("", 0, bytes_field(2, &bytes_field(1, "示例".as_bytes()))),
+                config_value("en", 0, bytes_field(2, &bytes_field(1, b"Demo"))),
+            ]
+            .concat(),
+        );
+        let icon_entry = bytes_field(
+            3,
+            &[
+                bytes_field(1, &varint_field(1, 1)),
+                config_value(
+                    "",
+                    480,
+                    bytes_field(5, &bytes_field(1, b"res/mipmap-xxhdpi/ic.png")),
+                ),
+            ]
+            .concat(),
+        );
+        let table = bytes_field(
+            2,
+            &[
+                bytes_field(1, &varint_field(1, 0x7f)),
+                bytes_field(
+                    3,
+                    &[bytes_field(1, &varint_field(1, 1)), string_entry].concat(),
+                ),
+                bytes_field(
+                    3,
+                    &[bytes_field(1, &varint_field(1, 2)), icon_entry].concat(),
+                ),
+            ]
+            .concat(),
+        );
+
+        let resources = parse_resource_table(&table).unwrap();
+        assert_eq!(
+            resolve_string(&resources, 0x7f01_0000, 0),
+            Some("示例".to_string())
+        );
+        let mut files = Vec::new();
+        collect_resource_files(&resources, 0x7f02_0001, 0, &mut files);
+        assert_eq!(files, vec![("res/mipmap-xxhdpi/ic.png".to_string(), 480)]);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::model::api_token::{ApiToken, ApiTokenScope};
+use crate::model::error::AppError;
+use crate::model::user_role::Permission;
+use crate::schema::api_token;
+use crate::utils::file_digest_utils::sha256_hex;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+/// API令牌明文前缀，鉴权中间件据此区分API令牌和JWT登录Token
+pub const API_TOKEN_PREFIX: &str = "aus_";
+
+// 列表中展示的令牌前缀长度
+const TOKEN_PREFIX_LEN: usize = 12;
+
+// 生成API令牌明文
+pub fn generate_api_token() -> String {
+    format!(
+        "{}{}{}",
+        API_TOKEN_PREFIX,
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+// 计算API令牌的 SHA-256 哈希，数据库只保存哈希
+pub fn hash_api_token(token: &str) -> String {
+    sha256_hex(token.as_bytes())
+}
+
+// 截取令牌前缀，用于在列表中辨认令牌
+pub fn api_token_prefix(token: &str) -> String {
+    token.chars().take(TOKEN_PREFIX_LEN).collect()
+}
+
+// 从 Authorization 请求头中取出API令牌，不是API令牌时返回 None
+pub fn bearer_api_token(authorization: Option<&str>) -> Option<&str> {
+    authorization
+        .and_then(|value| value.trim().strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| token.starts_with(API_TOKEN_PREFIX))
+}
+
+// 解析令牌权限范围，不允许为空、未知权限和用户管理权限
+pub fn parse_api_token_scopes(scopes: &[String]) -> Result<Vec<Permission>, AppError> {
+    let mut permissions = Vec::with_capacity(scopes.len());
+    for scope in scopes {
+        let permission = Permission::from_db(scope.trim())
+            .ok_or_else(|| AppError::BadRequest(format!("未知的令牌权限'{}'", scope)))?;
+        if permission == Permission::UserManage {
+            return Err(AppError::BadRequest(format!(
+                "API令牌不能授予'{}'权限",
+                permission.as_str()
+            )));
+        }
+        if !permissions.contains(&permission) {
+            permissions.push(permission);
+        }
+    }
+    if permissions.is_empty() {
+        return Err(AppError::BadRequest("令牌权限范围不能为空".to_string()));
+    }
+    Ok(permissions)
+}
+
+// 校验API令牌并记录最后使用时间，返回令牌记录
+pub fn authenticate_api_token(
+    conn: &mut PgConnection,
+    token: &str,
+    now: NaiveDateTime,
+) -> Result<ApiToken, AppError> {
+    let api_token = api_token::table
+        .filter(api_token::token_hash.eq(hash_api_token(token)))
+        .filter(api_token::is_delete.eq(false))
+        .first::<ApiToken>(conn)
+        .optional()
+        .map_err(|e| AppError::Internal(format!("查询API令牌失败: {}", e)))?
+        .ok_or_else(|| {
+            AppError::unauthorized_with_code("API令牌无效或已吊销", "API_TOKEN_INVALID")
+        })?;
+
+    if api_token
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= now)
+    {
+        return Err(AppError::unauthorized_with_code(
+            format!("API令牌'{}'已过期", api_token.token_name),
+            "API_TOKEN_EXPIRED",
+        ));
+    }
+
+    diesel::update(api_token::table.filter(api_token::id.eq(api_token.id)))
+        .set(api_token::last_used_time.eq(now))
+        .execute(conn)
+        .map_err(|e| AppError::Internal(format!("更新API令牌失败: {}", e)))?;
+
+    Ok(api_token)
+}
+
+// 将令牌记录转换为请求的权限范围
+pub fn api_token_scope(api_token: &ApiToken) -> ApiTokenScope {
+    ApiTokenScope {
+        token_id: api_token.id,
+        permissions: api_token
+            .scopes
+            .iter()
+            .filter_map(|scope| Permission::from_db(scope))
+            .collect(),
+        channel_ids: api_token.channel_ids.clone(),
+    }
+}
+
+// 使用限定渠道的API令牌时，校验渠道在令牌范围内
+pub fn ensure_api_token_channel(
+    scope: Option<&ApiTokenScope>,
+    channel_id: Uuid,
+) -> Result<(), AppError> {
+    match scope {
+        Some(scope) if !scope.allows_channel(channel_id) => Err(AppError::FORBIDDEN(format!(
+            "API令牌无权操作渠道'{}'",
+            channel_id
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// 使用限定渠道的API令牌时返回令牌范围内的渠道，读取列表时据此过滤；未限定渠道时为 None
+pub fn api_token_channel_ids(scope: Option<&ApiTokenScope>) -> Option<&[Uuid]> {
+    scope
+        .map(|scope| scope.channel_ids.as_slice())
+        .filter(|channel_ids| !channel_ids.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_token_helpers_parse_header_scopes_and_channels() {
+        let token = generate_api_token();
+        assert!(token.starts_with(API_TOKEN_PREFIX));
+        assert_eq!(token.len(), API_TOKEN_PREFIX.len() + 64);
+        assert_eq!(hash_api_token(&token).len(), 64);
+        assert_ne!(
+            hash_api_token(&token),
+            hash_api_token(&generate_api_token())
+        );
+        assert_eq!(api_token_prefix(&token), token[..TOKEN_PREFIX_LEN]);
+
+        let header = format!("Bearer {}", token);
+        assert_eq!(bearer_api_token(Some(&header)), Some(token.as_str()));
+        assert_eq!(bearer_api_token(Some("Bearer eyJhbGciOiJIUzI1NiJ9")), None);
+        assert_eq!(bearer_api_token(None), None);
+
+        assert_eq!(
+            parse_api_token_scopes(&["app:publish".to_string(), "app:publish".to_string()])
+                .unwrap(),
+            vec![Permission::AppPublish]
+        );
+        assert!(parse_api_token_scopes(&[]).is_err());
+        assert!(parse_api_token_scopes(&["app:delete".to_string()]).is_err());
+        assert!(parse_api_token_scopes(&["user:manage".to_string()]).is_err());
+
+        let channel_id = Uuid::new_v4();
+        let scope = ApiTokenScope {
+            token_id: Uuid::new_v4(),
+            permissions: vec![Permission::AppPublish],
+            channel_ids: vec![channel_id],
+        };
+        assert!(ensure_api_token_channel(Some(&scope), channel_id).is_ok());
+        assert!(matches!(
+            ensure_api_token_channel(Some(&scope), Uuid::new_v4()),
+            Err(AppError::FORBIDDEN(_))
+        ));
+        assert!(ensure_api_token_channel(None, Uuid::new_v4()).is_ok());
+        assert_eq!(
+            api_token_channel_ids(Some(&scope)),
+            Some([channel_id].as_slice())
+        );
+        let unlimited = ApiTokenScope {
+            channel_ids: Vec::new(),
+            ..scope
+        };
+        assert_eq!(api_token_channel_ids(Some(&unlimited)), None);
+        assert_eq!(api_token_channel_ids(None), None);
+    }
+}
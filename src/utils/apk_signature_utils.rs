@@ -0,0 +1,227 @@
+use crate::utils::apk_signing_block_utils::{
+    SIGNATURE_SCHEME_V2_BLOCK_ID, SIGNATURE_SCHEME_V3_BLOCK_ID, SIGNATURE_SCHEME_V31_BLOCK_ID,
+    read_signing_block_file,
+};
+use crate::utils::file_digest_utils::sha256_hex;
+use anyhow::{Context, Result, anyhow, bail};
+use std::path::Path;
+
+pub const SIGNATURE_SCHEME_V1: &str = "v1";
+pub const SIGNATURE_SCHEME_V2: &str = "v2";
+pub const SIGNATURE_SCHEME_V3: &str = "v3";
+pub const SIGNATURE_SCHEME_V31: &str = "v3.1";
+
+// v1 签名中 PKCS#7 签名文件的扩展名
+const V1_SIGNATURE_FILE_EXTENSIONS: &[&str] = &[".RSA", ".DSA", ".EC"];
+
+const DER_TAG_SEQUENCE: u8 = 0x30;
+const DER_TAG_CONTEXT_0: u8 = 0xa0;
+
+/// APK 签名信息
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ApkSignerInfo {
+    /// 签名证书 SHA-256 指纹（小写十六进制），未签名时为 None
+    pub signer_sha256: Option<String>,
+    /// APK 使用的签名方案，如 v1、v2、v3
+    pub signature_schemes: Vec<String>,
+}
+
+// 判断 ZIP 条目是否为 v1 签名的 PKCS#7 签名文件
+pub fn is_v1_signature_file(entry_name: &str) -> bool {
+    let upper = entry_name.to_ascii_uppercase();
+    upper
+        .strip_prefix("META-INF/")
+        .is_some_and(|name| !name.contains('/'))
+        && V1_SIGNATURE_FILE_EXTENSIONS
+            .iter()
+            .any(|extension| upper.ends_with(extension))
+}
+
+// 提取 APK 签名方案及签名证书指纹，优先使用 v3.1/v3 签名中的当前签名证书
+pub fn extract_signer_info(
+    apk_path: &Path,
+    v1_signature_files: &[Vec<u8>],
+) -> Result<ApkSignerInfo> {
+    let mut signature_schemes = Vec::new();
+    let mut certificates = Vec::new();
+
+    if let Some(signature_file) = v1_signature_files.first() {
+        signature_schemes.push(SIGNATURE_SCHEME_V1.to_string());
+        let certificate = read_pkcs7_certificate(signature_file).context("解析 v1 签名证书失败")?;
+        certificates.push(certificate.to_vec());
+    }
+
+    if let Some(block) = read_signing_block_file(apk_path)? {
+        for (block_id, scheme) in [
+            (SIGNATURE_SCHEME_V2_BLOCK_ID, SIGNATURE_SCHEME_V2),
+            (SIGNATURE_SCHEME_V3_BLOCK_ID, SIGNATURE_SCHEME_V3),
+            (SIGNATURE_SCHEME_V31_BLOCK_ID, SIGNATURE_SCHEME_V31),
+        ] {
+            let Some(value) = block.get(block_id) else {
+                continue;
+            };
+            signature_schemes.push(scheme.to_string());
+            let certificate = read_signing_block_certificate(value)
+                .with_context(|| format!("解析 {} 签名证书失败", scheme))?;
+            certificates.push(certificate.to_vec());
+        }
+    }
+
+    Ok(ApkSignerInfo {
+        signer_sha256: certificates
+            .last()
+            .map(|certificate| sha256_hex(certificate)),
+        signature_schemes,
+    })
+}
+
+// 读取 v2/v3 签名块中第一个签名者的第一张证书
+// 结构：signers -> signer -> signed data -> (digests, certificates)
+fn read_signing_block_certificate(value: &[u8]) -> Result<&[u8]> {
+    let mut signers = take_length_prefixed(&mut &value[..])?;
+    let mut signer = take_length_prefixed(&mut signers)?;
+    let mut signed_data = take_length_prefixed(&mut signer)?;
+    take_length_prefixed(&mut signed_data)?;
+    let mut certificates = take_length_prefixed(&mut signed_data)?;
+    let certificate = take_length_prefixed(&mut certificates)?;
+    if certificate.is_empty() {
+        bail!("签名证书为空");
+    }
+    Ok(certificate)
+}
+
+// 读取 PKCS#7 SignedData 中的第一张证书
+// 结构：ContentInfo { contentType, [0] SignedData { version, digestAlgorithms, contentInfo, [0] certificates } }
+fn read_pkcs7_certificate(data: &[u8]) -> Result<&[u8]> {
+    let (content_info, _) = read_der(data, DER_TAG_SEQUENCE)?;
+    let (_, rest) = read_der_any(content_info)?;
+    let (explicit, _) = read_der(rest, DER_TAG_CONTEXT_0)?;
+    let (signed_data, _) = read_der(explicit, DER_TAG_SEQUENCE)?;
+
+    let mut rest = signed_data;
+    for _ in 0..3 {
+        rest = read_der_any(rest)?.1;
+    }
+    let (certificates, _) = read_der(rest, DER_TAG_CONTEXT_0)?;
+    let (_, certificate_len) = read_der_header(certificates)?;
+    if certificates[0] != DER_TAG_SEQUENCE {
+        bail!("证书格式无效");
+    }
+    Ok(&certificates[..certificate_len])
+}
+
+// 读取指定标签的 DER 元素，返回元素内容和剩余数据
+fn read_der(data: &[u8], tag: u8) -> Result<(&[u8], &[u8])> {
+    if data.first() != Some(&tag) {
+        bail!("DER 标签不匹配，期望 {:#04x}", tag);
+    }
+    read_der_any(data)
+}
+
+fn read_der_any(data: &[u8]) -> Result<(&[u8], &[u8])> {
+    let (header_len, total_len) = read_der_header(data)?;
+    Ok((&data[header_len..total_len], &data[total_len..]))
+}
+
+// 解析 DER 元素头，返回头部长度和元素总长度，不支持 BER 不定长编码
+fn read_der_header(data: &[u8]) -> Result<(usize, usize)> {
+    let first_len_byte = *data.get(1).ok_or_else(|| anyhow!("DER 数据被截断"))?;
+    let (header_len, content_len) = if first_len_byte < 0x80 {
+        (2, first_len_byte as usize)
+    } else {
+        let len_bytes = (first_len_byte & 0x7f) as usize;
+        if len_bytes == 0 || len_bytes > 4 {
+            bail!("不支持的 DER 长度编码");
+        }
+        let bytes = data
+            .get(2..2 + len_bytes)
+            .ok_or_else(|| anyhow!("DER 数据被截断"))?;
+        let content_len = bytes
+            .iter()
+            .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+        (2 + len_bytes, content_len)
+    };
+
+    let total_len = header_len
+        .checked_add(content_len)
+        .filter(|len| *len <= data.len())
+        .ok_or_else(|| anyhow!("DER 数据被截断"))?;
+    Ok((header_len, total_len))
+}
+
+// 读取 4 字节小端长度前缀的数据段
+fn take_length_prefixed<'a>(data: &mut &'a [u8]) -> Result<&'a [u8]> {
+    let len_bytes: [u8; 4] = data
+        .get(..4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("签名数据被截断"))?;
+    let len = u32::from_le_bytes(len_bytes) as usize;
+    let value = data
+        .get(4..4 + len)
+        .ok_or_else(|| anyhow!("签名数据被截断"))?;
+    *data = &data[4 + len..];
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn length_prefixed(data: &[u8]) -> Vec<u8> {
+        let mut out = (data.len() as u32).to_le_bytes().to_vec();
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        if content.len() < 0x80 {
+            out.push(content.len() as u8);
+        } else {
+            out.push(0x82);
+            out.extend_from_slice(&(content.len() as u16).to_be_bytes());
+        }
+        out.extend_from_slice(content);
+        out
+    }
+
+    #[test]
+    fn read_signing_block_certificate_returns_first_certificate() {
+        let certificate = der(DER_TAG_SEQUENCE, b"certificate");
+        let signed_data = [
+            length_prefixed(b""),
+            length_prefixed(&length_prefixed(&certificate)),
+            length_prefixed(b""),
+        ]
+        .concat();
+        let signer = length_prefixed(&signed_data);
+        let value = length_prefixed(&length_prefixed(&signer));
+
+        assert_eq!(read_signing_block_certificate(&value).unwrap(), certificate);
+        assert!(read_signing_block_certificate(&value[..value.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn read_pkcs7_certificate_skips_to_certificates() {
+        let certificate = der(DER_TAG_SEQUENCE, &[7u8; 200]);
+        let signed_data = der(
+            DER_TAG_SEQUENCE,
+            &[
+                der(0x02, &[1]),
+                der(0x31, &[]),
+                der(DER_TAG_SEQUENCE, &der(0x06, &[1, 2, 3])),
+                der(DER_TAG_CONTEXT_0, &certificate),
+                der(0x31, &[]),
+            ]
+            .concat(),
+        );
+        let content_info = der(
+            DER_TAG_SEQUENCE,
+            &[der(0x06, &[1, 2, 3]), der(DER_TAG_CONTEXT_0, &signed_data)].concat(),
+        );
+
+        assert_eq!(read_pkcs7_certificate(&content_info).unwrap(), certificate);
+        assert!(is_v1_signature_file("META-INF/CERT.RSA"));
+        assert!(!is_v1_signature_file("META-INF/services/CERT.RSA"));
+    }
+}
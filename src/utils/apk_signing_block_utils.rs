@@ -0,0 +1,421 @@
+use anyhow::{Context, Result, anyhow, bail};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+// APK 签名块格式参考：https://source.android.com/docs/security/features/apksigning/v2#apk-signing-block
+const APK_SIGNING_BLOCK_MAGIC: &[u8] = b"APK Sig Block 42";
+const EOCD_SIGNATURE: &[u8] = b"PK\x05\x06";
+const EOCD_MIN_SIZE: usize = 22;
+const MAX_ZIP_COMMENT_SIZE: usize = 0xffff;
+const SIGNING_BLOCK_ALIGNMENT: usize = 4096;
+const VERITY_PADDING_BLOCK_ID: u32 = 0x42726577;
+
+pub const SIGNATURE_SCHEME_V2_BLOCK_ID: u32 = 0x7109871a;
+pub const SIGNATURE_SCHEME_V3_BLOCK_ID: u32 = 0xf05368c0;
+pub const SIGNATURE_SCHEME_V31_BLOCK_ID: u32 = 0x1b93ad61;
+/// VasDolly 渠道块，值为渠道名称的 UTF-8 字节
+pub const VASDOLLY_CHANNEL_BLOCK_ID: u32 = 0x881155ff;
+/// Walle 渠道块，值为 `{"channel":"渠道名称"}` 格式的 JSON
+pub const WALLE_CHANNEL_BLOCK_ID: u32 = 0x71777777;
+
+/// APK 签名块及其在文件中的位置
+#[derive(Debug, Clone)]
+pub struct ApkSigningBlock {
+    /// 签名块中的 ID-Value 键值对
+    pub pairs: Vec<(u32, Vec<u8>)>,
+    block_offset: usize,
+    central_directory_offset: usize,
+    eocd_offset: usize,
+    padded: bool,
+}
+
+impl ApkSigningBlock {
+    pub fn get(&self, id: u32) -> Option<&[u8]> {
+        self.pairs
+            .iter()
+            .find(|(pair_id, _)| *pair_id == id)
+            .map(|(_, value)| value.as_slice())
+    }
+}
+
+// 读取 APK 签名块，未使用 v2 及以上签名的 APK 返回 None
+pub fn read_signing_block(data: &[u8]) -> Result<Option<ApkSigningBlock>> {
+    parse_signing_block(data, 0)
+}
+
+// 读取 APK 文件的签名块，只读取签名块、中央目录和 EOCD，不把整个文件读入内存
+pub fn read_signing_block_file(path: &Path) -> Result<Option<ApkSigningBlock>> {
+    let mut file = File::open(path).with_context(|| format!("打开文件失败: {}", path.display()))?;
+    let start = signing_block_start(&mut file)?;
+    let mut data = Vec::new();
+    file.seek(SeekFrom::Start(start))?;
+    file.read_to_end(&mut data)?;
+    parse_signing_block(&data, start as usize)
+}
+
+// 解析签名块，`data` 为 APK 从 `base` 偏移开始到文件末尾的内容，返回的位置均为文件中的绝对偏移
+fn parse_signing_block(data: &[u8], base: usize) -> Result<Option<ApkSigningBlock>> {
+    let eocd_offset = find_eocd_offset(data)?;
+    let central_directory_offset = read_u32(data, eocd_offset + 16)? as usize;
+    let eocd_offset = base + eocd_offset;
+    if central_directory_offset == u32::MAX as usize {
+        bail!("暂不支持 ZIP64 格式的 APK");
+    }
+    if central_directory_offset > eocd_offset {
+        bail!("APK 中央目录偏移无效");
+    }
+
+    let magic_offset = match central_directory_offset.checked_sub(APK_SIGNING_BLOCK_MAGIC.len()) {
+        Some(offset) if offset >= base + 8 => offset,
+        _ => return Ok(None),
+    };
+    if &data[magic_offset - base..central_directory_offset - base] != APK_SIGNING_BLOCK_MAGIC {
+        return Ok(None);
+    }
+
+    let footer_size = read_u64(data, magic_offset - 8 - base)? as usize;
+    if footer_size < 8 + APK_SIGNING_BLOCK_MAGIC.len() {
+        bail!("APK 签名块大小无效");
+    }
+    let block_offset = footer_size
+        .checked_add(8)
+        .and_then(|size| central_directory_offset.checked_sub(size))
+        .filter(|offset| *offset >= base)
+        .ok_or_else(|| anyhow!("APK 签名块大小无效"))?;
+    if read_u64(data, block_offset - base)? as usize != footer_size {
+        bail!("APK 签名块头尾大小不一致");
+    }
+
+    let pairs = parse_pairs(&data[block_offset - base + 8..magic_offset - base - 8])?;
+    let padded = pairs.iter().any(|(id, _)| *id == VERITY_PADDING_BLOCK_ID);
+
+    Ok(Some(ApkSigningBlock {
+        pairs: pairs
+            .into_iter()
+            .filter(|(id, _)| *id != VERITY_PADDING_BLOCK_ID)
+            .collect(),
+        block_offset,
+        central_directory_offset,
+        eocd_offset,
+        padded,
+    }))
+}
+
+// 签名块在文件中的起始位置，没有签名块时返回中央目录或 EOCD 的位置
+fn signing_block_start(file: &mut File) -> Result<u64> {
+    let file_size = file.metadata()?.len();
+    let tail_size = file_size.min((EOCD_MIN_SIZE + MAX_ZIP_COMMENT_SIZE) as u64);
+    let tail = read_file_range(file, file_size - tail_size, tail_size as usize)?;
+    let eocd_offset = find_eocd_offset(&tail)?;
+    let central_directory_offset = read_u32(&tail, eocd_offset + 16)? as u64;
+    let eocd_offset = file_size - tail_size + eocd_offset as u64;
+    // 中央目录偏移无效时从 EOCD 开始读取，由解析时报告错误
+    if central_directory_offset > eocd_offset {
+        return Ok(eocd_offset);
+    }
+
+    let footer_len = (8 + APK_SIGNING_BLOCK_MAGIC.len()) as u64;
+    let Some(footer_offset) = central_directory_offset.checked_sub(footer_len) else {
+        return Ok(central_directory_offset);
+    };
+    let footer = read_file_range(file, footer_offset, footer_len as usize)?;
+    if &footer[8..] != APK_SIGNING_BLOCK_MAGIC {
+        return Ok(central_directory_offset);
+    }
+    // 签名块大小无效时只读取尾部，由解析时报告错误
+    let footer_size = read_u64(&footer, 0)?;
+    Ok(footer_size
+        .checked_add(8)
+        .and_then(|size| central_directory_offset.checked_sub(size))
+        .unwrap_or(footer_offset))
+}
+
+fn read_file_range(file: &mut File, offset: u64, len: usize) -> Result<Vec<u8>> {
+    let mut data = vec![0u8; len];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut data)?;
+    Ok(data)
+}
+
+// 读取 APK 中写入的渠道名称，优先读取 VasDolly 格式
+pub fn read_channel(data: &[u8]) -> Result<Option<String>> {
+    let Some(block) = read_signing_block(data)? else {
+        return Ok(None);
+    };
+
+    if let Some(value) = block.get(VASDOLLY_CHANNEL_BLOCK_ID) {
+        return Ok(Some(String::from_utf8_lossy(value).into_owned()));
+    }
+
+    Ok(block
+        .get(WALLE_CHANNEL_BLOCK_ID)
+        .and_then(|value| serde_json::from_slice::<serde_json::Value>(value).ok())
+        .and_then(|value| value.get("channel")?.as_str().map(ToOwned::to_owned)))
+}
+
+// 向 APK 签名块同时写入 VasDolly 和 Walle 格式的渠道信息，不需要重新签名
+pub fn write_channel(data: &[u8], channel: &str) -> Result<Vec<u8>> {
+    let block = read_signing_block(data)?
+        .ok_or_else(|| anyhow!("APK 未使用 v2 及以上签名，无法写入渠道信息"))?;
+    let (new_block, new_central_directory_offset) = encode_channel_block(&block, channel)?;
+
+    let mut output = Vec::with_capacity(data.len() + new_block.len());
+    output.extend_from_slice(&data[..block.block_offset]);
+    output.extend_from_slice(&new_block);
+    output.extend_from_slice(&data[block.central_directory_offset..block.eocd_offset]);
+    let eocd_start = output.len();
+    output.extend_from_slice(&data[block.eocd_offset..]);
+    output[eocd_start + 16..eocd_start + 20]
+        .copy_from_slice(&new_central_directory_offset.to_le_bytes());
+
+    Ok(output)
+}
+
+// 将写入渠道信息的 APK 输出到 `target`，按区段复制文件内容，不把整个 APK 读入内存
+pub fn write_channel_file(source: &Path, target: &Path, channel: &str) -> Result<()> {
+    let block = read_signing_block_file(source)?
+        .ok_or_else(|| anyhow!("APK 未使用 v2 及以上签名，无法写入渠道信息"))?;
+    let (new_block, new_central_directory_offset) = encode_channel_block(&block, channel)?;
+
+    let mut input =
+        File::open(source).with_context(|| format!("打开文件失败: {}", source.display()))?;
+    let mut output = BufWriter::new(
+        File::create(target).with_context(|| format!("创建文件失败: {}", target.display()))?,
+    );
+    copy_file_range(&mut input, &mut output, 0, block.block_offset)?;
+    output.write_all(&new_block)?;
+    copy_file_range(
+        &mut input,
+        &mut output,
+        block.central_directory_offset,
+        block.eocd_offset - block.central_directory_offset,
+    )?;
+    let mut eocd = Vec::new();
+    input.seek(SeekFrom::Start(block.eocd_offset as u64))?;
+    input.read_to_end(&mut eocd)?;
+    eocd[16..20].copy_from_slice(&new_central_directory_offset.to_le_bytes());
+    output.write_all(&eocd)?;
+    output
+        .flush()
+        .with_context(|| format!("写入文件失败: {}", target.display()))?;
+
+    Ok(())
+}
+
+// 生成写入渠道信息后的签名块，同时返回新的中央目录偏移
+fn encode_channel_block(block: &ApkSigningBlock, channel: &str) -> Result<(Vec<u8>, u32)> {
+    let has_signature = [
+        SIGNATURE_SCHEME_V2_BLOCK_ID,
+        SIGNATURE_SCHEME_V3_BLOCK_ID,
+        SIGNATURE_SCHEME_V31_BLOCK_ID,
+    ]
+    .iter()
+    .any(|id| block.get(*id).is_some());
+    if !has_signature {
+        bail!("APK 签名块中缺少 v2 及以上签名，无法写入渠道信息");
+    }
+
+    let walle_payload = serde_json::json!({ "channel": channel }).to_string();
+    let mut pairs: Vec<(u32, Vec<u8>)> = block
+        .pairs
+        .iter()
+        .filter(|(id, _)| *id != VASDOLLY_CHANNEL_BLOCK_ID && *id != WALLE_CHANNEL_BLOCK_ID)
+        .cloned()
+        .collect();
+    pairs.push((VASDOLLY_CHANNEL_BLOCK_ID, channel.as_bytes().to_vec()));
+    pairs.push((WALLE_CHANNEL_BLOCK_ID, walle_payload.into_bytes()));
+
+    let new_block = encode_signing_block(&pairs, block.padded);
+    let new_central_directory_offset = block.block_offset + new_block.len();
+    let new_central_directory_offset = u32::try_from(new_central_directory_offset)
+        .map_err(|_| anyhow!("写入渠道信息后 APK 超过 ZIP 大小限制"))?;
+
+    Ok((new_block, new_central_directory_offset))
+}
+
+fn copy_file_range(
+    input: &mut File,
+    output: &mut impl Write,
+    offset: usize,
+    len: usize,
+) -> Result<()> {
+    input.seek(SeekFrom::Start(offset as u64))?;
+    let copied = io::copy(&mut Read::by_ref(input).take(len as u64), output)?;
+    if copied != len as u64 {
+        bail!("读取 APK 数据越界");
+    }
+    Ok(())
+}
+
+// 从文件末尾向前查找 EOCD 记录
+fn find_eocd_offset(data: &[u8]) -> Result<usize> {
+    if data.len() < EOCD_MIN_SIZE {
+        bail!("文件过小，不是有效的 APK");
+    }
+
+    let last_offset = data.len() - EOCD_MIN_SIZE;
+    let first_offset = last_offset.saturating_sub(MAX_ZIP_COMMENT_SIZE);
+    (first_offset..=last_offset)
+        .rev()
+        .find(|offset| {
+            &data[*offset..*offset + 4] == EOCD_SIGNATURE
+                && read_u16(data, offset + 20).is_ok_and(|comment_size| {
+                    offset + EOCD_MIN_SIZE + comment_size as usize == data.len()
+                })
+        })
+        .ok_or_else(|| anyhow!("未找到 ZIP 结束记录，不是有效的 APK"))
+}
+
+fn parse_pairs(mut data: &[u8]) -> Result<Vec<(u32, Vec<u8>)>> {
+    let mut pairs = Vec::new();
+    while !data.is_empty() {
+        let pair_size = read_u64(data, 0)? as usize;
+        if pair_size < 4 || pair_size > data.len() - 8 {
+            bail!("APK 签名块键值对大小无效");
+        }
+        let id = read_u32(data, 8)?;
+        pairs.push((id, data[12..8 + pair_size].to_vec()));
+        data = &data[8 + pair_size..];
+    }
+
+    Ok(pairs)
+}
+
+// 重新组装签名块，原签名块有对齐填充时保持 4096 字节对齐
+fn encode_signing_block(pairs: &[(u32, Vec<u8>)], padded: bool) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (id, value) in pairs {
+        body.extend_from_slice(&((value.len() + 4) as u64).to_le_bytes());
+        body.extend_from_slice(&id.to_le_bytes());
+        body.extend_from_slice(value);
+    }
+
+    let footer_len = 8 + APK_SIGNING_BLOCK_MAGIC.len();
+    if padded {
+        let total = 8 + body.len() + footer_len;
+        let mut padding =
+            (SIGNING_BLOCK_ALIGNMENT - total % SIGNING_BLOCK_ALIGNMENT) % SIGNING_BLOCK_ALIGNMENT;
+        if padding != 0 && padding < 12 {
+            padding += SIGNING_BLOCK_ALIGNMENT;
+        }
+        if padding != 0 {
+            body.extend_from_slice(&((padding - 8) as u64).to_le_bytes());
+            body.extend_from_slice(&VERITY_PADDING_BLOCK_ID.to_le_bytes());
+            body.resize(body.len() + padding - 12, 0);
+        }
+    }
+
+    let block_size = (body.len() + footer_len) as u64;
+    let mut block = Vec::with_capacity(body.len() + footer_len + 8);
+    block.extend_from_slice(&block_size.to_le_bytes());
+    block.extend_from_slice(&body);
+    block.extend_from_slice(&block_size.to_le_bytes());
+    block.extend_from_slice(APK_SIGNING_BLOCK_MAGIC);
+    block
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| anyhow!("读取 APK 数据越界"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().expect("长度已校验")))
+        .ok_or_else(|| anyhow!("读取 APK 数据越界"))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().expect("长度已校验")))
+        .ok_or_else(|| anyhow!("读取 APK 数据越界"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 构造只包含签名块、中央目录和 EOCD 的最小 APK 结构
+    fn fake_apk(pairs: &[(u32, Vec<u8>)], padded: bool) -> Vec<u8> {
+        let mut data = b"local file entries".to_vec();
+        data.extend_from_slice(&encode_signing_block(pairs, padded));
+        let central_directory_offset = data.len() as u32;
+        data.extend_from_slice(b"central directory");
+        data.extend_from_slice(EOCD_SIGNATURE);
+        data.extend_from_slice(&[0; 12]);
+        data.extend_from_slice(&central_directory_offset.to_le_bytes());
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(b"ok");
+        data
+    }
+
+    #[test]
+    fn write_channel_keeps_signature_and_updates_central_directory_offset() {
+        let signature = (SIGNATURE_SCHEME_V2_BLOCK_ID, vec![7; 32]);
+        let apk = fake_apk(std::slice::from_ref(&signature), true);
+
+        let stamped = write_channel(&apk, "huawei").unwrap();
+        let block = read_signing_block(&stamped).unwrap().unwrap();
+        assert_eq!(
+            block.get(SIGNATURE_SCHEME_V2_BLOCK_ID),
+            Some(signature.1.as_slice())
+        );
+        assert_eq!(
+            block.get(VASDOLLY_CHANNEL_BLOCK_ID),
+            Some(b"huawei".as_slice())
+        );
+        assert_eq!(read_channel(&stamped).unwrap().as_deref(), Some("huawei"));
+        assert_eq!(
+            (block.central_directory_offset - block.block_offset) % SIGNING_BLOCK_ALIGNMENT,
+            0
+        );
+        assert_eq!(
+            &stamped[block.central_directory_offset..][..17],
+            b"central directory"
+        );
+
+        // 重复写入时替换旧渠道
+        let restamped = write_channel(&stamped, "xiaomi").unwrap();
+        assert_eq!(read_channel(&restamped).unwrap().as_deref(), Some("xiaomi"));
+        assert_eq!(
+            read_signing_block(&restamped).unwrap().unwrap().pairs.len(),
+            3
+        );
+    }
+
+    #[test]
+    fn write_channel_file_matches_in_memory_result() {
+        let apk = fake_apk(&[(SIGNATURE_SCHEME_V2_BLOCK_ID, vec![7; 32])], true);
+        let dir = std::env::temp_dir().join(format!("channel_{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("master.apk");
+        let target = dir.join("huawei.apk");
+        std::fs::write(&source, &apk).unwrap();
+
+        let block = read_signing_block_file(&source).unwrap().unwrap();
+        let expected = read_signing_block(&apk).unwrap().unwrap();
+        assert_eq!(block.pairs, expected.pairs);
+        assert_eq!(block.block_offset, expected.block_offset);
+        assert_eq!(block.eocd_offset, expected.eocd_offset);
+
+        write_channel_file(&source, &target, "huawei").unwrap();
+        let stamped = std::fs::read(&target).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(stamped, write_channel(&apk, "huawei").unwrap());
+    }
+
+    #[test]
+    fn write_channel_rejects_apk_without_v2_signature() {
+        let mut apk = b"local file entries".to_vec();
+        let central_directory_offset = apk.len() as u32;
+        apk.extend_from_slice(EOCD_SIGNATURE);
+        apk.extend_from_slice(&[0; 12]);
+        apk.extend_from_slice(&central_directory_offset.to_le_bytes());
+        apk.extend_from_slice(&0u16.to_le_bytes());
+
+        assert!(read_signing_block(&apk).unwrap().is_none());
+        assert!(write_channel(&apk, "huawei").is_err());
+    }
+}
@@ -1,8 +1,10 @@
+use crate::model::app_manage::ManifestComponents;
+use crate::utils::apk_signature_utils::{ApkSignerInfo, extract_signer_info, is_v1_signature_file};
+use crate::utils::blob_store_utils::blob_extension;
 use anyhow::{Context, Result, anyhow};
 use apk_info::Apk;
 use std::fs;
 use std::path::Path;
-use uuid::Uuid;
 
 const IMAGE_EXTENSIONS: &[&str] = &["png", "webp", "jpg", "jpeg"];
 const DENSITY_ORDER: &[&str] = &[
@@ -14,18 +16,44 @@ pub struct ApkMetadata {
     pub file_name: String,
     pub app_name: String,
     pub package_name: String,
-    pub app_icon_path: Option<String>,
+    pub app_icon: Option<ApkIcon>,
     pub version_name: Option<String>,
     pub version_code: Option<String>,
     pub file_size: u64,
+    pub min_sdk_version: Option<i32>,
+    pub native_abis: Vec<String>,
+    pub signer_sha256: Option<String>,
+    pub signature_schemes: Vec<String>,
+    pub target_sdk_version: Option<i32>,
+    pub permissions: Vec<String>,
+    pub features: Vec<String>,
+    pub is_debuggable: bool,
+    pub is_test_only: bool,
+    pub components: ManifestComponents,
+    pub split_name: Option<String>,
+}
+
+/// APK 中提取的图标资源
+#[derive(Debug, Clone)]
+pub struct ApkIcon {
+    pub data: Vec<u8>,
+    pub extension: String,
+}
+
+/// 拆分 APK 中用于校验和分发的清单信息
+#[derive(Debug, Clone)]
+pub struct SplitApkInfo {
+    pub file_name: String,
+    pub package_name: String,
+    pub version_code: Option<String>,
+    pub split_name: Option<String>,
+    pub native_abis: Vec<String>,
+    pub signer_sha256: Option<String>,
+    pub file_size: u64,
 }
 
 // 提取 APK 元数据
-pub fn extract_apk_metadata(
-    apk_path: &Path,
-    file_name: &str,
-    upload_dir: &Path,
-) -> Result<ApkMetadata> {
+pub fn extract_apk_metadata(apk_path: &Path, file_name: &str) -> Result<ApkMetadata> {
     let apk = Apk::new(apk_path).context("解析 APK 文件失败")?;
     let file_size = fs::metadata(apk_path)
         .with_context(|| format!("读取文件大小失败: {}", apk_path.display()))?
@@ -44,22 +72,165 @@ pub fn extract_apk_metadata(
     let icon_resource = apk
         .get_application_icon()
         .or_else(|| apk.get_attribute_value("application", "roundIcon"));
-    let app_icon_path = save_icon_from_apk(&apk, icon_resource.as_deref(), upload_dir)
-        .context("提取 APP 图标失败")?;
+    let app_icon =
+        read_icon_from_apk(&apk, icon_resource.as_deref()).context("提取 APP 图标失败")?;
+
+    let min_sdk_version = apk
+        .get_min_sdk_version()
+        .and_then(|value| value.trim().parse::<i32>().ok());
+    let mut native_abis = apk.get_native_codes();
+    native_abis.sort();
+    let target_sdk_version = target_sdk_version(
+        apk.get_attribute_value("uses-sdk", "targetSdkVersion")
+            .as_deref(),
+        min_sdk_version,
+    );
+    let permissions = sorted_unique(apk.get_permissions().chain(apk.get_permissions_sdk23()));
+    let features = sorted_unique(apk.get_features());
+    let is_debuggable = is_true_attribute(apk.get_application_debuggable());
+    let is_test_only = is_true_attribute(apk.get_attribute_value("application", "testOnly"));
+    let components = ManifestComponents {
+        activities: component_names(
+            &package_name,
+            apk.get_activities()
+                .map(|activity| activity.name)
+                .chain(apk.get_activity_aliases().map(|alias| alias.name)),
+        ),
+        services: component_names(&package_name, apk.get_services().map(|service| service.name)),
+        receivers: component_names(
+            &package_name,
+            apk.get_receivers().map(|receiver| receiver.name),
+        ),
+        providers: component_names(
+            &package_name,
+            apk.get_providers().map(|provider| provider.name),
+        ),
+    };
+
+    let signer_info = read_signer_info(&apk, apk_path)?;
 
     Ok(ApkMetadata {
         file_name: file_name.to_string(),
         app_name,
         package_name,
-        app_icon_path,
+        app_icon,
         version_name: apk.get_version_name(),
         version_code: apk.get_version_code(),
         file_size,
+        min_sdk_version,
+        native_abis,
+        signer_sha256: signer_info.signer_sha256,
+        signature_schemes: signer_info.signature_schemes,
+        target_sdk_version,
+        permissions,
+        features,
+        is_debuggable,
+        is_test_only,
+        components,
+        split_name: read_split_name(&apk),
+    })
+}
+
+// 提取拆分 APK 的包名、版本号、拆分名称及签名，配置包通常不含应用名称和图标
+pub fn extract_split_info(apk_path: &Path, file_name: &str) -> Result<SplitApkInfo> {
+    let apk = Apk::new(apk_path).context("解析 APK 文件失败")?;
+    let file_size = fs::metadata(apk_path)
+        .with_context(|| format!("读取文件大小失败: {}", apk_path.display()))?
+        .len();
+    let package_name = apk
+        .get_package_name()
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| anyhow!("APK 缺少有效的包名"))?;
+    let mut native_abis = apk.get_native_codes();
+    native_abis.sort();
+    let signer_info = read_signer_info(&apk, apk_path)?;
+
+    Ok(SplitApkInfo {
+        file_name: file_name.to_string(),
+        package_name,
+        version_code: apk.get_version_code(),
+        split_name: read_split_name(&apk),
+        native_abis,
+        signer_sha256: signer_info.signer_sha256,
+        file_size,
     })
 }
 
+// 读取清单中的 split 属性，基础包和单个 APK 没有该属性
+fn read_split_name(apk: &Apk) -> Option<String> {
+    apk.get_attribute_value("manifest", "split")
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+// 读取 APK 签名方案及签名证书指纹
+fn read_signer_info(apk: &Apk, apk_path: &Path) -> Result<ApkSignerInfo> {
+    let mut v1_signature_entries: Vec<&str> = apk
+        .namelist()
+        .filter(|entry| is_v1_signature_file(entry))
+        .collect();
+    v1_signature_entries.sort();
+    let v1_signature_files = v1_signature_entries
+        .into_iter()
+        .map(|entry| {
+            apk.read(entry)
+                .map(|(bytes, _)| bytes)
+                .with_context(|| format!("读取 v1 签名文件失败: {entry}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    extract_signer_info(apk_path, &v1_signature_files)
+}
+
+// 组件名称补全为完整类名后去重排序，`.Main` 和 `Main` 都按包名补全
+pub fn component_names<'a>(
+    package_name: &str,
+    names: impl Iterator<Item = Option<&'a str>>,
+) -> Vec<String> {
+    let mut names: Vec<String> = names
+        .flatten()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            if name.starts_with('.') {
+                format!("{package_name}{name}")
+            } else if !name.contains('.') {
+                format!("{package_name}.{name}")
+            } else {
+                name.to_string()
+            }
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+// 去重并排序清单中声明的名称
+pub fn sorted_unique<'a>(values: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut values: Vec<String> = values
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(ToOwned::to_owned)
+        .collect();
+    values.sort();
+    values.dedup();
+    values
+}
+
+// 解析清单中的布尔属性
+pub fn is_true_attribute(value: Option<String>) -> bool {
+    value.is_some_and(|value| value.trim().eq_ignore_ascii_case("true"))
+}
+
+// 未声明 targetSdkVersion 或无法解析时与系统行为一致，取 minSdkVersion
+pub fn target_sdk_version(target_sdk: Option<&str>, min_sdk_version: Option<i32>) -> Option<i32> {
+    target_sdk
+        .and_then(|value| value.trim().parse::<i32>().ok())
+        .or(min_sdk_version)
+}
+
 // 提取 APK 元数据失败时的回退 APP 名称
-fn fallback_app_name(apk_path: &Path, apk_name: &str) -> String {
+pub fn fallback_app_name(apk_path: &Path, apk_name: &str) -> String {
     Path::new(apk_name)
         .file_stem()
         .and_then(|value| value.to_str())
@@ -75,12 +246,8 @@ fn fallback_app_name(apk_path: &Path, apk_name: &str) -> String {
         .unwrap_or_else(|| "unknown_app".to_string())
 }
 
-// 保存 APK 图标到指定目录
-fn save_icon_from_apk(
-    apk: &Apk,
-    icon_resource: Option<&str>,
-    upload_dir: &Path,
-) -> Result<Option<String>> {
+// 读取 APK 图标资源
+fn read_icon_from_apk(apk: &Apk, icon_resource: Option<&str>) -> Result<Option<ApkIcon>> {
     let Some(resource) = icon_resource else {
         return Ok(None);
     };
@@ -94,27 +261,20 @@ fn save_icon_from_apk(
         .read(&icon_entry)
         .with_context(|| format!("读取 APK 图标资源失败: {icon_entry}"))?;
 
-    if bytes.is_empty() {
-        return Ok(None);
+    Ok(to_apk_icon(bytes, &icon_entry))
+}
+
+// 按资源路径确定图标扩展名，非常见图片格式按 png 处理
+pub fn to_apk_icon(data: Vec<u8>, icon_entry: &str) -> Option<ApkIcon> {
+    if data.is_empty() {
+        return None;
     }
 
-    let extension = Path::new(&icon_entry)
-        .extension()
-        .and_then(|value| value.to_str())
-        .map(|value| value.to_ascii_lowercase())
+    let extension = Some(blob_extension(icon_entry, "png"))
         .filter(|value| IMAGE_EXTENSIONS.contains(&value.as_str()))
         .unwrap_or_else(|| "png".to_string());
 
-    let icon_dir = upload_dir.join("icons");
-    fs::create_dir_all(&icon_dir)
-        .with_context(|| format!("创建图标目录失败: {}", icon_dir.display()))?;
-
-    let icon_name = format!("{}.{}", Uuid::new_v4(), extension);
-    let icon_path = icon_dir.join(icon_name);
-    fs::write(&icon_path, bytes)
-        .with_context(|| format!("写入图标文件失败: {}", icon_path.display()))?;
-
-    Ok(Some(normalize_relative_path(&icon_path)))
+    Some(ApkIcon { data, extension })
 }
 
 // 解析 APK 图标资源
@@ -125,10 +285,10 @@ fn resolve_icon_entry(apk: &Apk, resource: &str) -> Option<String> {
     }
 
     if normalized.ends_with(".xml") {
-        return fallback_image_entry(apk, &normalized);
+        return fallback_image_entry(apk.namelist(), &normalized);
     }
 
-    fallback_image_entry(apk, &normalized)
+    fallback_image_entry(apk.namelist(), &normalized)
 }
 
 // 规范化 APK 资源路径
@@ -137,7 +297,7 @@ fn normalize_apk_entry(resource: &str) -> String {
 }
 
 // 判断资源是否为图片类型
-fn is_image_entry(resource: &str) -> bool {
+pub fn is_image_entry(resource: &str) -> bool {
     Path::new(resource)
         .extension()
         .and_then(|value| value.to_str())
@@ -145,13 +305,16 @@ fn is_image_entry(resource: &str) -> bool {
         .is_some_and(|value| IMAGE_EXTENSIONS.contains(&value.as_str()))
 }
 
-// 查找图片资源的回退路径
-fn fallback_image_entry(apk: &Apk, resource: &str) -> Option<String> {
+// 查找图片资源的回退路径，按名称匹配 res/ 下密度最高的图片
+pub fn fallback_image_entry<'a>(
+    entries: impl Iterator<Item = &'a str>,
+    resource: &str,
+) -> Option<String> {
     let resource_path = Path::new(resource);
     let stem = resource_path.file_stem()?.to_str()?;
 
     let mut best_match: Option<(usize, String)> = None;
-    for entry in apk.namelist() {
+    for entry in entries {
         let normalized = normalize_apk_entry(entry);
         if !normalized.starts_with("res/") || !is_image_entry(&normalized) {
             continue;
@@ -189,17 +352,50 @@ fn density_priority(parent: &str) -> usize {
         .unwrap_or(DENSITY_ORDER.len())
 }
 
-//规范化相对路径
-fn normalize_relative_path(path: &Path) -> String {
-    let path = path.to_string_lossy().replace('\\', "/");
-    let cwd = std::env::current_dir()
-        .ok()
-        .map(|dir| dir.to_string_lossy().replace('\\', "/"));
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    match cwd {
-        Some(cwd) if path.starts_with(&cwd) => {
-            path[cwd.len()..].trim_start_matches('/').to_string()
-        }
-        _ => path,
+    #[test]
+    fn sorted_unique_trims_and_drops_duplicates() {
+        let values = [
+            "android.permission.INTERNET",
+            " android.permission.CAMERA ",
+            "",
+            "android.permission.INTERNET",
+            "  ",
+            "android.permission.CAMERA",
+        ];
+
+        assert_eq!(
+            sorted_unique(values.into_iter()),
+            vec![
+                "android.permission.CAMERA".to_string(),
+                "android.permission.INTERNET".to_string(),
+            ]
+        );
+        assert!(sorted_unique(std::iter::empty()).is_empty());
+    }
+
+    #[test]
+    fn is_true_attribute_only_accepts_true() {
+        assert!(is_true_attribute(Some("true".to_string())));
+        assert!(is_true_attribute(Some(" TRUE ".to_string())));
+        assert!(!is_true_attribute(Some("false".to_string())));
+        assert!(!is_true_attribute(Some("1".to_string())));
+        assert!(!is_true_attribute(Some(String::new())));
+        assert!(!is_true_attribute(None));
+    }
+
+    #[test]
+    fn target_sdk_version_falls_back_to_min_sdk() {
+        assert_eq!(target_sdk_version(Some("34"), Some(21)), Some(34));
+        assert_eq!(target_sdk_version(Some(" 33 "), None), Some(33));
+        assert_eq!(target_sdk_version(None, Some(21)), Some(21));
+        assert_eq!(
+            target_sdk_version(Some("@integer/target"), Some(21)),
+            Some(21)
+        );
+        assert_eq!(target_sdk_version(None, None), None);
     }
 }
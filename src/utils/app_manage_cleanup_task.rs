@@ -1,28 +1,36 @@
 use crate::db::DbPool;
-use crate::schema::app_manage;
+use crate::model::app_manage::{DeltaPatchStatus, SplitApkItem};
+use crate::schema::{app_blob, app_channel_apk, app_delta_patch, app_manage};
+use crate::store::FileStore;
+use crate::utils::blob_store_utils::{
+    APK_STORE_PREFIX, BLOB_KIND_APK, BLOB_KIND_ICON, BLOB_KIND_PATCH, ICON_STORE_PREFIX, blob_key,
+};
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use std::collections::HashSet;
-use std::fs;
-use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
+use tokio::runtime::Handle;
 use tracing::{error, info, warn};
+use uuid::Uuid;
 
 const APP_MANAGE_DIR: &str = "app_manage";
-const APK_DIR: &str = "apk";
-const ICON_DIR: &str = "icons";
 const ICON_PUBLIC_ROUTE: &str = "icon";
 const PUBLIC_APP_MANAGE_PREFIX: &str = "/api/public/app_manage";
+// 未被引用的文件至少保留一天，避免删除已上传但尚未发布的文件
+const UNREFERENCED_RETENTION_HOURS: i64 = 24;
 
-pub fn start_app_manage_cleanup_task(pool: Arc<DbPool>) {
+// 需在 tokio 运行时中调用，清理线程通过运行时句柄访问文件存储
+pub fn start_app_manage_cleanup_task(pool: Arc<DbPool>, file_store: Arc<dyn FileStore>) {
+    let runtime = Handle::current();
     thread::spawn(move || {
-        run_cleanup_once(&pool);
+        run_cleanup_once(&pool, file_store.as_ref(), &runtime);
 
         loop {
             let sleep_duration = duration_until_next_run();
             thread::sleep(sleep_duration);
-            run_cleanup_once(&pool);
+            run_cleanup_once(&pool, file_store.as_ref(), &runtime);
         }
     });
 }
@@ -38,12 +46,13 @@ fn duration_until_next_run() -> Duration {
     Duration::from_secs(seconds as u64)
 }
 
-fn run_cleanup_once(pool: &Arc<DbPool>) {
-    match cleanup_unused_files(pool) {
-        Ok((apk_count, icon_count)) => {
+fn run_cleanup_once(pool: &Arc<DbPool>, file_store: &dyn FileStore, runtime: &Handle) {
+    match cleanup_unused_files(pool, file_store, runtime) {
+        Ok((apk_count, icon_count, patch_count)) => {
             info!(
                 apk_deleted = apk_count,
                 icon_deleted = icon_count,
+                patch_deleted = patch_count,
                 "app_manage 无效文件清理完成"
             );
         }
@@ -53,67 +62,182 @@ fn run_cleanup_once(pool: &Arc<DbPool>) {
     }
 }
 
-fn cleanup_unused_files(pool: &Arc<DbPool>) -> anyhow::Result<(usize, usize)> {
+fn cleanup_unused_files(
+    pool: &Arc<DbPool>,
+    file_store: &dyn FileStore,
+    runtime: &Handle,
+) -> anyhow::Result<(usize, usize, usize)> {
     let mut conn = pool.get()?;
+    // 登记的文件按引用数清理；未登记的文件（早期按文件名保存的文件）仍按记录中的文件名判断是否被引用
     let referenced_files = app_manage::table
-        .select((app_manage::file_path, app_manage::app_icon_path))
+        .select((
+            app_manage::file_path,
+            app_manage::app_icon_path,
+            app_manage::splits,
+        ))
         .filter(app_manage::is_delete.eq(false))
-        .load::<(Option<String>, Option<String>)>(&mut conn)?;
+        .load::<(Option<String>, Option<String>, Option<serde_json::Value>)>(&mut conn)?;
 
-    let mut apk_names = HashSet::new();
-    let mut icon_names = HashSet::new();
+    let mut apk_names: HashSet<String> = HashSet::new();
+    let mut icon_names: HashSet<String> = HashSet::new();
 
-    for (file_path, icon_path) in referenced_files {
-        if let Some(name) = file_path
-            .as_deref()
-            .and_then(|value| extract_managed_filename(value, &[APK_DIR]))
-        {
-            apk_names.insert(name);
-        }
-        if let Some(name) = icon_path
-            .as_deref()
-            .and_then(|value| extract_managed_filename(value, &[ICON_DIR, ICON_PUBLIC_ROUTE]))
-        {
-            icon_names.insert(name);
-        }
+    for (file_path, icon_path, splits) in referenced_files {
+        // 拆分 APK 发布的版本同时引用基础包和全部配置包
+        let split_paths = splits
+            .and_then(|value| serde_json::from_value::<Vec<SplitApkItem>>(value).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|split| split.file_path);
+        apk_names.extend(
+            file_path
+                .into_iter()
+                .chain(split_paths)
+                .filter_map(|value| extract_managed_filename(&value, &[APK_STORE_PREFIX])),
+        );
+        icon_names.extend(icon_path.as_deref().and_then(|value| {
+            extract_managed_filename(value, &[ICON_STORE_PREFIX, ICON_PUBLIC_ROUTE])
+        }));
     }
+    apk_names.extend(
+        app_channel_apk::table
+            .select(app_channel_apk::file_name)
+            .load::<String>(&mut conn)?,
+    );
+
+    // 差分补丁按目标版本引用，目标版本删除后补丁不再下发
+    let patch_names: HashSet<String> = app_delta_patch::table
+        .inner_join(app_manage::table)
+        .filter(app_manage::is_delete.eq(false))
+        .filter(app_delta_patch::status.eq(DeltaPatchStatus::Ready.as_str()))
+        .select(app_delta_patch::file_name)
+        .load::<Option<String>>(&mut conn)?
+        .into_iter()
+        .flatten()
+        .collect();
 
-    let apk_deleted = cleanup_directory(&PathBuf::from(APP_MANAGE_DIR).join(APK_DIR), &apk_names)?;
-    let icon_deleted =
-        cleanup_directory(&PathBuf::from(APP_MANAGE_DIR).join(ICON_DIR), &icon_names)?;
+    let expire_before =
+        chrono::Local::now().naive_local() - chrono::Duration::hours(UNREFERENCED_RETENTION_HOURS);
+    let apk_deleted = cleanup_blobs(
+        &mut conn,
+        file_store,
+        runtime,
+        BLOB_KIND_APK,
+        &apk_names,
+        expire_before,
+    )?;
+    let icon_deleted = cleanup_blobs(
+        &mut conn,
+        file_store,
+        runtime,
+        BLOB_KIND_ICON,
+        &icon_names,
+        expire_before,
+    )?;
+    let patch_deleted = cleanup_blobs(
+        &mut conn,
+        file_store,
+        runtime,
+        BLOB_KIND_PATCH,
+        &patch_names,
+        expire_before,
+    )?;
 
-    Ok((apk_deleted, icon_deleted))
+    Ok((apk_deleted, icon_deleted, patch_deleted))
 }
 
-fn cleanup_directory(dir: &Path, referenced_names: &HashSet<String>) -> anyhow::Result<usize> {
-    if !dir.exists() {
-        return Ok(0);
-    }
+// 清理引用数为零且超过保留期的文件。逐个在事务中按条件删除登记，持有登记行的锁删除存储中的文件：
+// 并发的登记和增加引用会等待事务结束，删除存储文件失败时回滚，保留登记
+fn cleanup_blobs(
+    conn: &mut PgConnection,
+    file_store: &dyn FileStore,
+    runtime: &Handle,
+    kind: &str,
+    referenced_names: &HashSet<String>,
+    expire_before: NaiveDateTime,
+) -> anyhow::Result<usize> {
+    let unreferenced_ids = app_blob::table
+        .filter(app_blob::kind.eq(kind))
+        .filter(app_blob::ref_count.eq(0))
+        .filter(app_blob::update_time.lt(expire_before))
+        .select(app_blob::id)
+        .load::<Uuid>(conn)?;
 
     let mut deleted_count = 0;
-    for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if !path.is_file() {
-            continue;
+    for blob_id in unreferenced_ids {
+        let deleted_key = conn.transaction::<_, anyhow::Error, _>(|conn| {
+            let Some(file_name) = diesel::delete(
+                app_blob::table
+                    .filter(app_blob::id.eq(blob_id))
+                    .filter(app_blob::ref_count.eq(0))
+                    .filter(app_blob::update_time.lt(expire_before)),
+            )
+            .returning(app_blob::file_name)
+            .get_result::<String>(conn)
+            .optional()?
+            else {
+                return Ok(None);
+            };
+            let key = blob_key(kind, &file_name);
+            runtime.block_on(file_store.delete(&key))?;
+            Ok(Some(key))
+        })?;
+        if let Some(key) = deleted_key {
+            info!(key = %key, "删除未引用文件");
+            deleted_count += 1;
         }
+    }
+
+    let registered_names: HashSet<String> = app_blob::table
+        .filter(app_blob::kind.eq(kind))
+        .select(app_blob::file_name)
+        .load::<String>(conn)?
+        .into_iter()
+        .collect();
+    deleted_count += cleanup_unregistered_files(
+        file_store,
+        runtime,
+        kind,
+        &registered_names,
+        referenced_names,
+        expire_before,
+    )?;
 
-        let Some(file_name) = path
-            .file_name()
-            .and_then(|value| value.to_str())
-            .map(|value| value.to_string())
+    Ok(deleted_count)
+}
+
+// 清理未登记的文件（早期按文件名保存的文件、写入中断的临时文件），无引用且超过保留期时删除
+fn cleanup_unregistered_files(
+    file_store: &dyn FileStore,
+    runtime: &Handle,
+    kind: &str,
+    registered_names: &HashSet<String>,
+    referenced_names: &HashSet<String>,
+    expire_before: NaiveDateTime,
+) -> anyhow::Result<usize> {
+    let prefix = blob_key(kind, "");
+    let objects = runtime.block_on(file_store.list(&prefix))?;
+
+    let mut deleted_count = 0;
+    for object in objects {
+        let Some(file_name) = object
+            .key
+            .strip_prefix(&prefix)
+            .filter(|value| !value.is_empty() && !value.contains('/'))
         else {
-            warn!(path = %path.display(), "跳过无法识别名称的文件");
+            warn!(key = %object.key, "跳过无法识别名称的文件");
             continue;
         };
 
-        if referenced_names.contains(&file_name) {
+        if registered_names.contains(file_name) || referenced_names.contains(file_name) {
+            continue;
+        }
+        if object.last_modified >= expire_before {
             continue;
         }
 
-        fs::remove_file(&path)?;
+        runtime.block_on(file_store.delete(&object.key))?;
         deleted_count += 1;
-        info!(path = %path.display(), "删除未引用文件");
+        info!(key = %object.key, "删除未引用文件");
     }
 
     Ok(deleted_count)
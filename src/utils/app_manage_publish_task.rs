@@ -0,0 +1,100 @@
+use crate::db::DbPool;
+use crate::schema::{app_manage, users};
+use crate::utils::operation_log_utils::{OP_PUBLISH_APP, record_operation};
+use diesel::prelude::*;
+use std::env;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tracing::{error, info};
+use uuid::Uuid;
+
+// `APP_PUBLISH_CHECK_INTERVAL_SECS`：检查定时发布的间隔秒数，默认 30 秒
+const DEFAULT_CHECK_INTERVAL_SECS: u64 = 30;
+
+pub fn start_app_manage_publish_task(pool: Arc<DbPool>) {
+    let interval = env::var("APP_PUBLISH_CHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_CHECK_INTERVAL_SECS);
+
+    thread::spawn(move || {
+        loop {
+            run_publish_once(&pool);
+            thread::sleep(Duration::from_secs(interval));
+        }
+    });
+}
+
+fn run_publish_once(pool: &Arc<DbPool>) {
+    match publish_due_releases(pool) {
+        Ok(0) => {}
+        Ok(count) => info!(published = count, "定时发布应用完成"),
+        Err(e) => error!(error = %e, "定时发布应用失败"),
+    }
+}
+
+fn publish_due_releases(pool: &Arc<DbPool>) -> anyhow::Result<usize> {
+    let mut conn = pool.get()?;
+    let now = chrono::Local::now().naive_local();
+    let due_releases = app_manage::table
+        .filter(app_manage::is_delete.eq(false))
+        .filter(app_manage::is_published.eq(false))
+        .filter(
+            app_manage::publish_at
+                .is_null()
+                .or(app_manage::publish_at.le(now)),
+        )
+        .select((
+            app_manage::id,
+            app_manage::create_user_id,
+            app_manage::app_name,
+            app_manage::version_name,
+        ))
+        .load::<(Uuid, Uuid, String, Option<String>)>(&mut conn)?;
+
+    let mut published_count = 0;
+    for (app_id, create_user_id, app_name, version_name) in due_releases {
+        // 只有成功抢到状态变更的一方记录发布事件，避免重复记录；状态变更和发布记录在同一事务中提交
+        let published = conn.transaction::<_, anyhow::Error, _>(|conn| {
+            let updated = diesel::update(
+                app_manage::table
+                    .filter(app_manage::id.eq(app_id))
+                    .filter(app_manage::is_published.eq(false)),
+            )
+            .set(app_manage::is_published.eq(true))
+            .execute(conn)?;
+            if updated == 0 {
+                return Ok(false);
+            }
+
+            let username = users::table
+                .filter(users::id.eq(create_user_id))
+                .select(users::username)
+                .first::<String>(conn)
+                .optional()?
+                .unwrap_or_default();
+            record_operation(
+                conn,
+                create_user_id,
+                &username,
+                OP_PUBLISH_APP,
+                format!(
+                    "定时发布应用'{}'成功，版本：{}",
+                    app_name,
+                    version_name.as_deref().unwrap_or_default()
+                ),
+            )?;
+            Ok(true)
+        })?;
+        if !published {
+            continue;
+        }
+
+        published_count += 1;
+        info!(app_id = %app_id, "定时发布应用已上线");
+    }
+
+    Ok(published_count)
+}
@@ -0,0 +1,505 @@
+use crate::model::app_manage::{AppBlob, AppChannelApk};
+use crate::model::error::AppError;
+use crate::schema::{app_blob, app_channel, app_channel_apk};
+use crate::store::FileStore;
+use crate::utils::file_digest_utils::{FileDigest, compute_file_digest};
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use chrono::{Local, NaiveDateTime};
+use diesel::PgConnection;
+use diesel::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+pub const BLOB_KIND_APK: &str = "apk";
+pub const BLOB_KIND_ICON: &str = "icon";
+pub const BLOB_KIND_PATCH: &str = "patch";
+
+// 各类文件在存储中的目录
+pub const APK_STORE_PREFIX: &str = "apk";
+pub const ICON_STORE_PREFIX: &str = "icons";
+pub const PATCH_STORE_PREFIX: &str = "patches";
+const SHA256_HEX_LEN: usize = 64;
+
+/// 暂存在本地、等待写入存储的文件
+#[derive(Debug, Clone)]
+pub struct StagedFile {
+    /// 本地文件路径
+    pub path: PathBuf,
+    /// 存储文件名称：`{SHA-256}.{扩展名}`
+    pub file_name: String,
+    /// 上传时的原始文件名
+    pub original_name: String,
+    /// 文件摘要
+    pub digest: FileDigest,
+    /// 文件大小（字节）
+    pub file_size: u64,
+}
+
+/// 按内容摘要写入存储的文件
+#[derive(Debug, Clone)]
+pub struct StoredBlob {
+    /// 文件名称：`{SHA-256}.{扩展名}`
+    pub file_name: String,
+    /// 存储对象键
+    pub key: String,
+    /// 上传时的原始文件名，服务端生成的文件为空
+    pub original_name: Option<String>,
+    /// 文件摘要
+    pub digest: FileDigest,
+    /// 文件大小（字节）
+    pub file_size: u64,
+    /// 已存在相同内容的文件，本次未重新写入
+    pub is_deduplicated: bool,
+}
+
+/// 本地暂存目录，离开作用域时删除
+pub struct StagingDir {
+    path: PathBuf,
+}
+
+impl StagingDir {
+    pub fn new() -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("app_manage_{}", Uuid::new_v4().simple()));
+        fs::create_dir_all(&path)
+            .with_context(|| format!("创建暂存目录失败: {}", path.display()))?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // 将内存中的数据写入暂存目录
+    pub fn stage_bytes(
+        &self,
+        data: &[u8],
+        original_name: &str,
+        extension: &str,
+    ) -> Result<StagedFile> {
+        let digest = FileDigest::of_bytes(data);
+        let file_name = format!("{}.{}", digest.sha256, extension);
+        let path = self.path.join(&file_name);
+        fs::write(&path, data).with_context(|| format!("写入文件失败: {}", path.display()))?;
+        Ok(StagedFile {
+            path,
+            file_name,
+            original_name: original_name.to_string(),
+            digest,
+            file_size: data.len() as u64,
+        })
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+// 文件在存储中的对象键
+pub fn blob_key(kind: &str, file_name: &str) -> String {
+    let prefix = match kind {
+        BLOB_KIND_ICON => ICON_STORE_PREFIX,
+        BLOB_KIND_PATCH => PATCH_STORE_PREFIX,
+        _ => APK_STORE_PREFIX,
+    };
+    format!("{prefix}/{file_name}")
+}
+
+// 取文件扩展名作为存储扩展名，仅保留小写字母和数字
+pub fn blob_extension(file_name: &str, default_extension: &str) -> String {
+    Path::new(file_name)
+        .extension()
+        .and_then(|value| value.to_str())
+        .map(|value| value.to_ascii_lowercase())
+        .filter(|value| !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric()))
+        .unwrap_or_else(|| default_extension.to_string())
+}
+
+// 判断文件名是否为内容寻址的存储文件名
+pub fn is_blob_file_name(file_name: &str) -> bool {
+    file_name.split_once('.').is_some_and(|(stem, extension)| {
+        stem.len() == SHA256_HEX_LEN
+            && stem
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+            && !extension.is_empty()
+    })
+}
+
+// 计算本地文件的摘要，得到存储文件名
+pub fn stage_file(path: &Path, original_name: &str, extension: &str) -> Result<StagedFile> {
+    let digest = compute_file_digest(path)?;
+    let file_size = path
+        .metadata()
+        .with_context(|| format!("读取文件信息失败: {}", path.display()))?
+        .len();
+    Ok(StagedFile {
+        path: path.to_path_buf(),
+        file_name: format!("{}.{}", digest.sha256, extension),
+        original_name: original_name.to_string(),
+        digest,
+        file_size,
+    })
+}
+
+// 登记并写入暂存文件，存储中已有相同内容的文件时直接复用。
+// 先登记再写入：登记刷新了更新时间，清理任务只删除超过保留期的未引用文件，且删除时持有登记行的锁
+pub async fn store_staged_file(
+    conn: &mut PgConnection,
+    store: &dyn FileStore,
+    kind: &str,
+    staged: &StagedFile,
+) -> Result<StoredBlob, AppError> {
+    register_blob(
+        conn,
+        kind,
+        &staged.file_name,
+        Some(&staged.original_name),
+        staged.file_size,
+        Local::now().naive_local(),
+    )?;
+    put_staged_file(store, kind, staged).await
+}
+
+// 按内容摘要登记并写入内存中的数据，存储中已有相同内容的文件时直接复用
+pub async fn store_blob_bytes(
+    conn: &mut PgConnection,
+    store: &dyn FileStore,
+    kind: &str,
+    data: Vec<u8>,
+    extension: &str,
+    original_name: Option<&str>,
+) -> Result<StoredBlob, AppError> {
+    let digest = FileDigest::of_bytes(&data);
+    let file_name = format!("{}.{}", digest.sha256, extension);
+    register_blob(
+        conn,
+        kind,
+        &file_name,
+        original_name,
+        data.len() as u64,
+        Local::now().naive_local(),
+    )?;
+    let mut blob = put_blob_bytes(store, kind, data, file_name, digest).await?;
+    blob.original_name = original_name.map(str::to_string);
+    Ok(blob)
+}
+
+async fn put_staged_file(
+    store: &dyn FileStore,
+    kind: &str,
+    staged: &StagedFile,
+) -> Result<StoredBlob, AppError> {
+    let key = blob_key(kind, &staged.file_name);
+    let is_deduplicated = store.head(&key).await?.is_some();
+    if !is_deduplicated {
+        store.put_file(&key, &staged.path).await?;
+    }
+
+    Ok(StoredBlob {
+        file_name: staged.file_name.clone(),
+        key,
+        original_name: Some(staged.original_name.clone()),
+        digest: staged.digest.clone(),
+        file_size: staged.file_size,
+        is_deduplicated,
+    })
+}
+
+async fn put_blob_bytes(
+    store: &dyn FileStore,
+    kind: &str,
+    data: Vec<u8>,
+    file_name: String,
+    digest: FileDigest,
+) -> Result<StoredBlob, AppError> {
+    let key = blob_key(kind, &file_name);
+    let file_size = data.len() as u64;
+    let is_deduplicated = store.head(&key).await?.is_some();
+    if !is_deduplicated {
+        store.put(&key, Bytes::from(data)).await?;
+    }
+
+    Ok(StoredBlob {
+        file_name,
+        key,
+        original_name: None,
+        digest,
+        file_size,
+        is_deduplicated,
+    })
+}
+
+// 登记存储文件，已登记的文件刷新更新时间，清理任务据此保留尚未发布的文件；原始文件名以首次登记的为准
+fn register_blob(
+    conn: &mut PgConnection,
+    kind: &str,
+    file_name: &str,
+    original_name: Option<&str>,
+    file_size: u64,
+    now: NaiveDateTime,
+) -> Result<(), AppError> {
+    let new_blob = AppBlob {
+        id: Uuid::new_v4(),
+        kind: kind.to_string(),
+        file_name: file_name.to_string(),
+        file_size: file_size as i64,
+        ref_count: 0,
+        create_time: now,
+        update_time: now,
+        original_name: original_name.map(str::to_string),
+    };
+
+    diesel::insert_into(app_blob::table)
+        .values(&new_blob)
+        .on_conflict((app_blob::kind, app_blob::file_name))
+        .do_update()
+        .set(app_blob::update_time.eq(now))
+        .execute(conn)
+        .map_err(|e| AppError::Internal(format!("登记存储文件失败:{}", e)))?;
+
+    // 服务端生成过的相同内容后来被上传时，补充原始文件名
+    if let Some(original_name) = original_name {
+        diesel::update(
+            app_blob::table
+                .filter(app_blob::kind.eq(kind))
+                .filter(app_blob::file_name.eq(file_name))
+                .filter(app_blob::original_name.is_null()),
+        )
+        .set(app_blob::original_name.eq(original_name))
+        .execute(conn)
+        .map_err(|e| AppError::Internal(format!("登记存储文件失败:{}", e)))?;
+    }
+
+    Ok(())
+}
+
+// 增加文件引用数，需与新增引用的记录在同一事务中执行；每个元素计一次引用。
+// 登记已被清理任务删除时返回错误，此时存储中的文件可能已不存在，需重新上传
+pub fn add_blob_references(
+    conn: &mut PgConnection,
+    kind: &str,
+    file_names: &[String],
+) -> Result<(), AppError> {
+    for (file_name, count) in count_file_names(file_names) {
+        let updated = diesel::update(
+            app_blob::table
+                .filter(app_blob::kind.eq(kind))
+                .filter(app_blob::file_name.eq(file_name)),
+        )
+        .set((
+            app_blob::ref_count.eq(app_blob::ref_count + count),
+            app_blob::update_time.eq(Local::now().naive_local()),
+        ))
+        .execute(conn)?;
+        if updated == 0 {
+            return Err(AppError::NotFound(format!(
+                "文件'{}'不存在或已被清理，请重新上传",
+                file_name
+            )));
+        }
+    }
+    Ok(())
+}
+
+// 减少文件引用数，需与删除引用的记录在同一事务中执行；引用数归零的文件从此刻起计算保留期
+pub fn remove_blob_references(
+    conn: &mut PgConnection,
+    kind: &str,
+    file_names: &[String],
+) -> Result<(), AppError> {
+    for (file_name, count) in count_file_names(file_names) {
+        diesel::update(
+            app_blob::table
+                .filter(app_blob::kind.eq(kind))
+                .filter(app_blob::file_name.eq(file_name)),
+        )
+        .set((
+            app_blob::ref_count.eq(diesel::dsl::sql::<diesel::sql_types::Integer>(&format!(
+                "GREATEST(ref_count - {count}, 0)"
+            ))),
+            app_blob::update_time.eq(Local::now().naive_local()),
+        ))
+        .execute(conn)?;
+    }
+    Ok(())
+}
+
+fn count_file_names(file_names: &[String]) -> HashMap<&str, i32> {
+    let mut counts: HashMap<&str, i32> = HashMap::new();
+    for file_name in file_names {
+        *counts.entry(file_name.as_str()).or_default() += 1;
+    }
+    counts
+}
+
+// 从公开下载地址中取出存储文件名：`/api/public/app_manage/{类型}?name={文件名}`
+pub fn blob_file_name_from_url(url: &str) -> Option<&str> {
+    url.split_once("?name=")
+        .and_then(|(_, query)| query.split('&').next())
+        .filter(|name| !name.is_empty() && !name.contains(['/', '\\']))
+}
+
+// 记录生成的渠道包并增加引用；同一渠道为同一母包重新生成且内容变化（如渠道改名）时替换旧的引用
+pub fn record_channel_apk(
+    conn: &mut PgConnection,
+    channel_id: Uuid,
+    master_file_name: &str,
+    file_name: &str,
+) -> Result<(), AppError> {
+    let now = Local::now().naive_local();
+    let previous = app_channel_apk::table
+        .filter(app_channel_apk::channel_id.eq(channel_id))
+        .filter(app_channel_apk::master_file_name.eq(master_file_name))
+        .for_update()
+        .first::<AppChannelApk>(conn)
+        .optional()?;
+    match previous {
+        Some(previous) if previous.file_name == file_name => {
+            diesel::update(app_channel_apk::table.find(previous.id))
+                .set(app_channel_apk::update_time.eq(now))
+                .execute(conn)?;
+        }
+        Some(previous) => {
+            diesel::update(app_channel_apk::table.find(previous.id))
+                .set((
+                    app_channel_apk::file_name.eq(file_name),
+                    app_channel_apk::update_time.eq(now),
+                ))
+                .execute(conn)?;
+            add_blob_references(conn, BLOB_KIND_APK, &[file_name.to_string()])?;
+            remove_blob_references(conn, BLOB_KIND_APK, &[previous.file_name])?;
+        }
+        None => {
+            diesel::insert_into(app_channel_apk::table)
+                .values(&AppChannelApk {
+                    id: Uuid::new_v4(),
+                    channel_id,
+                    master_file_name: master_file_name.to_string(),
+                    file_name: file_name.to_string(),
+                    create_time: now,
+                    update_time: now,
+                })
+                .execute(conn)?;
+            add_blob_references(conn, BLOB_KIND_APK, &[file_name.to_string()])?;
+        }
+    }
+    Ok(())
+}
+
+// 按母包加事务级咨询锁：生成渠道包和删除母包版本互斥，后执行的一方能看到先提交的结果
+pub fn lock_channel_apk_master(
+    conn: &mut PgConnection,
+    master_file_name: &str,
+) -> Result<(), AppError> {
+    diesel::sql_query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind::<diesel::sql_types::Text, _>(format!("app_channel_apk:{master_file_name}"))
+        .execute(conn)?;
+    Ok(())
+}
+
+// 删除渠道的全部渠道包记录并释放引用，渠道删除时调用
+pub fn remove_channel_apks_by_channel(
+    conn: &mut PgConnection,
+    channel_id: Uuid,
+) -> Result<(), AppError> {
+    let file_names =
+        diesel::delete(app_channel_apk::table.filter(app_channel_apk::channel_id.eq(channel_id)))
+            .returning(app_channel_apk::file_name)
+            .get_results::<String>(conn)?;
+    remove_blob_references(conn, BLOB_KIND_APK, &file_names)
+}
+
+// 删除组织内为指定母包生成的渠道包记录并释放引用，母包不再被组织内任何版本引用时调用
+pub fn remove_channel_apks_by_master(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    master_file_name: &str,
+) -> Result<(), AppError> {
+    let channel_ids = app_channel::table
+        .filter(app_channel::organization_id.eq(organization_id))
+        .select(app_channel::id);
+    let file_names = diesel::delete(
+        app_channel_apk::table
+            .filter(app_channel_apk::master_file_name.eq(master_file_name))
+            .filter(app_channel_apk::channel_id.eq_any(channel_ids)),
+    )
+    .returning(app_channel_apk::file_name)
+    .get_results::<String>(conn)?;
+    remove_blob_references(conn, BLOB_KIND_APK, &file_names)
+}
+
+// 查询存储文件上传时的原始文件名
+pub fn find_blob_original_name(
+    conn: &mut PgConnection,
+    kind: &str,
+    file_name: &str,
+) -> QueryResult<Option<String>> {
+    app_blob::table
+        .filter(app_blob::kind.eq(kind))
+        .filter(app_blob::file_name.eq(file_name))
+        .select(app_blob::original_name)
+        .first::<Option<String>>(conn)
+        .optional()
+        .map(Option::flatten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::LocalFileStore;
+
+    #[tokio::test]
+    async fn store_blob_bytes_reuses_existing_content() {
+        let root = std::env::temp_dir().join(format!("blob_{}", Uuid::new_v4()));
+        let store = LocalFileStore::new(&root);
+        let put_bytes = async |data: &[u8]| {
+            let digest = FileDigest::of_bytes(data);
+            let file_name = format!("{}.apk", digest.sha256);
+            put_blob_bytes(&store, BLOB_KIND_APK, data.to_vec(), file_name, digest)
+                .await
+                .unwrap()
+        };
+        let first = put_bytes(b"apk-content").await;
+        let second = put_bytes(b"apk-content").await;
+        let staging_dir = StagingDir::new().unwrap();
+        let staged = staging_dir
+            .stage_bytes(b"other-content", "other.apk", "apk")
+            .unwrap();
+        let other = put_staged_file(&store, BLOB_KIND_APK, &staged)
+            .await
+            .unwrap();
+        let file_count = fs::read_dir(root.join(APK_STORE_PREFIX)).unwrap().count();
+        let staging_path = staging_dir.path().to_path_buf();
+        drop(staging_dir);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(!first.is_deduplicated);
+        assert!(second.is_deduplicated);
+        assert_eq!(first.file_name, second.file_name);
+        assert_eq!(first.file_name, format!("{}.apk", first.digest.sha256));
+        assert_eq!(first.key, format!("apk/{}", first.file_name));
+        assert!(is_blob_file_name(&first.file_name));
+        assert_ne!(first.file_name, other.file_name);
+        assert!(!other.is_deduplicated);
+        assert_eq!(other.original_name.as_deref(), Some("other.apk"));
+        assert_eq!(first.original_name, None);
+        assert_eq!(file_count, 2);
+        assert!(!staging_path.exists());
+
+        assert!(!is_blob_file_name("demo_20260101000000000.apk"));
+        assert_eq!(blob_extension("Demo.APK", "bin"), "apk");
+        assert_eq!(blob_extension("demo", "bin"), "bin");
+        assert_eq!(blob_key(BLOB_KIND_ICON, "a.png"), "icons/a.png");
+        assert_eq!(blob_key(BLOB_KIND_PATCH, "a.patch"), "patches/a.patch");
+        assert_eq!(
+            blob_file_name_from_url("/api/public/app_manage/apk?name=a.apk&expires=1"),
+            Some("a.apk")
+        );
+        assert_eq!(blob_file_name_from_url("/api/public/app_manage/apk"), None);
+    }
+}
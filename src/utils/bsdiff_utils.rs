@@ -0,0 +1,465 @@
+use anyhow::{Context, Result, bail, ensure};
+use bzip2::Compression;
+use bzip2::read::BzDecoder;
+use bzip2::write::BzEncoder;
+use std::io::{Read, Write};
+
+// 标准 BSDIFF40 格式：32 字节文件头 + 控制块 + 差异块 + 新增块，三个数据块使用 bzip2 压缩，可直接用 bspatch 还原
+pub const PATCH_FORMAT: &str = "bsdiff40";
+const PATCH_MAGIC: &[u8; 8] = b"BSDIFF40";
+const HEADER_LEN: usize = 32;
+const CONTROL_ENTRY_LEN: usize = 24;
+
+// 生成从旧文件到新文件的二进制补丁（bsdiff 算法）
+pub fn diff(old: &[u8], new: &[u8]) -> Result<Vec<u8>> {
+    ensure!(old.len() < i32::MAX as usize, "旧文件过大，无法生成补丁");
+    let suffix_array = qsufsort(old);
+    let old_size = old.len() as i64;
+    let new_size = new.len() as i64;
+
+    let mut control_block = Vec::new();
+    let mut diff_block = Vec::with_capacity(new.len());
+    let mut extra_block = Vec::new();
+
+    let (mut scan, mut len, mut pos) = (0i64, 0i64, 0i64);
+    let (mut last_scan, mut last_pos, mut last_offset) = (0i64, 0i64, 0i64);
+    let old_at = |index: i64| old[index as usize];
+    let new_at = |index: i64| new[index as usize];
+    // 旧文件中按上一段偏移对齐的位置与新文件相同
+    let matches_last_offset = |index: i64, last_offset: i64| {
+        let old_index = index + last_offset;
+        old_index >= 0 && old_index < old_size && old_at(old_index) == new_at(index)
+    };
+
+    while scan < new_size {
+        let mut old_score = 0i64;
+        scan += len;
+        let mut scsc = scan;
+        while scan < new_size {
+            let (match_pos, match_len) = search(&suffix_array, old, &new[scan as usize..]);
+            pos = match_pos as i64;
+            len = match_len as i64;
+
+            while scsc < scan + len {
+                if matches_last_offset(scsc, last_offset) {
+                    old_score += 1;
+                }
+                scsc += 1;
+            }
+            if (len == old_score && len != 0) || len > old_score + 8 {
+                break;
+            }
+            if matches_last_offset(scan, last_offset) {
+                old_score -= 1;
+            }
+            scan += 1;
+        }
+
+        if len == old_score && scan != new_size {
+            continue;
+        }
+
+        // 向前扩展上一段匹配
+        let (mut score, mut best_score, mut len_forward) = (0i64, 0i64, 0i64);
+        let mut i = 0i64;
+        while last_scan + i < scan && last_pos + i < old_size {
+            if old_at(last_pos + i) == new_at(last_scan + i) {
+                score += 1;
+            }
+            i += 1;
+            if score * 2 - i > best_score * 2 - len_forward {
+                best_score = score;
+                len_forward = i;
+            }
+        }
+
+        // 向后扩展本段匹配
+        let mut len_backward = 0i64;
+        if scan < new_size {
+            let (mut score, mut best_score) = (0i64, 0i64);
+            let mut i = 1i64;
+            while scan >= last_scan + i && pos >= i {
+                if old_at(pos - i) == new_at(scan - i) {
+                    score += 1;
+                }
+                if score * 2 - i > best_score * 2 - len_backward {
+                    best_score = score;
+                    len_backward = i;
+                }
+                i += 1;
+            }
+        }
+
+        // 两段扩展重叠时选择得分最高的分界点
+        if last_scan + len_forward > scan - len_backward {
+            let overlap = (last_scan + len_forward) - (scan - len_backward);
+            let (mut score, mut best_score, mut len_split) = (0i64, 0i64, 0i64);
+            for i in 0..overlap {
+                if new_at(last_scan + len_forward - overlap + i)
+                    == old_at(last_pos + len_forward - overlap + i)
+                {
+                    score += 1;
+                }
+                if new_at(scan - len_backward + i) == old_at(pos - len_backward + i) {
+                    score -= 1;
+                }
+                if score > best_score {
+                    best_score = score;
+                    len_split = i + 1;
+                }
+            }
+            len_forward += len_split - overlap;
+            len_backward -= len_split;
+        }
+
+        for i in 0..len_forward {
+            diff_block.push(new_at(last_scan + i).wrapping_sub(old_at(last_pos + i)));
+        }
+        let extra_start = (last_scan + len_forward) as usize;
+        let extra_end = (scan - len_backward) as usize;
+        extra_block.extend_from_slice(&new[extra_start..extra_end]);
+
+        write_offset(&mut control_block, len_forward);
+        write_offset(&mut control_block, (extra_end - extra_start) as i64);
+        write_offset(
+            &mut control_block,
+            (pos - len_backward) - (last_pos + len_forward),
+        );
+
+        last_scan = scan - len_backward;
+        last_pos = pos - len_backward;
+        last_offset = pos - scan;
+    }
+
+    let control_block = bzip2(&control_block)?;
+    let diff_block = bzip2(&diff_block)?;
+    let extra_block = bzip2(&extra_block)?;
+    let mut patch =
+        Vec::with_capacity(HEADER_LEN + control_block.len() + diff_block.len() + extra_block.len());
+    patch.extend_from_slice(PATCH_MAGIC);
+    write_offset(&mut patch, control_block.len() as i64);
+    write_offset(&mut patch, diff_block.len() as i64);
+    write_offset(&mut patch, new_size);
+    patch.extend_from_slice(&control_block);
+    patch.extend_from_slice(&diff_block);
+    patch.extend_from_slice(&extra_block);
+    Ok(patch)
+}
+
+// 将补丁应用到旧文件，还原出新文件
+pub fn patch(old: &[u8], patch: &[u8]) -> Result<Vec<u8>> {
+    ensure!(
+        patch.len() >= HEADER_LEN && &patch[..PATCH_MAGIC.len()] == PATCH_MAGIC,
+        "补丁文件头无效"
+    );
+    let control_len = read_offset(&patch[8..16]);
+    let diff_len = read_offset(&patch[16..24]);
+    let new_size = read_offset(&patch[24..32]);
+    ensure!(
+        control_len >= 0
+            && diff_len >= 0
+            && new_size >= 0
+            && (HEADER_LEN as i64)
+                .checked_add(control_len)
+                .and_then(|value| value.checked_add(diff_len))
+                .is_some_and(|value| value <= patch.len() as i64),
+        "补丁文件头长度字段无效"
+    );
+    let diff_start = HEADER_LEN + control_len as usize;
+    let extra_start = diff_start + diff_len as usize;
+    let control_block = bunzip2(&patch[HEADER_LEN..diff_start]).context("解压控制块失败")?;
+    let diff_block = bunzip2(&patch[diff_start..extra_start]).context("解压差异块失败")?;
+    let extra_block = bunzip2(&patch[extra_start..]).context("解压新增块失败")?;
+    ensure!(
+        control_block.len().is_multiple_of(CONTROL_ENTRY_LEN),
+        "补丁控制块长度无效"
+    );
+
+    let new_size = new_size as usize;
+    let mut new = Vec::new();
+    let (mut old_pos, mut diff_pos, mut extra_pos) = (0i64, 0usize, 0usize);
+    for entry in control_block.chunks_exact(CONTROL_ENTRY_LEN) {
+        let diff_len = read_offset(&entry[0..8]);
+        let extra_len = read_offset(&entry[8..16]);
+        let seek = read_offset(&entry[16..24]);
+        ensure!(diff_len >= 0 && extra_len >= 0, "补丁控制数据无效");
+        let (diff_len, extra_len) = (diff_len as usize, extra_len as usize);
+        ensure!(
+            new.len() + diff_len <= new_size && diff_pos + diff_len <= diff_block.len(),
+            "补丁差异数据越界"
+        );
+        for (offset, byte) in diff_block[diff_pos..diff_pos + diff_len].iter().enumerate() {
+            let old_index = old_pos + offset as i64;
+            new.push(if old_index >= 0 && old_index < old.len() as i64 {
+                byte.wrapping_add(old[old_index as usize])
+            } else {
+                *byte
+            });
+        }
+        diff_pos += diff_len;
+        old_pos += diff_len as i64;
+
+        ensure!(
+            new.len() + extra_len <= new_size && extra_pos + extra_len <= extra_block.len(),
+            "补丁新增数据越界"
+        );
+        new.extend_from_slice(&extra_block[extra_pos..extra_pos + extra_len]);
+        extra_pos += extra_len;
+        old_pos = old_pos.checked_add(seek).context("补丁控制数据无效")?;
+    }
+    if new.len() != new_size {
+        bail!("补丁还原的文件大小不一致");
+    }
+    Ok(new)
+}
+
+// 构造后缀数组（Larsson-Sadakane qsufsort），结果包含末尾的空后缀
+fn qsufsort(old: &[u8]) -> Vec<i32> {
+    let old_size = old.len();
+    let mut buckets = [0usize; 256];
+    let mut suffixes = vec![0i32; old_size + 1];
+    let mut groups = vec![0i32; old_size + 1];
+
+    for byte in old {
+        buckets[*byte as usize] += 1;
+    }
+    for i in 1..256 {
+        buckets[i] += buckets[i - 1];
+    }
+    buckets.copy_within(0..255, 1);
+    buckets[0] = 0;
+
+    for (i, byte) in old.iter().enumerate() {
+        buckets[*byte as usize] += 1;
+        suffixes[buckets[*byte as usize]] = i as i32;
+    }
+    suffixes[0] = old_size as i32;
+    for (i, byte) in old.iter().enumerate() {
+        groups[i] = buckets[*byte as usize] as i32;
+    }
+    groups[old_size] = 0;
+    for i in 1..256 {
+        if buckets[i] == buckets[i - 1] + 1 {
+            suffixes[buckets[i]] = -1;
+        }
+    }
+    suffixes[0] = -1;
+
+    let mut h = 1;
+    while suffixes[0] != -(old_size as i32 + 1) {
+        let mut sorted_len = 0usize;
+        let mut i = 0usize;
+        while i < old_size + 1 {
+            if suffixes[i] < 0 {
+                let run = (-suffixes[i]) as usize;
+                sorted_len += run;
+                i += run;
+            } else {
+                if sorted_len > 0 {
+                    suffixes[i - sorted_len] = -(sorted_len as i32);
+                }
+                let group_len = groups[suffixes[i] as usize] as usize + 1 - i;
+                split(&mut suffixes, &mut groups, i, group_len, h);
+                i += group_len;
+                sorted_len = 0;
+            }
+        }
+        if sorted_len > 0 {
+            suffixes[i - sorted_len] = -(sorted_len as i32);
+        }
+        h += h;
+    }
+
+    for (i, group) in groups.iter().enumerate() {
+        suffixes[*group as usize] = i as i32;
+    }
+    suffixes
+}
+
+// 按第 h 个字符后的分组号对一组后缀做三路快速排序
+fn split(suffixes: &mut [i32], groups: &mut [i32], start: usize, len: usize, h: usize) {
+    let key = |suffixes: &[i32], groups: &[i32], index: usize| groups[suffixes[index] as usize + h];
+
+    if len < 16 {
+        let mut k = start;
+        while k < start + len {
+            let mut j = 1;
+            let mut x = key(suffixes, groups, k);
+            let mut i = 1;
+            while k + i < start + len {
+                let value = key(suffixes, groups, k + i);
+                if value < x {
+                    x = value;
+                    j = 0;
+                }
+                if value == x {
+                    suffixes.swap(k + j, k + i);
+                    j += 1;
+                }
+                i += 1;
+            }
+            for i in 0..j {
+                groups[suffixes[k + i] as usize] = (k + j - 1) as i32;
+            }
+            if j == 1 {
+                suffixes[k] = -1;
+            }
+            k += j;
+        }
+        return;
+    }
+
+    let x = key(suffixes, groups, start + len / 2);
+    let (mut less, mut equal) = (0, 0);
+    for i in start..start + len {
+        let value = key(suffixes, groups, i);
+        if value < x {
+            less += 1;
+        }
+        if value == x {
+            equal += 1;
+        }
+    }
+    let jj = start + less;
+    let kk = jj + equal;
+
+    let (mut i, mut j, mut k) = (start, 0, 0);
+    while i < jj {
+        let value = key(suffixes, groups, i);
+        if value < x {
+            i += 1;
+        } else if value == x {
+            suffixes.swap(i, jj + j);
+            j += 1;
+        } else {
+            suffixes.swap(i, kk + k);
+            k += 1;
+        }
+    }
+    while jj + j < kk {
+        if key(suffixes, groups, jj + j) == x {
+            j += 1;
+        } else {
+            suffixes.swap(jj + j, kk + k);
+            k += 1;
+        }
+    }
+
+    if jj > start {
+        split(suffixes, groups, start, jj - start, h);
+    }
+    for i in 0..kk - jj {
+        groups[suffixes[jj + i] as usize] = (kk - 1) as i32;
+    }
+    if jj == kk - 1 {
+        suffixes[jj] = -1;
+    }
+    if start + len > kk {
+        split(suffixes, groups, kk, start + len - kk, h);
+    }
+}
+
+// 在后缀数组中二分查找与 new 前缀匹配最长的旧文件位置，返回（位置，匹配长度）
+fn search(suffix_array: &[i32], old: &[u8], new: &[u8]) -> (usize, usize) {
+    let (mut start, mut end) = (0, old.len());
+    while end - start >= 2 {
+        let middle = start + (end - start) / 2;
+        let pos = suffix_array[middle] as usize;
+        let compare_len = (old.len() - pos).min(new.len());
+        if old[pos..pos + compare_len] < new[..compare_len] {
+            start = middle;
+        } else {
+            end = middle;
+        }
+    }
+
+    let start_pos = suffix_array[start] as usize;
+    let end_pos = suffix_array[end] as usize;
+    let start_len = match_len(&old[start_pos..], new);
+    let end_len = match_len(&old[end_pos..], new);
+    if start_len > end_len {
+        (start_pos, start_len)
+    } else {
+        (end_pos, end_len)
+    }
+}
+
+fn match_len(old: &[u8], new: &[u8]) -> usize {
+    old.iter()
+        .zip(new)
+        .take_while(|(left, right)| left == right)
+        .count()
+}
+
+// 8 字节小端整数，最高位为符号位（与 bsdiff 一致）
+fn write_offset(buf: &mut Vec<u8>, value: i64) {
+    let mut bytes = value.unsigned_abs().to_le_bytes();
+    if value < 0 {
+        bytes[7] |= 0x80;
+    }
+    buf.extend_from_slice(&bytes);
+}
+
+fn read_offset(bytes: &[u8]) -> i64 {
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&bytes[..8]);
+    let is_negative = value[7] & 0x80 != 0;
+    value[7] &= 0x7f;
+    let magnitude = i64::from_le_bytes(value);
+    if is_negative { -magnitude } else { magnitude }
+}
+
+fn bzip2(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = BzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+fn bunzip2(data: &[u8]) -> Result<Vec<u8>> {
+    let mut decoded = Vec::new();
+    BzDecoder::new(data).read_to_end(&mut decoded)?;
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patch_restores_new_file_from_old() {
+        // 伪随机数据模拟压缩后的 APK 内容
+        let mut seed = 7u32;
+        let old: Vec<u8> = (0..20_000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (seed >> 16) as u8
+            })
+            .collect();
+        let mut new = old.clone();
+        new[100..140].copy_from_slice(&[0xAB; 40]);
+        new.splice(5_000..5_000, b"inserted-bytes".iter().copied());
+        new.drain(12_000..12_500);
+        new.extend_from_slice(&old[..3_000]);
+
+        let delta = diff(&old, &new).unwrap();
+        assert_eq!(&delta[..8], PATCH_MAGIC);
+        assert!(delta.len() < new.len() / 4);
+        assert_eq!(patch(&old, &delta).unwrap(), new);
+
+        assert_eq!(patch(&[], &diff(&[], &new).unwrap()).unwrap(), new);
+        assert_eq!(
+            patch(&old, &diff(&old, &[]).unwrap()).unwrap(),
+            Vec::<u8>::new()
+        );
+        assert!(patch(&old, &delta[..HEADER_LEN - 1]).is_err());
+        let mut corrupted = delta.clone();
+        corrupted[24] ^= 0x01;
+        assert!(patch(&old, &corrupted).is_err());
+
+        for value in [0, 1, -1, i64::from(i32::MAX), -12_345_678_901] {
+            let mut buf = Vec::new();
+            write_offset(&mut buf, value);
+            assert_eq!(read_offset(&buf), value);
+        }
+    }
+}
@@ -1,10 +1,15 @@
 use crate::db::DbPool;
+use crate::model::api_token::ApiTokenScope;
 use crate::model::error::AppError;
+use crate::model::organization::CurrentOrganization;
+use crate::model::user_role::CurrentRoles;
+use crate::model::user_session::CurrentSession;
 use crate::model::users::User;
 use diesel::PgConnection;
 use diesel::r2d2::{ConnectionManager, PooledConnection};
 use salvo::Depot;
 use std::sync::Arc;
+use uuid::Uuid;
 
 //连接数据库
 pub fn connect_database(depot: &mut Depot) -> PooledConnection<ConnectionManager<PgConnection>> {
@@ -27,3 +32,29 @@ pub fn current_user(depot: &mut Depot) -> Result<User, AppError> {
         .cloned()
         .map_err(|_| AppError::UnAuthorized("未找到当前登录用户".to_string()))
 }
+
+// 当前登录用户的角色，由鉴权中间件写入
+pub fn current_user_roles(depot: &mut Depot) -> CurrentRoles {
+    depot.obtain::<CurrentRoles>().cloned().unwrap_or_default()
+}
+
+// 当前请求所在的组织，由鉴权中间件写入
+pub fn current_organization(depot: &mut Depot) -> Result<CurrentOrganization, AppError> {
+    depot
+        .obtain::<CurrentOrganization>()
+        .cloned()
+        .map_err(|_| AppError::FORBIDDEN("未找到当前组织".to_string()))
+}
+
+// 当前请求使用的API令牌权限范围，使用登录Token时为 None
+pub fn current_api_token_scope(depot: &mut Depot) -> Option<ApiTokenScope> {
+    depot.obtain::<ApiTokenScope>().ok().cloned()
+}
+
+// 当前请求所在的登录会话ID，使用API令牌时为 None
+pub fn current_session_id(depot: &mut Depot) -> Option<Uuid> {
+    depot
+        .obtain::<CurrentSession>()
+        .ok()
+        .map(|session| session.0)
+}
@@ -0,0 +1,548 @@
+use crate::db::DbPool;
+use crate::model::app_manage::{AppDeltaPatch, DeltaPatchStatus};
+use crate::schema::{app_delta_patch, app_manage};
+use crate::store::{FileStore, read_all};
+use crate::utils::blob_store_utils::{
+    BLOB_KIND_APK, BLOB_KIND_PATCH, add_blob_references, blob_key, store_blob_bytes,
+};
+use crate::utils::bsdiff_utils;
+use crate::utils::file_digest_utils::FileDigest;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+// `DELTA_PATCH_CHECK_INTERVAL_SECS`：检查待生成补丁的间隔秒数，默认 60 秒
+const DEFAULT_CHECK_INTERVAL_SECS: u64 = 60;
+// `DELTA_PATCH_BASE_COUNT`：为每个版本生成补丁的历史版本数，默认 3 个
+const DEFAULT_BASE_COUNT: usize = 3;
+// `DELTA_PATCH_MAX_FILE_SIZE`：参与差分的 APK 最大字节数，默认 100MB；差分需要约 10 倍于旧文件的内存
+const DEFAULT_MAX_FILE_SIZE: u64 = 100 * 1024 * 1024;
+// 生成中的补丁超过该时间未完成视为进程中断，按失败处理后重试
+const PENDING_TIMEOUT_MINUTES: i64 = 60;
+// 生成失败后首次重试的间隔分钟数，之后每次失败翻倍，最长 1 天
+const RETRY_BASE_MINUTES: i64 = 5;
+const MAX_RETRY_MINUTES: i64 = 24 * 60;
+// 连续失败达到该次数后不再重试
+const MAX_FAILURE_COUNT: i32 = 8;
+const PUBLIC_APK_URL_PREFIX: &str = "/api/public/app_manage/apk?name=";
+
+#[derive(Debug, Clone, Copy)]
+struct DeltaPatchConfig {
+    base_count: usize,
+    max_file_size: u64,
+}
+
+/// 参与差分的版本
+#[derive(Debug, Clone)]
+struct PatchRelease {
+    id: Uuid,
+    package_name: String,
+    channel_id: Uuid,
+    version_code: i64,
+    apk_file_name: String,
+    is_published: bool,
+    is_revoked: bool,
+    create_time: NaiveDateTime,
+}
+
+enum PatchOutcome {
+    Ready {
+        base_file_sha256: String,
+        file_name: String,
+        patch_sha256: String,
+        patch_size: i64,
+    },
+    Skipped(String),
+}
+
+// 需在 tokio 运行时中调用，生成线程通过运行时句柄访问文件存储
+pub fn start_delta_patch_task(pool: Arc<DbPool>, file_store: Arc<dyn FileStore>) {
+    let interval = env::var("DELTA_PATCH_CHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_CHECK_INTERVAL_SECS);
+    let config = DeltaPatchConfig {
+        base_count: env::var("DELTA_PATCH_BASE_COUNT")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_BASE_COUNT),
+        max_file_size: env::var("DELTA_PATCH_MAX_FILE_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(DEFAULT_MAX_FILE_SIZE),
+    };
+    if config.base_count == 0 {
+        info!("DELTA_PATCH_BASE_COUNT 为 0，不生成差分补丁");
+        return;
+    }
+
+    let runtime = Handle::current();
+    thread::spawn(move || {
+        loop {
+            run_generate_once(&pool, file_store.as_ref(), &runtime, config);
+            thread::sleep(Duration::from_secs(interval));
+        }
+    });
+}
+
+fn run_generate_once(
+    pool: &Arc<DbPool>,
+    file_store: &dyn FileStore,
+    runtime: &Handle,
+    config: DeltaPatchConfig,
+) {
+    match generate_missing_patches(pool, file_store, runtime, config) {
+        Ok(0) => {}
+        Ok(count) => info!(processed = count, "差分补丁生成完成"),
+        Err(e) => error!(error = %e, "差分补丁生成失败"),
+    }
+}
+
+fn generate_missing_patches(
+    pool: &Arc<DbPool>,
+    file_store: &dyn FileStore,
+    runtime: &Handle,
+    config: DeltaPatchConfig,
+) -> anyhow::Result<usize> {
+    let mut conn = pool.get()?;
+    let now = chrono::Local::now().naive_local();
+    fail_stale_pending_patches(&mut conn, now)?;
+
+    let releases = load_patch_releases(&mut conn)?;
+    let existing_pairs: HashSet<(Uuid, Uuid)> = app_delta_patch::table
+        .select((app_delta_patch::app_id, app_delta_patch::base_app_id))
+        .load::<(Uuid, Uuid)>(&mut conn)?
+        .into_iter()
+        .collect();
+    // 已到重试时间的失败补丁及其失败次数
+    let retry_pairs: HashMap<(Uuid, Uuid), i32> = app_delta_patch::table
+        .filter(app_delta_patch::status.eq(DeltaPatchStatus::Failed.as_str()))
+        .filter(app_delta_patch::next_retry_time.le(now))
+        .select((
+            app_delta_patch::app_id,
+            app_delta_patch::base_app_id,
+            app_delta_patch::failure_count,
+        ))
+        .load::<(Uuid, Uuid, i32)>(&mut conn)?
+        .into_iter()
+        .map(|(app_id, base_app_id, failure_count)| ((app_id, base_app_id), failure_count))
+        .collect();
+
+    let mut processed_count = 0;
+    for (target, base) in select_patch_pairs(&releases, config.base_count) {
+        let failure_count = retry_pairs.get(&(target.id, base.id)).copied();
+        if failure_count.is_none() && existing_pairs.contains(&(target.id, base.id)) {
+            continue;
+        }
+        // 抢占生成任务，多个实例同时运行时只有一方生成
+        let now = chrono::Local::now().naive_local();
+        let pair = app_delta_patch::table
+            .filter(app_delta_patch::app_id.eq(target.id))
+            .filter(app_delta_patch::base_app_id.eq(base.id));
+        let claimed = match failure_count {
+            Some(_) => diesel::update(
+                pair.filter(app_delta_patch::status.eq(DeltaPatchStatus::Failed.as_str()))
+                    .filter(app_delta_patch::next_retry_time.le(now)),
+            )
+            .set((
+                app_delta_patch::status.eq(DeltaPatchStatus::Pending.as_str()),
+                app_delta_patch::update_time.eq(now),
+            ))
+            .execute(&mut conn)?,
+            None => diesel::insert_into(app_delta_patch::table)
+                .values(&AppDeltaPatch {
+                    id: Uuid::new_v4(),
+                    app_id: target.id,
+                    base_app_id: base.id,
+                    status: DeltaPatchStatus::Pending.as_str().to_string(),
+                    base_file_sha256: None,
+                    file_name: None,
+                    patch_sha256: None,
+                    patch_size: None,
+                    error_msg: None,
+                    create_time: now,
+                    update_time: now,
+                    failure_count: 0,
+                    next_retry_time: None,
+                })
+                .on_conflict_do_nothing()
+                .execute(&mut conn)?,
+        };
+        if claimed == 0 {
+            continue;
+        }
+
+        let outcome = runtime.block_on(generate_patch(
+            &mut conn,
+            file_store,
+            &base.apk_file_name,
+            &target.apk_file_name,
+            config.max_file_size,
+        ));
+        let now = chrono::Local::now().naive_local();
+        match outcome {
+            Ok(PatchOutcome::Ready {
+                base_file_sha256,
+                file_name,
+                patch_sha256,
+                patch_size,
+            }) => {
+                // 补丁按目标版本引用，锁定目标版本避免与删除版本交错；目标版本已删除时不再引用
+                conn.transaction::<_, anyhow::Error, _>(|conn| {
+                    let target_exists = app_manage::table
+                        .find(target.id)
+                        .filter(app_manage::is_delete.eq(false))
+                        .select(app_manage::id)
+                        .for_share()
+                        .first::<Uuid>(conn)
+                        .optional()?
+                        .is_some();
+                    if !target_exists {
+                        diesel::delete(pair).execute(conn)?;
+                        return Ok(());
+                    }
+                    diesel::update(pair)
+                        .set((
+                            app_delta_patch::status.eq(DeltaPatchStatus::Ready.as_str()),
+                            app_delta_patch::base_file_sha256.eq(Some(base_file_sha256)),
+                            app_delta_patch::file_name.eq(Some(&file_name)),
+                            app_delta_patch::patch_sha256.eq(Some(patch_sha256)),
+                            app_delta_patch::patch_size.eq(Some(patch_size)),
+                            app_delta_patch::update_time.eq(now),
+                        ))
+                        .execute(conn)?;
+                    add_blob_references(conn, BLOB_KIND_PATCH, std::slice::from_ref(&file_name))?;
+                    Ok(())
+                })?;
+                info!(
+                    app_id = %target.id,
+                    base_app_id = %base.id,
+                    patch_size,
+                    "生成差分补丁 {}",
+                    file_name
+                );
+            }
+            Ok(PatchOutcome::Skipped(reason)) => {
+                diesel::update(pair)
+                    .set((
+                        app_delta_patch::status.eq(DeltaPatchStatus::Skipped.as_str()),
+                        app_delta_patch::error_msg.eq(Some(&reason)),
+                        app_delta_patch::update_time.eq(now),
+                    ))
+                    .execute(&mut conn)?;
+                info!(app_id = %target.id, base_app_id = %base.id, reason = %reason, "跳过差分补丁");
+            }
+            Err(e) => {
+                let failure_count = failure_count.unwrap_or(0) + 1;
+                let next_retry_time = retry_delay(failure_count).map(|delay| now + delay);
+                diesel::update(pair)
+                    .set((
+                        app_delta_patch::status.eq(DeltaPatchStatus::Failed.as_str()),
+                        app_delta_patch::error_msg.eq(Some(e.to_string())),
+                        app_delta_patch::failure_count.eq(failure_count),
+                        app_delta_patch::next_retry_time.eq(next_retry_time),
+                        app_delta_patch::update_time.eq(now),
+                    ))
+                    .execute(&mut conn)?;
+                warn!(
+                    app_id = %target.id,
+                    base_app_id = %base.id,
+                    failure_count,
+                    next_retry_time = ?next_retry_time,
+                    error = %e,
+                    "生成差分补丁失败"
+                );
+            }
+        }
+        processed_count += 1;
+    }
+
+    Ok(processed_count)
+}
+
+// 生成中超时的补丁按失败处理，累计失败次数并按退避时间重试，避免导致进程中断的补丁无限重试
+fn fail_stale_pending_patches(conn: &mut PgConnection, now: NaiveDateTime) -> anyhow::Result<()> {
+    let stale_before = now - chrono::Duration::minutes(PENDING_TIMEOUT_MINUTES);
+    let stale_patches = app_delta_patch::table
+        .filter(app_delta_patch::status.eq(DeltaPatchStatus::Pending.as_str()))
+        .filter(app_delta_patch::update_time.lt(stale_before))
+        .select((
+            app_delta_patch::id,
+            app_delta_patch::app_id,
+            app_delta_patch::base_app_id,
+            app_delta_patch::failure_count,
+        ))
+        .load::<(Uuid, Uuid, Uuid, i32)>(conn)?;
+    for (id, app_id, base_app_id, failure_count) in stale_patches {
+        let failure_count = failure_count + 1;
+        let next_retry_time = retry_delay(failure_count).map(|delay| now + delay);
+        // 仅更新仍处于超时状态的记录，其他实例已处理时跳过
+        diesel::update(
+            app_delta_patch::table
+                .find(id)
+                .filter(app_delta_patch::status.eq(DeltaPatchStatus::Pending.as_str()))
+                .filter(app_delta_patch::update_time.lt(stale_before)),
+        )
+        .set((
+            app_delta_patch::status.eq(DeltaPatchStatus::Failed.as_str()),
+            app_delta_patch::error_msg.eq(Some("生成补丁超时，进程可能已中断")),
+            app_delta_patch::failure_count.eq(failure_count),
+            app_delta_patch::next_retry_time.eq(next_retry_time),
+            app_delta_patch::update_time.eq(now),
+        ))
+        .execute(conn)?;
+        warn!(
+            app_id = %app_id,
+            base_app_id = %base_app_id,
+            failure_count,
+            next_retry_time = ?next_retry_time,
+            "生成差分补丁超时"
+        );
+    }
+    Ok(())
+}
+
+// 第 N 次失败后的重试间隔，达到重试上限时返回 None
+fn retry_delay(failure_count: i32) -> Option<chrono::Duration> {
+    if failure_count >= MAX_FAILURE_COUNT {
+        return None;
+    }
+    let exponent = (failure_count - 1).clamp(0, 30) as u32;
+    let minutes = RETRY_BASE_MINUTES
+        .saturating_mul(2i64.pow(exponent))
+        .min(MAX_RETRY_MINUTES);
+    Some(chrono::Duration::minutes(minutes))
+}
+
+// 查询可参与差分的版本：未删除、可检查更新、以单个 APK 发布
+fn load_patch_releases(conn: &mut PgConnection) -> anyhow::Result<Vec<PatchRelease>> {
+    let rows = app_manage::table
+        .filter(app_manage::is_delete.eq(false))
+        .filter(app_manage::is_archive_only.eq(false))
+        .filter(app_manage::splits.is_null())
+        .select((
+            app_manage::id,
+            app_manage::package_name,
+            app_manage::channel_id,
+            app_manage::version_code,
+            app_manage::app_download_url,
+            app_manage::is_published,
+            app_manage::is_revoked,
+            app_manage::create_time,
+        ))
+        .load::<(
+            Uuid,
+            Option<String>,
+            Uuid,
+            String,
+            String,
+            bool,
+            bool,
+            NaiveDateTime,
+        )>(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(
+            |(
+                id,
+                package_name,
+                channel_id,
+                version_code,
+                app_download_url,
+                is_published,
+                is_revoked,
+                create_time,
+            )| {
+                Some(PatchRelease {
+                    id,
+                    package_name: package_name?,
+                    channel_id,
+                    version_code: version_code.trim().parse::<i64>().ok()?,
+                    apk_file_name: apk_file_name(&app_download_url)?.to_string(),
+                    is_published,
+                    is_revoked,
+                    create_time,
+                })
+            },
+        )
+        .collect())
+}
+
+// 为每个未撤回的版本选出同一包名、同一渠道中版本号更低的最近 N 个已发布版本作为补丁基础版本
+fn select_patch_pairs(
+    releases: &[PatchRelease],
+    base_count: usize,
+) -> Vec<(&PatchRelease, &PatchRelease)> {
+    let mut groups: HashMap<(&str, Uuid), Vec<&PatchRelease>> = HashMap::new();
+    for release in releases {
+        groups
+            .entry((release.package_name.as_str(), release.channel_id))
+            .or_default()
+            .push(release);
+    }
+
+    let mut pairs = Vec::new();
+    for group in groups.values_mut() {
+        // 新版本在前，同一版本号重复上传时取最新的记录
+        group.sort_by_key(|release| std::cmp::Reverse((release.version_code, release.create_time)));
+        for target in group.iter().filter(|release| !release.is_revoked) {
+            let mut base_files: HashSet<&str> = HashSet::new();
+            let mut base_version_codes: HashSet<i64> = HashSet::new();
+            for base in group.iter() {
+                if base_version_codes.len() >= base_count {
+                    break;
+                }
+                if base.version_code >= target.version_code
+                    || !base.is_published
+                    || base.apk_file_name == target.apk_file_name
+                    || !base_files.insert(base.apk_file_name.as_str())
+                {
+                    continue;
+                }
+                base_version_codes.insert(base.version_code);
+                pairs.push((*target, *base));
+            }
+        }
+    }
+    pairs
+}
+
+// 下载基础版本和目标版本的 APK 生成补丁，生成后还原校验一次再写入存储
+async fn generate_patch(
+    conn: &mut PgConnection,
+    file_store: &dyn FileStore,
+    base_file_name: &str,
+    target_file_name: &str,
+    max_file_size: u64,
+) -> anyhow::Result<PatchOutcome> {
+    let base_key = blob_key(BLOB_KIND_APK, base_file_name);
+    let target_key = blob_key(BLOB_KIND_APK, target_file_name);
+    for key in [&base_key, &target_key] {
+        let Some(info) = file_store.head(key).await? else {
+            anyhow::bail!("文件不存在: {}", key);
+        };
+        if info.size > max_file_size {
+            return Ok(PatchOutcome::Skipped(format!(
+                "文件 {} 大小 {} 字节超过差分上限 {} 字节",
+                key, info.size, max_file_size
+            )));
+        }
+    }
+
+    let old = read_all(file_store, &base_key)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("文件不存在: {}", base_key))?;
+    let new = read_all(file_store, &target_key)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("文件不存在: {}", target_key))?;
+    let (patch, old, new) = tokio::task::spawn_blocking(move || {
+        bsdiff_utils::diff(&old, &new).map(|patch| (patch, old, new))
+    })
+    .await??;
+    if patch.len() >= new.len() {
+        return Ok(PatchOutcome::Skipped(format!(
+            "补丁大小 {} 字节不小于完整安装包 {} 字节",
+            patch.len(),
+            new.len()
+        )));
+    }
+    if bsdiff_utils::patch(&old, &patch)? != new {
+        anyhow::bail!("补丁还原校验失败");
+    }
+
+    let base_file_sha256 = FileDigest::of_bytes(&old).sha256;
+    let blob = store_blob_bytes(conn, file_store, BLOB_KIND_PATCH, patch, "patch", None).await?;
+    Ok(PatchOutcome::Ready {
+        base_file_sha256,
+        file_name: blob.file_name,
+        patch_sha256: blob.digest.sha256,
+        patch_size: blob.file_size as i64,
+    })
+}
+
+fn apk_file_name(app_download_url: &str) -> Option<&str> {
+    app_download_url
+        .strip_prefix(PUBLIC_APK_URL_PREFIX)
+        .and_then(|value| value.split('&').next())
+        .filter(|value| {
+            !value.is_empty()
+                && !value.contains('/')
+                && !value.contains('\\')
+                && !value.contains("..")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(version_code: i64, file: &str, is_published: bool) -> PatchRelease {
+        PatchRelease {
+            id: Uuid::new_v4(),
+            package_name: "com.example".to_string(),
+            channel_id: Uuid::nil(),
+            version_code,
+            apk_file_name: file.to_string(),
+            is_published,
+            is_revoked: false,
+            create_time: NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn select_patch_pairs_uses_previous_published_releases() {
+        let mut other_channel = release(1, "other.apk", true);
+        other_channel.channel_id = Uuid::new_v4();
+        let releases = vec![
+            release(1, "v1.apk", true),
+            release(2, "v2.apk", true),
+            release(3, "v3.apk", false),
+            release(4, "v4.apk", true),
+            release(5, "v4.apk", true),
+            other_channel,
+        ];
+
+        let pairs: Vec<(i64, i64)> = select_patch_pairs(&releases, 2)
+            .into_iter()
+            .map(|(target, base)| (target.version_code, base.version_code))
+            .collect();
+        assert!(pairs.contains(&(5, 2)) && pairs.contains(&(5, 1)));
+        assert!(pairs.contains(&(4, 2)) && pairs.contains(&(4, 1)));
+        // 定时发布的版本可作为目标提前生成补丁，但不作为基础版本
+        assert!(pairs.contains(&(3, 2)) && pairs.contains(&(3, 1)));
+        assert!(pairs.contains(&(2, 1)));
+        assert_eq!(pairs.len(), 7);
+        assert!(select_patch_pairs(&releases, 0).is_empty());
+
+        assert_eq!(
+            apk_file_name("/api/public/app_manage/apk?name=a.apk&expires=1"),
+            Some("a.apk")
+        );
+        assert_eq!(
+            apk_file_name("/api/public/app_manage/apk?name=../a.apk"),
+            None
+        );
+    }
+
+    #[test]
+    fn retry_delay_backs_off_until_limit() {
+        assert_eq!(retry_delay(1), Some(chrono::Duration::minutes(5)));
+        assert_eq!(retry_delay(2), Some(chrono::Duration::minutes(10)));
+        assert_eq!(retry_delay(4), Some(chrono::Duration::minutes(40)));
+        assert_eq!(
+            retry_delay(MAX_FAILURE_COUNT - 1),
+            Some(chrono::Duration::minutes(320))
+        );
+        assert_eq!(retry_delay(MAX_FAILURE_COUNT), None);
+    }
+}
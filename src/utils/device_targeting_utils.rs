@@ -0,0 +1,211 @@
+use crate::model::app_manage::{AppCheckUpdateReq, AppManage, TargetingRules};
+use crate::model::error::AppError;
+
+// 判断版本是否能安装到设备上并命中设备定向规则
+pub fn is_release_available_for_device(app: &AppManage, device: &AppCheckUpdateReq) -> bool {
+    is_installable_on_device(app, device) && matches_app_targeting_rules(app, device)
+}
+
+// 校验设备定向规则
+pub fn validate_targeting_rules(rules: &TargetingRules) -> Result<(), AppError> {
+    if rules.min_sdk_int.is_some_and(|value| value < 1)
+        || rules.max_sdk_int.is_some_and(|value| value < 1)
+    {
+        return Err(AppError::BadRequest("SDK版本必须大于0".to_string()));
+    }
+
+    if let (Some(min_sdk_int), Some(max_sdk_int)) = (rules.min_sdk_int, rules.max_sdk_int)
+        && min_sdk_int > max_sdk_int
+    {
+        return Err(AppError::BadRequest(
+            "最低SDK版本不能大于最高SDK版本".to_string(),
+        ));
+    }
+
+    let has_blank_value = [
+        &rules.abis,
+        &rules.manufacturers,
+        &rules.exclude_manufacturers,
+        &rules.models,
+        &rules.exclude_models,
+        &rules.locales,
+    ]
+    .iter()
+    .any(|values| values.iter().any(|value| value.trim().is_empty()));
+    if has_blank_value {
+        return Err(AppError::BadRequest("定向规则中不能包含空值".to_string()));
+    }
+
+    Ok(())
+}
+
+// 根据 APK 的 minSdkVersion 和原生库 ABI 判断能否安装，设备未上报的信息不做限制
+fn is_installable_on_device(app: &AppManage, device: &AppCheckUpdateReq) -> bool {
+    if let (Some(min_sdk_version), Some(sdk_int)) = (app.min_sdk_version, device.sdk_int)
+        && sdk_int < min_sdk_version
+    {
+        return false;
+    }
+
+    app.native_abis.is_empty()
+        || device.supported_abis.is_empty()
+        || contains_any_ignore_case(&app.native_abis, &device.supported_abis)
+}
+
+// 判断是否命中版本的设备定向规则，规则无法解析时不下发
+fn matches_app_targeting_rules(app: &AppManage, device: &AppCheckUpdateReq) -> bool {
+    let Some(value) = &app.targeting_rules else {
+        return true;
+    };
+
+    serde_json::from_value::<TargetingRules>(value.clone())
+        .is_ok_and(|rules| matches_targeting_rules(&rules, device))
+}
+
+// 判断设备是否满足定向规则，规则要求的信息设备未上报时视为不满足
+fn matches_targeting_rules(rules: &TargetingRules, device: &AppCheckUpdateReq) -> bool {
+    if rules.min_sdk_int.is_some() || rules.max_sdk_int.is_some() {
+        let Some(sdk_int) = device.sdk_int else {
+            return false;
+        };
+        if rules
+            .min_sdk_int
+            .is_some_and(|min_sdk_int| sdk_int < min_sdk_int)
+            || rules
+                .max_sdk_int
+                .is_some_and(|max_sdk_int| sdk_int > max_sdk_int)
+        {
+            return false;
+        }
+    }
+
+    if !rules.abis.is_empty() && !contains_any_ignore_case(&rules.abis, &device.supported_abis) {
+        return false;
+    }
+
+    matches_include_exclude(
+        &rules.manufacturers,
+        &rules.exclude_manufacturers,
+        device.manufacturer.as_deref(),
+    ) && matches_include_exclude(
+        &rules.models,
+        &rules.exclude_models,
+        device.model.as_deref(),
+    ) && matches_locale(&rules.locales, device.locale.as_deref())
+}
+
+// 白名单为空时不限制，黑名单命中时不下发
+fn matches_include_exclude(include: &[String], exclude: &[String], value: Option<&str>) -> bool {
+    let value = value.map(str::trim).filter(|value| !value.is_empty());
+    let in_list = |list: &[String]| {
+        value.is_some_and(|value| {
+            list.iter()
+                .any(|item| item.trim().eq_ignore_ascii_case(value))
+        })
+    };
+
+    (include.is_empty() || in_list(include)) && !in_list(exclude)
+}
+
+// 语言区域按前缀匹配，zh 可匹配 zh-CN 和 zh_TW
+fn matches_locale(locales: &[String], locale: Option<&str>) -> bool {
+    if locales.is_empty() {
+        return true;
+    }
+
+    let Some(locale) = locale
+        .map(normalize_locale)
+        .filter(|value| !value.is_empty())
+    else {
+        return false;
+    };
+
+    locales
+        .iter()
+        .map(|value| normalize_locale(value))
+        .any(|rule| {
+            locale == rule
+                || locale
+                    .strip_prefix(&rule)
+                    .is_some_and(|rest| rest.starts_with('-'))
+        })
+}
+
+fn normalize_locale(locale: &str) -> String {
+    locale.trim().replace('_', "-").to_ascii_lowercase()
+}
+
+fn contains_any_ignore_case(left: &[String], right: &[String]) -> bool {
+    left.iter().any(|a| {
+        right
+            .iter()
+            .any(|b| a.trim().eq_ignore_ascii_case(b.trim()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device() -> AppCheckUpdateReq {
+        AppCheckUpdateReq {
+            package_name: "com.example.demo".to_string(),
+            channel_name: "official".to_string(),
+            version_code: 1,
+            device_id: None,
+            sdk_int: Some(28),
+            supported_abis: vec!["arm64-v8a".to_string(), "armeabi-v7a".to_string()],
+            manufacturer: Some("Xiaomi".to_string()),
+            model: Some("M2012K11AC".to_string()),
+            locale: Some("zh_CN".to_string()),
+            screen_density: None,
+            current_file_sha256: None,
+        }
+    }
+
+    #[test]
+    fn matches_targeting_rules_checks_every_condition() {
+        let device = device();
+        let rules = TargetingRules {
+            min_sdk_int: Some(26),
+            manufacturers: vec!["xiaomi".to_string()],
+            locales: vec!["zh".to_string()],
+            ..Default::default()
+        };
+        assert!(matches_targeting_rules(&rules, &device));
+
+        let excluded = TargetingRules {
+            exclude_models: vec!["m2012k11ac".to_string()],
+            ..Default::default()
+        };
+        assert!(!matches_targeting_rules(&excluded, &device));
+
+        let too_new = TargetingRules {
+            max_sdk_int: Some(27),
+            ..Default::default()
+        };
+        assert!(!matches_targeting_rules(&too_new, &device));
+
+        let other_locale = TargetingRules {
+            locales: vec!["zh-TW".to_string(), "en".to_string()],
+            ..Default::default()
+        };
+        assert!(!matches_targeting_rules(&other_locale, &device));
+
+        // 规则要求的信息设备未上报时不下发
+        let mut unknown = device.clone();
+        unknown.sdk_int = None;
+        assert!(!matches_targeting_rules(&rules, &unknown));
+    }
+
+    #[test]
+    fn validate_targeting_rules_rejects_inverted_sdk_range() {
+        let rules = TargetingRules {
+            min_sdk_int: Some(30),
+            max_sdk_int: Some(28),
+            ..Default::default()
+        };
+        assert!(validate_targeting_rules(&rules).is_err());
+        assert!(validate_targeting_rules(&TargetingRules::default()).is_ok());
+    }
+}
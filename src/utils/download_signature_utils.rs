@@ -0,0 +1,294 @@
+use crate::schema::{app_channel, app_delta_patch, app_manage};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::env;
+
+// `DOWNLOAD_URL_EXPIRES_SECS`：私有渠道签名下载地址的有效期秒数，默认 1 小时
+const DEFAULT_DOWNLOAD_URL_EXPIRES_SECS: i64 = 60 * 60;
+const PUBLIC_APK_URL_PREFIX: &str = "/api/public/app_manage/apk?name=";
+const PUBLIC_PATCH_URL_PREFIX: &str = "/api/public/app_manage/patch?name=";
+// 绑定设备的下载地址需在下载请求头中携带检查更新时上报的设备ID
+pub const DEVICE_ID_HEADER: &str = "x-device-id";
+
+/// 签名下载地址对应的下载路由，签名只对签发时的路由有效
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadKind {
+    ///完整安装包
+    Apk,
+    ///差分补丁
+    Patch,
+}
+
+impl DownloadKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            DownloadKind::Apk => "apk",
+            DownloadKind::Patch => "patch",
+        }
+    }
+
+    fn url_prefix(self) -> &'static str {
+        match self {
+            DownloadKind::Apk => PUBLIC_APK_URL_PREFIX,
+            DownloadKind::Patch => PUBLIC_PATCH_URL_PREFIX,
+        }
+    }
+}
+
+/// 签名下载地址校验失败原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadSignatureError {
+    ///缺少签名或过期时间
+    Missing,
+    ///下载地址已过期
+    Expired,
+    ///签名不匹配（地址被篡改或设备ID不一致）
+    Invalid,
+}
+
+// 签名密钥，`DOWNLOAD_URL_SECRET` 未设置时使用 JWT 密钥
+pub fn download_url_secret() -> String {
+    env::var("DOWNLOAD_URL_SECRET")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| env::var("JWT_SECRET_KEY").expect("要在env中设置JWT_SECRET_KEY！"))
+}
+
+pub fn download_url_expires_secs() -> i64 {
+    env::var("DOWNLOAD_URL_EXPIRES_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_DOWNLOAD_URL_EXPIRES_SECS)
+}
+
+// 为公开 APK 和差分补丁地址追加过期时间和签名，其他地址原样返回
+pub fn sign_download_url(
+    secret: &str,
+    url: &str,
+    device_id: Option<&str>,
+    expires_at: i64,
+) -> String {
+    let Some((kind, file_name)) = [DownloadKind::Apk, DownloadKind::Patch]
+        .into_iter()
+        .find_map(|kind| Some((kind, url.strip_prefix(kind.url_prefix())?)))
+    else {
+        return url.to_string();
+    };
+    let signature = URL_SAFE_NO_PAD.encode(
+        download_mac(secret, kind, file_name, expires_at, device_id)
+            .finalize()
+            .into_bytes(),
+    );
+    format!("{url}&expires={expires_at}&signature={signature}")
+}
+
+// 校验签名下载地址，签名覆盖下载路由、文件名、过期时间和绑定的设备ID
+pub fn verify_download_signature(
+    secret: &str,
+    kind: DownloadKind,
+    file_name: &str,
+    expires_at: Option<i64>,
+    signature: Option<&str>,
+    device_id: Option<&str>,
+    now: i64,
+) -> Result<(), DownloadSignatureError> {
+    let (Some(expires_at), Some(signature)) = (expires_at, signature) else {
+        return Err(DownloadSignatureError::Missing);
+    };
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature)
+        .map_err(|_| DownloadSignatureError::Invalid)?;
+    download_mac(secret, kind, file_name, expires_at, device_id)
+        .verify_slice(&signature)
+        .map_err(|_| DownloadSignatureError::Invalid)?;
+    if expires_at < now {
+        return Err(DownloadSignatureError::Expired);
+    }
+    Ok(())
+}
+
+fn download_mac(
+    secret: &str,
+    kind: DownloadKind,
+    file_name: &str,
+    expires_at: i64,
+    device_id: Option<&str>,
+) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 支持任意长度的密钥");
+    mac.update(kind.as_str().as_bytes());
+    mac.update(b"\n");
+    mac.update(file_name.as_bytes());
+    mac.update(b"\n");
+    mac.update(expires_at.to_string().as_bytes());
+    mac.update(b"\n");
+    mac.update(device_id.unwrap_or_default().as_bytes());
+    mac
+}
+
+// 管理接口返回的下载地址按当前时间签名，未发布或私有渠道的文件也能下载
+pub fn sign_download_url_now(url: &str) -> String {
+    let expires_at = chrono::Local::now().timestamp() + download_url_expires_secs();
+    sign_download_url(&download_url_secret(), url, None, expires_at)
+}
+
+// APK 只有被公开渠道中已发布且未撤回的版本引用时才能直接下载，未发布、已删除、私有渠道的版本和渠道包都需要签名下载
+pub fn requires_signed_download(conn: &mut PgConnection, file_name: &str) -> QueryResult<bool> {
+    let url = format!("{PUBLIC_APK_URL_PREFIX}{file_name}");
+    let split_ref = serde_json::json!([{ "file_path": url }]);
+    let is_public = diesel::select(exists(
+        app_manage::table
+            .inner_join(app_channel::table)
+            .filter(
+                app_manage::app_download_url
+                    .eq(&url)
+                    .or(app_manage::splits.contains(split_ref)),
+            )
+            .filter(app_manage::is_delete.eq(false))
+            .filter(app_manage::is_published.eq(true))
+            .filter(app_manage::is_revoked.eq(false))
+            .filter(app_channel::is_private.eq(false))
+            .filter(app_channel::is_delete.eq(false)),
+    ))
+    .get_result::<bool>(conn)?;
+    Ok(!is_public)
+}
+
+// 差分补丁的目标版本是公开渠道中已发布且未撤回的版本时才能直接下载
+pub fn requires_signed_patch_download(
+    conn: &mut PgConnection,
+    file_name: &str,
+) -> QueryResult<bool> {
+    let is_public = diesel::select(exists(
+        app_delta_patch::table
+            .inner_join(app_manage::table.inner_join(app_channel::table))
+            .filter(app_delta_patch::file_name.eq(file_name))
+            .filter(app_manage::is_delete.eq(false))
+            .filter(app_manage::is_published.eq(true))
+            .filter(app_manage::is_revoked.eq(false))
+            .filter(app_channel::is_private.eq(false))
+            .filter(app_channel::is_delete.eq(false)),
+    ))
+    .get_result::<bool>(conn)?;
+    Ok(!is_public)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query_value<'a>(url: &'a str, key: &str) -> Option<&'a str> {
+        url.split(['?', '&'])
+            .find_map(|pair| pair.strip_prefix(key)?.strip_prefix('='))
+    }
+
+    #[test]
+    fn signed_url_rejects_expired_tampered_and_other_device() {
+        let url = sign_download_url(
+            "secret",
+            "/api/public/app_manage/apk?name=a.apk",
+            Some("device-1"),
+            1000,
+        );
+        let signature = query_value(&url, "signature");
+        assert_eq!(query_value(&url, "expires"), Some("1000"));
+
+        let verify = |name, expires, device, now| {
+            verify_download_signature(
+                "secret",
+                DownloadKind::Apk,
+                name,
+                Some(expires),
+                signature,
+                device,
+                now,
+            )
+        };
+        assert_eq!(verify("a.apk", 1000, Some("device-1"), 999), Ok(()));
+        assert_eq!(
+            verify("a.apk", 1000, Some("device-1"), 1001),
+            Err(DownloadSignatureError::Expired)
+        );
+        assert_eq!(
+            verify("a.apk", 2000, Some("device-1"), 999),
+            Err(DownloadSignatureError::Invalid)
+        );
+        assert_eq!(
+            verify("b.apk", 1000, Some("device-1"), 999),
+            Err(DownloadSignatureError::Invalid)
+        );
+        assert_eq!(
+            verify("a.apk", 1000, None, 999),
+            Err(DownloadSignatureError::Invalid)
+        );
+        assert_eq!(
+            verify_download_signature("secret", DownloadKind::Apk, "a.apk", None, None, None, 0),
+            Err(DownloadSignatureError::Missing)
+        );
+
+        let patch_url = sign_download_url(
+            "secret",
+            "/api/public/app_manage/patch?name=a.patch",
+            None,
+            1000,
+        );
+        assert_eq!(
+            verify_download_signature(
+                "secret",
+                DownloadKind::Patch,
+                "a.patch",
+                Some(1000),
+                query_value(&patch_url, "signature"),
+                None,
+                999
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            sign_download_url("secret", "/other?name=a", None, 1000),
+            "/other?name=a"
+        );
+    }
+
+    #[test]
+    fn signed_url_only_verifies_on_its_own_route() {
+        let verify = |kind, url: &str| {
+            verify_download_signature(
+                "secret",
+                kind,
+                "same-name",
+                Some(1000),
+                query_value(url, "signature"),
+                None,
+                999,
+            )
+        };
+        let apk_url = sign_download_url(
+            "secret",
+            "/api/public/app_manage/apk?name=same-name",
+            None,
+            1000,
+        );
+        let patch_url = sign_download_url(
+            "secret",
+            "/api/public/app_manage/patch?name=same-name",
+            None,
+            1000,
+        );
+        assert_eq!(verify(DownloadKind::Apk, &apk_url), Ok(()));
+        assert_eq!(verify(DownloadKind::Patch, &patch_url), Ok(()));
+        assert_eq!(
+            verify(DownloadKind::Patch, &apk_url),
+            Err(DownloadSignatureError::Invalid)
+        );
+        assert_eq!(
+            verify(DownloadKind::Apk, &patch_url),
+            Err(DownloadSignatureError::Invalid)
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use md5::Md5;
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+// 摘要缓存最多保存的文件数，超出时淘汰最久未使用的文件
+const FILE_DIGEST_CACHE_CAPACITY: usize = 1024;
+
+// 已计算的文件摘要，文件大小或修改时间变化后重新计算
+static FILE_DIGEST_CACHE: Lazy<Mutex<DigestCache>> =
+    Lazy::new(|| Mutex::new(DigestCache::new(FILE_DIGEST_CACHE_CAPACITY)));
+
+// 缓存项：文件大小、修改时间、对应的摘要及最近使用序号
+struct CachedDigest {
+    file_size: u64,
+    modified: SystemTime,
+    digest: FileDigest,
+    last_used: u64,
+}
+
+// 按最近使用淘汰的摘要缓存，容量较小，淘汰时直接遍历查找最久未使用的文件
+struct DigestCache {
+    capacity: usize,
+    entries: HashMap<PathBuf, CachedDigest>,
+    clock: u64,
+}
+
+impl DigestCache {
+    fn new(capacity: usize) -> Self {
+        DigestCache {
+            capacity,
+            entries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, path: &Path, file_size: u64, modified: SystemTime) -> Option<FileDigest> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(path)?;
+        if entry.file_size != file_size || entry.modified != modified {
+            return None;
+        }
+        entry.last_used = self.clock;
+        Some(entry.digest.clone())
+    }
+
+    fn insert(&mut self, path: &Path, file_size: u64, modified: SystemTime, digest: FileDigest) {
+        self.clock += 1;
+        if self.entries.len() >= self.capacity
+            && !self.entries.contains_key(path)
+            && let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(path, _)| path.clone())
+        {
+            self.entries.remove(&oldest);
+        }
+        self.entries.insert(
+            path.to_path_buf(),
+            CachedDigest {
+                file_size,
+                modified,
+                digest,
+                last_used: self.clock,
+            },
+        );
+    }
+}
+
+/// 文件内容摘要
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDigest {
+    /// SHA-256（小写十六进制）
+    pub sha256: String,
+    /// MD5（小写十六进制），供旧版客户端校验
+    pub md5: String,
+}
+
+impl FileDigest {
+    // 计算内存数据的摘要
+    pub fn of_bytes(data: &[u8]) -> Self {
+        FileDigest {
+            sha256: sha256_hex(data),
+            md5: to_hex(&Md5::digest(data)),
+        }
+    }
+
+    // ETag 响应头，使用 SHA-256 作为强校验值
+    pub fn etag(&self) -> String {
+        format!("\"{}\"", self.sha256)
+    }
+
+    // Digest 响应头（RFC 3230），摘要为 Base64 编码
+    pub fn digest_header(&self) -> String {
+        format!(
+            "SHA-256={}, MD5={}",
+            hex_to_base64(&self.sha256),
+            hex_to_base64(&self.md5)
+        )
+    }
+}
+
+// 分块读取文件计算摘要，避免大文件一次性读入内存
+pub fn compute_file_digest(path: &Path) -> Result<FileDigest> {
+    let mut file = File::open(path).with_context(|| format!("打开文件失败: {}", path.display()))?;
+    let mut sha256 = Sha256::new();
+    let mut md5 = Md5::new();
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    loop {
+        let len = file
+            .read(&mut buffer)
+            .with_context(|| format!("读取文件失败: {}", path.display()))?;
+        if len == 0 {
+            break;
+        }
+        sha256.update(&buffer[..len]);
+        md5.update(&buffer[..len]);
+    }
+
+    Ok(FileDigest {
+        sha256: to_hex(&sha256.finalize()),
+        md5: to_hex(&md5.finalize()),
+    })
+}
+
+// 读取文件摘要，同一文件未变化时复用已计算的结果
+pub fn cached_file_digest(path: &Path) -> Result<FileDigest> {
+    let metadata = path
+        .metadata()
+        .with_context(|| format!("读取文件信息失败: {}", path.display()))?;
+    let file_size = metadata.len();
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+
+    if let Ok(mut cache) = FILE_DIGEST_CACHE.lock()
+        && let Some(digest) = cache.get(path, file_size, modified)
+    {
+        return Ok(digest);
+    }
+
+    let digest = compute_file_digest(path)?;
+    if let Ok(mut cache) = FILE_DIGEST_CACHE.lock() {
+        cache.insert(path, file_size, modified, digest.clone());
+    }
+    Ok(digest)
+}
+
+// 只有 SHA-256 时的 Digest 响应头，如对象存储中按内容摘要命名的文件
+pub fn sha256_digest_header(sha256: &str) -> String {
+    format!("SHA-256={}", hex_to_base64(sha256))
+}
+
+// 内存数据的 SHA-256（小写十六进制），也用于只保存哈希的令牌和证书指纹
+pub fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hex_to_base64(hex: &str) -> String {
+    let bytes: Vec<u8> = (0..hex.len())
+        .step_by(2)
+        .filter_map(|index| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok())
+        .collect();
+    STANDARD.encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_digest_matches_known_values_and_headers() {
+        let digest = FileDigest::of_bytes(b"abc");
+        assert_eq!(
+            digest.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(digest.md5, "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(
+            digest.digest_header(),
+            "SHA-256=ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=, MD5=kAFQmDzST7DWlj99KOF/cg=="
+        );
+        assert_eq!(
+            sha256_digest_header(&digest.sha256),
+            "SHA-256=ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0="
+        );
+
+        let path = std::env::temp_dir().join(format!("digest_{}.bin", uuid::Uuid::new_v4()));
+        std::fs::write(&path, vec![b'a'; READ_BUFFER_SIZE + 3]).unwrap();
+        let file_digest = cached_file_digest(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            file_digest,
+            FileDigest::of_bytes(&vec![b'a'; READ_BUFFER_SIZE + 3])
+        );
+    }
+
+    #[test]
+    fn digest_cache_evicts_least_recently_used_file() {
+        let mut cache = DigestCache::new(2);
+        let modified = SystemTime::UNIX_EPOCH;
+        let digest = FileDigest::of_bytes(b"abc");
+        cache.insert(Path::new("a"), 3, modified, digest.clone());
+        cache.insert(Path::new("b"), 3, modified, digest.clone());
+        assert!(cache.get(Path::new("a"), 3, modified).is_some());
+
+        cache.insert(Path::new("c"), 3, modified, digest.clone());
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get(Path::new("b"), 3, modified).is_none());
+        assert!(cache.get(Path::new("a"), 3, modified).is_some());
+        // 文件大小变化后不使用缓存
+        assert!(cache.get(Path::new("c"), 4, modified).is_none());
+    }
+}
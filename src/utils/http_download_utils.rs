@@ -0,0 +1,310 @@
+use chrono::{DateTime, Utc};
+use salvo::http::HeaderMap;
+use salvo::http::header::{
+    IF_MATCH, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, IF_UNMODIFIED_SINCE, RANGE,
+};
+
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// 下载请求的响应方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadPlan {
+    ///返回完整文件（200）
+    Full,
+    ///返回部分内容（206），`start` 和 `end` 均包含在内
+    Partial { start: u64, end: u64 },
+    ///文件未修改（304）
+    NotModified,
+    ///前置条件不满足（412）
+    PreconditionFailed,
+    ///请求范围无法满足（416）
+    RangeNotSatisfiable,
+}
+
+/// 文件的缓存校验信息
+#[derive(Debug, Clone, Copy)]
+pub struct DownloadValidators<'a> {
+    ///强 ETag，含双引号，如 `"{SHA-256}"`
+    pub etag: Option<&'a str>,
+    ///最后修改时间
+    pub last_modified: Option<DateTime<Utc>>,
+    ///文件大小（字节）
+    pub size: u64,
+}
+
+// 按 RFC 9110 的顺序处理条件请求和范围请求：If-Match/If-Unmodified-Since → If-None-Match/If-Modified-Since → If-Range/Range
+pub fn plan_download(headers: &HeaderMap, validators: &DownloadValidators) -> DownloadPlan {
+    let last_modified = validators.last_modified.map(truncate_to_seconds);
+
+    if let Some(if_match) = header_str(headers, IF_MATCH.as_str()) {
+        if !matches_etag_list(if_match, validators.etag, true) {
+            return DownloadPlan::PreconditionFailed;
+        }
+    } else if let (Some(since), Some(last_modified)) = (
+        header_str(headers, IF_UNMODIFIED_SINCE.as_str()).and_then(parse_http_date),
+        last_modified,
+    ) && last_modified > since
+    {
+        return DownloadPlan::PreconditionFailed;
+    }
+
+    if let Some(if_none_match) = header_str(headers, IF_NONE_MATCH.as_str()) {
+        if matches_etag_list(if_none_match, validators.etag, false) {
+            return DownloadPlan::NotModified;
+        }
+    } else if let (Some(since), Some(last_modified)) = (
+        header_str(headers, IF_MODIFIED_SINCE.as_str()).and_then(parse_http_date),
+        last_modified,
+    ) && last_modified <= since
+    {
+        return DownloadPlan::NotModified;
+    }
+
+    let Some(range) = header_str(headers, RANGE.as_str()) else {
+        return DownloadPlan::Full;
+    };
+    // If-Range 不匹配时说明客户端已下载的部分已过期，返回完整文件
+    if let Some(if_range) = header_str(headers, IF_RANGE.as_str())
+        && !if_range_matches(if_range, validators.etag, last_modified)
+    {
+        return DownloadPlan::Full;
+    }
+    match parse_range(range, validators.size) {
+        Some(Ok((start, end))) => DownloadPlan::Partial { start, end },
+        Some(Err(())) => DownloadPlan::RangeNotSatisfiable,
+        None => DownloadPlan::Full,
+    }
+}
+
+// 解析单个字节范围：无法识别或包含多个范围时返回 None（按完整文件响应），范围超出文件大小时返回 Err
+fn parse_range(range: &str, size: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = range.trim().strip_prefix("bytes=")?.trim();
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+    if start.is_empty() {
+        // 后缀范围：最后 N 个字节
+        let suffix_len = end.parse::<u64>().ok()?;
+        if suffix_len == 0 || size == 0 {
+            return Some(Err(()));
+        }
+        return Some(Ok((size.saturating_sub(suffix_len), size - 1)));
+    }
+
+    let start = start.parse::<u64>().ok()?;
+    let end = if end.is_empty() {
+        None
+    } else {
+        Some(end.parse::<u64>().ok()?)
+    };
+    if end.is_some_and(|end| end < start) {
+        return None;
+    }
+    if start >= size {
+        return Some(Err(()));
+    }
+    Some(Ok((start, end.map_or(size - 1, |end| end.min(size - 1)))))
+}
+
+// If-Range 为 ETag 时需与当前 ETag 强匹配，为日期时需与最后修改时间完全一致
+fn if_range_matches(
+    if_range: &str,
+    etag: Option<&str>,
+    last_modified: Option<DateTime<Utc>>,
+) -> bool {
+    let if_range = if_range.trim();
+    if if_range.starts_with('"') || if_range.starts_with("W/") {
+        return matches_etag_list(if_range, etag, true) && !if_range.contains(',');
+    }
+    parse_http_date(if_range).is_some_and(|date| Some(date) == last_modified)
+}
+
+// 判断 ETag 列表（If-Match/If-None-Match）是否包含当前 ETag，`*` 匹配任意存在的文件
+fn matches_etag_list(header: &str, etag: Option<&str>, strong: bool) -> bool {
+    let Some((current_is_weak, current)) = etag.and_then(|etag| parse_etags(etag).pop()) else {
+        return false;
+    };
+    if header.trim() == "*" {
+        return true;
+    }
+    parse_etags(header)
+        .into_iter()
+        .any(|(is_weak, tag)| tag == current && !(strong && (is_weak || current_is_weak)))
+}
+
+// 解析逗号分隔的 ETag 列表，返回（是否弱校验，不含引号的标签）
+fn parse_etags(header: &str) -> Vec<(bool, &str)> {
+    let mut etags = Vec::new();
+    let mut rest = header;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_ascii_whitespace());
+        if rest.is_empty() {
+            break;
+        }
+        let (is_weak, tagged) = match rest.strip_prefix("W/") {
+            Some(tagged) => (true, tagged),
+            None => (false, rest),
+        };
+        let Some(quoted) = tagged.strip_prefix('"') else {
+            break;
+        };
+        let Some(end) = quoted.find('"') else {
+            break;
+        };
+        etags.push((is_weak, &quoted[..end]));
+        rest = &quoted[end + 1..];
+    }
+    etags
+}
+
+// HTTP 日期（IMF-fixdate），如 `Sun, 06 Nov 1994 08:49:37 GMT`
+pub fn format_http_date(time: DateTime<Utc>) -> String {
+    time.format(HTTP_DATE_FORMAT).to_string()
+}
+
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn truncate_to_seconds(time: DateTime<Utc>) -> DateTime<Utc> {
+    DateTime::from_timestamp(time.timestamp(), 0).unwrap_or(time)
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+// 以附件形式下载时的 Content-Disposition，非 ASCII 文件名按 RFC 6266 使用 `filename*` 编码
+pub fn attachment_disposition(file_name: &str) -> String {
+    let fallback: String = file_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let encoded: String = file_name
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+                (byte as char).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect();
+    format!("attachment; filename=\"{fallback}\"; filename*=UTF-8''{encoded}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use salvo::http::HeaderValue;
+
+    fn plan(headers: &[(&'static str, &str)]) -> DownloadPlan {
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            header_map.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        let validators = DownloadValidators {
+            etag: Some("\"abc\""),
+            last_modified: parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            size: 100,
+        };
+        plan_download(&header_map, &validators)
+    }
+
+    #[test]
+    fn plan_download_handles_range_and_conditional_headers() {
+        let partial = |start, end| DownloadPlan::Partial { start, end };
+        assert_eq!(plan(&[]), DownloadPlan::Full);
+        assert_eq!(plan(&[("range", "bytes=10-19")]), partial(10, 19));
+        assert_eq!(plan(&[("range", "bytes=90-")]), partial(90, 99));
+        assert_eq!(plan(&[("range", "bytes=-30")]), partial(70, 99));
+        assert_eq!(plan(&[("range", "bytes=50-500")]), partial(50, 99));
+        assert_eq!(plan(&[("range", "bytes=-500")]), partial(0, 99));
+        assert_eq!(
+            plan(&[("range", "bytes=100-")]),
+            DownloadPlan::RangeNotSatisfiable
+        );
+        assert_eq!(
+            plan(&[("range", "bytes=-0")]),
+            DownloadPlan::RangeNotSatisfiable
+        );
+        assert_eq!(plan(&[("range", "bytes=0-1,5-6")]), DownloadPlan::Full);
+        assert_eq!(plan(&[("range", "items=0-1")]), DownloadPlan::Full);
+        assert_eq!(plan(&[("range", "bytes=5-1")]), DownloadPlan::Full);
+
+        let if_range = |value| plan(&[("range", "bytes=10-19"), ("if-range", value)]);
+        assert_eq!(if_range("\"abc\""), partial(10, 19));
+        assert_eq!(if_range("W/\"abc\""), DownloadPlan::Full);
+        assert_eq!(if_range("\"old\""), DownloadPlan::Full);
+        assert_eq!(if_range("Sun, 06 Nov 1994 08:49:37 GMT"), partial(10, 19));
+        assert_eq!(
+            if_range("Sun, 06 Nov 1994 08:49:36 GMT"),
+            DownloadPlan::Full
+        );
+
+        assert_eq!(
+            plan(&[("if-none-match", "\"x\", W/\"abc\"")]),
+            DownloadPlan::NotModified
+        );
+        assert_eq!(plan(&[("if-none-match", "*")]), DownloadPlan::NotModified);
+        assert_eq!(plan(&[("if-none-match", "\"x\"")]), DownloadPlan::Full);
+        let modified_since = |value| plan(&[("if-modified-since", value)]);
+        assert_eq!(
+            modified_since("Sun, 06 Nov 1994 08:49:37 GMT"),
+            DownloadPlan::NotModified
+        );
+        assert_eq!(
+            modified_since("Sun, 06 Nov 1994 08:49:36 GMT"),
+            DownloadPlan::Full
+        );
+        assert_eq!(modified_since("not a date"), DownloadPlan::Full);
+        // If-None-Match 存在时忽略 If-Modified-Since
+        assert_eq!(
+            plan(&[
+                ("if-none-match", "\"x\""),
+                ("if-modified-since", "Sun, 06 Nov 1994 08:49:37 GMT")
+            ]),
+            DownloadPlan::Full
+        );
+        assert_eq!(
+            plan(&[("if-none-match", "\"abc\""), ("range", "bytes=1000-")]),
+            DownloadPlan::NotModified
+        );
+
+        assert_eq!(
+            plan(&[("if-match", "\"x\"")]),
+            DownloadPlan::PreconditionFailed
+        );
+        assert_eq!(
+            plan(&[("if-match", "W/\"abc\"")]),
+            DownloadPlan::PreconditionFailed
+        );
+        assert_eq!(
+            plan(&[("if-match", "\"abc\""), ("range", "bytes=0-0")]),
+            partial(0, 0)
+        );
+        assert_eq!(
+            plan(&[("if-unmodified-since", "Sat, 05 Nov 1994 08:49:37 GMT")]),
+            DownloadPlan::PreconditionFailed
+        );
+
+        assert_eq!(
+            format_http_date(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap()),
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        );
+        assert_eq!(
+            attachment_disposition("演示 v1.apk"),
+            "attachment; filename=\"__ v1.apk\"; filename*=UTF-8''%E6%BC%94%E7%A4%BA%20v1.apk"
+        );
+    }
+}
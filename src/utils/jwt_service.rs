@@ -5,12 +5,14 @@ use chrono::{Duration, Local};
 use jsonwebtoken::{
     DecodingKey, EncodingKey, Header, Validation, decode, encode, errors::ErrorKind,
 };
+use uuid::Uuid;
 // JWT工具函数
 
 //创建访问令牌
 pub fn generate_access_token(
     user_id: &str,
     user_name: &str,
+    session_id: &str,
 ) -> Result<String, jsonwebtoken::errors::Error> {
     let now = Local::now().naive_local();
     let expires_at = now + Duration::seconds(JWT_CONFIG.access_expires_in);
@@ -18,6 +20,7 @@ pub fn generate_access_token(
     let claims = AccessTokenClaims {
         user_name: user_name.to_string(),
         user_id: user_id.to_string(),
+        session_id: session_id.to_string(),
         exp: expires_at.and_utc().timestamp(),
         iat: now.and_utc().timestamp(),
         token_type: TokenType::Access,
@@ -34,6 +37,7 @@ pub fn generate_access_token(
 pub fn generate_refresh_token(
     user_id: &str,
     user_name: &str,
+    session_id: &str,
 ) -> Result<String, jsonwebtoken::errors::Error> {
     let now = Local::now().naive_local();
     let expires_at = now + Duration::seconds(JWT_CONFIG.refresh_expires_in);
@@ -41,8 +45,10 @@ pub fn generate_refresh_token(
     let claims = RefreshTokenClaims {
         user_name: user_name.to_string(),
         user_id: user_id.to_string(),
+        session_id: session_id.to_string(),
         exp: expires_at.and_utc().timestamp(),
         iat: now.and_utc().timestamp(),
+        jti: Uuid::new_v4().to_string(),
         token_type: TokenType::Refresh,
     };
 
@@ -61,10 +67,12 @@ pub fn refresh_access_token(
     let new_access_token = generate_access_token(
         &refresh_token_claims.user_id,
         &refresh_token_claims.user_name,
+        &refresh_token_claims.session_id,
     )?;
     let new_refresh_token = generate_refresh_token(
         &refresh_token_claims.user_id,
         &refresh_token_claims.user_name,
+        &refresh_token_claims.session_id,
     )?;
 
     Ok(RefreshTokenResp {
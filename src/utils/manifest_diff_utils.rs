@@ -0,0 +1,168 @@
+use crate::model::app_manage::{
+    AppManage, AppReleaseDiffResp, ComponentsDiff, FlagChange, ListDiff, ManifestComponents,
+    SdkVersionChange,
+};
+use std::collections::BTreeSet;
+
+// 运行时需要用户授权的危险权限（protectionLevel 为 dangerous）
+// 参考：https://developer.android.com/reference/android/Manifest.permission
+const DANGEROUS_PERMISSIONS: &[&str] = &[
+    "android.permission.ACCEPT_HANDOVER",
+    "android.permission.ACCESS_BACKGROUND_LOCATION",
+    "android.permission.ACCESS_COARSE_LOCATION",
+    "android.permission.ACCESS_FINE_LOCATION",
+    "android.permission.ACCESS_MEDIA_LOCATION",
+    "android.permission.ACTIVITY_RECOGNITION",
+    "android.permission.ANSWER_PHONE_CALLS",
+    "android.permission.BLUETOOTH_ADVERTISE",
+    "android.permission.BLUETOOTH_CONNECT",
+    "android.permission.BLUETOOTH_SCAN",
+    "android.permission.BODY_SENSORS",
+    "android.permission.BODY_SENSORS_BACKGROUND",
+    "android.permission.CALL_PHONE",
+    "android.permission.CAMERA",
+    "android.permission.GET_ACCOUNTS",
+    "android.permission.NEARBY_WIFI_DEVICES",
+    "android.permission.POST_NOTIFICATIONS",
+    "android.permission.PROCESS_OUTGOING_CALLS",
+    "android.permission.READ_CALENDAR",
+    "android.permission.READ_CALL_LOG",
+    "android.permission.READ_CONTACTS",
+    "android.permission.READ_EXTERNAL_STORAGE",
+    "android.permission.READ_MEDIA_AUDIO",
+    "android.permission.READ_MEDIA_IMAGES",
+    "android.permission.READ_MEDIA_VIDEO",
+    "android.permission.READ_MEDIA_VISUAL_USER_SELECTED",
+    "android.permission.READ_PHONE_NUMBERS",
+    "android.permission.READ_PHONE_STATE",
+    "android.permission.READ_SMS",
+    "android.permission.RECEIVE_MMS",
+    "android.permission.RECEIVE_SMS",
+    "android.permission.RECEIVE_WAP_PUSH",
+    "android.permission.RECORD_AUDIO",
+    "android.permission.SEND_SMS",
+    "android.permission.USE_SIP",
+    "android.permission.UWB_RANGING",
+    "android.permission.WRITE_CALENDAR",
+    "android.permission.WRITE_CALL_LOG",
+    "android.permission.WRITE_CONTACTS",
+    "android.permission.WRITE_EXTERNAL_STORAGE",
+    "com.android.voicemail.permission.ADD_VOICEMAIL",
+];
+
+// 判断是否为危险权限
+pub fn is_dangerous_permission(permission: &str) -> bool {
+    DANGEROUS_PERMISSIONS.contains(&permission.trim())
+}
+
+// 计算目标权限列表相比基准新增的危险权限
+pub fn added_dangerous_permissions(base: &[String], target: &[String]) -> Vec<String> {
+    diff_list(base, target)
+        .added
+        .into_iter()
+        .filter(|permission| is_dangerous_permission(permission))
+        .collect()
+}
+
+// 根据两个版本解析出的清单信息计算差异
+pub fn diff_releases(base: &AppManage, target: &AppManage) -> AppReleaseDiffResp {
+    let min_sdk_version = diff_sdk_version(base.min_sdk_version, target.min_sdk_version);
+    let min_sdk_raised = matches!(
+        (base.min_sdk_version, target.min_sdk_version),
+        (Some(from), Some(to)) if to > from
+    ) || (base.min_sdk_version.is_none() && target.min_sdk_version.is_some());
+
+    AppReleaseDiffResp {
+        base_app_id: base.id,
+        base_version_code: base.version_code.clone(),
+        target_app_id: target.id,
+        target_version_code: target.version_code.clone(),
+        min_sdk_version,
+        min_sdk_raised,
+        target_sdk_version: diff_sdk_version(base.target_sdk_version, target.target_sdk_version),
+        permissions: diff_list(&base.permissions, &target.permissions),
+        added_dangerous_permissions: added_dangerous_permissions(
+            &base.permissions,
+            &target.permissions,
+        ),
+        features: diff_list(&base.features, &target.features),
+        native_abis: diff_list(&base.native_abis, &target.native_abis),
+        components: diff_components(base, target),
+        is_debuggable: diff_flag(base.is_debuggable, target.is_debuggable),
+        is_test_only: diff_flag(base.is_test_only, target.is_test_only),
+        signer_changed: base.signer_sha256 != target.signer_sha256,
+    }
+}
+
+fn diff_sdk_version(from: Option<i32>, to: Option<i32>) -> Option<SdkVersionChange> {
+    (from != to).then_some(SdkVersionChange { from, to })
+}
+
+fn diff_flag(from: bool, to: bool) -> Option<FlagChange> {
+    (from != to).then_some(FlagChange { from, to })
+}
+
+fn diff_list(base: &[String], target: &[String]) -> ListDiff {
+    let base: BTreeSet<&str> = base.iter().map(String::as_str).collect();
+    let target: BTreeSet<&str> = target.iter().map(String::as_str).collect();
+    ListDiff {
+        added: target
+            .difference(&base)
+            .map(|value| value.to_string())
+            .collect(),
+        removed: base
+            .difference(&target)
+            .map(|value| value.to_string())
+            .collect(),
+    }
+}
+
+// 早期版本未记录组件信息，无法比较时返回 None
+fn diff_components(base: &AppManage, target: &AppManage) -> Option<ComponentsDiff> {
+    let base = parse_components(base)?;
+    let target = parse_components(target)?;
+    Some(ComponentsDiff {
+        activities: diff_list(&base.activities, &target.activities),
+        services: diff_list(&base.services, &target.services),
+        receivers: diff_list(&base.receivers, &target.receivers),
+        providers: diff_list(&base.providers, &target.providers),
+    })
+}
+
+fn parse_components(app: &AppManage) -> Option<ManifestComponents> {
+    serde_json::from_value(app.components.clone()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn added_dangerous_permissions_ignores_existing_and_normal_permissions() {
+        let base = vec![
+            "android.permission.INTERNET".to_string(),
+            "android.permission.CAMERA".to_string(),
+        ];
+        let target = vec![
+            "android.permission.INTERNET".to_string(),
+            "android.permission.CAMERA".to_string(),
+            "android.permission.ACCESS_FINE_LOCATION".to_string(),
+            "android.permission.VIBRATE".to_string(),
+        ];
+
+        assert_eq!(
+            added_dangerous_permissions(&base, &target),
+            vec!["android.permission.ACCESS_FINE_LOCATION".to_string()]
+        );
+        assert_eq!(
+            diff_list(&target, &base),
+            ListDiff {
+                added: Vec::new(),
+                removed: vec![
+                    "android.permission.ACCESS_FINE_LOCATION".to_string(),
+                    "android.permission.VIBRATE".to_string(),
+                ],
+            }
+        );
+    }
+}
@@ -1,8 +1,28 @@
+pub mod aab_utils;
+pub mod api_token_utils;
+pub mod apk_signature_utils;
+pub mod apk_signing_block_utils;
 pub mod apk_utils;
 pub mod app_manage_cleanup_task;
+pub mod app_manage_publish_task;
 pub mod auth_captcha_utils;
+pub mod blob_store_utils;
+pub mod bsdiff_utils;
 pub mod database_utils;
+pub mod delta_patch_task;
+pub mod device_targeting_utils;
+pub mod download_signature_utils;
+pub mod file_digest_utils;
+pub mod http_download_utils;
 pub mod json_error_catcher;
 pub mod jwt_service;
+pub mod manifest_diff_utils;
 pub mod operation_log_utils;
+pub mod organization_utils;
 pub mod password_utils;
+pub mod request_nonce_cleanup_task;
+pub mod request_signature_utils;
+pub mod split_apk_utils;
+pub mod upload_session_cleanup_task;
+pub mod upload_session_utils;
+pub mod user_role_utils;
@@ -12,6 +12,25 @@ pub const OP_PUBLISH_APP: &str = "PUBLISH_APP";
 pub const OP_CREATE_APP_CHANNEL: &str = "CREATE_APP_CHANNEL";
 pub const OP_DELETE_APP_CHANNEL: &str = "DELETE_APP_CHANNEL";
 pub const OP_DELETE_APP: &str = "DELETE_APP";
+pub const OP_UPDATE_APP_FORCE_UPDATE: &str = "UPDATE_APP_FORCE_UPDATE";
+pub const OP_UPDATE_APP_ROLLOUT: &str = "UPDATE_APP_ROLLOUT";
+pub const OP_UPDATE_APP_TARGETING: &str = "UPDATE_APP_TARGETING";
+pub const OP_REVOKE_APP: &str = "REVOKE_APP";
+pub const OP_SCHEDULE_APP: &str = "SCHEDULE_APP";
+pub const OP_GENERATE_CHANNEL_APKS: &str = "GENERATE_CHANNEL_APKS";
+pub const OP_APPROVE_SIGNER_ROTATION: &str = "APPROVE_SIGNER_ROTATION";
+pub const OP_ROTATE_APP_SIGNER: &str = "ROTATE_APP_SIGNER";
+pub const OP_CREATE_APP_CHANNEL_KEY: &str = "CREATE_APP_CHANNEL_KEY";
+pub const OP_DELETE_APP_CHANNEL_KEY: &str = "DELETE_APP_CHANNEL_KEY";
+pub const OP_UPDATE_USER_ROLES: &str = "UPDATE_USER_ROLES";
+pub const OP_CREATE_ORGANIZATION: &str = "CREATE_ORGANIZATION";
+pub const OP_UPDATE_ORGANIZATION_MEMBER: &str = "UPDATE_ORGANIZATION_MEMBER";
+pub const OP_REMOVE_ORGANIZATION_MEMBER: &str = "REMOVE_ORGANIZATION_MEMBER";
+pub const OP_CREATE_API_TOKEN: &str = "CREATE_API_TOKEN";
+pub const OP_DELETE_API_TOKEN: &str = "DELETE_API_TOKEN";
+pub const OP_REVOKE_SESSION: &str = "REVOKE_SESSION";
+pub const OP_REVOKE_ALL_SESSIONS: &str = "REVOKE_ALL_SESSIONS";
+pub const OP_REFRESH_TOKEN_REUSED: &str = "REFRESH_TOKEN_REUSED";
 
 pub fn record_operation(
     conn: &mut PgConnection,
@@ -0,0 +1,225 @@
+use crate::model::error::AppError;
+use crate::model::organization::{
+    CurrentOrganization, MemberRole, Organization, OrganizationItem, OrganizationMember,
+};
+use crate::model::users::User;
+use crate::schema::{organization, organization_member};
+use chrono::NaiveDateTime;
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+/// 指定当前操作组织的请求头，未传时使用用户的个人组织
+pub const ORGANIZATION_ID_HEADER: &str = "x-organization-id";
+
+// 创建组织并将创建者加入为所有者
+pub fn create_organization_with_owner(
+    conn: &mut PgConnection,
+    name: &str,
+    is_personal: bool,
+    user_id: Uuid,
+    now: NaiveDateTime,
+) -> QueryResult<Organization> {
+    let new_organization = Organization {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        is_personal,
+        create_user_id: user_id,
+        create_time: now,
+        update_time: now,
+        is_delete: false,
+    };
+
+    conn.transaction(|conn| {
+        diesel::insert_into(organization::table)
+            .values(&new_organization)
+            .execute(conn)?;
+        diesel::insert_into(organization_member::table)
+            .values(&OrganizationMember {
+                organization_id: new_organization.id,
+                user_id,
+                member_role: MemberRole::Owner.as_str().to_string(),
+                create_time: now,
+            })
+            .execute(conn)?;
+        Ok(new_organization)
+    })
+}
+
+// 注册用户时创建个人组织
+pub fn create_personal_organization(
+    conn: &mut PgConnection,
+    user: &User,
+    now: NaiveDateTime,
+) -> QueryResult<Organization> {
+    create_organization_with_owner(conn, &user.username, true, user.id, now)
+}
+
+// 解析请求头中的组织ID，空值视为未指定
+pub fn parse_organization_id(value: Option<&str>) -> Result<Option<Uuid>, AppError> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => Uuid::parse_str(value).map(Some).map_err(|_| {
+            AppError::BadRequest(format!(
+                "请求头'{}'不是有效的组织ID",
+                ORGANIZATION_ID_HEADER
+            ))
+        }),
+    }
+}
+
+// 确定当前请求所在的组织：指定组织时校验成员身份，否则使用个人组织，没有个人组织时使用最早加入的组织
+pub fn resolve_current_organization(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    requested_id: Option<Uuid>,
+) -> Result<CurrentOrganization, AppError> {
+    let mut query = organization_member::table
+        .inner_join(organization::table)
+        .filter(organization_member::user_id.eq(user_id))
+        .filter(organization::is_delete.eq(false))
+        .into_boxed();
+    query = match requested_id {
+        Some(organization_id) => query.filter(organization::id.eq(organization_id)),
+        None => query.order((
+            organization::is_personal.desc(),
+            organization_member::create_time.asc(),
+        )),
+    };
+
+    let membership = query
+        .select((
+            organization::id,
+            organization::name,
+            organization_member::member_role,
+        ))
+        .first::<(Uuid, String, String)>(conn)
+        .optional()
+        .map_err(|e| AppError::Internal(format!("查询组织失败: {}", e)))?;
+
+    match (membership, requested_id) {
+        (Some((id, name, member_role)), _) => Ok(CurrentOrganization {
+            id,
+            name,
+            member_role: MemberRole::from_db(&member_role).unwrap_or(MemberRole::Member),
+        }),
+        (None, Some(organization_id)) => Err(AppError::FORBIDDEN(format!(
+            "当前用户不是组织'{}'的成员",
+            organization_id
+        ))),
+        (None, None) => Err(AppError::FORBIDDEN(
+            "当前用户不属于任何组织，请联系管理员添加".to_string(),
+        )),
+    }
+}
+
+// 查询未删除的组织
+pub fn find_organization(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+) -> Result<Organization, AppError> {
+    organization::table
+        .filter(organization::id.eq(organization_id))
+        .filter(organization::is_delete.eq(false))
+        .first::<Organization>(conn)
+        .optional()
+        .map_err(|e| AppError::Internal(format!("查询组织失败: {}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("组织Id'{}' 未找到", organization_id)))
+}
+
+// 查询用户在组织中的角色，不是成员时返回 None
+pub fn find_member_role(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    user_id: Uuid,
+) -> QueryResult<Option<MemberRole>> {
+    organization_member::table
+        .filter(organization_member::organization_id.eq(organization_id))
+        .filter(organization_member::user_id.eq(user_id))
+        .select(organization_member::member_role)
+        .first::<String>(conn)
+        .optional()
+        .map(|role| role.and_then(|role| MemberRole::from_db(&role)))
+}
+
+// 除指定用户外组织是否还有其他所有者
+pub fn has_other_owner(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    user_id: Uuid,
+) -> QueryResult<bool> {
+    diesel::select(exists(
+        organization_member::table
+            .filter(organization_member::organization_id.eq(organization_id))
+            .filter(organization_member::member_role.eq(MemberRole::Owner.as_str()))
+            .filter(organization_member::user_id.ne(user_id)),
+    ))
+    .get_result::<bool>(conn)
+}
+
+// 查询用户加入的全部组织，个人组织在前
+pub fn load_user_organizations(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+) -> QueryResult<Vec<OrganizationItem>> {
+    organization_member::table
+        .inner_join(organization::table)
+        .filter(organization_member::user_id.eq(user_id))
+        .filter(organization::is_delete.eq(false))
+        .order((
+            organization::is_personal.desc(),
+            organization_member::create_time.asc(),
+        ))
+        .select((
+            organization::id,
+            organization::name,
+            organization::is_personal,
+            organization_member::member_role,
+            organization::create_time,
+        ))
+        .load::<(Uuid, String, bool, String, NaiveDateTime)>(conn)
+        .map(|rows| {
+            rows.into_iter()
+                .map(
+                    |(organization_id, name, is_personal, member_role, create_time)| {
+                        OrganizationItem {
+                            organization_id,
+                            name,
+                            is_personal,
+                            member_role: MemberRole::from_db(&member_role)
+                                .unwrap_or(MemberRole::Member),
+                            create_time,
+                        }
+                    },
+                )
+                .collect()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_organization_id_accepts_missing_and_rejects_invalid_header() {
+        assert_eq!(parse_organization_id(None).unwrap(), None);
+        assert_eq!(parse_organization_id(Some("  ")).unwrap(), None);
+
+        let organization_id = Uuid::new_v4();
+        assert_eq!(
+            parse_organization_id(Some(&format!(" {} ", organization_id))).unwrap(),
+            Some(organization_id)
+        );
+        assert!(matches!(
+            parse_organization_id(Some("team-a")),
+            Err(AppError::BadRequest(_))
+        ));
+
+        assert_eq!(MemberRole::from_db("owner"), Some(MemberRole::Owner));
+        assert_eq!(
+            MemberRole::from_db(MemberRole::Member.as_str()),
+            Some(MemberRole::Member)
+        );
+        assert_eq!(MemberRole::from_db("admin"), None);
+    }
+}
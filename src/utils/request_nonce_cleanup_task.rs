@@ -0,0 +1,31 @@
+use crate::store::NonceStore;
+use std::env;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tracing::{error, info};
+
+// `REQUEST_NONCE_CLEANUP_INTERVAL_SECS`：清理过期请求随机数的间隔秒数，默认 10 分钟
+const DEFAULT_CLEANUP_INTERVAL_SECS: u64 = 10 * 60;
+
+// 需在 tokio 运行时中调用，清理线程通过运行时句柄访问随机数存储
+pub fn start_request_nonce_cleanup_task(nonce_store: Arc<dyn NonceStore>) {
+    let interval = env::var("REQUEST_NONCE_CLEANUP_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_CLEANUP_INTERVAL_SECS);
+    let runtime = Handle::current();
+
+    thread::spawn(move || {
+        loop {
+            match runtime.block_on(nonce_store.purge_expired()) {
+                Ok(0) => {}
+                Ok(count) => info!(deleted = count, "过期请求随机数清理完成"),
+                Err(e) => error!(error = %e, "过期请求随机数清理失败"),
+            }
+            thread::sleep(Duration::from_secs(interval));
+        }
+    });
+}
@@ -0,0 +1,168 @@
+use crate::model::app_channel::AppChannelKey;
+use crate::schema::app_channel_key;
+use diesel::prelude::*;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+pub const APP_KEY_HEADER: &str = "x-app-key";
+pub const TIMESTAMP_HEADER: &str = "x-timestamp";
+pub const NONCE_HEADER: &str = "x-nonce";
+pub const SIGNATURE_HEADER: &str = "x-signature";
+// 检查更新接口参与签名的固定路径，与服务实际挂载的路由前缀（如反向代理添加的前缀）无关
+pub const CHECK_UPDATE_SIGNATURE_PATH: &str = "/api/public/app_manage/app_check_update";
+// 请求时间戳与服务器时间允许的最大偏差，随机数保留时间需覆盖整个偏差窗口
+pub const SIGNATURE_TIMESTAMP_TOLERANCE_SECS: i64 = 5 * 60;
+const MAX_NONCE_LENGTH: usize = 64;
+
+/// 客户端请求签名参数
+#[derive(Debug, Clone)]
+pub struct RequestSignature<'a> {
+    ///请求方法
+    pub method: &'a str,
+    ///参与签名的规范路径，不含查询参数，与实际请求路由无关
+    pub path: &'a str,
+    ///Unix 时间戳（秒）
+    pub timestamp: i64,
+    ///随机数，同一 app_key 在有效期内不能重复使用
+    pub nonce: &'a str,
+    ///请求体原始字节
+    pub body: &'a [u8],
+}
+
+impl RequestSignature<'_> {
+    // 待签名字符串：请求方法、路径、时间戳、随机数和请求体 SHA-256 以换行连接
+    pub fn string_to_sign(&self) -> String {
+        format!(
+            "{}\n{}\n{}\n{}\n{}",
+            self.method.to_ascii_uppercase(),
+            self.path,
+            self.timestamp,
+            self.nonce,
+            hex_encode(&Sha256::digest(self.body))
+        )
+    }
+
+    // 计算签名（HMAC-SHA256，小写十六进制）
+    pub fn sign(&self, app_secret: &str) -> String {
+        hex_encode(&self.mac(app_secret).finalize().into_bytes())
+    }
+
+    // 校验签名，使用常量时间比较
+    pub fn verify(&self, app_secret: &str, signature: &str) -> bool {
+        hex_decode(signature.trim())
+            .is_some_and(|signature| self.mac(app_secret).verify_slice(&signature).is_ok())
+    }
+
+    fn mac(&self, app_secret: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(app_secret.as_bytes()).expect("HMAC 支持任意长度的密钥");
+        mac.update(self.string_to_sign().as_bytes());
+        mac
+    }
+}
+
+// 时间戳是否在允许的偏差范围内
+pub fn is_timestamp_fresh(timestamp: i64, now: i64) -> bool {
+    (now - timestamp).abs() <= SIGNATURE_TIMESTAMP_TOLERANCE_SECS
+}
+
+pub fn is_valid_nonce(nonce: &str) -> bool {
+    !nonce.is_empty()
+        && nonce.len() <= MAX_NONCE_LENGTH
+        && nonce
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// 生成访问标识和签名密钥
+pub fn generate_app_key_pair() -> (String, String) {
+    let app_key = format!("ak_{}", Uuid::new_v4().simple());
+    let app_secret = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    (app_key, app_secret)
+}
+
+// 查询未吊销的访问密钥
+pub fn find_active_channel_key(
+    conn: &mut PgConnection,
+    app_key: &str,
+) -> QueryResult<Option<AppChannelKey>> {
+    app_channel_key::table
+        .filter(app_channel_key::app_key.eq(app_key))
+        .filter(app_channel_key::is_delete.eq(false))
+        .first::<AppChannelKey>(conn)
+        .optional()
+}
+
+// 查询给定渠道中配置了访问密钥的渠道
+pub fn channels_with_active_keys(
+    conn: &mut PgConnection,
+    channel_ids: &[Uuid],
+) -> QueryResult<HashSet<Uuid>> {
+    app_channel_key::table
+        .filter(app_channel_key::channel_id.eq_any(channel_ids))
+        .filter(app_channel_key::is_delete.eq(false))
+        .select(app_channel_key::channel_id)
+        .distinct()
+        .load::<Uuid>(conn)
+        .map(|channel_ids| channel_ids.into_iter().collect())
+}
+
+fn hex_encode(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn hex_decode(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(value.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_covers_timestamp_nonce_and_body() {
+        let request = RequestSignature {
+            method: "post",
+            path: CHECK_UPDATE_SIGNATURE_PATH,
+            timestamp: 1_700_000_000,
+            nonce: "n-1",
+            body: b"{\"package_name\":\"com.example\"}",
+        };
+        assert_eq!(
+            request.string_to_sign(),
+            format!(
+                "POST\n/api/public/app_manage/app_check_update\n1700000000\nn-1\n{}",
+                hex_encode(&Sha256::digest(request.body))
+            )
+        );
+
+        let signature = request.sign("secret");
+        assert!(request.verify("secret", &signature));
+        assert!(request.verify("secret", &signature.to_ascii_uppercase()));
+        assert!(!request.verify("other", &signature));
+        assert!(!request.verify("secret", "zz"));
+
+        let tampered = RequestSignature {
+            body: b"{\"package_name\":\"com.other\"}",
+            ..request.clone()
+        };
+        assert!(!tampered.verify("secret", &signature));
+        let replayed_later = RequestSignature {
+            timestamp: request.timestamp + 1,
+            ..request
+        };
+        assert!(!replayed_later.verify("secret", &signature));
+
+        assert!(is_timestamp_fresh(1_700_000_000, 1_700_000_300));
+        assert!(!is_timestamp_fresh(1_700_000_000, 1_700_000_301));
+        assert!(is_valid_nonce("abc_123-XYZ") && !is_valid_nonce("a b") && !is_valid_nonce(""));
+    }
+}
@@ -0,0 +1,336 @@
+use crate::model::app_manage::{AppCheckUpdateReq, SplitApkItem, SplitKind};
+use crate::utils::apk_utils::SplitApkInfo;
+use anyhow::{Context, Result, anyhow, bail};
+use apk_info::ZipEntry;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+pub const BASE_SPLIT_NAME: &str = "base";
+
+// 拆分 APK 安装包格式：bundletool 生成的 .apks 和第三方商店常用的 .xapk
+const BUNDLE_EXTENSIONS: &[&str] = &["apks", "xapk"];
+// bundletool 为 Android 5.0 以下设备生成的完整 APK，与拆分包重复
+const BUNDLE_STANDALONE_DIR: &str = "standalones/";
+const CONFIG_SPLIT_PREFIX: &str = "config.";
+
+// 配置包名称中的 ABI 与 Build.SUPPORTED_ABIS 的对应关系
+const SPLIT_ABIS: &[(&str, &str)] = &[
+    ("armeabi", "armeabi"),
+    ("armeabi_v7a", "armeabi-v7a"),
+    ("arm64_v8a", "arm64-v8a"),
+    ("x86", "x86"),
+    ("x86_64", "x86_64"),
+    ("mips", "mips"),
+    ("mips64", "mips64"),
+    ("riscv64", "riscv64"),
+];
+// 屏幕密度分组及对应的 densityDpi
+const SPLIT_DENSITIES: &[(&str, i32)] = &[
+    ("ldpi", 120),
+    ("mdpi", 160),
+    ("tvdpi", 213),
+    ("hdpi", 240),
+    ("xhdpi", 320),
+    ("xxhdpi", 480),
+    ("xxxhdpi", 640),
+];
+
+// 判断上传文件是否为拆分 APK 安装包
+pub fn is_bundle_file(file_name: &str) -> bool {
+    Path::new(file_name)
+        .extension()
+        .and_then(|value| value.to_str())
+        .is_some_and(|value| {
+            BUNDLE_EXTENSIONS
+                .iter()
+                .any(|extension| value.eq_ignore_ascii_case(extension))
+        })
+}
+
+// 读取安装包中的 APK，返回 APK 文件名和文件内容
+pub fn extract_bundle_apks(bundle_path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    let data = fs::read(bundle_path)
+        .with_context(|| format!("读取安装包失败: {}", bundle_path.display()))?;
+    let bundle = ZipEntry::new(data).map_err(|e| anyhow!("解析安装包失败: {:?}", e))?;
+
+    let mut entries: Vec<&str> = bundle
+        .namelist()
+        .filter(|entry| entry.to_ascii_lowercase().ends_with(".apk"))
+        .filter(|entry| !entry.starts_with(BUNDLE_STANDALONE_DIR))
+        .collect();
+    entries.sort();
+    if entries.is_empty() {
+        bail!("安装包中未找到 APK 文件");
+    }
+
+    let mut apk_files = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let (bytes, _) = bundle
+            .read(entry)
+            .map_err(|e| anyhow!("读取安装包中的 APK 失败 {entry}: {:?}", e))?;
+        let entry_name = entry.rsplit('/').next().unwrap_or(entry);
+        apk_files.push((entry_name.to_string(), bytes));
+    }
+
+    Ok(apk_files)
+}
+
+// 根据拆分名称判断拆分类型及配置限定值，功能模块的配置包（如 feature.config.x86）按配置类型归类
+pub fn classify_split(split_name: Option<&str>) -> (SplitKind, Option<String>) {
+    let Some(split_name) = split_name else {
+        return (SplitKind::Base, None);
+    };
+
+    let config = split_name.strip_prefix(CONFIG_SPLIT_PREFIX).or_else(|| {
+        split_name
+            .split_once(&format!(".{CONFIG_SPLIT_PREFIX}"))
+            .map(|(_, config)| config)
+    });
+    let Some(config) = config else {
+        return (SplitKind::Feature, None);
+    };
+
+    if let Some((_, abi)) = SPLIT_ABIS.iter().find(|(name, _)| *name == config) {
+        return (SplitKind::Abi, Some(abi.to_string()));
+    }
+    if SPLIT_DENSITIES.iter().any(|(name, _)| *name == config) {
+        return (SplitKind::Density, Some(config.to_string()));
+    }
+    if (2..=3).contains(&config.len()) && config.chars().all(|c| c.is_ascii_lowercase()) {
+        return (SplitKind::Language, Some(config.to_string()));
+    }
+
+    (SplitKind::Feature, None)
+}
+
+// 校验拆分 APK 属于同一版本：有且仅有一个基础包，包名、版本号、签名一致且拆分名称不重复
+pub fn validate_split_set(splits: &[SplitApkInfo]) -> Result<()> {
+    let mut bases = splits.iter().filter(|split| split.split_name.is_none());
+    let base = bases
+        .next()
+        .ok_or_else(|| anyhow!("缺少基础包（base APK）"))?;
+    if let Some(other) = bases.next() {
+        bail!("存在多个基础包：{}、{}", base.file_name, other.file_name);
+    }
+
+    let mut split_names = HashSet::new();
+    for split in splits {
+        if split.package_name != base.package_name {
+            bail!(
+                "{} 的包名 {} 与基础包 {} 不一致",
+                split.file_name,
+                split.package_name,
+                base.package_name
+            );
+        }
+        if split.version_code != base.version_code {
+            bail!(
+                "{} 的版本号 {} 与基础包 {} 不一致",
+                split.file_name,
+                split.version_code.as_deref().unwrap_or_default(),
+                base.version_code.as_deref().unwrap_or_default()
+            );
+        }
+        if split.signer_sha256 != base.signer_sha256 {
+            bail!("{} 的签名证书与基础包不一致", split.file_name);
+        }
+        if let Some(split_name) = &split.split_name
+            && !split_names.insert(split_name.as_str())
+        {
+            bail!("拆分名称 {} 重复", split_name);
+        }
+    }
+
+    Ok(())
+}
+
+// 拆分 APK 支持的 ABI：基础包自带的原生库与 ABI 配置包的并集
+pub fn split_native_abis(base_native_abis: &[String], splits: &[SplitApkItem]) -> Vec<String> {
+    let mut native_abis: Vec<String> = base_native_abis
+        .iter()
+        .cloned()
+        .chain(
+            splits
+                .iter()
+                .filter(|split| split.kind == SplitKind::Abi)
+                .filter_map(|split| split.qualifier.clone()),
+        )
+        .collect();
+    native_abis.sort();
+    native_abis.dedup();
+    native_abis
+}
+
+// 选择适配设备的拆分 APK：基础包和功能模块包始终下发，设备未上报的配置维度下发全部配置包
+pub fn select_splits_for_device(
+    splits: &[SplitApkItem],
+    device: &AppCheckUpdateReq,
+) -> Vec<SplitApkItem> {
+    let abi = select_abi(splits, &device.supported_abis);
+    let density = device
+        .screen_density
+        .and_then(|density_dpi| select_density(splits, density_dpi));
+    let language = device
+        .locale
+        .as_deref()
+        .map(locale_language)
+        .filter(|language| !language.is_empty());
+
+    splits
+        .iter()
+        .filter(|split| {
+            let qualifier = split.qualifier.as_deref();
+            match split.kind {
+                SplitKind::Base | SplitKind::Feature => true,
+                SplitKind::Abi => device.supported_abis.is_empty() || qualifier == abi,
+                SplitKind::Density => device.screen_density.is_none() || qualifier == density,
+                SplitKind::Language => language.is_none() || qualifier == language.as_deref(),
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+// 按设备 ABI 优先级选择第一个有对应配置包的 ABI
+fn select_abi<'a>(splits: &'a [SplitApkItem], supported_abis: &[String]) -> Option<&'a str> {
+    supported_abis.iter().find_map(|abi| {
+        splits
+            .iter()
+            .filter(|split| split.kind == SplitKind::Abi)
+            .filter_map(|split| split.qualifier.as_deref())
+            .find(|qualifier| qualifier.eq_ignore_ascii_case(abi.trim()))
+    })
+}
+
+// 选择不低于设备密度的最小密度分组，没有时选择最大的密度分组
+fn select_density(splits: &[SplitApkItem], density_dpi: i32) -> Option<&str> {
+    let mut densities: Vec<(&str, i32)> = splits
+        .iter()
+        .filter(|split| split.kind == SplitKind::Density)
+        .filter_map(|split| split.qualifier.as_deref())
+        .filter_map(|qualifier| {
+            SPLIT_DENSITIES
+                .iter()
+                .find(|(name, _)| *name == qualifier)
+                .map(|(_, dpi)| (qualifier, *dpi))
+        })
+        .collect();
+    densities.sort_by_key(|(_, dpi)| *dpi);
+
+    densities
+        .iter()
+        .find(|(_, dpi)| *dpi >= density_dpi)
+        .or(densities.last())
+        .map(|(name, _)| *name)
+}
+
+// 取语言区域中的语言部分，如 zh-CN、zh_CN 均为 zh
+fn locale_language(locale: &str) -> String {
+    locale
+        .trim()
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(split_name: Option<&str>) -> SplitApkItem {
+        let (kind, qualifier) = classify_split(split_name);
+        SplitApkItem {
+            split_name: split_name.unwrap_or(BASE_SPLIT_NAME).to_string(),
+            kind,
+            qualifier,
+            file_name: String::new(),
+            file_path: String::new(),
+            file_size: 0,
+            file_sha256: None,
+            file_md5: None,
+        }
+    }
+
+    fn device() -> AppCheckUpdateReq {
+        AppCheckUpdateReq {
+            package_name: "com.example.app".to_string(),
+            channel_name: "official".to_string(),
+            version_code: 0,
+            device_id: None,
+            sdk_int: None,
+            supported_abis: Vec::new(),
+            manufacturer: None,
+            model: None,
+            locale: None,
+            screen_density: None,
+            current_file_sha256: None,
+        }
+    }
+
+    #[test]
+    fn classify_split_detects_config_dimensions() {
+        assert_eq!(classify_split(None), (SplitKind::Base, None));
+        assert_eq!(
+            classify_split(Some("config.arm64_v8a")),
+            (SplitKind::Abi, Some("arm64-v8a".to_string()))
+        );
+        assert_eq!(
+            classify_split(Some("feature_camera.config.xxhdpi")),
+            (SplitKind::Density, Some("xxhdpi".to_string()))
+        );
+        assert_eq!(
+            classify_split(Some("config.zh")),
+            (SplitKind::Language, Some("zh".to_string()))
+        );
+        assert_eq!(
+            classify_split(Some("feature_camera")),
+            (SplitKind::Feature, None)
+        );
+    }
+
+    #[test]
+    fn select_splits_for_device_picks_matching_configs() {
+        let splits: Vec<SplitApkItem> = [
+            None,
+            Some("feature_camera"),
+            Some("config.armeabi_v7a"),
+            Some("config.arm64_v8a"),
+            Some("config.hdpi"),
+            Some("config.xxhdpi"),
+            Some("config.en"),
+            Some("config.zh"),
+        ]
+        .into_iter()
+        .map(split)
+        .collect();
+        let selected_names = |device: &AppCheckUpdateReq| {
+            select_splits_for_device(&splits, device)
+                .into_iter()
+                .map(|split| split.split_name)
+                .collect::<Vec<_>>()
+        };
+
+        let mut arm64_device = device();
+        arm64_device.supported_abis = vec!["arm64-v8a".to_string(), "armeabi-v7a".to_string()];
+        arm64_device.screen_density = Some(420);
+        arm64_device.locale = Some("zh-CN".to_string());
+        assert_eq!(
+            selected_names(&arm64_device),
+            vec![
+                "base",
+                "feature_camera",
+                "config.arm64_v8a",
+                "config.xxhdpi",
+                "config.zh"
+            ]
+        );
+
+        // 未上报的维度下发全部配置包，密度高于所有分组时取最大分组
+        let mut unknown_device = device();
+        unknown_device.screen_density = Some(640);
+        assert_eq!(selected_names(&unknown_device).len(), 7);
+        assert!(!selected_names(&unknown_device).contains(&"config.hdpi".to_string()));
+    }
+}
@@ -0,0 +1,90 @@
+use crate::db::DbPool;
+use crate::schema::app_upload_session;
+use crate::store::FileStore;
+use crate::utils::upload_session_utils::{remove_session_chunks, stored_session_ids};
+use diesel::prelude::*;
+use std::collections::HashSet;
+use std::env;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+// `UPLOAD_SESSION_CLEANUP_INTERVAL_SECS`：清理过期上传会话的间隔秒数，默认 1 小时
+const DEFAULT_CLEANUP_INTERVAL_SECS: u64 = 60 * 60;
+
+// 需在 tokio 运行时中调用，清理线程通过运行时句柄访问文件存储
+pub fn start_upload_session_cleanup_task(pool: Arc<DbPool>, file_store: Arc<dyn FileStore>) {
+    let interval = env::var("UPLOAD_SESSION_CLEANUP_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_CLEANUP_INTERVAL_SECS);
+    let runtime = Handle::current();
+
+    thread::spawn(move || {
+        loop {
+            run_cleanup_once(&pool, file_store.as_ref(), &runtime);
+            thread::sleep(Duration::from_secs(interval));
+        }
+    });
+}
+
+fn run_cleanup_once(pool: &Arc<DbPool>, file_store: &dyn FileStore, runtime: &Handle) {
+    match cleanup_expired_sessions(pool, file_store, runtime) {
+        Ok(0) => {}
+        Ok(count) => info!(deleted = count, "过期上传会话清理完成"),
+        Err(e) => error!(error = %e, "过期上传会话清理失败"),
+    }
+}
+
+fn cleanup_expired_sessions(
+    pool: &Arc<DbPool>,
+    file_store: &dyn FileStore,
+    runtime: &Handle,
+) -> anyhow::Result<usize> {
+    let mut conn = pool.get()?;
+    let now = chrono::Local::now().naive_local();
+    // 锁定过期会话后再删除分片；正在写入分片的会话持有行锁，跳过等下次清理。
+    // 合并中的会话在认领时已顺延有效期，只有合并中断且超过有效期的会话才会过期
+    let expired_ids = conn.transaction::<_, anyhow::Error, _>(|conn| {
+        let expired_ids = app_upload_session::table
+            .filter(app_upload_session::expires_at.le(now))
+            .select(app_upload_session::id)
+            .for_update()
+            .skip_locked()
+            .load::<Uuid>(conn)?;
+        for session_id in &expired_ids {
+            if let Err(e) = runtime.block_on(remove_session_chunks(file_store, *session_id)) {
+                warn!(session_id = %session_id, error = %e, "删除过期上传会话分片失败");
+            }
+        }
+        diesel::delete(
+            app_upload_session::table.filter(app_upload_session::id.eq_any(&expired_ids)),
+        )
+        .execute(conn)?;
+        Ok(expired_ids)
+    })?;
+
+    // 会话记录已不存在的分片（如删除分片失败）一并清理；先列出分片再查询会话，避免误删刚创建的会话
+    let stored_ids = runtime.block_on(stored_session_ids(file_store))?;
+    let live_ids: HashSet<Uuid> = app_upload_session::table
+        .select(app_upload_session::id)
+        .load::<Uuid>(&mut conn)?
+        .into_iter()
+        .collect();
+    let mut orphan_count = 0;
+    for session_id in stored_ids {
+        if expired_ids.contains(&session_id) || live_ids.contains(&session_id) {
+            continue;
+        }
+        match runtime.block_on(remove_session_chunks(file_store, session_id)) {
+            Ok(()) => orphan_count += 1,
+            Err(e) => warn!(session_id = %session_id, error = %e, "删除上传会话分片失败"),
+        }
+    }
+
+    Ok(expired_ids.len() + orphan_count)
+}
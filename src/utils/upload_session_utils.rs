@@ -0,0 +1,185 @@
+use crate::model::error::AppError;
+use crate::store::FileStore;
+use bytes::Bytes;
+use futures_util::StreamExt;
+use std::collections::HashSet;
+use std::path::Path;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+// 分片暂存在文件存储中，多实例部署时任意节点都能读取其他节点接收的分片
+pub const UPLOAD_SESSION_STORE_PREFIX: &str = "upload_sessions";
+const CHUNK_FILE_EXTENSION: &str = "part";
+
+// 会话分片在存储中的前缀：`upload_sessions/{会话ID}/`
+fn session_prefix(session_id: Uuid) -> String {
+    format!("{UPLOAD_SESSION_STORE_PREFIX}/{session_id}/")
+}
+
+fn chunk_key(session_id: Uuid, index: i32) -> String {
+    format!(
+        "{}{index}.{CHUNK_FILE_EXTENSION}",
+        session_prefix(session_id)
+    )
+}
+
+// 分片总数
+pub fn chunk_count(file_size: i64, chunk_size: i64) -> i32 {
+    ((file_size + chunk_size - 1) / chunk_size) as i32
+}
+
+// 指定分片应有的大小，最后一个分片可以小于分片大小
+pub fn expected_chunk_size(file_size: i64, chunk_size: i64, index: i32) -> i64 {
+    let offset = index as i64 * chunk_size;
+    (file_size - offset).clamp(0, chunk_size)
+}
+
+// 写入分片，存储保证写入完整后才可见，重复上传同一分片时直接覆盖
+pub async fn write_chunk(
+    store: &dyn FileStore,
+    session_id: Uuid,
+    index: i32,
+    data: Bytes,
+) -> Result<(), AppError> {
+    store.put(&chunk_key(session_id, index), data).await
+}
+
+// 按存储中的分片统计已接收的分片，大小不符的分片视为未接收
+pub async fn received_chunks(
+    store: &dyn FileStore,
+    session_id: Uuid,
+    file_size: i64,
+    chunk_size: i64,
+) -> Result<Vec<i32>, AppError> {
+    let prefix = session_prefix(session_id);
+    let total_chunks = chunk_count(file_size, chunk_size);
+    let mut received: Vec<i32> = store
+        .list(&prefix)
+        .await?
+        .into_iter()
+        .filter_map(|object| {
+            let index = object
+                .key
+                .strip_prefix(&prefix)?
+                .strip_suffix(&format!(".{CHUNK_FILE_EXTENSION}"))?
+                .parse::<i32>()
+                .ok()
+                .filter(|index| (0..total_chunks).contains(index))?;
+            (object.size as i64 == expected_chunk_size(file_size, chunk_size, index))
+                .then_some(index)
+        })
+        .collect();
+    received.sort_unstable();
+    received.dedup();
+    Ok(received)
+}
+
+// 按顺序将全部分片合并到本地目标文件
+pub async fn assemble_chunks(
+    store: &dyn FileStore,
+    session_id: Uuid,
+    total_chunks: i32,
+    target: &Path,
+) -> Result<(), AppError> {
+    let mut output = fs::File::create(target)
+        .await
+        .map_err(|e| AppError::Internal(format!("创建文件失败 {}: {}", target.display(), e)))?;
+    for index in 0..total_chunks {
+        let mut chunk = store
+            .get(&chunk_key(session_id, index))
+            .await?
+            .ok_or_else(|| AppError::BadRequest(format!("分片 {} 不存在", index)))?;
+        while let Some(data) = chunk.stream.next().await {
+            let data =
+                data.map_err(|e| AppError::Internal(format!("读取分片 {} 失败: {}", index, e)))?;
+            output
+                .write_all(&data)
+                .await
+                .map_err(|e| AppError::Internal(format!("合并分片 {} 失败: {}", index, e)))?;
+        }
+    }
+    output
+        .sync_all()
+        .await
+        .map_err(|e| AppError::Internal(format!("合并分片失败: {}", e)))?;
+    Ok(())
+}
+
+// 删除会话的全部分片
+pub async fn remove_session_chunks(
+    store: &dyn FileStore,
+    session_id: Uuid,
+) -> Result<(), AppError> {
+    for object in store.list(&session_prefix(session_id)).await? {
+        store.delete(&object.key).await?;
+    }
+    Ok(())
+}
+
+// 存储中有分片的会话ID
+pub async fn stored_session_ids(store: &dyn FileStore) -> Result<HashSet<Uuid>, AppError> {
+    let prefix = format!("{UPLOAD_SESSION_STORE_PREFIX}/");
+    Ok(store
+        .list(&prefix)
+        .await?
+        .into_iter()
+        .filter_map(|object| {
+            let (session_id, _) = object.key.strip_prefix(&prefix)?.split_once('/')?;
+            Uuid::parse_str(session_id).ok()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::LocalFileStore;
+
+    #[tokio::test]
+    async fn chunks_are_tracked_and_assembled_in_order() {
+        let root = std::env::temp_dir().join(format!("upload-session-test-{}", Uuid::new_v4()));
+        let store = LocalFileStore::new(&root);
+        let session_id = Uuid::new_v4();
+        assert_eq!(chunk_count(10, 4), 3);
+        assert_eq!(expected_chunk_size(10, 4, 2), 2);
+
+        let write = async |index, data: &'static [u8]| {
+            write_chunk(&store, session_id, index, Bytes::from_static(data))
+                .await
+                .unwrap()
+        };
+        write(2, b"ij").await;
+        write(0, b"abcd").await;
+        write(1, b"ef").await;
+        assert_eq!(
+            received_chunks(&store, session_id, 10, 4).await.unwrap(),
+            vec![0, 2]
+        );
+
+        write(1, b"efgh").await;
+        assert_eq!(
+            received_chunks(&store, session_id, 10, 4).await.unwrap(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            stored_session_ids(&store).await.unwrap(),
+            HashSet::from([session_id])
+        );
+
+        let target = root.join("assembled.bin");
+        assemble_chunks(&store, session_id, 3, &target)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), b"abcdefghij");
+
+        remove_session_chunks(&store, session_id).await.unwrap();
+        assert!(
+            received_chunks(&store, session_id, 10, 4)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+use crate::model::user_role::{Role, UserRole};
+use crate::schema::{user_role, users};
+use chrono::Local;
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use std::env;
+use uuid::Uuid;
+
+// 查询用户的角色，忽略无法识别的角色
+pub fn load_user_roles(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<Vec<Role>> {
+    user_role::table
+        .filter(user_role::user_id.eq(user_id))
+        .order(user_role::create_time.asc())
+        .select(user_role::role)
+        .load::<String>(conn)
+        .map(|roles| {
+            roles
+                .iter()
+                .filter_map(|role| Role::from_db(role))
+                .collect()
+        })
+}
+
+// 加事务级咨询锁：注册用户和修改角色互斥，判断是否还有管理员时不会与其他事务交错
+pub fn lock_admin_roles(conn: &mut PgConnection) -> QueryResult<()> {
+    diesel::sql_query("SELECT pg_advisory_xact_lock(hashtext('user_role:admin'))").execute(conn)?;
+    Ok(())
+}
+
+// 新注册用户的角色：系统中还没有管理员时为管理员，否则为 DEFAULT_USER_ROLE（默认只读）。
+// 需在插入用户的事务中调用，先加锁再判断，避免同时注册的多个用户都成为管理员
+pub fn registration_role(conn: &mut PgConnection) -> QueryResult<Role> {
+    lock_admin_roles(conn)?;
+    let has_admin = diesel::select(exists(
+        user_role::table.filter(user_role::role.eq(Role::Admin.as_str())),
+    ))
+    .get_result::<bool>(conn)?;
+    if !has_admin {
+        return Ok(Role::Admin);
+    }
+
+    Ok(env::var("DEFAULT_USER_ROLE")
+        .ok()
+        .and_then(|value| Role::from_db(value.trim()))
+        .unwrap_or(Role::Viewer))
+}
+
+// 除指定用户外是否还有其他未删除的管理员，需在持有 `lock_admin_roles` 的事务中调用
+pub fn has_other_admin(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<bool> {
+    diesel::select(exists(
+        user_role::table
+            .inner_join(users::table)
+            .filter(user_role::role.eq(Role::Admin.as_str()))
+            .filter(user_role::user_id.ne(user_id))
+            .filter(users::is_delete.eq(false)),
+    ))
+    .get_result::<bool>(conn)
+}
+
+// 覆盖用户的全部角色
+pub fn replace_user_roles(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    roles: &[Role],
+) -> QueryResult<()> {
+    let now = Local::now().naive_local();
+    let new_roles: Vec<UserRole> = roles
+        .iter()
+        .map(|role| UserRole {
+            user_id,
+            role: role.as_str().to_string(),
+            create_time: now,
+        })
+        .collect();
+
+    conn.transaction(|conn| {
+        diesel::delete(user_role::table.filter(user_role::user_id.eq(user_id))).execute(conn)?;
+        diesel::insert_into(user_role::table)
+            .values(&new_roles)
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        Ok(())
+    })
+}